@@ -30,6 +30,19 @@ struct PendingTilesetLoad {
     path: std::path::PathBuf,
     tile_width: u32,
     tile_height: u32,
+    /// Outer border and gap between tiles, in pixels.
+    margin: u32,
+    spacing: u32,
+    /// Frame/layer choices when loading an Aseprite sprite.
+    aseprite: Option<PendingAseprite>,
+}
+
+/// Aseprite sprite details shown in the tile size dialog.
+struct PendingAseprite {
+    frame_count: usize,
+    /// (layer index, indented name) for every non-group layer.
+    layers: Vec<(usize, String)>,
+    selection: crate::tile::aseprite::AsepriteSelection,
 }
 
 /// Pending confirmation dialog.
@@ -38,6 +51,12 @@ enum ConfirmDialog {
     Quit,
 }
 
+/// Outcome of Optimize Mesh or an optimized export, shown until dismissed.
+struct OptimizeResult {
+    title: &'static str,
+    report: crate::scene::optimize::OptimizeReport,
+}
+
 /// Data stored in the clipboard for copy/paste.
 struct ClipboardData {
     faces: Vec<Face>,
@@ -105,6 +124,18 @@ pub struct App {
     screenshot_flash: f32,
     /// Path of last screenshot for status bar display.
     screenshot_last_path: Option<String>,
+    /// When the last autosave snapshot was taken (or the app started).
+    last_autosave: std::time::Instant,
+    /// History revision captured by the last autosave, to skip unchanged scenes.
+    autosaved_revision: u64,
+    /// Background thread writing the current autosave snapshot.
+    autosave_thread: Option<std::thread::JoinHandle<()>>,
+    /// When tileset source files were last checked for changes.
+    last_tileset_poll: std::time::Instant,
+    /// Snapshot left behind by a crashed session, offered for restore on startup.
+    recovery_prompt: Option<crate::io::recovery::Snapshot>,
+    /// Face and vertex savings of the last mesh optimization, for the result dialog.
+    optimize_result: Option<OptimizeResult>,
 }
 
 /// Everything that requires the window to exist.
@@ -118,6 +149,8 @@ struct GpuState {
 impl App {
     pub fn new(_event_loop: &winit::event_loop::EventLoop<()>) -> Self {
         let recent_files = crate::io::load_recent_files();
+        // Snapshots only survive an unclean exit; offer one from a crashed session if it holds unsaved work.
+        let recovery_prompt = crate::io::recovery::recoverable_snapshot();
         Self {
             gpu: None,
             scene: Scene::new(),
@@ -154,6 +187,12 @@ impl App {
             screenshot_pending: false,
             screenshot_flash: 0.0,
             screenshot_last_path: None,
+            last_autosave: std::time::Instant::now(),
+            last_tileset_poll: std::time::Instant::now(),
+            autosaved_revision: 0,
+            autosave_thread: None,
+            recovery_prompt,
+            optimize_result: None,
         }
     }
 
@@ -206,6 +245,7 @@ impl ApplicationHandler for App {
 
         match &event {
             WindowEvent::CloseRequested => {
+                Self::discard_autosaves(&mut self.autosave_thread);
                 event_loop.exit();
             }
             WindowEvent::Resized(new_size) => {
@@ -459,7 +499,7 @@ impl App {
                 // Mouse released — place the fill
                 if !self.preview_faces.is_empty() {
                     let layer_idx = self.scene.active_layer;
-                    let (object_idx, create_object) = crate::tools::draw::find_target_object(&self.scene, layer_idx, self.scene.active_tileset);
+                    let (object_idx, create_object) = crate::tools::draw::find_target_object(&self.scene, layer_idx, self.scene.active_tileset, self.draw_state.combine_tilesets);
                     let cmd = commands::PlaceTile {
                         layer: layer_idx,
                         object: object_idx,
@@ -467,7 +507,7 @@ impl App {
                         create_object,
                         tileset_index: self.scene.active_tileset,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
                 self.rect_fill_start = None;
             } else if !shift_held {
@@ -531,7 +571,7 @@ impl App {
                         new_color: paint_color,
                         old_colors: Vec::new(),
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             } else if self.draw_state.tool == DrawTool::Block && self.draw_state.block_subtract {
                 // Block subtract mode: compute block AABB and remove overlapping faces
@@ -540,7 +580,7 @@ impl App {
                 {
                     let (aabb_min, aabb_max) = compute_faces_aabb(&result.faces);
                     let cmd = commands::SubtractBlock::new(aabb_min, aabb_max);
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             } else {
                 let backup = self.draw_state.apply_palette(&mut self.scene);
@@ -557,7 +597,7 @@ impl App {
                         create_object: result.create_object,
                         tileset_index: result.tileset_index,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
                 if let Some(b) = backup {
                     self.draw_state.restore_palette(&mut self.scene, b);
@@ -601,7 +641,7 @@ impl App {
                         create_object: result.create_object,
                         tileset_index: result.tileset_index,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             if let Some(b) = backup {
@@ -630,7 +670,7 @@ impl App {
             );
             if let Some((layer, object, face_index, face)) = self.draw_state.compute_erase(&self.scene, &ray) {
                 let cmd = commands::EraseTile { layer, object, face_index, face };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
             }
         }
 
@@ -649,12 +689,17 @@ impl App {
             if let Some(hit) = crate::util::picking::pick_face_culled(&ray, &self.scene) {
                 let face = &self.scene.layers[hit.layer_index].objects[hit.object_index].faces[hit.face_index];
                 let obj = &self.scene.layers[hit.layer_index].objects[hit.object_index];
-                if let Some(ts_idx) = obj.tileset_index {
+                if let Some(ts_idx) = obj.face_tileset(face) {
                     self.scene.active_tileset = Some(ts_idx);
-                    if let Some(tileset) = self.scene.tilesets.get(ts_idx) {
-                        let uv = face.uvs[0];
-                        let col = (uv.x * tileset.image_width as f32 / tileset.tile_width as f32).floor() as u32;
-                        let row = (uv.y * tileset.image_height as f32 / tileset.tile_height as f32).floor() as u32;
+                    // Sample just inside the top-left UV corner so rotated/flipped faces resolve
+                    // to the same tile, and multi-tile faces to their first tile.
+                    let uv_min = face.uvs.iter().fold(glam::Vec2::MAX, |m, &uv| m.min(uv));
+                    if let Some(tileset) = self.scene.tilesets.get(ts_idx)
+                        && let Some((col, row)) = tileset.tile_at_pixel(
+                            uv_min.x * tileset.image_width as f32 + 0.5,
+                            uv_min.y * tileset.image_height as f32 + 0.5,
+                        )
+                    {
                         self.draw_state.selected_tile = (col, row);
                         self.draw_state.selected_tile_end = (col, row);
                     }
@@ -736,7 +781,7 @@ impl App {
                                 let total_delta = cur - drag.start_point;
                                 let incremental = total_delta - drag.applied_delta;
                                 if incremental.length_squared() > 1e-8 {
-                                    Self::apply_translate_live(&self.edit_state.selection, &mut self.scene, incremental, &gpu.renderer.device);
+                                    Self::apply_translate_live(&self.edit_state.selection, &mut self.scene, incremental);
                                     drag.applied_delta = total_delta;
                                 }
                             }
@@ -748,7 +793,7 @@ impl App {
                                 let total_angle = angle - drag.start_angle;
                                 let incremental = total_angle - drag.applied_angle;
                                 if incremental.abs() > 1e-5 {
-                                    Self::apply_rotate_live(&self.edit_state.selection, &mut self.scene, rot_axis, incremental, drag.origin, &gpu.renderer.device);
+                                    Self::apply_rotate_live(&self.edit_state.selection, &mut self.scene, rot_axis, incremental, drag.origin);
                                     drag.applied_angle = total_angle;
                                 }
                             }
@@ -776,8 +821,8 @@ impl App {
                                     1.0 / drag.applied_scale.y,
                                     1.0 / drag.applied_scale.z,
                                 );
-                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, undo_scale, drag.origin, &gpu.renderer.device);
-                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, new_scale, drag.origin, &gpu.renderer.device);
+                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, undo_scale, drag.origin);
+                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, new_scale, drag.origin);
                                 drag.applied_scale = new_scale;
                             }
                         }
@@ -791,7 +836,7 @@ impl App {
                     match self.edit_state.gizmo_mode {
                         GizmoMode::Translate => {
                             if drag.applied_delta.length_squared() > 1e-6 {
-                                Self::apply_translate_live(&self.edit_state.selection, &mut self.scene, -drag.applied_delta, &gpu.renderer.device);
+                                Self::apply_translate_live(&self.edit_state.selection, &mut self.scene, -drag.applied_delta);
                                 // After undo, current state = pre-drag. Capture old_transforms.
                                 if has_instances {
                                     let targets = self.edit_state.selection.instances.clone();
@@ -805,7 +850,7 @@ impl App {
                                         (pos + drag.applied_delta, rot, scl)
                                     }).collect();
                                     let cmd = commands::TransformInstance { targets, old_transforms, new_transforms };
-                                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                    self.history.push(Box::new(cmd), &mut self.scene);
                                 }
                                 let cmd = commands::TranslateSelection {
                                     faces: self.edit_state.selection.faces.clone(),
@@ -813,12 +858,12 @@ impl App {
                                     vertices: self.edit_state.selection.vertices.clone(),
                                     delta: drag.applied_delta,
                                 };
-                                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                self.history.push(Box::new(cmd), &mut self.scene);
                             }
                         }
                         GizmoMode::Rotate => {
                             if drag.applied_angle.abs() > 1e-5 {
-                                Self::apply_rotate_live(&self.edit_state.selection, &mut self.scene, drag.axis.direction(), -drag.applied_angle, drag.origin, &gpu.renderer.device);
+                                Self::apply_rotate_live(&self.edit_state.selection, &mut self.scene, drag.axis.direction(), -drag.applied_angle, drag.origin);
                                 if has_instances {
                                     let quat = glam::Quat::from_axis_angle(drag.axis.direction(), drag.applied_angle);
                                     let targets = self.edit_state.selection.instances.clone();
@@ -832,7 +877,7 @@ impl App {
                                         (quat * (pos - drag.origin) + drag.origin, quat * rot, scl)
                                     }).collect();
                                     let cmd = commands::TransformInstance { targets, old_transforms, new_transforms };
-                                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                    self.history.push(Box::new(cmd), &mut self.scene);
                                 }
                                 let cmd = commands::RotateSelection {
                                     faces: self.edit_state.selection.faces.clone(),
@@ -842,7 +887,7 @@ impl App {
                                     angle: drag.applied_angle,
                                     center: drag.origin,
                                 };
-                                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                self.history.push(Box::new(cmd), &mut self.scene);
                             }
                         }
                         GizmoMode::Scale => {
@@ -852,7 +897,7 @@ impl App {
                                     1.0 / drag.applied_scale.y,
                                     1.0 / drag.applied_scale.z,
                                 );
-                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, undo_scale, drag.origin, &gpu.renderer.device);
+                                Self::apply_scale_live(&self.edit_state.selection, &mut self.scene, undo_scale, drag.origin);
                                 if has_instances {
                                     let targets = self.edit_state.selection.instances.clone();
                                     let old_transforms: Vec<_> = targets.iter().filter_map(|&(li, oi, ii)| {
@@ -865,7 +910,7 @@ impl App {
                                         (drag.origin + (pos - drag.origin) * drag.applied_scale, rot, scl * drag.applied_scale)
                                     }).collect();
                                     let cmd = commands::TransformInstance { targets, old_transforms, new_transforms };
-                                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                    self.history.push(Box::new(cmd), &mut self.scene);
                                 }
                                 let cmd = commands::ScaleSelection {
                                     faces: self.edit_state.selection.faces.clone(),
@@ -874,7 +919,7 @@ impl App {
                                     scale_factor: drag.applied_scale,
                                     center: drag.origin,
                                 };
-                                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                                self.history.push(Box::new(cmd), &mut self.scene);
                             }
                         }
                     }
@@ -885,7 +930,6 @@ impl App {
                             &self.edit_state.selection.faces,
                             &self.edit_state.selection.objects,
                             &self.edit_state.selection.vertices,
-                            &gpu.renderer.device,
                         );
                     }
                 }
@@ -958,15 +1002,9 @@ impl App {
                         let incremental = total_delta - drag.applied_delta;
                         if incremental.length_squared() > 1e-8 {
                             for &(li, oi, fi, vi, _) in &drag.targets {
-                                self.scene.layers[li].objects[oi].faces[fi].positions[vi] += incremental;
-                            }
-                            // Rebuild affected GPU meshes
-                            let mut rebuild = std::collections::HashSet::new();
-                            for &(li, oi, _, _, _) in &drag.targets {
-                                rebuild.insert((li, oi));
-                            }
-                            for (li, oi) in rebuild {
-                                self.scene.layers[li].objects[oi].rebuild_gpu_mesh(&gpu.renderer.device);
+                                let obj = &mut self.scene.layers[li].objects[oi];
+                                obj.faces[fi].positions[vi] += incremental;
+                                obj.mark_face_dirty(fi);
                             }
                             drag.applied_delta = total_delta;
                         }
@@ -977,14 +1015,9 @@ impl App {
                     if drag.applied_delta.length_squared() > 1e-6 {
                         // Undo the live preview
                         for &(li, oi, fi, vi, _) in &drag.targets {
-                            self.scene.layers[li].objects[oi].faces[fi].positions[vi] -= drag.applied_delta;
-                        }
-                        let mut rebuild = std::collections::HashSet::new();
-                        for &(li, oi, _, _, _) in &drag.targets {
-                            rebuild.insert((li, oi));
-                        }
-                        for (li, oi) in &rebuild {
-                            self.scene.layers[*li].objects[*oi].rebuild_gpu_mesh(&gpu.renderer.device);
+                            let obj = &mut self.scene.layers[li].objects[oi];
+                            obj.faces[fi].positions[vi] -= drag.applied_delta;
+                            obj.mark_face_dirty(fi);
                         }
 
                         // Push as TranslateSelection with the specific vertices
@@ -998,7 +1031,7 @@ impl App {
                             vertices,
                             delta: drag.applied_delta,
                         };
-                        self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                        self.history.push(Box::new(cmd), &mut self.scene);
                         // Auto-flatten UVs after vertex drag
                         if self.settings.edit.auto_flatten_uvs {
                             auto_flatten_selection_uvs(
@@ -1006,7 +1039,6 @@ impl App {
                                 &[],
                                 &[],
                                 &flatten_verts,
-                                &gpu.renderer.device,
                             );
                         }
                     }
@@ -1075,14 +1107,13 @@ impl App {
                     vertices: self.edit_state.selection.vertices.clone(),
                     delta,
                 };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
                 if self.settings.edit.auto_flatten_uvs {
                     auto_flatten_selection_uvs(
                         &mut self.scene,
                         &self.edit_state.selection.faces,
                         &self.edit_state.selection.objects,
                         &self.edit_state.selection.vertices,
-                        &gpu.renderer.device,
                     );
                 }
             }
@@ -1109,7 +1140,7 @@ impl App {
                 angle,
                 center,
             };
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
         }
 
         // Edit mode: Flip normals (F)
@@ -1122,7 +1153,7 @@ impl App {
                 faces: self.edit_state.selection.faces.clone(),
                 objects: self.edit_state.selection.objects.clone(),
             };
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
         }
 
         // Edit mode: Extrude faces (E)
@@ -1135,7 +1166,7 @@ impl App {
                 self.edit_state.selection.faces.clone(),
                 self.scene.grid_cell_size,
             );
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
         }
 
         // Edit mode: Scale selection (+/- keys when GizmoMode::Scale)
@@ -1160,7 +1191,7 @@ impl App {
                     scale_factor: factor,
                     center,
                 };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
             }
         }
 
@@ -1176,7 +1207,7 @@ impl App {
                 new_uvs,
                 old_uvs: Vec::new(),
             };
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
         }
 
         // Edit mode: Center camera on selection (C)
@@ -1213,7 +1244,7 @@ impl App {
             }
 
             let cmd = commands::DeleteSelection { removed_faces, removed_objects };
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
             self.edit_state.selection.clear();
         }
 
@@ -1226,7 +1257,7 @@ impl App {
             let cmd = commands::SubdivideFaces::new(
                 self.edit_state.selection.faces.clone(),
             );
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
             self.edit_state.selection.clear();
         }
 
@@ -1251,7 +1282,7 @@ impl App {
                 self.scene.active_layer,
                 name,
             );
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
             self.edit_state.selection.clear();
         }
 
@@ -1294,7 +1325,7 @@ impl App {
             }
             if !to_hide.is_empty() {
                 let cmd = commands::HideFaces { faces: to_hide };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
                 self.edit_state.selection.clear();
             }
         }
@@ -1313,7 +1344,7 @@ impl App {
             }
             if !previously_hidden.is_empty() {
                 let cmd = commands::ShowAllFaces { previously_hidden };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
             }
         }
 
@@ -1328,10 +1359,10 @@ impl App {
         // Undo/Redo hotkeys
         let ctrl = self.input.key_held(KeyCode::ControlLeft) || self.input.key_held(KeyCode::ControlRight);
         if self.keybindings.is_triggered(crate::keybindings::Action::Undo, &self.input) {
-            self.history.undo(&mut self.scene, &gpu.renderer.device);
+            self.history.undo(&mut self.scene);
         }
         if self.keybindings.is_triggered(crate::keybindings::Action::Redo, &self.input) {
-            self.history.redo(&mut self.scene, &gpu.renderer.device);
+            self.history.redo(&mut self.scene);
         }
 
         // New scene (confirm if unsaved)
@@ -1383,15 +1414,13 @@ impl App {
             let mut tileset_index = None;
 
             for &(li, oi, fi) in &self.edit_state.selection.faces {
-                if let Some(face) = self.scene.layers.get(li)
-                    .and_then(|l| l.objects.get(oi))
-                    .and_then(|o| o.faces.get(fi))
+                if let Some(obj) = self.scene.layers.get(li).and_then(|l| l.objects.get(oi))
+                    && let Some(face) = obj.faces.get(fi)
                 {
-                    faces.push(face.clone());
+                    // Faces remember their tileset so a mixed selection pastes intact.
+                    faces.push(Face { tileset: obj.face_tileset(face), ..face.clone() });
                     if tileset_index.is_none() {
-                        tileset_index = self.scene.layers.get(li)
-                            .and_then(|l| l.objects.get(oi))
-                            .and_then(|o| o.tileset_index);
+                        tileset_index = obj.tileset_index;
                     }
                 }
             }
@@ -1399,7 +1428,7 @@ impl App {
             for &(li, oi) in &self.edit_state.selection.objects {
                 if let Some(obj) = self.scene.layers.get(li).and_then(|l| l.objects.get(oi)) {
                     for face in &obj.faces {
-                        faces.push(face.clone());
+                        faces.push(Face { tileset: obj.face_tileset(face), ..face.clone() });
                     }
                     if tileset_index.is_none() {
                         tileset_index = obj.tileset_index;
@@ -1437,7 +1466,7 @@ impl App {
             if !pasted_faces.is_empty() {
                 let layer_idx = self.scene.active_layer;
                 let ts_idx = clip.tileset_index;
-                let (object_idx, create_object) = crate::tools::draw::find_target_object(&self.scene, layer_idx, ts_idx);
+                let (object_idx, create_object) = crate::tools::draw::find_target_object(&self.scene, layer_idx, ts_idx, self.draw_state.combine_tilesets);
                 let cmd = commands::PlaceTile {
                     layer: layer_idx,
                     object: object_idx,
@@ -1445,7 +1474,7 @@ impl App {
                     create_object,
                     tileset_index: ts_idx,
                 };
-                self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                self.history.push(Box::new(cmd), &mut self.scene);
             }
         }
 
@@ -1693,6 +1722,49 @@ impl App {
                 }
             }
 
+            // Crash recovery prompt
+            if let Some(ref snapshot) = self.recovery_prompt {
+                let source = snapshot.source.as_ref()
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "an unsaved scene".to_string());
+                let minutes = snapshot.modified.elapsed().map(|d| d.as_secs() / 60).unwrap_or(0);
+                egui::Window::new("Recover Autosave")
+                    .collapsible(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "Cracktile 3D did not exit cleanly. An autosave of {source} from {minutes} min ago is newer than the last save."
+                        ));
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Restore").clicked() { ui_result.action = UiAction::RestoreAutosave; }
+                            if ui.button("Discard").clicked() { ui_result.action = UiAction::DiscardAutosave; }
+                        });
+                    });
+            }
+
+            // Optimize result dialog
+            if let Some(ref result) = self.optimize_result {
+                let report = result.report;
+                let mut dismissed = false;
+                egui::Window::new(result.title)
+                    .collapsible(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!("Faces: {} → {}", report.faces_before, report.faces_after));
+                        ui.label(format!("Vertices: {} → {}", report.vertices_before, report.vertices_after));
+                        if report.faces_after == report.faces_before {
+                            ui.weak("No coplanar faces could be merged.");
+                        }
+                        ui.add_space(8.0);
+                        if ui.button("OK").clicked() { dismissed = true; }
+                    });
+                if dismissed {
+                    self.optimize_result = None;
+                }
+            }
+
             // Tile size dialog
             if let Some(ref mut pending) = pending_tileset {
                 let mut confirmed = false;
@@ -1709,6 +1781,39 @@ impl App {
                             ui.label("Tile Height:");
                             ui.add(egui::DragValue::new(&mut pending.tile_height).range(1..=512));
                         });
+                        ui.horizontal(|ui| {
+                            ui.label("Margin:");
+                            ui.add(egui::DragValue::new(&mut pending.margin).range(0..=256));
+                            ui.label("Spacing:");
+                            ui.add(egui::DragValue::new(&mut pending.spacing).range(0..=256));
+                        });
+                        if let Some(ref mut ase) = pending.aseprite {
+                            ui.separator();
+                            if ase.frame_count > 1 {
+                                ui.horizontal(|ui| {
+                                    ui.label("Frame:");
+                                    let mut frame = ase.selection.frame + 1;
+                                    ui.add(egui::DragValue::new(&mut frame).range(1..=ase.frame_count));
+                                    ase.selection.frame = frame - 1;
+                                });
+                            }
+                            let layer_label = |layer: Option<usize>| match layer {
+                                Some(i) => ase.layers.iter().find(|(l, _)| *l == i).map(|(_, n)| n.clone()).unwrap_or_default(),
+                                None => "All visible layers".to_string(),
+                            };
+                            let selected = layer_label(ase.selection.layer);
+                            ui.horizontal(|ui| {
+                                ui.label("Layer:");
+                                egui::ComboBox::from_id_salt("aseprite_layer")
+                                    .selected_text(selected)
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut ase.selection.layer, None, "All visible layers");
+                                        for (i, name) in &ase.layers {
+                                            ui.selectable_value(&mut ase.selection.layer, Some(*i), name);
+                                        }
+                                    });
+                            });
+                        }
                         ui.add_space(4.0);
                         ui.horizontal(|ui| {
                             if ui.button("Load").clicked() { confirmed = true; }
@@ -1762,7 +1867,7 @@ impl App {
                 new_uvs: commit.new_uvs,
                 new_colors: commit.new_colors,
             };
-            self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+            self.history.push(Box::new(cmd), &mut self.scene);
         }
 
         // Handle UI actions
@@ -1776,59 +1881,65 @@ impl App {
                 self.property_snapshot = None;
             }
             UiAction::Undo => {
-                self.history.undo(&mut self.scene, &gpu.renderer.device);
+                self.history.undo(&mut self.scene);
             }
             UiAction::Redo => {
-                self.history.redo(&mut self.scene, &gpu.renderer.device);
+                self.history.redo(&mut self.scene);
             }
             UiAction::LoadTileset => {
                 let file = rfd::FileDialog::new()
-                    .add_filter("Images", &["png", "jpg", "jpeg", "bmp"])
+                    .add_filter("Images", &["png", "jpg", "jpeg", "bmp", "ase", "aseprite"])
                     .set_title("Load Tileset Image")
                     .pick_file();
 
                 if let Some(path) = file {
-                    self.pending_tileset = Some(PendingTilesetLoad {
+                    let mut pending = PendingTilesetLoad {
                         path,
                         tile_width: 16,
                         tile_height: 16,
-                    });
+                        margin: 0,
+                        spacing: 0,
+                        aseprite: None,
+                    };
+                    if crate::tile::is_aseprite(&pending.path) {
+                        match crate::tile::aseprite::read(&pending.path) {
+                            Ok(sprite) => {
+                                (pending.tile_width, pending.tile_height) = sprite.tile_size();
+                                pending.aseprite = Some(PendingAseprite {
+                                    frame_count: sprite.frame_count(),
+                                    layers: sprite.layers.iter().enumerate()
+                                        .filter(|(_, l)| l.kind != crate::tile::aseprite::LayerKind::Group)
+                                        .map(|(i, l)| (i, format!("{}{}", "  ".repeat(l.depth as usize), l.name)))
+                                        .collect(),
+                                    selection: Default::default(),
+                                });
+                            }
+                            Err(e) => log::error!("Failed to read Aseprite file: {e}"),
+                        }
+                    }
+                    self.pending_tileset = Some(pending);
                 }
             }
             UiAction::ConfirmTilesetLoad => {
                 if let Some(pending) = self.pending_tileset.take() {
-                    Self::do_load_tileset(
-                        &mut self.scene,
-                        &mut gpu.egui_renderer,
-                        &gpu.renderer,
-                        &pending.path,
-                        pending.tile_width,
-                        pending.tile_height,
-                    );
+                    Self::do_load_tileset(&mut self.scene, &mut gpu.egui_renderer, &gpu.renderer, pending);
                 }
             }
             UiAction::RemoveTileset(idx) => {
                 // Check if any objects reference this tileset
                 let refs: usize = self.scene.layers.iter()
                     .flat_map(|l| &l.objects)
-                    .filter(|o| o.tileset_index == Some(idx))
+                    .filter(|o| o.tileset_index == Some(idx) || o.faces.iter().any(|f| f.tileset == Some(idx)))
                     .count();
                 if refs > 0 {
                     log::warn!("Tileset {} is referenced by {refs} object(s) — clearing their tileset reference", idx);
                 }
-                // Clear tileset references on affected objects
-                for layer in &mut self.scene.layers {
-                    for obj in &mut layer.objects {
-                        if obj.tileset_index == Some(idx) {
-                            obj.tileset_index = None;
-                        } else if let Some(ti) = obj.tileset_index {
-                            // Shift down references above the removed index
-                            if ti > idx {
-                                obj.tileset_index = Some(ti - 1);
-                            }
-                        }
-                    }
-                }
+                // Clear references to the removed tileset and shift down those above it
+                self.scene.remap_tileset_refs(|ti| match ti.cmp(&idx) {
+                    std::cmp::Ordering::Less => Some(ti),
+                    std::cmp::Ordering::Equal => None,
+                    std::cmp::Ordering::Greater => Some(ti - 1),
+                });
                 self.scene.tilesets.remove(idx);
                 // Fix active_tileset
                 if self.scene.tilesets.is_empty() {
@@ -1846,21 +1957,10 @@ impl App {
             }
             UiAction::DuplicateTileset(idx) => {
                 if let Some(ts) = self.scene.tilesets.get(idx)
-                    && let Some(ref data) = ts.image_data
+                    && ts.image_data.is_some()
                 {
-                    let mat = ts.material.clone();
-                    let mut dup = crate::tile::Tileset {
-                        name: format!("{} (copy)", ts.name),
-                        image_width: ts.image_width,
-                        image_height: ts.image_height,
-                        tile_width: ts.tile_width,
-                        tile_height: ts.tile_height,
-                        gpu_texture: None,
-                        bind_group: None,
-                        egui_texture_id: None,
-                        image_data: Some(data.clone()),
-                        material: mat,
-                    };
+                    let mut dup = ts.cpu_copy();
+                    dup.name = format!("{} (copy)", ts.name);
                     // Recreate GPU resources from the cloned image data
                     Self::create_gpu_tileset(
                         &mut dup,
@@ -1891,6 +1991,7 @@ impl App {
                                 ts.image_width = w;
                                 ts.image_height = h;
                                 ts.image_data = Some(raw);
+                                ts.sync_indexed();
                                 ts.name = path.file_stem()
                                     .map(|s| s.to_string_lossy().to_string())
                                     .unwrap_or_default();
@@ -1904,14 +2005,15 @@ impl App {
                                 // Re-register with egui
                                 ts.egui_texture_id = None;
                                 ts.register_with_egui(&mut gpu.egui_renderer, &gpu.renderer.device, &gpu.renderer.queue);
-                                // Rebuild all objects using this tileset to pick up texture changes
+                                // Re-upload all objects using this tileset to pick up texture changes
                                 for layer in &mut self.scene.layers {
                                     for obj in &mut layer.objects {
                                         if obj.tileset_index == Some(idx) {
-                                            obj.rebuild_gpu_mesh(&gpu.renderer.device);
+                                            obj.mark_faces_dirty_from(0);
                                         }
                                     }
                                 }
+                                self.scene.atlas_dirty = true;
                                 log::info!("Replaced tileset {idx} with {:?}", path);
                             }
                         }
@@ -1944,44 +2046,28 @@ impl App {
                 }
             }
             UiAction::RemoveUnusedTilesets => {
-                // Find which tileset indices are referenced
-                let mut used = std::collections::HashSet::new();
-                for layer in &self.scene.layers {
-                    for obj in &layer.objects {
-                        if let Some(ti) = obj.tileset_index {
-                            used.insert(ti);
-                        }
+                // Find which tileset indices are referenced (by objects or individual faces)
+                let used = self.scene.used_tilesets();
+                // New index of each kept tileset
+                let total = self.scene.tilesets.len();
+                let mut new_index = vec![None; total];
+                let mut kept = 0usize;
+                for (i, slot) in new_index.iter_mut().enumerate() {
+                    if used.contains(&i) {
+                        *slot = Some(kept);
+                        kept += 1;
                     }
                 }
                 // Remove from highest index to lowest to preserve indices
-                let total = self.scene.tilesets.len();
-                let mut removed = 0usize;
                 for i in (0..total).rev() {
-                    if !used.contains(&i) {
+                    if new_index[i].is_none() {
                         self.scene.tilesets.remove(i);
-                        // Shift down references above this index
-                        for layer in &mut self.scene.layers {
-                            for obj in &mut layer.objects {
-                                if let Some(ti) = obj.tileset_index
-                                    && ti > i
-                                {
-                                    obj.tileset_index = Some(ti - 1);
-                                }
-                            }
-                        }
-                        // Update used set for subsequent removals
-                        let mut new_used = std::collections::HashSet::new();
-                        for &u in &used {
-                            if u > i {
-                                new_used.insert(u - 1);
-                            } else {
-                                new_used.insert(u);
-                            }
-                        }
-                        used = new_used;
-                        removed += 1;
                     }
                 }
+                let removed = total - kept;
+                if removed > 0 {
+                    self.scene.remap_tileset_refs(|ti| new_index.get(ti).copied().flatten());
+                }
                 // Fix active_tileset
                 if self.scene.tilesets.is_empty() {
                     self.scene.active_tileset = None;
@@ -1998,6 +2084,7 @@ impl App {
                         Ok(()) => {
                             log::info!("Saved scene to {:?}", path);
                             self.history.mark_saved();
+                            Self::discard_autosaves(&mut self.autosave_thread);
                             self.recent_files.retain(|p| p != &path);
                             self.recent_files.insert(0, path);
                             self.recent_files.truncate(10);
@@ -2006,11 +2093,11 @@ impl App {
                         Err(e) => log::error!("Failed to save: {e}"),
                     }
                 } else {
-                    Self::do_save_scene(&self.scene, &mut self.last_save_path, &mut self.history, &mut self.recent_files);
+                    Self::do_save_scene(&self.scene, &mut self.last_save_path, &mut self.history, &mut self.recent_files, &mut self.autosave_thread);
                 }
             }
             UiAction::SaveSceneAs => {
-                Self::do_save_scene(&self.scene, &mut self.last_save_path, &mut self.history, &mut self.recent_files);
+                Self::do_save_scene(&self.scene, &mut self.last_save_path, &mut self.history, &mut self.recent_files, &mut self.autosave_thread);
             }
             UiAction::OpenScene => {
                 Self::do_open_scene(
@@ -2018,6 +2105,7 @@ impl App {
                     &mut self.edit_state,
                     &mut self.history,
                     &gpu.renderer,
+                    &mut gpu.egui_renderer,
                     &mut self.last_save_path,
                     &mut self.recent_files,
                 );
@@ -2026,11 +2114,7 @@ impl App {
                 if let Some(path) = self.recent_files.get(idx).cloned() {
                     match crate::io::load_scene(&path) {
                         Ok(mut loaded) => {
-                            for layer in &mut loaded.layers {
-                                for obj in &mut layer.objects {
-                                    obj.rebuild_gpu_mesh(&gpu.renderer.device);
-                                }
-                            }
+                            Self::upload_loaded_scene(&mut loaded, &self.scene, &gpu.renderer, &mut gpu.egui_renderer);
                             self.scene = loaded;
                             self.edit_state.selection.clear();
                             self.history.clear();
@@ -2045,29 +2129,71 @@ impl App {
                     }
                 }
             }
+            UiAction::RestoreAutosave => {
+                if let Some(snapshot) = self.recovery_prompt.take() {
+                    match crate::io::load_scene(&snapshot.path) {
+                        Ok(mut loaded) => {
+                            Self::upload_loaded_scene(&mut loaded, &self.scene, &gpu.renderer, &mut gpu.egui_renderer);
+                            self.scene = loaded;
+                            self.edit_state.selection.clear();
+                            self.history.clear();
+                            // Restored work is unsaved until the user saves it over the source.
+                            self.history.dirty = true;
+                            self.autosaved_revision = self.history.revision;
+                            crate::io::recovery::adopt_session(&snapshot);
+                            log::info!("Restored autosave {:?}", snapshot.path);
+                            self.last_save_path = snapshot.source;
+                        }
+                        Err(e) => log::error!("Failed to restore autosave: {e}"),
+                    }
+                }
+            }
+            UiAction::DiscardAutosave => {
+                if let Some(snapshot) = self.recovery_prompt.take() {
+                    crate::io::recovery::discard_session(&snapshot);
+                }
+            }
             UiAction::ExportObj => {
-                Self::do_export_obj(&self.scene);
+                if let Some(report) = Self::do_export_obj(&self.scene, &self.settings.file.obj_export_options()) {
+                    self.optimize_result = Some(OptimizeResult { title: "Optimized OBJ Export", report });
+                }
             }
             UiAction::ExportGlb => {
-                Self::do_export_glb(&self.scene);
+                if let Some(report) = Self::do_export_glb(&self.scene, &self.settings.file.gltf_export_options()) {
+                    self.optimize_result = Some(OptimizeResult { title: "Optimized GLB Export", report });
+                }
             }
             UiAction::ImportObj => {
-                Self::do_import_obj(&mut self.scene, &mut self.history, &gpu.renderer);
+                Self::do_import_obj(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
             }
             UiAction::ImportGlb => {
-                Self::do_import_glb(&mut self.scene, &mut self.history, &gpu.renderer);
+                Self::do_import_glb(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
             }
             UiAction::ExportGltf => {
-                Self::do_export_gltf(&self.scene);
+                if let Some(report) = Self::do_export_gltf(&self.scene, &self.settings.file.gltf_export_options()) {
+                    self.optimize_result = Some(OptimizeResult { title: "Optimized glTF Export", report });
+                }
             }
             UiAction::ExportDae => {
                 Self::do_export_dae(&self.scene);
             }
             UiAction::ImportGltf => {
-                Self::do_import_gltf(&mut self.scene, &mut self.history, &gpu.renderer);
+                Self::do_import_gltf(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
             }
             UiAction::ImportDae => {
-                Self::do_import_dae(&mut self.scene, &mut self.history, &gpu.renderer);
+                Self::do_import_dae(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
+            }
+            UiAction::ImportVox => {
+                Self::do_import_vox(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
+            }
+            UiAction::ExportVox => {
+                Self::do_export_vox(&self.scene);
+            }
+            UiAction::ImportTiled => {
+                Self::do_import_tiled(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
+            }
+            UiAction::ImportLdtk => {
+                Self::do_import_ldtk(&mut self.scene, &mut self.history, &gpu.renderer, &mut gpu.egui_renderer);
             }
             UiAction::ToggleWireframe => {
                 self.wireframe = !self.wireframe;
@@ -2123,7 +2249,7 @@ impl App {
                         angle: std::f32::consts::FRAC_PI_2,
                         center,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::RotateCCW => {
@@ -2137,7 +2263,7 @@ impl App {
                         angle: -std::f32::consts::FRAC_PI_2,
                         center,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::FlipNormals => {
@@ -2146,7 +2272,7 @@ impl App {
                         faces: self.edit_state.selection.faces.clone(),
                         objects: self.edit_state.selection.objects.clone(),
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::ExtrudeFaces => {
@@ -2155,7 +2281,7 @@ impl App {
                         self.edit_state.selection.faces.clone(),
                         self.scene.grid_cell_size,
                     );
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::Retile => {
@@ -2166,7 +2292,7 @@ impl App {
                         new_uvs,
                         old_uvs: Vec::new(),
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::SubdivideFaces => {
@@ -2174,7 +2300,7 @@ impl App {
                     let cmd = commands::SubdivideFaces::new(
                         self.edit_state.selection.faces.clone(),
                     );
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
@@ -2184,7 +2310,7 @@ impl App {
                         self.edit_state.selection.faces.clone(),
                         diagonal,
                     );
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
@@ -2193,7 +2319,7 @@ impl App {
                 let pairs = find_triangle_merge_pairs(&self.scene, &self.edit_state.selection.faces);
                 if !pairs.is_empty() {
                     let cmd = commands::TriangleMerge::new(pairs);
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
@@ -2210,46 +2336,67 @@ impl App {
                     }
                 }
             }
+            UiAction::OptimizeMesh => {
+                // Selected objects and those owning selected faces; the whole scene when nothing is selected
+                let sel = &self.edit_state.selection;
+                let mut objects: Vec<(usize, usize)> = sel.objects.iter().copied()
+                    .chain(sel.faces.iter().map(|&(li, oi, _)| (li, oi)))
+                    .collect();
+                if objects.is_empty() {
+                    objects = self.scene.layers.iter().enumerate()
+                        .flat_map(|(li, l)| (0..l.objects.len()).map(move |oi| (li, oi)))
+                        .collect();
+                }
+                objects.sort_unstable();
+                objects.dedup();
+                let cmd = commands::OptimizeMesh::new(&self.scene, &objects);
+                log::info!("Optimize mesh: {}", cmd.report);
+                self.optimize_result = Some(OptimizeResult { title: "Optimize Mesh", report: cmd.report });
+                if !cmd.is_empty() {
+                    self.history.push(Box::new(cmd), &mut self.scene);
+                    self.edit_state.selection.clear();
+                }
+            }
             UiAction::PushVertices => {
                 let moves = compute_push_pull_moves(&self.scene, &self.edit_state.selection, self.scene.grid_cell_size);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::PullVertices => {
                 let moves = compute_push_pull_moves(&self.scene, &self.edit_state.selection, -self.scene.grid_cell_size);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::CenterToX => {
                 let moves = compute_center_moves(&self.scene, &self.edit_state.selection, 0, self.scene.crosshair_pos.x);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::CenterToY => {
                 let moves = compute_center_moves(&self.scene, &self.edit_state.selection, 1, self.scene.crosshair_pos.y);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::CenterToZ => {
                 let moves = compute_center_moves(&self.scene, &self.edit_state.selection, 2, self.scene.crosshair_pos.z);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::StraightenVertices => {
                 let moves = compute_straighten_moves(&self.scene, &self.edit_state.selection);
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::DeleteSelection => {
@@ -2270,7 +2417,7 @@ impl App {
                         }
                     }
                     let cmd = commands::DeleteSelection { removed_faces, removed_objects };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
@@ -2311,50 +2458,50 @@ impl App {
             }
             // UV operations
             UiAction::UVRotateCW => {
-                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, |uvs| {
+                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, |uvs| {
                     [uvs[3], uvs[0], uvs[1], uvs[2]]
                 });
             }
             UiAction::UVRotateCCW => {
-                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, |uvs| {
+                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, |uvs| {
                     [uvs[1], uvs[2], uvs[3], uvs[0]]
                 });
             }
             UiAction::UVFlipH => {
-                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, |uvs| {
+                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, |uvs| {
                     [uvs[1], uvs[0], uvs[3], uvs[2]]
                 });
             }
             UiAction::UVFlipV => {
-                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, |uvs| {
+                Self::apply_uv_op(&self.edit_state, &mut self.scene, &mut self.history, |uvs| {
                     [uvs[3], uvs[2], uvs[1], uvs[0]]
                 });
             }
             // Geometry operations
             UiAction::MergeVertices => {
-                Self::apply_merge_vertices(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device);
+                Self::apply_merge_vertices(&self.edit_state, &mut self.scene, &mut self.history);
             }
             UiAction::MirrorX => {
-                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, 0);
+                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, 0);
             }
             UiAction::MirrorY => {
-                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, 1);
+                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, 1);
             }
             UiAction::MirrorZ => {
-                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, &gpu.renderer.device, 2);
+                Self::apply_mirror(&self.edit_state, &mut self.scene, &mut self.history, 2);
             }
             // Edge operations
             UiAction::SplitEdge => {
                 if !self.edit_state.selection.edges.is_empty() {
                     let cmd = commands::SplitEdge::new(self.edit_state.selection.edges.clone());
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
             UiAction::CollapseEdge => {
                 if !self.edit_state.selection.edges.is_empty() {
                     let cmd = commands::CollapseEdge::new(self.edit_state.selection.edges.clone());
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                     self.edit_state.selection.clear();
                 }
             }
@@ -2370,6 +2517,7 @@ impl App {
                 }
             }
             UiAction::Quit => {
+                Self::discard_autosaves(&mut self.autosave_thread);
                 std::process::exit(0);
             }
             UiAction::UvVertexDrag { targets, original_uvs, delta } => {
@@ -2407,32 +2555,154 @@ impl App {
                         old_uvs: old_uvs_list,
                         new_uvs: new_uvs_list,
                     };
-                    self.history.push(Box::new(cmd), &mut self.scene, &gpu.renderer.device);
+                    self.history.push(Box::new(cmd), &mut self.scene);
                 }
             }
             UiAction::RebuildMaterial(idx) => {
                 if let Some(tileset) = self.scene.tilesets.get_mut(idx) {
-                    tileset.rebuild_bind_group(
+                    // Re-upload too: edge extrusion changes the texture itself.
+                    Self::create_gpu_tileset(
+                        tileset,
                         &gpu.renderer.device,
+                        &gpu.renderer.queue,
                         &gpu.renderer.tile_bind_group_layout,
                     );
+                    self.scene.atlas_dirty = true;
                     self.has_unsaved_changes = true;
                 }
             }
+            UiAction::TilePropertiesChanged => {
+                self.has_unsaved_changes = true;
+            }
+            UiAction::LoadMaterialMap(idx, channel) => {
+                let file = rfd::FileDialog::new()
+                    .add_filter("Images", &["png", "jpg", "jpeg", "bmp"])
+                    .set_title(format!("Load {} Map", channel.label()))
+                    .pick_file();
+
+                if let Some(path) = file
+                    && let Some(tileset) = self.scene.tilesets.get_mut(idx)
+                {
+                    match crate::tile::maps::read_map_rgba(&path, tileset.image_width, tileset.image_height) {
+                        Ok(data) => {
+                            *tileset.maps.get_mut(channel) = Some(data);
+                            Self::create_gpu_tileset(
+                                tileset,
+                                &gpu.renderer.device,
+                                &gpu.renderer.queue,
+                                &gpu.renderer.tile_bind_group_layout,
+                            );
+                            self.has_unsaved_changes = true;
+                            log::info!("Loaded {} map for tileset {idx} from {:?}", channel.label(), path);
+                        }
+                        Err(e) => log::error!("Failed to load {} map: {e}", channel.label()),
+                    }
+                }
+            }
+            UiAction::ClearMaterialMap(idx, channel) => {
+                if let Some(tileset) = self.scene.tilesets.get_mut(idx) {
+                    *tileset.maps.get_mut(channel) = None;
+                    Self::create_gpu_tileset(
+                        tileset,
+                        &gpu.renderer.device,
+                        &gpu.renderer.queue,
+                        &gpu.renderer.tile_bind_group_layout,
+                    );
+                    self.has_unsaved_changes = true;
+                }
+            }
+            UiAction::ConvertTilesetToIndexed(idx) => {
+                if let Some(tileset) = self.scene.tilesets.get_mut(idx)
+                    && let Some(ref data) = tileset.image_data
+                {
+                    match crate::tile::indexed::IndexedColors::from_rgba(data) {
+                        Ok(indexed) => {
+                            log::info!("Indexed tileset {idx} with {} colors", indexed.colors.len());
+                            tileset.indexed = Some(indexed);
+                            Self::refresh_indexed_tileset(tileset, &gpu.renderer, &mut gpu.egui_renderer);
+                            self.has_unsaved_changes = true;
+                        }
+                        Err(e) => log::error!("Failed to index tileset: {e}"),
+                    }
+                }
+            }
+            UiAction::ConvertTilesetWithPalette(idx) => {
+                let file = rfd::FileDialog::new()
+                    .add_filter("Palettes", &["gpl", "hex", "pal"])
+                    .set_title("Convert Tileset with Palette")
+                    .pick_file();
+
+                if let Some(path) = file
+                    && let Some(tileset) = self.scene.tilesets.get_mut(idx)
+                    && let Some(ref data) = tileset.image_data
+                {
+                    match crate::tile::indexed::read_palette_file(&path) {
+                        Ok(colors) => {
+                            tileset.indexed = Some(crate::tile::indexed::IndexedColors::quantize(data, colors));
+                            Self::refresh_indexed_tileset(tileset, &gpu.renderer, &mut gpu.egui_renderer);
+                            self.scene.atlas_dirty = true;
+                            self.has_unsaved_changes = true;
+                            log::info!("Indexed tileset {idx} with palette {:?}", path);
+                        }
+                        Err(e) => log::error!("Failed to load palette: {e}"),
+                    }
+                }
+            }
+            UiAction::RevertTilesetToRgba(idx) => {
+                if let Some(tileset) = self.scene.tilesets.get_mut(idx) {
+                    tileset.indexed = None;
+                    Self::refresh_indexed_tileset(tileset, &gpu.renderer, &mut gpu.egui_renderer);
+                    self.has_unsaved_changes = true;
+                }
+            }
+            UiAction::IndexedColorsChanged(idx) => {
+                if let Some(tileset) = self.scene.tilesets.get_mut(idx) {
+                    Self::refresh_indexed_tileset(tileset, &gpu.renderer, &mut gpu.egui_renderer);
+                    self.scene.atlas_dirty = true;
+                    self.has_unsaved_changes = true;
+                }
+            }
+            UiAction::ImportPaletteVariant(idx) => {
+                let file = rfd::FileDialog::new()
+                    .add_filter("Palettes", &["gpl", "hex", "pal"])
+                    .set_title("Import Palette Variant")
+                    .pick_file();
+
+                if let Some(path) = file
+                    && let Some(tileset) = self.scene.tilesets.get_mut(idx)
+                    && let Some(ref mut indexed) = tileset.indexed
+                {
+                    match crate::tile::indexed::read_palette_file(&path) {
+                        Ok(colors) => {
+                            if colors.len() < indexed.colors.len() {
+                                log::warn!(
+                                    "Palette {:?} has {} colors, the tileset {}; the rest keep their base colors",
+                                    path, colors.len(), indexed.colors.len(),
+                                );
+                            }
+                            let name = path.file_stem()
+                                .map(|s| s.to_string_lossy().to_string())
+                                .unwrap_or_else(|| format!("Variant {}", indexed.variants.len() + 1));
+                            indexed.variants.push(crate::tile::indexed::PaletteVariant { name, colors });
+                            Self::refresh_indexed_tileset(tileset, &gpu.renderer, &mut gpu.egui_renderer);
+                            self.has_unsaved_changes = true;
+                            log::info!("Imported palette variant for tileset {idx} from {:?}", path);
+                        }
+                        Err(e) => log::error!("Failed to load palette: {e}"),
+                    }
+                }
+            }
             UiAction::CreatePrefab => {
                 // Gather selected faces into a new prefab
                 let mut faces = Vec::new();
                 let mut ts_idx = None;
                 for &(li, oi, fi) in &self.edit_state.selection.faces {
-                    if let Some(face) = self.scene.layers.get(li)
-                        .and_then(|l| l.objects.get(oi))
-                        .and_then(|o| o.faces.get(fi))
+                    if let Some(obj) = self.scene.layers.get(li).and_then(|l| l.objects.get(oi))
+                        && let Some(face) = obj.faces.get(fi)
                     {
-                        faces.push(face.clone());
+                        faces.push(Face { tileset: obj.face_tileset(face), ..face.clone() });
                         if ts_idx.is_none() {
-                            ts_idx = self.scene.layers.get(li)
-                                .and_then(|l| l.objects.get(oi))
-                                .and_then(|o| o.tileset_index);
+                            ts_idx = obj.tileset_index;
                         }
                     }
                 }
@@ -2522,7 +2792,7 @@ impl App {
                         };
                         self.history.push(Box::new(crate::history::commands::CreateInstance {
                             layer: li, object: oi, instance: inst,
-                        }), &mut self.scene, &gpu.renderer.device);
+                        }), &mut self.scene);
                     }
                     self.has_unsaved_changes = true;
                 }
@@ -2535,7 +2805,7 @@ impl App {
                 for (li, oi, ii) in targets {
                     self.history.push(Box::new(crate::history::commands::DeleteInstance {
                         layer: li, object: oi, instance_index: ii, stored: None,
-                    }), &mut self.scene, &gpu.renderer.device);
+                    }), &mut self.scene);
                 }
                 self.edit_state.selection.instances.clear();
                 self.has_unsaved_changes = true;
@@ -2547,7 +2817,7 @@ impl App {
                 for (li, oi, ii) in targets {
                     self.history.push(Box::new(
                         crate::history::commands::DeconstructInstance::new(li, oi, ii)
-                    ), &mut self.scene, &gpu.renderer.device);
+                    ), &mut self.scene);
                 }
                 self.edit_state.selection.instances.clear();
                 self.has_unsaved_changes = true;
@@ -2573,28 +2843,26 @@ impl App {
                 {
                     // Update tileset image_data from paint buffer
                     tileset.image_data = Some(self.paint_state.pixels.clone());
+                    self.scene.atlas_dirty = true;
 
-                    // Re-upload to wgpu texture
-                    if let Some(ref texture) = tileset.gpu_texture {
-                        gpu.renderer.queue.write_texture(
-                            wgpu::TexelCopyTextureInfo {
-                                texture,
-                                mip_level: 0,
-                                origin: wgpu::Origin3d::ZERO,
-                                aspect: wgpu::TextureAspect::All,
-                            },
-                            &self.paint_state.pixels,
-                            wgpu::TexelCopyBufferLayout {
-                                offset: 0,
-                                bytes_per_row: Some(4 * tileset.image_width),
-                                rows_per_image: Some(tileset.image_height),
-                            },
-                            wgpu::Extent3d {
-                                width: tileset.image_width,
-                                height: tileset.image_height,
-                                depth_or_array_layers: 1,
-                            },
-                        );
+                    // Indexed tilesets snap painted colors to their table; show the snapped result
+                    if tileset.indexed.is_some() {
+                        tileset.sync_indexed();
+                        if let Some(ref data) = tileset.image_data {
+                            self.paint_state.pixels.clone_from(data);
+                        }
+                    }
+
+                    // Re-upload to wgpu texture (re-extruded when padding is on, mipmaps regenerated)
+                    if let Some(ref texture) = tileset.gpu_texture
+                        && let Some((_, _, data)) = tileset.gpu_image()
+                    {
+                        crate::tile::mipmap::write_levels(&gpu.renderer.queue, texture, &data, tileset.material.mip_filter);
+                    }
+                    for (v, texture) in tileset.variant_textures.iter().enumerate() {
+                        if let Some((_, _, data)) = tileset.gpu_variant_image(v) {
+                            crate::tile::mipmap::write_levels(&gpu.renderer.queue, texture, &data, tileset.material.mip_filter);
+                        }
                     }
 
                     // Re-register egui texture with updated data
@@ -2636,16 +2904,14 @@ impl App {
         // Sync bg_color to/from settings (View menu edits bg_color directly)
         self.settings.display.bg_color = self.bg_color;
 
-        // Rebuild GPU meshes for objects dirtied by property edits
-        if !self.scene.dirty_objects.is_empty() {
-            let dirty: std::collections::HashSet<(usize, usize)> = self.scene.dirty_objects.drain(..).collect();
-            for (li, oi) in dirty {
-                if let Some(obj) = self.scene.layers.get_mut(li).and_then(|l| l.objects.get_mut(oi)) {
-                    obj.rebuild_gpu_mesh(&gpu.renderer.device);
-                }
-            }
+        // Upload faces edited this frame (commands, live previews, property edits)
+        let chunking = self.settings.display.chunk_large_objects;
+        for obj in self.scene.layers.iter_mut().flat_map(|l| &mut l.objects) {
+            obj.sync_gpu_mesh(&gpu.renderer.device, &gpu.renderer.queue, chunking);
         }
 
+        Self::refresh_atlas(&mut self.scene, &gpu.renderer);
+
         let paint_jobs = egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
         let screen_descriptor = egui_wgpu::ScreenDescriptor {
             size_in_pixels: [
@@ -2661,7 +2927,7 @@ impl App {
         }
 
         // Upload per-frame data before render pass
-        gpu.renderer.prepare_frame(&self.scene);
+        gpu.renderer.prepare_frame(&mut self.scene);
 
         // Main 3D render pass
         {
@@ -2782,16 +3048,18 @@ impl App {
         for id in &full_output.textures_delta.free {
             gpu.egui_renderer.free_texture(id);
         }
+
+        self.tick_autosave();
+        self.tick_tileset_reload();
     }
 
     fn do_load_tileset(
         scene: &mut Scene,
         egui_renderer: &mut egui_wgpu::Renderer,
         renderer: &Renderer,
-        path: &std::path::Path,
-        tile_w: u32,
-        tile_h: u32,
+        pending: PendingTilesetLoad,
     ) {
+        let (path, tile_w, tile_h) = (&pending.path, pending.tile_width, pending.tile_height);
         match crate::tile::Tileset::load(
             &renderer.device,
             &renderer.queue,
@@ -2799,8 +3067,11 @@ impl App {
             path,
             tile_w,
             tile_h,
+            pending.aseprite.map(|a| a.selection).unwrap_or_default(),
         ) {
             Ok(mut tileset) => {
+                tileset.margin = pending.margin;
+                tileset.spacing = pending.spacing;
                 tileset.register_with_egui(egui_renderer, &renderer.device, &renderer.queue);
                 scene.tilesets.push(tileset);
                 scene.active_tileset = Some(scene.tilesets.len() - 1);
@@ -2812,54 +3083,53 @@ impl App {
         }
     }
 
-    /// Create GPU texture and bind group for a tileset from its image_data.
+    /// Create GPU textures and bind group for a tileset from its image_data and companion maps
+    /// (padded copies when edge extrusion is on, with mipmaps per the material's mip filter).
     fn create_gpu_tileset(
         ts: &mut crate::tile::Tileset,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         bind_group_layout: &wgpu::BindGroupLayout,
     ) {
-        let Some(ref data) = ts.image_data else { return };
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("tileset_texture"),
-            size: wgpu::Extent3d {
-                width: ts.image_width,
-                height: ts.image_height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            data,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * ts.image_width),
-                rows_per_image: Some(ts.image_height),
-            },
-            wgpu::Extent3d {
-                width: ts.image_width,
-                height: ts.image_height,
-                depth_or_array_layers: 1,
-            },
+        if ts.padding_exceeds_limit() {
+            log::warn!(
+                "Tileset '{}': {} px edge extrusion would exceed the {} px texture limit; uploading unpadded",
+                ts.name, ts.material.extrude, crate::tile::max_texture_dimension(),
+            );
+        }
+        let Some((width, height, data)) = ts.gpu_image() else { return };
+        let texture = crate::tile::mipmap::create_texture(
+            device, queue, "tileset_texture", wgpu::TextureFormat::Rgba8UnormSrgb, width, height, &data, ts.material.mip_filter,
         );
         ts.gpu_texture = Some(texture);
+        ts.upload_maps(device, queue);
+        ts.upload_variants(device, queue);
         ts.rebuild_bind_group(device, bind_group_layout);
     }
 
-    fn do_save_scene(scene: &Scene, last_save_path: &mut Option<std::path::PathBuf>, history: &mut History, recent_files: &mut Vec<std::path::PathBuf>) {
+    /// Build the scene atlas while any object mixes tilesets (after tileset edits, or when the
+    /// tileset list changed), and drop it once none does.
+    fn refresh_atlas(scene: &mut Scene, renderer: &Renderer) {
+        let mixed = scene.layers.iter()
+            .flat_map(|l| &l.objects)
+            .any(|o| o.gpu_mesh.as_ref().is_some_and(|m| m.mixed));
+        if !mixed {
+            scene.atlas = None;
+            return;
+        }
+        let stale = scene.atlas.as_ref().is_none_or(|a| a.tileset_count() != scene.tilesets.len());
+        if stale || scene.atlas_dirty {
+            let mut atlas = crate::tile::atlas::TilesetAtlas::build(&scene.tilesets);
+            atlas.upload(&renderer.device, &renderer.queue, &renderer.tile_bind_group_layout);
+            scene.atlas = Some(atlas);
+            scene.atlas_dirty = false;
+        }
+    }
+
+    fn do_save_scene(scene: &Scene, last_save_path: &mut Option<std::path::PathBuf>, history: &mut History, recent_files: &mut Vec<std::path::PathBuf>, autosave_thread: &mut Option<std::thread::JoinHandle<()>>) {
         let file = rfd::FileDialog::new()
             .add_filter("Cracktile 3D", &["ct3d"])
+            .add_filter("Cracktile 3D Text (JSON)", &[crate::io::TEXT_SCENE_EXTENSION])
             .set_title("Save Scene")
             .save_file();
 
@@ -2869,6 +3139,7 @@ impl App {
                     log::info!("Saved scene to {:?}", path);
                     *last_save_path = Some(path.clone());
                     history.mark_saved();
+                    Self::discard_autosaves(autosave_thread);
                     recent_files.retain(|p| p != &path);
                     recent_files.insert(0, path);
                     recent_files.truncate(10);
@@ -2879,27 +3150,174 @@ impl App {
         }
     }
 
+    /// Write an autosave snapshot if autosave is enabled, the scene has unsaved changes since the
+    /// last snapshot, the interval has elapsed, and no previous snapshot is still being written.
+    fn tick_autosave(&mut self) {
+        let file = &self.settings.file;
+        if !file.autosave_enabled
+            || !self.history.dirty
+            || self.history.revision == self.autosaved_revision
+            || self.last_autosave.elapsed().as_secs_f32() < file.autosave_interval_minutes * 60.0
+            || self.autosave_thread.as_ref().is_some_and(|t| !t.is_finished())
+        {
+            return;
+        }
+        self.last_autosave = std::time::Instant::now();
+        self.autosaved_revision = self.history.revision;
+        match crate::io::recovery::write_snapshot(&self.scene, self.last_save_path.as_deref(), file.autosave_keep) {
+            Ok(handle) => self.autosave_thread = Some(handle),
+            Err(e) => log::error!("Autosave failed: {e}"),
+        }
+    }
+
+    /// Poll tileset source files about once a second and reload any that changed on disk.
+    fn tick_tileset_reload(&mut self) {
+        if !self.settings.file.tileset_hot_reload
+            || self.last_tileset_poll.elapsed().as_secs_f32() < 1.0
+        {
+            return;
+        }
+        self.last_tileset_poll = std::time::Instant::now();
+        let Some(gpu) = &mut self.gpu else { return };
+        for ts in &mut self.scene.tilesets {
+            if ts.source.as_ref().is_some_and(|src| src.changed_on_disk()) {
+                Self::reload_tileset(ts, &gpu.renderer, &mut gpu.egui_renderer);
+                self.scene.atlas_dirty = true;
+            }
+        }
+    }
+
+    /// Re-resolve an indexed tileset's colors after its table, variants or indexing changed, and
+    /// refresh its GPU textures and panel preview.
+    fn refresh_indexed_tileset(
+        ts: &mut crate::tile::Tileset,
+        renderer: &Renderer,
+        egui_renderer: &mut egui_wgpu::Renderer,
+    ) {
+        ts.apply_indexed();
+        let variants = ts.indexed.as_ref().map_or(0, |ix| ix.variants.len());
+        if let Some(ref texture) = ts.gpu_texture
+            && ts.variant_textures.len() == variants
+            && let Some((_, _, data)) = ts.gpu_image()
+        {
+            // Same set of textures: rewrite their pixels (cheap while dragging a color picker)
+            let filter = ts.material.mip_filter;
+            crate::tile::mipmap::write_levels(&renderer.queue, texture, &data, filter);
+            for (v, texture) in ts.variant_textures.iter().enumerate() {
+                if let Some((_, _, data)) = ts.gpu_variant_image(v) {
+                    crate::tile::mipmap::write_levels(&renderer.queue, texture, &data, filter);
+                }
+            }
+        } else {
+            Self::create_gpu_tileset(ts, &renderer.device, &renderer.queue, &renderer.tile_bind_group_layout);
+        }
+        if let Some(id) = ts.egui_texture_id.take() {
+            egui_renderer.free_texture(&id);
+        }
+        ts.register_with_egui(egui_renderer, &renderer.device, &renderer.queue);
+    }
+
+    /// Re-read a tileset's source image and replace its texture, bind group and egui texture
+    /// in place, so objects keep referencing it by index.
+    fn reload_tileset(
+        ts: &mut crate::tile::Tileset,
+        renderer: &Renderer,
+        egui_renderer: &mut egui_wgpu::Renderer,
+    ) {
+        let Some(src) = &mut ts.source else { return };
+        // Record the new time up front so a broken file is not retried every poll.
+        src.mark_read();
+        let (w, h, data) = match crate::tile::read_image_rgba(&src.path, src.aseprite) {
+            Ok(image) => image,
+            Err(e) => {
+                // Editors often write in several steps; the next save triggers another attempt.
+                log::warn!("Failed to reload tileset '{}': {e}", ts.name);
+                return;
+            }
+        };
+        let (old_w, old_h) = (ts.image_width, ts.image_height);
+        if (w, h) != (old_w, old_h) && old_w > 0 && old_h > 0 {
+            if w * old_h == h * old_w
+                && [ts.tile_width, ts.tile_height, ts.margin, ts.spacing].iter().all(|&v| (v * w).is_multiple_of(old_w))
+            {
+                // Uniformly rescaled: keep the tile grid by scaling the tile size with it.
+                ts.tile_width = ts.tile_width * w / old_w;
+                ts.tile_height = ts.tile_height * w / old_w;
+                ts.margin = ts.margin * w / old_w;
+                ts.spacing = ts.spacing * w / old_w;
+                log::info!(
+                    "Tileset '{}' resized {old_w}x{old_h} -> {w}x{h}; tile size is now {}x{}",
+                    ts.name, ts.tile_width, ts.tile_height,
+                );
+            } else {
+                log::warn!(
+                    "Tileset '{}' changed size {old_w}x{old_h} -> {w}x{h}; existing tile UVs no longer line up with the grid",
+                    ts.name,
+                );
+            }
+        }
+        ts.image_width = w;
+        ts.image_height = h;
+        ts.image_data = Some(data);
+        ts.sync_indexed();
+        Self::create_gpu_tileset(ts, &renderer.device, &renderer.queue, &renderer.tile_bind_group_layout);
+        if let Some(id) = ts.egui_texture_id.take() {
+            egui_renderer.free_texture(&id);
+        }
+        ts.register_with_egui(egui_renderer, &renderer.device, &renderer.queue);
+        log::info!("Reloaded tileset '{}' ({w}x{h})", ts.name);
+    }
+
+    /// Wait for any in-flight autosave, then delete this session's snapshots (clean save or exit).
+    fn discard_autosaves(autosave_thread: &mut Option<std::thread::JoinHandle<()>>) {
+        if let Some(handle) = autosave_thread.take() {
+            let _ = handle.join();
+        }
+        crate::io::recovery::clear_snapshots();
+    }
+
+    /// Create tileset textures and egui registrations for a freshly loaded scene, and free the
+    /// egui textures of the scene it replaces. Object meshes upload on the next frame's sync.
+    fn upload_loaded_scene(
+        loaded: &mut Scene,
+        old_scene: &Scene,
+        renderer: &Renderer,
+        egui_renderer: &mut egui_wgpu::Renderer,
+    ) {
+        for ts in &old_scene.tilesets {
+            if let Some(id) = ts.egui_texture_id {
+                egui_renderer.free_texture(&id);
+            }
+        }
+        for ts in &mut loaded.tilesets {
+            Self::create_gpu_tileset(
+                ts,
+                &renderer.device,
+                &renderer.queue,
+                &renderer.tile_bind_group_layout,
+            );
+            ts.register_with_egui(egui_renderer, &renderer.device, &renderer.queue);
+        }
+    }
+
     fn do_open_scene(
         scene: &mut Scene,
         edit_state: &mut EditState,
         history: &mut History,
         renderer: &Renderer,
+        egui_renderer: &mut egui_wgpu::Renderer,
         last_save_path: &mut Option<std::path::PathBuf>,
         recent_files: &mut Vec<std::path::PathBuf>,
     ) {
         let file = rfd::FileDialog::new()
-            .add_filter("Cracktile 3D", &["ct3d"])
+            .add_filter("Cracktile 3D", &["ct3d", crate::io::TEXT_SCENE_EXTENSION])
             .set_title("Open Scene")
             .pick_file();
 
         if let Some(path) = file {
             match crate::io::load_scene(&path) {
                 Ok(mut loaded) => {
-                    for layer in &mut loaded.layers {
-                        for obj in &mut layer.objects {
-                            obj.rebuild_gpu_mesh(&renderer.device);
-                        }
-                    }
+                    Self::upload_loaded_scene(&mut loaded, scene, renderer, egui_renderer);
                     *scene = loaded;
                     edit_state.selection.clear();
                     history.clear();
@@ -2915,35 +3333,51 @@ impl App {
         }
     }
 
-    fn do_export_glb(scene: &Scene) {
+    /// Returns the optimize report of a successful export with mesh optimization on.
+    fn do_export_glb(scene: &Scene, options: &crate::io::GltfExportOptions) -> Option<crate::scene::optimize::OptimizeReport> {
         let file = rfd::FileDialog::new()
             .add_filter("glTF Binary", &["glb"])
             .set_title("Export GLB")
             .save_file();
 
         if let Some(path) = file {
-            match crate::io::export_glb(scene, &path) {
-                Ok(()) => log::info!("Exported GLB to {:?}", path),
+            match crate::io::export_glb(scene, &path, options) {
+                Ok(report) => {
+                    log::info!("Exported GLB to {:?}", path);
+                    if let Some(report) = report {
+                        log::info!("Optimized GLB export: {report}");
+                    }
+                    return report;
+                }
                 Err(e) => log::error!("Failed to export GLB: {e}"),
             }
         }
+        None
     }
 
-    fn do_export_obj(scene: &Scene) {
+    /// Returns the optimize report of a successful export with mesh optimization on.
+    fn do_export_obj(scene: &Scene, options: &crate::io::ObjExportOptions) -> Option<crate::scene::optimize::OptimizeReport> {
         let file = rfd::FileDialog::new()
             .add_filter("Wavefront OBJ", &["obj"])
             .set_title("Export OBJ")
             .save_file();
 
         if let Some(path) = file {
-            match crate::io::export_obj(scene, &path) {
-                Ok(()) => log::info!("Exported OBJ to {:?}", path),
+            match crate::io::export_obj(scene, &path, options) {
+                Ok(report) => {
+                    log::info!("Exported OBJ to {:?}", path);
+                    if let Some(report) = report {
+                        log::info!("Optimized OBJ export: {report}");
+                    }
+                    return report;
+                }
                 Err(e) => log::error!("Failed to export OBJ: {e}"),
             }
         }
+        None
     }
 
-    fn do_import_obj(scene: &mut Scene, history: &mut History, renderer: &Renderer) {
+    fn do_import_obj(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
         let file = rfd::FileDialog::new()
             .add_filter("Wavefront OBJ", &["obj"])
             .set_title("Import OBJ")
@@ -2952,7 +3386,7 @@ impl App {
         if let Some(path) = file {
             match crate::io::import_obj(&path) {
                 Ok(objects) => {
-                    Self::import_objects(scene, history, renderer, objects);
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
                     log::info!("Imported OBJ from {:?}", path);
                 }
                 Err(e) => log::error!("Failed to import OBJ: {e}"),
@@ -2960,7 +3394,7 @@ impl App {
         }
     }
 
-    fn do_import_glb(scene: &mut Scene, history: &mut History, renderer: &Renderer) {
+    fn do_import_glb(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
         let file = rfd::FileDialog::new()
             .add_filter("glTF Binary", &["glb"])
             .set_title("Import GLB")
@@ -2969,7 +3403,7 @@ impl App {
         if let Some(path) = file {
             match crate::io::import_glb(&path) {
                 Ok(objects) => {
-                    Self::import_objects(scene, history, renderer, objects);
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
                     log::info!("Imported GLB from {:?}", path);
                 }
                 Err(e) => log::error!("Failed to import GLB: {e}"),
@@ -2977,18 +3411,26 @@ impl App {
         }
     }
 
-    fn do_export_gltf(scene: &Scene) {
+    /// Returns the optimize report of a successful export with mesh optimization on.
+    fn do_export_gltf(scene: &Scene, options: &crate::io::GltfExportOptions) -> Option<crate::scene::optimize::OptimizeReport> {
         let file = rfd::FileDialog::new()
             .add_filter("glTF JSON", &["gltf"])
             .set_title("Export glTF")
             .save_file();
 
         if let Some(path) = file {
-            match crate::io::export_gltf(scene, &path) {
-                Ok(()) => log::info!("Exported glTF to {:?}", path),
+            match crate::io::export_gltf(scene, &path, options) {
+                Ok(report) => {
+                    log::info!("Exported glTF to {:?}", path);
+                    if let Some(report) = report {
+                        log::info!("Optimized glTF export: {report}");
+                    }
+                    return report;
+                }
                 Err(e) => log::error!("Failed to export glTF: {e}"),
             }
         }
+        None
     }
 
     fn do_export_dae(scene: &Scene) {
@@ -3005,7 +3447,7 @@ impl App {
         }
     }
 
-    fn do_import_gltf(scene: &mut Scene, history: &mut History, renderer: &Renderer) {
+    fn do_import_gltf(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
         let file = rfd::FileDialog::new()
             .add_filter("glTF JSON", &["gltf"])
             .set_title("Import glTF")
@@ -3014,7 +3456,7 @@ impl App {
         if let Some(path) = file {
             match crate::io::import_gltf(&path) {
                 Ok(objects) => {
-                    Self::import_objects(scene, history, renderer, objects);
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
                     log::info!("Imported glTF from {:?}", path);
                 }
                 Err(e) => log::error!("Failed to import glTF: {e}"),
@@ -3022,7 +3464,7 @@ impl App {
         }
     }
 
-    fn do_import_dae(scene: &mut Scene, history: &mut History, renderer: &Renderer) {
+    fn do_import_dae(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
         let file = rfd::FileDialog::new()
             .add_filter("Collada", &["dae"])
             .set_title("Import DAE")
@@ -3031,7 +3473,7 @@ impl App {
         if let Some(path) = file {
             match crate::io::import_dae(&path) {
                 Ok(objects) => {
-                    Self::import_objects(scene, history, renderer, objects);
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects.into());
                     log::info!("Imported DAE from {:?}", path);
                 }
                 Err(e) => log::error!("Failed to import DAE: {e}"),
@@ -3039,25 +3481,120 @@ impl App {
         }
     }
 
+    fn do_export_vox(scene: &Scene) {
+        let file = rfd::FileDialog::new()
+            .add_filter("MagicaVoxel", &["vox"])
+            .set_title("Export VOX")
+            .save_file();
+
+        if let Some(path) = file {
+            match crate::io::export_vox(scene, &path) {
+                Ok(()) => log::info!("Exported VOX to {:?}", path),
+                Err(e) => log::error!("Failed to export VOX: {e}"),
+            }
+        }
+    }
+
+    fn do_import_vox(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
+        let file = rfd::FileDialog::new()
+            .add_filter("MagicaVoxel", &["vox"])
+            .set_title("Import VOX")
+            .pick_file();
+
+        if let Some(path) = file {
+            match crate::io::import_vox(&path, scene.grid_cell_size) {
+                Ok(objects) => {
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
+                    log::info!("Imported VOX from {:?}", path);
+                }
+                Err(e) => log::error!("Failed to import VOX: {e}"),
+            }
+        }
+    }
+
+    fn do_import_tiled(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
+        let file = rfd::FileDialog::new()
+            .add_filter("Tiled map", &["tmx", "tmj", "json"])
+            .set_title("Import Tiled Map")
+            .pick_file();
+
+        if let Some(path) = file {
+            match crate::io::import_tiled(&path, scene.grid_cell_size) {
+                Ok(objects) => {
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
+                    log::info!("Imported Tiled map from {:?}", path);
+                }
+                Err(e) => log::error!("Failed to import Tiled map: {e}"),
+            }
+        }
+    }
+
+    fn do_import_ldtk(scene: &mut Scene, history: &mut History, renderer: &Renderer, egui_renderer: &mut egui_wgpu::Renderer) {
+        let file = rfd::FileDialog::new()
+            .add_filter("LDtk project", &["ldtk"])
+            .set_title("Import LDtk Project")
+            .pick_file();
+
+        if let Some(path) = file {
+            match crate::io::import_ldtk(&path, scene.grid_cell_size) {
+                Ok(objects) => {
+                    Self::import_objects(scene, history, renderer, egui_renderer, objects);
+                    log::info!("Imported LDtk project from {:?}", path);
+                }
+                Err(e) => log::error!("Failed to import LDtk project: {e}"),
+            }
+        }
+    }
+
+    /// Add imported objects to the active layer (or to new layers the import asks for),
+    /// creating tilesets for any imported textures.
     fn import_objects(
         scene: &mut Scene,
         history: &mut History,
         renderer: &Renderer,
-        objects: Vec<(Vec<Face>, Option<String>)>,
+        egui_renderer: &mut egui_wgpu::Renderer,
+        imported: crate::io::ImportedScene,
     ) {
-        for (faces, name) in objects {
-            let layer_idx = scene.active_layer;
-            let (obj_idx, create) = crate::tools::draw::find_target_object(scene, layer_idx, None);
+        let tileset_base = scene.tilesets.len();
+        for mut ts in imported.tilesets {
+            Self::create_gpu_tileset(
+                &mut ts,
+                &renderer.device,
+                &renderer.queue,
+                &renderer.tile_bind_group_layout,
+            );
+            ts.register_with_egui(egui_renderer, &renderer.device, &renderer.queue);
+            log::info!("Imported tileset '{}' ({}x{})", ts.name, ts.image_width, ts.image_height);
+            scene.tilesets.push(ts);
+        }
+        if scene.tilesets.len() > tileset_base && scene.active_tileset.is_none() {
+            scene.active_tileset = Some(tileset_base);
+        }
+
+        let layer_base = scene.layers.len();
+        for layer in imported.layers {
+            scene.layers.push(crate::scene::Layer {
+                name: layer.name,
+                visible: layer.visible,
+                objects: Vec::new(),
+                palette_variant: None,
+            });
+        }
+
+        for object in imported.objects {
+            let layer_idx = object.layer.map_or(scene.active_layer, |i| layer_base + i);
+            let tileset_index = object.tileset.map(|i| tileset_base + i);
+            let (obj_idx, create) = crate::tools::draw::find_target_object(scene, layer_idx, tileset_index, false);
             let cmd = commands::PlaceTile {
                 layer: layer_idx,
                 object: obj_idx,
-                faces,
+                faces: object.faces,
                 create_object: create,
-                tileset_index: None,
+                tileset_index,
             };
-            history.push(Box::new(cmd), scene, &renderer.device);
+            history.push(Box::new(cmd), scene);
             if let Some(obj) = scene.layers.get_mut(layer_idx).and_then(|l| l.objects.get_mut(obj_idx))
-                && let Some(n) = name
+                && let Some(n) = object.name
             {
                 obj.name = n;
             }
@@ -3068,7 +3605,6 @@ impl App {
         edit_state: &EditState,
         scene: &mut Scene,
         history: &mut History,
-        device: &wgpu::Device,
         transform: impl Fn([glam::Vec2; 4]) -> [glam::Vec2; 4],
     ) {
         if edit_state.selection.faces.is_empty() { return; }
@@ -3086,14 +3622,13 @@ impl App {
             }
         }
         let cmd = commands::ManipulateUVs { faces: face_indices, old_uvs, new_uvs };
-        history.push(Box::new(cmd), scene, device);
+        history.push(Box::new(cmd), scene);
     }
 
     fn apply_merge_vertices(
         edit_state: &EditState,
         scene: &mut Scene,
         history: &mut History,
-        device: &wgpu::Device,
     ) {
         if !edit_state.selection.vertices.is_empty() {
             // Vertex mode: merge all to centroid
@@ -3123,7 +3658,7 @@ impl App {
                 }
                 if !moves.is_empty() {
                     let cmd = commands::MergeVertices { moves };
-                    history.push(Box::new(cmd), scene, device);
+                    history.push(Box::new(cmd), scene);
                 }
             }
         } else if !edit_state.selection.faces.is_empty() {
@@ -3166,7 +3701,7 @@ impl App {
 
             if !moves.is_empty() {
                 let cmd = commands::MergeVertices { moves };
-                history.push(Box::new(cmd), scene, device);
+                history.push(Box::new(cmd), scene);
             }
         }
     }
@@ -3175,7 +3710,6 @@ impl App {
         edit_state: &EditState,
         scene: &mut Scene,
         history: &mut History,
-        device: &wgpu::Device,
         axis: usize, // 0=X, 1=Y, 2=Z
     ) {
         let crosshair = scene.crosshair_pos;
@@ -3183,22 +3717,19 @@ impl App {
         let mut tileset_index = None;
 
         for &(li, oi, fi) in &edit_state.selection.faces {
-            if let Some(face) = scene.layers.get(li)
-                .and_then(|l| l.objects.get(oi))
-                .and_then(|o| o.faces.get(fi))
+            if let Some(obj) = scene.layers.get(li).and_then(|l| l.objects.get(oi))
+                && let Some(face) = obj.faces.get(fi)
             {
-                faces_to_mirror.push(face.clone());
+                faces_to_mirror.push(Face { tileset: obj.face_tileset(face), ..face.clone() });
                 if tileset_index.is_none() {
-                    tileset_index = scene.layers.get(li)
-                        .and_then(|l| l.objects.get(oi))
-                        .and_then(|o| o.tileset_index);
+                    tileset_index = obj.tileset_index;
                 }
             }
         }
         for &(li, oi) in &edit_state.selection.objects {
             if let Some(obj) = scene.layers.get(li).and_then(|l| l.objects.get(oi)) {
                 for face in &obj.faces {
-                    faces_to_mirror.push(face.clone());
+                    faces_to_mirror.push(Face { tileset: obj.face_tileset(face), ..face.clone() });
                 }
                 if tileset_index.is_none() {
                     tileset_index = obj.tileset_index;
@@ -3226,7 +3757,7 @@ impl App {
         }).collect();
 
         let layer_idx = scene.active_layer;
-        let (object_idx, create_object) = crate::tools::draw::find_target_object(scene, layer_idx, tileset_index);
+        let (object_idx, create_object) = crate::tools::draw::find_target_object(scene, layer_idx, tileset_index, false);
         let cmd = commands::PlaceTile {
             layer: layer_idx,
             object: object_idx,
@@ -3234,7 +3765,7 @@ impl App {
             create_object,
             tileset_index,
         };
-        history.push(Box::new(cmd), scene, device);
+        history.push(Box::new(cmd), scene);
     }
 
     /// Apply a translation directly to selected geometry (for live gizmo preview).
@@ -3242,14 +3773,12 @@ impl App {
         selection: &crate::tools::edit::Selection,
         scene: &mut Scene,
         delta: glam::Vec3,
-        _device: &wgpu::Device,
     ) {
-        let mut rebuild = std::collections::HashSet::new();
         for &(li, oi, fi) in &selection.faces {
             for pos in &mut scene.layers[li].objects[oi].faces[fi].positions {
                 *pos += delta;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
         for &(li, oi) in &selection.objects {
             for face in &mut scene.layers[li].objects[oi].faces {
@@ -3257,13 +3786,13 @@ impl App {
                     *pos += delta;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
         for &(li, oi, fi, vi) in &selection.vertices {
             scene.layers[li].objects[oi].faces[fi].positions[vi] += delta;
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
-        // Instance transforms: translate instance position (no GPU mesh rebuild needed)
+        // Instance transforms: translate instance position (no GPU mesh upload needed)
         for &(li, oi, ii) in &selection.instances {
             if let Some(inst) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
@@ -3272,9 +3801,6 @@ impl App {
                 inst.position += delta;
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(_device);
-        }
     }
 
     /// Apply a rotation directly to selected geometry (for live gizmo preview).
@@ -3284,15 +3810,13 @@ impl App {
         axis: glam::Vec3,
         angle: f32,
         center: glam::Vec3,
-        device: &wgpu::Device,
     ) {
         let quat = glam::Quat::from_axis_angle(axis, angle);
-        let mut rebuild = std::collections::HashSet::new();
         for &(li, oi, fi) in &selection.faces {
             for pos in &mut scene.layers[li].objects[oi].faces[fi].positions {
                 *pos = quat * (*pos - center) + center;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
         for &(li, oi) in &selection.objects {
             for face in &mut scene.layers[li].objects[oi].faces {
@@ -3300,12 +3824,12 @@ impl App {
                     *pos = quat * (*pos - center) + center;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
         for &(li, oi, fi, vi) in &selection.vertices {
             let pos = &mut scene.layers[li].objects[oi].faces[fi].positions[vi];
             *pos = quat * (*pos - center) + center;
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
         // Instance transforms: rotate position around center and accumulate rotation
         for &(li, oi, ii) in &selection.instances {
@@ -3317,9 +3841,6 @@ impl App {
                 inst.rotation = quat * inst.rotation;
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     /// Apply a scale directly to selected geometry (for live gizmo preview).
@@ -3328,14 +3849,12 @@ impl App {
         scene: &mut Scene,
         factor: glam::Vec3,
         center: glam::Vec3,
-        device: &wgpu::Device,
     ) {
-        let mut rebuild = std::collections::HashSet::new();
         for &(li, oi, fi) in &selection.faces {
             for pos in &mut scene.layers[li].objects[oi].faces[fi].positions {
                 *pos = center + (*pos - center) * factor;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
         for &(li, oi) in &selection.objects {
             for face in &mut scene.layers[li].objects[oi].faces {
@@ -3343,12 +3862,12 @@ impl App {
                     *pos = center + (*pos - center) * factor;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
         for &(li, oi, fi, vi) in &selection.vertices {
             let pos = &mut scene.layers[li].objects[oi].faces[fi].positions[vi];
             *pos = center + (*pos - center) * factor;
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
         // Instance transforms: scale position relative to center and accumulate scale
         for &(li, oi, ii) in &selection.instances {
@@ -3360,9 +3879,6 @@ impl App {
                 inst.scale *= factor;
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 }
 
@@ -3752,7 +4268,6 @@ fn auto_flatten_selection_uvs(
     faces: &[(usize, usize, usize)],
     objects: &[(usize, usize)],
     vertices: &[(usize, usize, usize, usize)],
-    device: &wgpu::Device,
 ) {
     let mut affected: std::collections::HashSet<(usize, usize, usize)> = std::collections::HashSet::new();
 
@@ -3770,19 +4285,12 @@ fn auto_flatten_selection_uvs(
         affected.insert((li, oi, fi));
     }
 
-    let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
     for (li, oi, fi) in affected {
-        if let Some(face) = scene.layers.get_mut(li)
-            .and_then(|l| l.objects.get_mut(oi))
-            .and_then(|o| o.faces.get_mut(fi))
+        if let Some(obj) = scene.layers.get_mut(li).and_then(|l| l.objects.get_mut(oi))
+            && let Some(face) = obj.faces.get_mut(fi)
         {
             face.flatten_uvs();
-            rebuild.insert((li, oi));
-        }
-    }
-    for (li, oi) in rebuild {
-        if let Some(obj) = scene.layers.get_mut(li).and_then(|l| l.objects.get_mut(oi)) {
-            obj.rebuild_gpu_mesh(device);
+            obj.mark_face_dirty(fi);
         }
     }
 }
@@ -1,6 +1,7 @@
 use glam::{Quat, Vec2, Vec3, Vec4};
 use crate::history::Command;
 use crate::scene::mesh::Face;
+use crate::scene::optimize::{self, OptimizeReport, Tiling};
 use crate::scene::{Instance, Object, Scene};
 use crate::tools::draw::default_uvs;
 
@@ -10,36 +11,28 @@ pub struct HideFaces {
 }
 
 impl Command for HideFaces {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         for &(li, oi, fi) in &self.faces {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.hidden = true;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn undo(&mut self, scene: &mut Scene) {
         for &(li, oi, fi) in &self.faces {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.hidden = false;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -53,36 +46,28 @@ pub struct ShowAllFaces {
 }
 
 impl Command for ShowAllFaces {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         for &(li, oi, fi) in &self.previously_hidden {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.hidden = false;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn undo(&mut self, scene: &mut Scene) {
         for &(li, oi, fi) in &self.previously_hidden {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.hidden = true;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -102,7 +87,7 @@ pub struct EditFaceProperty {
 }
 
 impl Command for EditFaceProperty {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         let (li, oi, fi) = self.face;
         if let Some(face) = scene.layers.get_mut(li)
             .and_then(|l| l.objects.get_mut(oi))
@@ -111,11 +96,11 @@ impl Command for EditFaceProperty {
             face.positions = self.new_positions;
             face.uvs = self.new_uvs;
             face.colors = self.new_colors;
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
-        scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         let (li, oi, fi) = self.face;
         if let Some(face) = scene.layers.get_mut(li)
             .and_then(|l| l.objects.get_mut(oi))
@@ -124,8 +109,8 @@ impl Command for EditFaceProperty {
             face.positions = self.old_positions;
             face.uvs = self.old_uvs;
             face.colors = self.old_colors;
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
-        scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
     }
 
     fn description(&self) -> &str {
@@ -141,36 +126,28 @@ pub struct ManipulateUVs {
 }
 
 impl Command for ManipulateUVs {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, fi)) in self.faces.iter().enumerate() {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.uvs = self.new_uvs[i];
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn undo(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, fi)) in self.faces.iter().enumerate() {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.uvs = self.old_uvs[i];
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -184,36 +161,28 @@ pub struct MergeVertices {
 }
 
 impl Command for MergeVertices {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         for &(li, oi, fi, vi, _, new_pos) in &self.moves {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.positions[vi] = new_pos;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn undo(&mut self, scene: &mut Scene) {
         for &(li, oi, fi, vi, old_pos, _) in &self.moves {
             if let Some(face) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
                 .and_then(|o| o.faces.get_mut(fi))
             {
                 face.positions[vi] = old_pos;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -235,7 +204,7 @@ impl SplitEdge {
 }
 
 impl Command for SplitEdge {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.original_faces.clear();
         self.added_per_object.clear();
         let mut adds_per_obj: std::collections::HashMap<(usize, usize), usize> = std::collections::HashMap::new();
@@ -272,15 +241,18 @@ impl Command for SplitEdge {
                 uvs: [uv[e], mid_uv, mid_opp_uv, uv[oppn]],
                 colors: [c[e], mid_c, mid_opp_c, c[oppn]],
                 hidden: false,
+                tileset: face.tileset,
             };
             let face_b = Face {
                 positions: [mid_p, p[en], p[opp], mid_opp_p],
                 uvs: [mid_uv, uv[en], uv[opp], mid_opp_uv],
                 colors: [mid_c, c[en], c[opp], mid_opp_c],
                 hidden: false,
+                tileset: face.tileset,
             };
 
             scene.layers[li].objects[oi].faces.remove(fi);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             scene.layers[li].objects[oi].faces.push(face_a);
             scene.layers[li].objects[oi].faces.push(face_b);
             *adds_per_obj.entry((li, oi)).or_insert(0) += 2;
@@ -289,17 +261,9 @@ impl Command for SplitEdge {
         for ((li, oi), count) in &adds_per_obj {
             self.added_per_object.push((*li, *oi, *count));
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _, _) in &self.targets {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove added faces
         for &(li, oi, count) in &self.added_per_object {
             for _ in 0..count {
@@ -310,14 +274,7 @@ impl Command for SplitEdge {
         // Re-insert original faces (stored in reverse order, so insert in reverse)
         for (li, oi, fi, face) in self.original_faces.iter().rev() {
             scene.layers[*li].objects[*oi].faces.insert(*fi, face.clone());
-        }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _, _) in &self.targets {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[*li].objects[*oi].mark_faces_dirty_from(*fi);
         }
     }
 
@@ -339,9 +296,8 @@ impl CollapseEdge {
 }
 
 impl Command for CollapseEdge {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.old_positions.clear();
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
 
         for &(li, oi, fi, edge_idx) in &self.targets {
             let face = &scene.layers[li].objects[oi].faces[fi];
@@ -352,22 +308,14 @@ impl Command for CollapseEdge {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             face.positions[edge_idx] = mid;
             face.positions[en] = mid;
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn undo(&mut self, scene: &mut Scene) {
         for &(li, oi, fi, positions) in &self.old_positions {
             scene.layers[li].objects[oi].faces[fi].positions = positions;
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 
@@ -388,28 +336,35 @@ pub struct PlaceTile {
 }
 
 impl Command for PlaceTile {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         let layer = &mut scene.layers[self.layer];
         if self.create_object && layer.objects.len() <= self.object {
             layer.objects.push(Object::new(format!("Object {}", self.object + 1)));
             self.create_object = false;
         }
         let object = &mut layer.objects[self.object];
-        for face in &self.faces {
-            object.faces.push(face.clone());
-        }
-        if let Some(ts_idx) = self.tileset_index {
+        // Joining an object textured by another tileset: the new faces keep their own.
+        let joins_other = !object.faces.is_empty()
+            && object.tileset_index.is_some()
+            && self.tileset_index.is_some_and(|ts| object.tileset_index != Some(ts));
+        if !joins_other && let Some(ts_idx) = self.tileset_index {
             object.tileset_index = Some(ts_idx);
         }
-        object.rebuild_gpu_mesh(device);
+        for face in &self.faces {
+            let mut face = face.clone();
+            face.tileset = face.tileset.or(self.tileset_index)
+                .filter(|&ts| object.tileset_index != Some(ts));
+            object.faces.push(face);
+        }
+        object.mark_faces_dirty_from(object.faces.len() - self.faces.len());
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         let object = &mut scene.layers[self.layer].objects[self.object];
         for _ in 0..self.faces.len() {
             object.faces.pop();
         }
-        object.rebuild_gpu_mesh(device);
+        object.mark_faces_dirty_from(object.faces.len());
     }
 
     fn description(&self) -> &str {
@@ -426,16 +381,16 @@ pub struct EraseTile {
 }
 
 impl Command for EraseTile {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         let object = &mut scene.layers[self.layer].objects[self.object];
         object.faces.remove(self.face_index);
-        object.rebuild_gpu_mesh(device);
+        object.mark_faces_dirty_from(self.face_index);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         let object = &mut scene.layers[self.layer].objects[self.object];
         object.faces.insert(self.face_index, self.face.clone());
-        object.rebuild_gpu_mesh(device);
+        object.mark_faces_dirty_from(self.face_index);
     }
 
     fn description(&self) -> &str {
@@ -452,12 +407,12 @@ pub struct TranslateSelection {
 }
 
 impl Command for TranslateSelection {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.translate(scene, device, self.delta);
+    fn apply(&mut self, scene: &mut Scene) {
+        self.translate(scene, self.delta);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.translate(scene, device, -self.delta);
+    fn undo(&mut self, scene: &mut Scene) {
+        self.translate(scene, -self.delta);
     }
 
     fn description(&self) -> &str {
@@ -466,15 +421,13 @@ impl Command for TranslateSelection {
 }
 
 impl TranslateSelection {
-    fn translate(&self, scene: &mut Scene, device: &wgpu::Device, delta: Vec3) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn translate(&self, scene: &mut Scene, delta: Vec3) {
         for &(li, oi, fi) in &self.faces {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             for pos in &mut face.positions {
                 *pos += delta;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
 
         for &(li, oi) in &self.objects {
@@ -483,16 +436,12 @@ impl TranslateSelection {
                     *pos += delta;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
 
         for &(li, oi, fi, vi) in &self.vertices {
             scene.layers[li].objects[oi].faces[fi].positions[vi] += delta;
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 }
@@ -508,12 +457,12 @@ pub struct RotateSelection {
 }
 
 impl Command for RotateSelection {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.rotate(scene, device, self.angle);
+    fn apply(&mut self, scene: &mut Scene) {
+        self.rotate(scene, self.angle);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.rotate(scene, device, -self.angle);
+    fn undo(&mut self, scene: &mut Scene) {
+        self.rotate(scene, -self.angle);
     }
 
     fn description(&self) -> &str {
@@ -522,16 +471,15 @@ impl Command for RotateSelection {
 }
 
 impl RotateSelection {
-    fn rotate(&self, scene: &mut Scene, device: &wgpu::Device, angle: f32) {
+    fn rotate(&self, scene: &mut Scene, angle: f32) {
         let quat = Quat::from_axis_angle(self.axis, angle);
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
 
         for &(li, oi, fi) in &self.faces {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             for pos in &mut face.positions {
                 *pos = quat * (*pos - self.center) + self.center;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
 
         for &(li, oi) in &self.objects {
@@ -540,17 +488,13 @@ impl RotateSelection {
                     *pos = quat * (*pos - self.center) + self.center;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
 
         for &(li, oi, fi, vi) in &self.vertices {
             let pos = &mut scene.layers[li].objects[oi].faces[fi].positions[vi];
             *pos = quat * (*pos - self.center) + self.center;
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 }
@@ -562,12 +506,12 @@ pub struct FlipNormals {
 }
 
 impl Command for FlipNormals {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.flip(scene, device);
+    fn apply(&mut self, scene: &mut Scene) {
+        self.flip(scene);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.flip(scene, device); // Self-inverse
+    fn undo(&mut self, scene: &mut Scene) {
+        self.flip(scene); // Self-inverse
     }
 
     fn description(&self) -> &str {
@@ -576,15 +520,13 @@ impl Command for FlipNormals {
 }
 
 impl FlipNormals {
-    fn flip(&self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn flip(&self, scene: &mut Scene) {
         for &(li, oi, fi) in &self.faces {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             face.positions.swap(1, 3);
             face.uvs.swap(1, 3);
             face.colors.swap(1, 3);
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
 
         for &(li, oi) in &self.objects {
@@ -593,11 +535,7 @@ impl FlipNormals {
                 face.uvs.swap(1, 3);
                 face.colors.swap(1, 3);
             }
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
     }
 }
@@ -624,7 +562,7 @@ impl ExtrudeFaces {
 }
 
 impl Command for ExtrudeFaces {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.original_positions.clear();
         self.sides_added.clear();
 
@@ -654,9 +592,13 @@ impl Command for ExtrudeFaces {
                     uvs: default_uvs(),
                     colors: [Vec4::ONE; 4],
                     hidden: false,
+                    tileset: scene.layers[li].objects[oi].faces[fi].tileset,
                 };
                 scene.layers[li].objects[oi].faces.push(side);
             }
+            let obj = &mut scene.layers[li].objects[oi];
+            obj.mark_face_dirty(fi);
+            obj.mark_faces_dirty_from(obj.faces.len() - 4);
             *sides_per_obj.entry((li, oi)).or_insert(0) += 4;
         }
 
@@ -664,18 +606,9 @@ impl Command for ExtrudeFaces {
         for ((li, oi), count) in &sides_per_obj {
             self.sides_added.push((*li, *oi, *count));
         }
-
-        // Rebuild affected objects
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.face_indices {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove side faces (pop from end of each object)
         for &(li, oi, count) in &self.sides_added {
             for _ in 0..count {
@@ -687,17 +620,9 @@ impl Command for ExtrudeFaces {
         for (i, &(li, oi, fi)) in self.face_indices.iter().enumerate() {
             if let Some(orig) = self.original_positions.get(i) {
                 scene.layers[li].objects[oi].faces[fi].positions = *orig;
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-
-        // Rebuild
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.face_indices {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -712,15 +637,13 @@ pub struct DeleteSelection {
 }
 
 impl Command for DeleteSelection {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn apply(&mut self, scene: &mut Scene) {
         // Remove faces (sorted in reverse order to preserve indices)
         let mut faces = self.removed_faces.iter().map(|(l, o, f, _)| (*l, *o, *f)).collect::<Vec<_>>();
         faces.sort_by(|a, b| b.2.cmp(&a.2));
         for (li, oi, fi) in faces {
             scene.layers[li].objects[oi].faces.remove(fi);
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
 
         // Remove objects (sorted in reverse order)
@@ -729,15 +652,9 @@ impl Command for DeleteSelection {
         for (li, oi) in objs {
             scene.layers[li].objects.remove(oi);
         }
-
-        for (li, oi) in rebuild {
-            if oi < scene.layers[li].objects.len() {
-                scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-            }
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Re-insert objects (in forward order)
         for (li, oi, name, faces) in &self.removed_objects {
             let mut obj = Object::new(name.clone());
@@ -750,20 +667,7 @@ impl Command for DeleteSelection {
         faces_sorted.sort_by_key(|(_, _, fi, _)| *fi);
         for (li, oi, fi, face) in &faces_sorted {
             scene.layers[*li].objects[*oi].faces.insert(*fi, face.clone());
-        }
-
-        // Rebuild all affected objects
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for (li, oi, _, _) in &self.removed_faces {
-            rebuild.insert((*li, *oi));
-        }
-        for (li, oi, _, _) in &self.removed_objects {
-            rebuild.insert((*li, *oi));
-        }
-        for (li, oi) in rebuild {
-            if oi < scene.layers[li].objects.len() {
-                scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-            }
+            scene.layers[*li].objects[*oi].mark_faces_dirty_from(*fi);
         }
     }
 
@@ -782,13 +686,13 @@ pub struct ScaleSelection {
 }
 
 impl Command for ScaleSelection {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        self.scale(scene, device, self.scale_factor);
+    fn apply(&mut self, scene: &mut Scene) {
+        self.scale(scene, self.scale_factor);
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         let inv = Vec3::new(1.0 / self.scale_factor.x, 1.0 / self.scale_factor.y, 1.0 / self.scale_factor.z);
-        self.scale(scene, device, inv);
+        self.scale(scene, inv);
     }
 
     fn description(&self) -> &str {
@@ -797,15 +701,13 @@ impl Command for ScaleSelection {
 }
 
 impl ScaleSelection {
-    fn scale(&self, scene: &mut Scene, device: &wgpu::Device, factor: Vec3) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn scale(&self, scene: &mut Scene, factor: Vec3) {
         for &(li, oi, fi) in &self.faces {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             for pos in &mut face.positions {
                 *pos = self.center + (*pos - self.center) * factor;
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
 
         for &(li, oi) in &self.objects {
@@ -814,17 +716,13 @@ impl ScaleSelection {
                     *pos = self.center + (*pos - self.center) * factor;
                 }
             }
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(0);
         }
 
         for &(li, oi, fi, vi) in &self.vertices {
             let pos = &mut scene.layers[li].objects[oi].faces[fi].positions[vi];
             *pos = self.center + (*pos - self.center) * factor;
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 }
@@ -837,35 +735,24 @@ pub struct RetileFaces {
 }
 
 impl Command for RetileFaces {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         self.old_uvs.clear();
 
         for &(li, oi, fi) in &self.faces {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             self.old_uvs.push(face.uvs);
             face.uvs = self.new_uvs;
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn undo(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, fi)) in self.faces.iter().enumerate() {
             if let Some(old) = self.old_uvs.get(i) {
                 scene.layers[li].objects[oi].faces[fi].uvs = *old;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -881,35 +768,24 @@ pub struct PaintVertexColor {
 }
 
 impl Command for PaintVertexColor {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+    fn apply(&mut self, scene: &mut Scene) {
         self.old_colors.clear();
 
         for &(li, oi, fi) in &self.targets {
             let face = &mut scene.layers[li].objects[oi].faces[fi];
             self.old_colors.push(face.colors);
             face.colors = [self.new_color; 4];
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_face_dirty(fi);
         }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-
+    fn undo(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, fi)) in self.targets.iter().enumerate() {
             if let Some(old) = self.old_colors.get(i) {
                 scene.layers[li].objects[oi].faces[fi].colors = *old;
-                rebuild.insert((li, oi));
+                scene.layers[li].objects[oi].mark_face_dirty(fi);
             }
         }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -932,7 +808,7 @@ impl SubdivideFaces {
 }
 
 impl Command for SubdivideFaces {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.original_faces.clear();
         self.added_per_object.clear();
         let mut adds_per_obj: std::collections::HashMap<(usize, usize), usize> = std::collections::HashMap::new();
@@ -969,14 +845,15 @@ impl Command for SubdivideFaces {
             let cc = (c[0] + c[1] + c[2] + c[3]) * 0.25;
 
             let sub_faces = [
-                Face { positions: [p[0], m01, center, m30], uvs: [uv[0], uvm01, uvc, uvm30], colors: [c[0], cm01, cc, cm30], hidden: false },
-                Face { positions: [m01, p[1], m12, center], uvs: [uvm01, uv[1], uvm12, uvc], colors: [cm01, c[1], cm12, cc], hidden: false },
-                Face { positions: [center, m12, p[2], m23], uvs: [uvc, uvm12, uv[2], uvm23], colors: [cc, cm12, c[2], cm23], hidden: false },
-                Face { positions: [m30, center, m23, p[3]], uvs: [uvm30, uvc, uvm23, uv[3]], colors: [cm30, cc, cm23, c[3]], hidden: false },
+                Face { positions: [p[0], m01, center, m30], uvs: [uv[0], uvm01, uvc, uvm30], colors: [c[0], cm01, cc, cm30], hidden: false, tileset: face.tileset },
+                Face { positions: [m01, p[1], m12, center], uvs: [uvm01, uv[1], uvm12, uvc], colors: [cm01, c[1], cm12, cc], hidden: false, tileset: face.tileset },
+                Face { positions: [center, m12, p[2], m23], uvs: [uvc, uvm12, uv[2], uvm23], colors: [cc, cm12, c[2], cm23], hidden: false, tileset: face.tileset },
+                Face { positions: [m30, center, m23, p[3]], uvs: [uvm30, uvc, uvm23, uv[3]], colors: [cm30, cc, cm23, c[3]], hidden: false, tileset: face.tileset },
             ];
 
             // Remove original face, add 4 new ones
             scene.layers[li].objects[oi].faces.remove(fi);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             for sf in sub_faces {
                 scene.layers[li].objects[oi].faces.push(sf);
             }
@@ -986,17 +863,9 @@ impl Command for SubdivideFaces {
         for ((li, oi), count) in &adds_per_obj {
             self.added_per_object.push((*li, *oi, *count));
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.faces {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove added faces (pop from end)
         for &(li, oi, count) in &self.added_per_object {
             for _ in 0..count {
@@ -1010,16 +879,9 @@ impl Command for SubdivideFaces {
         for (i, &(li, oi, fi)) in sorted.iter().enumerate() {
             if let Some(orig) = self.original_faces.get(i) {
                 scene.layers[li].objects[oi].faces.insert(fi, orig.clone());
+                scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             }
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.faces {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
     fn description(&self) -> &str {
@@ -1044,7 +906,7 @@ impl CreateObjectFromSelection {
 }
 
 impl Command for CreateObjectFromSelection {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.moved_faces.clear();
 
         // Collect faces to move (sorted in reverse to preserve indices during removal)
@@ -1054,6 +916,7 @@ impl Command for CreateObjectFromSelection {
         let mut collected_faces = Vec::new();
         for &(li, oi, fi) in &sorted {
             let face = scene.layers[li].objects[oi].faces.remove(fi);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             self.moved_faces.push((li, oi, fi, face.clone()));
             collected_faces.push(face);
         }
@@ -1062,21 +925,11 @@ impl Command for CreateObjectFromSelection {
         // Create new object
         let mut new_obj = Object::new(self.object_name.clone());
         new_obj.faces = collected_faces;
-        new_obj.rebuild_gpu_mesh(device);
         scene.layers[self.target_layer].objects.push(new_obj);
         self.created_object_index = Some(scene.layers[self.target_layer].objects.len() - 1);
-
-        // Rebuild source objects
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _, _) in &self.moved_faces {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove created object
         if let Some(idx) = self.created_object_index {
             scene.layers[self.target_layer].objects.remove(idx);
@@ -1087,15 +940,7 @@ impl Command for CreateObjectFromSelection {
         to_restore.sort_by_key(|(_, _, fi, _)| *fi);
         for (li, oi, fi, face) in to_restore {
             scene.layers[li].objects[oi].faces.insert(fi, face);
-        }
-
-        // Rebuild all affected objects
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _, _) in &self.moved_faces {
-            rebuild.insert((li, oi));
-        }
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
     }
 
@@ -1122,7 +967,7 @@ impl TriangleDivide {
 }
 
 impl Command for TriangleDivide {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.original_faces.clear();
         self.added_per_object.clear();
         let mut adds_per_obj: std::collections::HashMap<(usize, usize), usize> = std::collections::HashMap::new();
@@ -1142,18 +987,19 @@ impl Command for TriangleDivide {
             let triangles = if self.diagonal == 0 {
                 // Split along 0→2 diagonal: triangles (0,1,2) and (0,2,3)
                 [
-                    Face { positions: [p[0], p[1], p[2], p[2]], uvs: [uv[0], uv[1], uv[2], uv[2]], colors: [c[0], c[1], c[2], c[2]], hidden: false },
-                    Face { positions: [p[0], p[2], p[3], p[3]], uvs: [uv[0], uv[2], uv[3], uv[3]], colors: [c[0], c[2], c[3], c[3]], hidden: false },
+                    Face { positions: [p[0], p[1], p[2], p[2]], uvs: [uv[0], uv[1], uv[2], uv[2]], colors: [c[0], c[1], c[2], c[2]], hidden: false, tileset: face.tileset },
+                    Face { positions: [p[0], p[2], p[3], p[3]], uvs: [uv[0], uv[2], uv[3], uv[3]], colors: [c[0], c[2], c[3], c[3]], hidden: false, tileset: face.tileset },
                 ]
             } else {
                 // Split along 1→3 diagonal: triangles (0,1,3) and (1,2,3)
                 [
-                    Face { positions: [p[0], p[1], p[3], p[3]], uvs: [uv[0], uv[1], uv[3], uv[3]], colors: [c[0], c[1], c[3], c[3]], hidden: false },
-                    Face { positions: [p[1], p[2], p[3], p[3]], uvs: [uv[1], uv[2], uv[3], uv[3]], colors: [c[1], c[2], c[3], c[3]], hidden: false },
+                    Face { positions: [p[0], p[1], p[3], p[3]], uvs: [uv[0], uv[1], uv[3], uv[3]], colors: [c[0], c[1], c[3], c[3]], hidden: false, tileset: face.tileset },
+                    Face { positions: [p[1], p[2], p[3], p[3]], uvs: [uv[1], uv[2], uv[3], uv[3]], colors: [c[1], c[2], c[3], c[3]], hidden: false, tileset: face.tileset },
                 ]
             };
 
             scene.layers[li].objects[oi].faces.remove(fi);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             for tf in triangles {
                 scene.layers[li].objects[oi].faces.push(tf);
             }
@@ -1163,13 +1009,9 @@ impl Command for TriangleDivide {
         for ((li, oi), count) in &adds_per_obj {
             self.added_per_object.push((*li, *oi, *count));
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.faces { rebuild.insert((li, oi)); }
-        for (li, oi) in rebuild { scene.layers[li].objects[oi].rebuild_gpu_mesh(device); }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove added triangles
         for &(li, oi, count) in &self.added_per_object {
             for _ in 0..count {
@@ -1183,12 +1025,9 @@ impl Command for TriangleDivide {
         for (i, &(li, oi, fi)) in sorted.iter().enumerate() {
             if let Some(orig) = self.original_faces.get(i) {
                 scene.layers[li].objects[oi].faces.insert(fi, orig.clone());
+                scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
             }
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &self.faces { rebuild.insert((li, oi)); }
-        for (li, oi) in rebuild { scene.layers[li].objects[oi].rebuild_gpu_mesh(device); }
     }
 
     fn description(&self) -> &str {
@@ -1212,7 +1051,7 @@ impl TriangleMerge {
 }
 
 impl Command for TriangleMerge {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.removed.clear();
         self.added.clear();
 
@@ -1229,7 +1068,8 @@ impl Command for TriangleMerge {
             let verts_a = degenerate_tri_verts(&face_a);
             let verts_b = degenerate_tri_verts(&face_b);
 
-            if let Some(merged) = merge_two_triangles(&verts_a, &verts_b) {
+            if let Some(mut merged) = merge_two_triangles(&verts_a, &verts_b) {
+                merged.tileset = face_a.tileset;
                 self.removed.push(((li_a, oi_a, fi_a), face_a, (li_b, oi_b, fi_b), face_b));
                 to_remove.push((li_a, oi_a, fi_a));
                 to_remove.push((li_b, oi_b, fi_b));
@@ -1241,6 +1081,7 @@ impl Command for TriangleMerge {
         to_remove.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| b.1.cmp(&a.1)).then_with(|| b.0.cmp(&a.0)));
         for &(li, oi, fi) in &to_remove {
             scene.layers[li].objects[oi].faces.remove(fi);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
 
         // Add merged faces
@@ -1248,13 +1089,9 @@ impl Command for TriangleMerge {
             scene.layers[li].objects[oi].faces.push(face);
             self.added.push((li, oi));
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi, _) in &to_remove { rebuild.insert((li, oi)); }
-        for (li, oi) in rebuild { scene.layers[li].objects[oi].rebuild_gpu_mesh(device); }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Remove merged faces (they were pushed to end)
         for &(li, oi) in self.added.iter().rev() {
             scene.layers[li].objects[oi].faces.pop();
@@ -1269,11 +1106,8 @@ impl Command for TriangleMerge {
         to_restore.sort_by_key(|(_, _, fi, _)| *fi);
         for (li, oi, fi, face) in to_restore {
             scene.layers[li].objects[oi].faces.insert(fi, face);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
-
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
-        for &(li, oi) in &self.added { rebuild.insert((li, oi)); }
-        for (li, oi) in rebuild { scene.layers[li].objects[oi].rebuild_gpu_mesh(device); }
     }
 
     fn description(&self) -> &str {
@@ -1296,16 +1130,18 @@ impl SubtractBlock {
 }
 
 impl Command for SubtractBlock {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         self.removed.clear();
         let min = self.aabb_min;
         let max = self.aabb_max;
 
-        // Collect faces to remove
+        // Collect faces to remove (a face's center is inside its bounds, so the BVH box query
+        // finds every candidate)
         let mut to_remove: Vec<(usize, usize, usize, Face)> = Vec::new();
         for (li, layer) in scene.layers.iter().enumerate() {
             for (oi, obj) in layer.objects.iter().enumerate() {
-                for (fi, face) in obj.faces.iter().enumerate() {
+                let mut test = |fi: usize| {
+                    let face = &obj.faces[fi];
                     let center = (face.positions[0] + face.positions[1] + face.positions[2] + face.positions[3]) * 0.25;
                     if center.x >= min.x && center.x <= max.x
                         && center.y >= min.y && center.y <= max.y
@@ -1313,38 +1149,32 @@ impl Command for SubtractBlock {
                     {
                         to_remove.push((li, oi, fi, face.clone()));
                     }
+                };
+                match obj.bvh() {
+                    Some(bvh) => bvh.query_aabb(min, max, &mut test),
+                    None => (0..obj.faces.len()).for_each(&mut test),
                 }
             }
         }
 
         // Remove in reverse fi order
         to_remove.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| b.1.cmp(&a.1)).then_with(|| b.0.cmp(&a.0)));
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
         for &(li, oi, fi, _) in &to_remove {
             scene.layers[li].objects[oi].faces.remove(fi);
-            rebuild.insert((li, oi));
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
 
         self.removed = to_remove;
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
-        }
     }
 
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         // Re-insert in ascending fi order
         let mut to_restore = self.removed.clone();
         to_restore.sort_by_key(|(_, _, fi, _)| *fi);
 
-        let mut rebuild: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
         for (li, oi, fi, face) in to_restore {
             scene.layers[li].objects[oi].faces.insert(fi, face);
-            rebuild.insert((li, oi));
-        }
-
-        for (li, oi) in rebuild {
-            scene.layers[li].objects[oi].rebuild_gpu_mesh(device);
+            scene.layers[li].objects[oi].mark_faces_dirty_from(fi);
         }
     }
 
@@ -1361,11 +1191,11 @@ pub struct CreateInstance {
 }
 
 impl Command for CreateInstance {
-    fn apply(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         scene.layers[self.layer].objects[self.object].instances.push(self.instance.clone());
     }
 
-    fn undo(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         scene.layers[self.layer].objects[self.object].instances.pop();
     }
 
@@ -1383,12 +1213,12 @@ pub struct DeleteInstance {
 }
 
 impl Command for DeleteInstance {
-    fn apply(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         let inst = scene.layers[self.layer].objects[self.object].instances.remove(self.instance_index);
         self.stored = Some(inst);
     }
 
-    fn undo(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         if let Some(inst) = self.stored.take() {
             scene.layers[self.layer].objects[self.object].instances.insert(self.instance_index, inst);
         }
@@ -1416,7 +1246,7 @@ impl DeconstructInstance {
 }
 
 impl Command for DeconstructInstance {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         let inst = scene.layers[self.layer].objects[self.object].instances.remove(self.instance_index);
         let m = inst.model_matrix();
 
@@ -1432,13 +1262,12 @@ impl Command for DeconstructInstance {
         let mut new_obj = Object::new(format!("{} (deconstruct)", inst.name));
         new_obj.faces = faces;
         new_obj.tileset_index = scene.layers[self.layer].objects[self.object].tileset_index;
-        new_obj.rebuild_gpu_mesh(device);
         scene.layers[self.layer].objects.push(new_obj);
         self.created_object_index = Some(scene.layers[self.layer].objects.len() - 1);
         self.stored_instance = Some(inst);
     }
 
-    fn undo(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         if let Some(idx) = self.created_object_index.take() {
             scene.layers[self.layer].objects.remove(idx);
         }
@@ -1460,7 +1289,7 @@ pub struct TransformInstance {
 }
 
 impl Command for TransformInstance {
-    fn apply(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn apply(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, ii)) in self.targets.iter().enumerate() {
             if let Some(inst) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
@@ -1474,7 +1303,7 @@ impl Command for TransformInstance {
         }
     }
 
-    fn undo(&mut self, scene: &mut Scene, _device: &wgpu::Device) {
+    fn undo(&mut self, scene: &mut Scene) {
         for (i, &(li, oi, ii)) in self.targets.iter().enumerate() {
             if let Some(inst) = scene.layers.get_mut(li)
                 .and_then(|l| l.objects.get_mut(oi))
@@ -1493,6 +1322,62 @@ impl Command for TransformInstance {
     }
 }
 
+/// Merge adjacent coplanar faces of whole objects into larger quads (see
+/// `optimize::merge_coplanar`). Faces are computed up front so the report is known before
+/// the command is pushed.
+pub struct OptimizeMesh {
+    /// (li, oi, faces): the optimized faces before apply, the originals after.
+    faces: Vec<(usize, usize, Vec<Face>)>,
+    pub report: OptimizeReport,
+}
+
+impl OptimizeMesh {
+    pub fn new(scene: &Scene, objects: &[(usize, usize)]) -> Self {
+        let mut faces = Vec::new();
+        let mut report = OptimizeReport::default();
+        for &(li, oi) in objects {
+            let Some(obj) = scene.layers.get(li).and_then(|l| l.objects.get(oi)) else { continue };
+            // Mixed objects sample the scene atlas, where tiles can't repeat
+            let mixed = obj.is_mixed();
+            let merged = optimize::merge_coplanar(&obj.faces, |f| {
+                Tiling::of(obj.face_tileset(f).and_then(|i| scene.tilesets.get(i)), mixed)
+            });
+            report.add(OptimizeReport::new(&obj.faces, &merged));
+            if merged.len() < obj.faces.len() {
+                faces.push((li, oi, merged));
+            }
+        }
+        Self { faces, report }
+    }
+
+    /// Whether any object has faces to merge.
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    fn swap(&mut self, scene: &mut Scene) {
+        for (li, oi, faces) in &mut self.faces {
+            let obj = &mut scene.layers[*li].objects[*oi];
+            std::mem::swap(&mut obj.faces, faces);
+            obj.mark_faces_dirty_from(0);
+        }
+    }
+}
+
+impl Command for OptimizeMesh {
+    fn apply(&mut self, scene: &mut Scene) {
+        self.swap(scene);
+    }
+
+    fn undo(&mut self, scene: &mut Scene) {
+        self.swap(scene);
+    }
+
+    fn description(&self) -> &str {
+        "Optimize Mesh"
+    }
+}
+
 /// Extract the 3 unique vertices from a degenerate quad (triangle).
 /// Returns [(pos, uv, color); 3] or all 4 if not degenerate.
 fn degenerate_tri_verts(face: &Face) -> Vec<(Vec3, Vec2, Vec4)> {
@@ -1557,5 +1442,6 @@ fn merge_two_triangles(
         uvs: [va.1, s0.1, vb.1, s1.1],
         colors: [va.2, s0.2, vb.2, s1.2],
         hidden: false,
+        tileset: None,
     })
 }
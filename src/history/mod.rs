@@ -9,11 +9,13 @@ pub struct History {
     max_depth: usize,
     /// Set to true on push/undo/redo, cleared by `mark_saved()`.
     pub dirty: bool,
+    /// Incremented on every push/undo/redo; lets autosave tell whether anything changed.
+    pub revision: u64,
 }
 
 pub trait Command {
-    fn apply(&mut self, scene: &mut Scene, device: &wgpu::Device);
-    fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device);
+    fn apply(&mut self, scene: &mut Scene);
+    fn undo(&mut self, scene: &mut Scene);
     fn description(&self) -> &str;
 }
 
@@ -24,32 +26,36 @@ impl History {
             redo_stack: Vec::new(),
             max_depth: 100,
             dirty: false,
+            revision: 0,
         }
     }
 
-    pub fn push(&mut self, mut cmd: Box<dyn Command>, scene: &mut Scene, device: &wgpu::Device) {
-        cmd.apply(scene, device);
+    pub fn push(&mut self, mut cmd: Box<dyn Command>, scene: &mut Scene) {
+        cmd.apply(scene);
         self.undo_stack.push(cmd);
         self.redo_stack.clear();
         if self.undo_stack.len() > self.max_depth {
             self.undo_stack.remove(0);
         }
         self.dirty = true;
+        self.revision += 1;
     }
 
-    pub fn undo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    pub fn undo(&mut self, scene: &mut Scene) {
         if let Some(mut cmd) = self.undo_stack.pop() {
-            cmd.undo(scene, device);
+            cmd.undo(scene);
             self.redo_stack.push(cmd);
             self.dirty = true;
+            self.revision += 1;
         }
     }
 
-    pub fn redo(&mut self, scene: &mut Scene, device: &wgpu::Device) {
+    pub fn redo(&mut self, scene: &mut Scene) {
         if let Some(mut cmd) = self.redo_stack.pop() {
-            cmd.apply(scene, device);
+            cmd.apply(scene);
             self.undo_stack.push(cmd);
             self.dirty = true;
+            self.revision += 1;
         }
     }
 
@@ -0,0 +1,583 @@
+//! glTF 2.0 importer (.gltf and .glb).
+//!
+//! Walks the default scene's node hierarchy and bakes node transforms into the faces. Accessors
+//! are read with byteStride, sparse substitution and every component type (normalized integers
+//! included). Triangles, strips and fans are supported, and EXT_mesh_gpu_instancing nodes are
+//! expanded into one copy per instance. Each base color texture becomes a new
+//! tileset, and primitives that share a node and a tileset are merged into one object.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+use serde_json::Value;
+use crate::scene::mesh::Face;
+use crate::tile::{AlphaMode, FilterMode, MaterialSettings, Tileset, WrapMode};
+use super::{ImportedObject, ImportedScene};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F534A;
+const CHUNK_BIN: u32 = 0x004E4942;
+/// Most elements an accessor without a buffer view (all zeros plus sparse values) may have,
+/// since nothing in the file bounds its count.
+const MAX_UNBACKED_ELEMENTS: usize = 1 << 24;
+
+/// Import a .glb or .gltf file (detected by content).
+pub fn import(path: &Path) -> Result<ImportedScene, String> {
+    let data = fs::read(path)
+        .map_err(|e| format!("Read failed: {e}"))?;
+
+    let (json_bytes, glb_bin) = if data.starts_with(GLB_MAGIC) {
+        split_glb(&data)?
+    } else {
+        (data.as_slice(), None)
+    };
+    let doc: Value = serde_json::from_slice(json_bytes)
+        .map_err(|e| format!("Invalid glTF JSON: {e}"))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let buffers = load_buffers(&doc, glb_bin, base_dir)?;
+    let gltf = Gltf { doc: &doc, buffers };
+
+    let mut importer = Importer {
+        gltf: &gltf,
+        base_dir,
+        result: ImportedScene::default(),
+        material_tilesets: HashMap::new(),
+    };
+
+    let nodes = gltf.array("nodes");
+    if nodes.is_empty() {
+        // No node hierarchy: import every mesh untransformed.
+        for mesh_idx in 0..gltf.array("meshes").len() {
+            importer.import_mesh(mesh_idx, None, Mat4::IDENTITY)?;
+        }
+    } else {
+        for root in gltf.scene_roots() {
+            importer.import_node(root, Mat4::IDENTITY, 0)?;
+        }
+    }
+
+    if importer.result.objects.is_empty() {
+        return Err("No geometry found in glTF file".to_string());
+    }
+    Ok(importer.result)
+}
+
+/// Split a GLB container into its JSON chunk and optional BIN chunk.
+fn split_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    if data.len() < 12 {
+        return Err("File too small for GLB".to_string());
+    }
+    let mut offset = 12;
+    let mut json = None;
+    let mut bin = None;
+    while offset + 8 <= data.len() {
+        let len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let kind = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap());
+        offset += 8;
+        let chunk = data.get(offset..offset + len)
+            .ok_or("GLB chunk extends past end of file")?;
+        match kind {
+            CHUNK_JSON => json = Some(chunk),
+            CHUNK_BIN => bin = Some(chunk),
+            _ => {}
+        }
+        offset += len;
+    }
+    Ok((json.ok_or("No JSON chunk in GLB")?, bin))
+}
+
+/// Resolve every buffer: the GLB BIN chunk, a base64 data URI, or a file next to the asset.
+fn load_buffers(doc: &Value, glb_bin: Option<&[u8]>, base_dir: &Path) -> Result<Vec<Vec<u8>>, String> {
+    let mut buffers = Vec::new();
+    for (i, buffer) in doc["buffers"].as_array().into_iter().flatten().enumerate() {
+        let data = match buffer["uri"].as_str() {
+            Some(uri) => load_uri(uri, base_dir).map_err(|e| format!("Buffer {i}: {e}"))?,
+            None if i == 0 => glb_bin.map(<[u8]>::to_vec).ok_or("Buffer 0 has no URI and no GLB BIN chunk")?,
+            None => return Err(format!("Buffer {i} has no URI")),
+        };
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+fn load_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>, String> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let (header, payload) = rest.split_once(',').ok_or("Malformed data URI")?;
+        if !header.ends_with(";base64") {
+            return Err("Only base64 data URIs are supported".to_string());
+        }
+        return decode_base64(payload);
+    }
+    let path = base_dir.join(percent_decode(uri));
+    fs::read(&path).map_err(|e| format!("Read {:?} failed: {e}", path))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub(super) fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b'\n' | b'\r' | b' ' => continue,
+            _ => return Err("Invalid base64 data".to_string()),
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Parsed document plus resolved buffers.
+struct Gltf<'a> {
+    doc: &'a Value,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Gltf<'_> {
+    fn array(&self, key: &str) -> &[Value] {
+        self.doc[key].as_array().map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn get(&self, key: &str, index: usize) -> Result<&Value, String> {
+        self.array(key).get(index).ok_or_else(|| format!("{key}[{index}] does not exist"))
+    }
+
+    /// Root nodes of the default scene, or every parentless node if there are no scenes.
+    fn scene_roots(&self) -> Vec<usize> {
+        let scene_idx = self.doc["scene"].as_u64().unwrap_or(0) as usize;
+        if let Some(scene) = self.array("scenes").get(scene_idx) {
+            return indices(&scene["nodes"]);
+        }
+        let mut is_child = vec![false; self.array("nodes").len()];
+        for node in self.array("nodes") {
+            for child in indices(&node["children"]) {
+                if let Some(flag) = is_child.get_mut(child) { *flag = true; }
+            }
+        }
+        (0..is_child.len()).filter(|&i| !is_child[i]).collect()
+    }
+
+    /// Bytes of a buffer view and its stride (0 = tightly packed).
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], usize), String> {
+        let view = self.get("bufferViews", index)?;
+        let buffer = self.buffers.get(uint(&view["buffer"]))
+            .ok_or_else(|| format!("bufferViews[{index}] references a missing buffer"))?;
+        let offset = uint(&view["byteOffset"]);
+        let length = uint(&view["byteLength"]);
+        let bytes = offset.checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| format!("bufferViews[{index}] is out of range"))?;
+        Ok((bytes, uint(&view["byteStride"])))
+    }
+
+    /// Read an accessor as `count` elements of `components` values each, flattened.
+    /// Normalized integers are mapped to [0, 1] / [-1, 1]; sparse values are applied.
+    fn read_accessor(&self, index: usize) -> Result<(Vec<f64>, usize), String> {
+        let acc = self.get("accessors", index)?;
+        let count = uint(&acc["count"]);
+        let component_type = uint(&acc["componentType"]) as u32;
+        let normalized = acc["normalized"].as_bool().unwrap_or(false);
+        let components = match acc["type"].as_str().unwrap_or("") {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" | "MAT2" => 4,
+            "MAT3" => 9,
+            "MAT4" => 16,
+            other => return Err(format!("accessors[{index}] has unknown type '{other}'")),
+        };
+        let size = component_size(component_type)
+            .ok_or_else(|| format!("accessors[{index}] has unknown componentType {component_type}"))?;
+
+        // Check the count against the bytes backing it before allocating anything from it
+        let view = match acc["bufferView"].as_u64() {
+            Some(view_idx) => {
+                let (bytes, stride) = self.buffer_view(view_idx as usize)?;
+                let stride = if stride == 0 { size * components } else { stride };
+                let offset = uint(&acc["byteOffset"]);
+                if !fits(bytes.len(), offset, stride, size * components, count) {
+                    return Err(format!("accessors[{index}] reads past its buffer view"));
+                }
+                Some((bytes, offset, stride))
+            }
+            None if count > MAX_UNBACKED_ELEMENTS => {
+                return Err(format!("accessors[{index}] has too many elements ({count})"));
+            }
+            None => None,
+        };
+        let mut values = vec![0.0; count * components];
+        if let Some((bytes, offset, stride)) = view {
+            read_elements(bytes, offset, stride, component_type, normalized, components, &mut values, 0..count)
+                .ok_or_else(|| format!("accessors[{index}] reads past its buffer view"))?;
+        }
+
+        let sparse = &acc["sparse"];
+        if sparse.is_object() {
+            let sparse_count = uint(&sparse["count"]);
+            let idx_type = uint(&sparse["indices"]["componentType"]) as u32;
+            let idx_size = component_size(idx_type)
+                .ok_or_else(|| format!("accessors[{index}] has bad sparse index type"))?;
+            let (idx_bytes, _) = self.buffer_view(uint(&sparse["indices"]["bufferView"]))?;
+            let (val_bytes, _) = self.buffer_view(uint(&sparse["values"]["bufferView"]))?;
+            if !fits(idx_bytes.len(), uint(&sparse["indices"]["byteOffset"]), idx_size, idx_size, sparse_count) {
+                return Err(format!("accessors[{index}] sparse indices out of range"));
+            }
+            if !fits(val_bytes.len(), uint(&sparse["values"]["byteOffset"]), size * components, size * components, sparse_count) {
+                return Err(format!("accessors[{index}] sparse values out of range"));
+            }
+            let mut targets = vec![0.0; sparse_count];
+            read_elements(idx_bytes, uint(&sparse["indices"]["byteOffset"]), idx_size, idx_type, false, 1, &mut targets, 0..sparse_count)
+                .ok_or_else(|| format!("accessors[{index}] sparse indices out of range"))?;
+
+            let mut sparse_values = vec![0.0; sparse_count * components];
+            read_elements(val_bytes, uint(&sparse["values"]["byteOffset"]), size * components,
+                component_type, normalized, components, &mut sparse_values, 0..sparse_count)
+                .ok_or_else(|| format!("accessors[{index}] sparse values out of range"))?;
+
+            for (i, &target) in targets.iter().enumerate() {
+                let target = target as usize;
+                if target < count {
+                    values[target * components..(target + 1) * components]
+                        .copy_from_slice(&sparse_values[i * components..(i + 1) * components]);
+                }
+            }
+        }
+        Ok((values, components))
+    }
+}
+
+/// Whether `count` elements of `element` bytes, `stride` apart from `offset`, lie within `len`
+/// bytes. Checked, since every term comes from the file.
+fn fits(len: usize, offset: usize, stride: usize, element: usize, count: usize) -> bool {
+    let Some(last) = count.checked_sub(1) else { return offset <= len };
+    last.checked_mul(stride)
+        .and_then(|span| span.checked_add(offset))
+        .and_then(|start| start.checked_add(element))
+        .is_some_and(|end| end <= len)
+}
+
+fn component_size(component_type: u32) -> Option<usize> {
+    match component_type {
+        5120 | 5121 => Some(1), // BYTE, UNSIGNED_BYTE
+        5122 | 5123 => Some(2), // SHORT, UNSIGNED_SHORT
+        5125 | 5126 => Some(4), // UNSIGNED_INT, FLOAT
+        _ => None,
+    }
+}
+
+/// Decode `components` values per element for each element in `range` into `out`.
+/// Returns None if any element lies outside `bytes`.
+#[allow(clippy::too_many_arguments)]
+fn read_elements(
+    bytes: &[u8],
+    offset: usize,
+    stride: usize,
+    component_type: u32,
+    normalized: bool,
+    components: usize,
+    out: &mut [f64],
+    range: std::ops::Range<usize>,
+) -> Option<()> {
+    let size = component_size(component_type)?;
+    for i in range {
+        for c in 0..components {
+            let at = offset + i * stride + c * size;
+            let b = bytes.get(at..at + size)?;
+            let v = match component_type {
+                5120 => {
+                    let v = b[0] as i8 as f64;
+                    if normalized { (v / 127.0).max(-1.0) } else { v }
+                }
+                5121 => {
+                    let v = b[0] as f64;
+                    if normalized { v / 255.0 } else { v }
+                }
+                5122 => {
+                    let v = i16::from_le_bytes([b[0], b[1]]) as f64;
+                    if normalized { (v / 32767.0).max(-1.0) } else { v }
+                }
+                5123 => {
+                    let v = u16::from_le_bytes([b[0], b[1]]) as f64;
+                    if normalized { v / 65535.0 } else { v }
+                }
+                5125 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            };
+            out[i * components + c] = v;
+        }
+    }
+    Some(())
+}
+
+fn uint(v: &Value) -> usize {
+    v.as_u64().unwrap_or(0) as usize
+}
+
+fn indices(v: &Value) -> Vec<usize> {
+    v.as_array().into_iter().flatten().filter_map(Value::as_u64).map(|i| i as usize).collect()
+}
+
+fn floats<const N: usize>(v: &Value) -> Option<[f32; N]> {
+    let arr = v.as_array()?;
+    if arr.len() != N { return None; }
+    let mut out = [0.0; N];
+    for (o, x) in out.iter_mut().zip(arr) {
+        *o = x.as_f64()? as f32;
+    }
+    Some(out)
+}
+
+fn node_local_matrix(node: &Value) -> Mat4 {
+    if let Some(m) = floats::<16>(&node["matrix"]) {
+        return Mat4::from_cols_array(&m);
+    }
+    let t = floats::<3>(&node["translation"]).map(Vec3::from_array).unwrap_or(Vec3::ZERO);
+    let r = floats::<4>(&node["rotation"]).map(Quat::from_array).unwrap_or(Quat::IDENTITY);
+    let s = floats::<3>(&node["scale"]).map(Vec3::from_array).unwrap_or(Vec3::ONE);
+    Mat4::from_scale_rotation_translation(s, r.normalize(), t)
+}
+
+struct Importer<'a> {
+    gltf: &'a Gltf<'a>,
+    base_dir: &'a Path,
+    result: ImportedScene,
+    /// glTF material index → imported tileset index (None = untextured).
+    material_tilesets: HashMap<usize, Option<usize>>,
+}
+
+impl Importer<'_> {
+    fn import_node(&mut self, index: usize, parent: Mat4, depth: usize) -> Result<(), String> {
+        // Guard against cyclic (invalid) hierarchies.
+        if depth > 256 {
+            return Err("Node hierarchy is too deep (cycle?)".to_string());
+        }
+        let node = self.gltf.get("nodes", index)?;
+        let world = parent * node_local_matrix(node);
+        if let Some(mesh) = node["mesh"].as_u64() {
+            let instancing = &node["extensions"]["EXT_mesh_gpu_instancing"]["attributes"];
+            if instancing.is_object() {
+                for instance in self.gpu_instances(instancing)? {
+                    self.import_mesh(mesh as usize, node["name"].as_str(), world * instance)?;
+                }
+            } else {
+                self.import_mesh(mesh as usize, node["name"].as_str(), world)?;
+            }
+        }
+        for child in indices(&node["children"]) {
+            self.import_node(child, world, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Per-instance matrices from EXT_mesh_gpu_instancing TRANSLATION/ROTATION/SCALE accessors.
+    fn gpu_instances(&self, attributes: &Value) -> Result<Vec<Mat4>, String> {
+        let read = |key: &str| -> Result<Option<Vec<f64>>, String> {
+            attributes[key].as_u64()
+                .map(|acc| self.gltf.read_accessor(acc as usize).map(|(v, _)| v))
+                .transpose()
+        };
+        let translations = read("TRANSLATION")?;
+        let rotations = read("ROTATION")?;
+        let scales = read("SCALE")?;
+        let count = [translations.as_ref().map(|v| v.len() / 3), rotations.as_ref().map(|v| v.len() / 4), scales.as_ref().map(|v| v.len() / 3)]
+            .into_iter().flatten().min().unwrap_or(0);
+
+        let vec3 = |v: &Option<Vec<f64>>, i: usize, default: Vec3| {
+            v.as_ref().map(|v| Vec3::new(v[i * 3] as f32, v[i * 3 + 1] as f32, v[i * 3 + 2] as f32)).unwrap_or(default)
+        };
+        Ok((0..count).map(|i| {
+            let r = rotations.as_ref()
+                .map(|v| Quat::from_xyzw(v[i * 4] as f32, v[i * 4 + 1] as f32, v[i * 4 + 2] as f32, v[i * 4 + 3] as f32))
+                .unwrap_or(Quat::IDENTITY);
+            Mat4::from_scale_rotation_translation(vec3(&scales, i, Vec3::ONE), r.normalize(), vec3(&translations, i, Vec3::ZERO))
+        }).collect())
+    }
+
+    /// Convert a mesh's primitives to faces, one object per tileset.
+    fn import_mesh(&mut self, index: usize, node_name: Option<&str>, world: Mat4) -> Result<(), String> {
+        let mesh = self.gltf.get("meshes", index)?;
+        let name = node_name.or(mesh["name"].as_str()).map(str::to_string);
+        let mirrored = world.determinant() < 0.0;
+
+        let mut groups: Vec<(Option<usize>, Vec<Face>)> = Vec::new();
+        for (p, prim) in mesh["primitives"].as_array().into_iter().flatten().enumerate() {
+            let mode = prim["mode"].as_u64().unwrap_or(4);
+            if !(4..=6).contains(&mode) {
+                log::warn!("Skipping meshes[{index}].primitives[{p}]: mode {mode} is not a triangle mode");
+                continue;
+            }
+            let attrs = &prim["attributes"];
+            let Some(pos_acc) = attrs["POSITION"].as_u64() else { continue };
+
+            let (pos, _) = self.gltf.read_accessor(pos_acc as usize)?;
+            let positions: Vec<Vec3> = pos.chunks_exact(3)
+                .map(|c| world.transform_point3(Vec3::new(c[0] as f32, c[1] as f32, c[2] as f32)))
+                .collect();
+            let uvs: Vec<Vec2> = match attrs["TEXCOORD_0"].as_u64() {
+                Some(acc) => self.gltf.read_accessor(acc as usize)?.0.chunks_exact(2)
+                    .map(|c| Vec2::new(c[0] as f32, c[1] as f32))
+                    .collect(),
+                None => Vec::new(),
+            };
+            let colors: Vec<Vec4> = match attrs["COLOR_0"].as_u64() {
+                Some(acc) => {
+                    let (values, n) = self.gltf.read_accessor(acc as usize)?;
+                    values.chunks_exact(n.max(1))
+                        .map(|c| Vec4::new(
+                            c[0] as f32,
+                            c.get(1).copied().unwrap_or(1.0) as f32,
+                            c.get(2).copied().unwrap_or(1.0) as f32,
+                            if n == 4 { c[3] as f32 } else { 1.0 },
+                        ))
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+            let vertex_indices: Vec<u32> = match prim["indices"].as_u64() {
+                Some(acc) => self.gltf.read_accessor(acc as usize)?.0.iter().map(|&i| i as u32).collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+
+            let mut triangles = triangle_list(&vertex_indices, mode);
+            if mirrored {
+                for tri in triangles.chunks_exact_mut(3) {
+                    tri.swap(1, 2);
+                }
+            }
+            let faces = super::triangles_to_faces(&triangles, &positions, &uvs, &colors);
+
+            let tileset = match prim["material"].as_u64() {
+                Some(m) => self.material_tileset(m as usize)?,
+                None => None,
+            };
+            match groups.iter_mut().find(|(t, _)| *t == tileset) {
+                Some((_, existing)) => existing.extend(faces),
+                None => groups.push((tileset, faces)),
+            }
+        }
+
+        for (tileset, faces) in groups {
+            if faces.is_empty() { continue; }
+            self.result.objects.push(ImportedObject { name: name.clone(), faces, tileset, layer: None });
+        }
+        Ok(())
+    }
+
+    /// Tileset for a material's base color texture, importing the image on first use.
+    fn material_tileset(&mut self, material_idx: usize) -> Result<Option<usize>, String> {
+        if let Some(&cached) = self.material_tilesets.get(&material_idx) {
+            return Ok(cached);
+        }
+        let material = self.gltf.get("materials", material_idx)?;
+        let tileset = match material["pbrMetallicRoughness"]["baseColorTexture"]["index"].as_u64() {
+            Some(tex) => self.import_texture(tex as usize, material)?,
+            None => None,
+        };
+        self.material_tilesets.insert(material_idx, tileset);
+        Ok(tileset)
+    }
+
+    fn import_texture(&mut self, texture_idx: usize, material: &Value) -> Result<Option<usize>, String> {
+        let texture = self.gltf.get("textures", texture_idx)?;
+        let Some(source) = texture["source"].as_u64() else { return Ok(None) };
+        let image = self.gltf.get("images", source as usize)?;
+
+        let bytes = if let Some(view) = image["bufferView"].as_u64() {
+            self.gltf.buffer_view(view as usize)?.0.to_vec()
+        } else if let Some(uri) = image["uri"].as_str() {
+            match load_uri(uri, self.base_dir) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::warn!("Skipping image {source}: {e}");
+                    return Ok(None);
+                }
+            }
+        } else {
+            return Ok(None);
+        };
+        let rgba = match image::load_from_memory(&bytes) {
+            Ok(img) => img.to_rgba8(),
+            Err(e) => {
+                log::warn!("Skipping image {source}: decode failed: {e}");
+                return Ok(None);
+            }
+        };
+        let (w, h) = rgba.dimensions();
+
+        let mut settings = MaterialSettings::default();
+        if let Some(sampler) = texture["sampler"].as_u64().and_then(|s| self.gltf.array("samplers").get(s as usize)) {
+            // 9728 = NEAREST; linear and mipmapped filters all map to Linear.
+            settings.filter = match sampler["magFilter"].as_u64() {
+                Some(9728) | None => FilterMode::Nearest,
+                Some(_) => FilterMode::Linear,
+            };
+            settings.wrap = match sampler["wrapS"].as_u64().unwrap_or(10497) {
+                33071 => WrapMode::ClampToEdge,
+                33648 => WrapMode::MirroredRepeat,
+                _ => WrapMode::Repeat,
+            };
+        }
+        match material["alphaMode"].as_str().unwrap_or("OPAQUE") {
+            "MASK" => {
+                settings.alpha_mode = AlphaMode::AlphaTest;
+                settings.alpha_cutoff = material["alphaCutoff"].as_f64().unwrap_or(0.5) as f32;
+            }
+            "BLEND" => settings.alpha_mode = AlphaMode::AlphaBlend,
+            _ => settings.alpha_mode = AlphaMode::Opaque,
+        }
+
+        let name = image["name"].as_str()
+            .or(texture["name"].as_str())
+            .or(material["name"].as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("image_{source}"));
+        let (tile_w, tile_h) = super::imported_tile_size(w, h);
+        self.result.tilesets.push(Tileset::from_rgba(name, w, h, Some(rgba.into_raw()), tile_w, tile_h, settings));
+        Ok(Some(self.result.tilesets.len() - 1))
+    }
+}
+
+/// Expand indices of a triangle list (4), strip (5) or fan (6) into a triangle list.
+fn triangle_list(indices: &[u32], mode: u64) -> Vec<u32> {
+    match mode {
+        5 => (2..indices.len()).flat_map(|i| {
+            if i % 2 == 0 {
+                [indices[i - 2], indices[i - 1], indices[i]]
+            } else {
+                [indices[i - 1], indices[i - 2], indices[i]]
+            }
+        }).collect(),
+        6 => (2..indices.len()).flat_map(|i| [indices[0], indices[i - 1], indices[i]]).collect(),
+        _ => indices[..indices.len() - indices.len() % 3].to_vec(),
+    }
+}
@@ -5,34 +5,109 @@ use std::path::{Path, PathBuf};
 use glam::{Vec2, Vec3, Vec4};
 use crate::scene::Scene;
 use crate::scene::mesh::Face;
+use crate::scene::optimize::{self, OptimizeReport, Tiling};
+use crate::tile::atlas::TilesetAtlas;
+use crate::tile::maps::MapChannel;
+use crate::tile::properties::TileProperties;
+
+mod gltf_import;
+pub mod recovery;
+mod schema;
+mod tilemap;
+mod vox;
+
+pub use tilemap::{import_ldtk, import_tiled};
+pub use vox::{export_vox, import_vox};
 
 /// Magic header bytes for the .ct3d file format.
 const MAGIC: &[u8; 4] = b"CT3D";
-/// Current file format version.
-const VERSION: u32 = 1;
+/// Current file format version. See `schema` for the per-version layouts and migrations.
+const VERSION: u32 = 10;
+
+/// File extension of the human-readable JSON scene format.
+pub const TEXT_SCENE_EXTENSION: &str = "ct3dj";
+
+/// Result of importing a model file: objects plus any textures, which become new tilesets.
+#[derive(Default)]
+pub struct ImportedScene {
+    pub objects: Vec<ImportedObject>,
+    /// CPU-only tilesets (no GPU resources yet), referenced by `ImportedObject::tileset`.
+    pub tilesets: Vec<crate::tile::Tileset>,
+    /// New layers to create, referenced by `ImportedObject::layer`.
+    pub layers: Vec<ImportedLayer>,
+}
+
+/// One imported object.
+pub struct ImportedObject {
+    pub name: Option<String>,
+    pub faces: Vec<Face>,
+    /// Index into `ImportedScene::tilesets`.
+    pub tileset: Option<usize>,
+    /// Index into `ImportedScene::layers` (None = the active layer).
+    pub layer: Option<usize>,
+}
+
+/// A layer created by an import (e.g. one per tile layer of a 2D map).
+pub struct ImportedLayer {
+    pub name: String,
+    pub visible: bool,
+}
 
-/// Save a scene to a .ct3d file.
+impl From<Vec<(Vec<Face>, Option<String>)>> for ImportedScene {
+    /// Wrap untextured (faces, name) lists from importers that do not read materials.
+    fn from(objects: Vec<(Vec<Face>, Option<String>)>) -> Self {
+        Self {
+            objects: objects.into_iter()
+                .map(|(faces, name)| ImportedObject { name, faces, tileset: None, layer: None })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Save a scene to a .ct3d file, or to the JSON text format if the path ends in `.ct3dj`.
 pub fn save_scene(scene: &Scene, path: &Path) -> Result<(), String> {
-    let payload = bincode::serialize(scene)
-        .map_err(|e| format!("Serialization failed: {e}"))?;
+    let mut latest = schema::SceneLatest::from_scene(scene)?;
+    relativize_tileset_sources(&mut latest, path);
 
-    let mut data = Vec::with_capacity(MAGIC.len() + 4 + payload.len());
-    data.extend_from_slice(MAGIC);
-    data.extend_from_slice(&VERSION.to_le_bytes());
-    data.extend_from_slice(&payload);
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case(TEXT_SCENE_EXTENSION)) {
+        return save_scene_text(latest, path);
+    }
 
+    let data = encode_binary(&latest)?;
     fs::write(path, &data)
         .map_err(|e| format!("Write failed: {e}"))?;
 
     Ok(())
 }
 
-/// Load a scene from a .ct3d file.
-/// GPU meshes will be None — caller must call rebuild_gpu_mesh() on all objects.
+/// Encode a scene in the binary .ct3d layout (magic, version, payload).
+fn encode_binary(latest: &schema::SceneLatest) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&VERSION.to_le_bytes());
+    schema::encode(latest, &mut data)?;
+    Ok(data)
+}
+
+/// Load a scene from a .ct3d file, migrating older format versions forward.
+/// GPU meshes and tileset textures will be None — objects upload on their first
+/// `sync_gpu_mesh()`, and the caller must create GPU resources for every tileset.
 pub fn load_scene(path: &Path) -> Result<Scene, String> {
+    let mut scene = read_scene(path)?;
+    resolve_tileset_sources(&mut scene, path);
+    Ok(scene)
+}
+
+fn read_scene(path: &Path) -> Result<Scene, String> {
     let data = fs::read(path)
         .map_err(|e| format!("Read failed: {e}"))?;
 
+    // Text scenes are JSON; detect by content so a renamed file still loads.
+    if data.trim_ascii_start().starts_with(b"{") {
+        return load_scene_text(&data, path);
+    }
+
     if data.len() < 8 {
         return Err("File too small".to_string());
     }
@@ -46,82 +121,419 @@ pub fn load_scene(path: &Path) -> Result<Scene, String> {
         return Err(format!("File version {version} is newer than supported ({VERSION})"));
     }
 
-    let scene: Scene = bincode::deserialize(&data[8..])
-        .map_err(|e| format!("Deserialization failed: {e}"))?;
+    schema::decode(version, &data[8..])?.into_scene()
+}
 
-    Ok(scene)
+/// Store tileset source paths relative to the scene file where both share a root, so a
+/// project folder can move as a whole. Other paths stay absolute.
+fn relativize_tileset_sources(latest: &mut schema::SceneLatest, scene_path: &Path) {
+    let Some(scene_dir) = std::path::absolute(scene_path).ok().and_then(|p| p.parent().map(Path::to_path_buf)) else {
+        return;
+    };
+    for source in latest.tilesets.iter_mut().filter_map(|ts| ts.source.as_mut()) {
+        if let Some(rel) = relative_path(Path::new(&source.path), &scene_dir) {
+            source.path = rel;
+        }
+    }
+}
+
+/// Resolve relative tileset source paths (see `relativize_tileset_sources`) against the
+/// directory of the scene file they were loaded from.
+fn resolve_tileset_sources(scene: &mut Scene, scene_path: &Path) {
+    let scene_dir = scene_path.parent().unwrap_or(Path::new("."));
+    for source in scene.tilesets.iter_mut().filter_map(|ts| ts.source.as_mut()) {
+        if source.path.is_relative() {
+            source.path = scene_dir.join(&source.path);
+        }
+    }
+}
+
+/// `path` relative to the directory `base`, with `/` separators, or None if either is not
+/// absolute or they sit on different roots (drives).
+fn relative_path(path: &Path, base: &Path) -> Option<String> {
+    use std::path::Component;
+    if !path.is_absolute() || !base.is_absolute() {
+        return None;
+    }
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    // Sharing only the root (or nothing, e.g. another drive) would climb to the top anyway
+    let root = path.iter().take_while(|c| matches!(c, Component::Prefix(_) | Component::RootDir)).count();
+    if common <= root {
+        return None;
+    }
+    let parts: Vec<String> = std::iter::repeat_n("..".to_string(), base.len() - common)
+        .chain(path[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()))
+        .collect();
+    Some(parts.join("/"))
 }
 
-/// Export the scene as a Wavefront .obj file.
-pub fn export_obj(scene: &Scene, path: &Path) -> Result<(), String> {
-    let mut positions = Vec::new();
-    let mut texcoords = Vec::new();
-    let mut objects: Vec<(String, Vec<(usize, usize)>)> = Vec::new();
+/// Directory holding the tileset PNGs of a text scene: `<stem>_tilesets/` next to the file.
+fn text_scene_image_dir(path: &Path) -> (PathBuf, String) {
+    let stem = path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "scene".to_string());
+    let dir_name = format!("{stem}_tilesets");
+    (path.with_file_name(&dir_name), dir_name)
+}
+
+fn save_scene_text(latest: schema::SceneLatest, path: &Path) -> Result<(), String> {
+    let (image_dir, dir_name) = text_scene_image_dir(path);
+    let previous = previous_text_scene_images(path, &dir_name);
+    let mut written = std::collections::HashSet::new();
+    let json = schema::encode_text(latest, VERSION, |i, name, png| {
+        fs::create_dir_all(&image_dir)
+            .map_err(|e| format!("Create tileset directory failed: {e}"))?;
+        let file_name = format!("{i:02}_{}.png", sanitize_file_name(name));
+        fs::write(image_dir.join(&file_name), png)
+            .map_err(|e| format!("Write tileset image failed: {e}"))?;
+        written.insert(file_name.clone());
+        Ok(format!("{dir_name}/{file_name}"))
+    })?;
+
+    fs::write(path, json)
+        .map_err(|e| format!("Write failed: {e}"))?;
+    for name in previous.difference(&written) {
+        if let Err(e) = fs::remove_file(image_dir.join(name)) {
+            log::warn!("Could not remove stale tileset image {name}: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// File names of the images the text scene currently at `path` references in its tileset
+/// directory: the ones an earlier save wrote, so they can be pruned when this save no longer
+/// does (removed or renamed tilesets). Anything else in the directory is left alone.
+fn previous_text_scene_images(path: &Path, dir_name: &str) -> std::collections::HashSet<String> {
+    fn collect(value: &serde_json::Value, prefix: &str, out: &mut std::collections::HashSet<String>) {
+        match value {
+            serde_json::Value::String(s) => {
+                // Only plain file names directly inside the directory
+                if let Some(name) = s.strip_prefix(prefix).filter(|n| !n.is_empty() && !n.contains(['/', '\\']) && *n != "..") {
+                    out.insert(name.to_string());
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect(v, prefix, out)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| collect(v, prefix, out)),
+            _ => {}
+        }
+    }
+    let mut names = std::collections::HashSet::new();
+    let root = fs::read(path).ok().and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok());
+    if let Some(root) = root {
+        collect(&root["tilesets"], &format!("{dir_name}/"), &mut names);
+    }
+    names
+}
+
+fn load_scene_text(data: &[u8], path: &Path) -> Result<Scene, String> {
+    let json = std::str::from_utf8(data)
+        .map_err(|e| format!("Invalid UTF-8 in text scene: {e}"))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    schema::decode_text(json, VERSION, |rel| {
+        if !is_contained_path(rel) {
+            return Err(format!("Tileset image path {rel} points outside the scene's folder"));
+        }
+        fs::read(base.join(rel)).map_err(|e| format!("Read {rel} failed: {e}"))
+    })?.into_scene()
+}
+
+/// Whether a path read from a scene file stays below the scene's folder: relative, with no
+/// `..` (shared scenes must not pull arbitrary files off disk).
+fn is_contained_path(rel: &str) -> bool {
+    use std::path::Component;
+    !rel.is_empty() && Path::new(rel).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Replace characters that are awkward in file names.
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() { "tileset".to_string() } else { cleaned }
+}
+
+/// Tilesets as exporters see them: the scene's, followed by a baked copy of every palette
+/// variant a visible layer or object draws with, so each variant becomes its own material.
+struct ExportTilesets<'a> {
+    scene_tilesets: &'a [crate::tile::Tileset],
+    /// CPU copies of the scene's tilesets plus the variant copies; empty when no variant is used.
+    owned: Vec<crate::tile::Tileset>,
+    /// (scene tileset, variant name) → index of its copy in `tilesets()`.
+    variants: std::collections::HashMap<(usize, String), usize>,
+}
+
+impl<'a> ExportTilesets<'a> {
+    fn new(scene: &'a Scene) -> Self {
+        let mut copies = Vec::new();
+        let mut variants = std::collections::HashMap::new();
+        for layer in scene.layers.iter().filter(|l| l.visible) {
+            for object in &layer.objects {
+                let Some(name) = Self::object_variant(layer, object) else { continue };
+                for ts_idx in object.faces.iter().filter_map(|f| object.face_tileset(f)) {
+                    let key = (ts_idx, name.to_string());
+                    if variants.contains_key(&key) { continue; }
+                    let Some(copy) = scene.tilesets.get(ts_idx).and_then(|ts| {
+                        ts.variant_copy(ts.indexed.as_ref()?.variant_index(name)?)
+                    }) else { continue };
+                    variants.insert(key, scene.tilesets.len() + copies.len());
+                    copies.push(copy);
+                }
+            }
+        }
+        let mut owned = Vec::new();
+        if !copies.is_empty() {
+            owned.extend(scene.tilesets.iter().map(|ts| ts.cpu_copy()));
+            owned.append(&mut copies);
+        }
+        Self { scene_tilesets: &scene.tilesets, owned, variants }
+    }
+
+    /// The palette variant an object exports with, matching the viewport: mixed-tileset objects
+    /// draw from the scene atlas, which has no variants, so they keep the base colors.
+    fn object_variant<'s>(layer: &'s crate::scene::Layer, object: &'s crate::scene::Object) -> Option<&'s str> {
+        layer.object_variant(object).filter(|_| !object.is_mixed())
+    }
+
+    fn tilesets(&self) -> &[crate::tile::Tileset] {
+        if self.owned.is_empty() { self.scene_tilesets } else { &self.owned }
+    }
+
+    /// An object's visible faces, each tagged with the tileset its UVs refer to (the object's
+    /// unless the face overrides it, swapped for the palette variant copy the object draws with
+    /// (see `object_variant`); dangling indices dropped).
+    fn faces(&self, layer: &crate::scene::Layer, object: &crate::scene::Object) -> Vec<Face> {
+        let variant = Self::object_variant(layer, object);
+        object.faces.iter().filter(|f| !f.hidden).map(|f| {
+            let mut face = f.clone();
+            face.tileset = object.face_tileset(f).filter(|&i| i < self.scene_tilesets.len()).map(|i| {
+                variant.and_then(|name| self.variants.get(&(i, name.to_string())).copied()).unwrap_or(i)
+            });
+            face
+        }).collect()
+    }
+
+    /// `faces` (from `faces()`) with coplanar same-tile runs merged into larger quads, and
+    /// their counts. `atlas` = UVs get remapped into the packed tileset atlas, so only
+    /// untextured faces can grow.
+    fn optimize(&self, faces: &[Face], atlas: bool) -> (Vec<Face>, OptimizeReport) {
+        let tilesets = self.tilesets();
+        let merged = optimize::merge_coplanar(faces, |f| Tiling::of(f.tileset.map(|i| &tilesets[i]), atlas));
+        let report = OptimizeReport::new(faces, &merged);
+        (merged, report)
+    }
+}
+
+/// Alpha handling for a material shared by several tilesets: the most permissive mode wins
+/// (blend over mask over opaque), with the lowest cutoff among masked tilesets.
+fn combined_alpha(tilesets: &[crate::tile::Tileset]) -> (crate::tile::AlphaMode, f32) {
+    use crate::tile::AlphaMode;
+    let modes = || tilesets.iter().filter(|ts| ts.image_data.is_some()).map(|ts| &ts.material);
+    if modes().any(|m| m.alpha_mode == AlphaMode::AlphaBlend) {
+        return (AlphaMode::AlphaBlend, 0.0);
+    }
+    match modes().filter(|m| m.alpha_mode == AlphaMode::AlphaTest).map(|m| m.alpha_cutoff).reduce(f32::min) {
+        Some(cutoff) => (AlphaMode::AlphaTest, cutoff),
+        None => (AlphaMode::Opaque, 0.0),
+    }
+}
+
+/// Export the scene as a Wavefront .obj file with a companion .mtl.
+///
+/// Each tileset becomes one material whose `map_Kd` is a PNG written next to the .obj (companion
+/// maps as `map_Bump`, `map_Ke` and `map_Ks`); objects mixing tilesets switch material per face
+/// group. With `tileset_atlas`, all tilesets are packed into one atlas PNG and material instead.
+/// Vertex colors use the common `v x y z r g b` extension; V is flipped to OBJ's bottom-up convention.
+/// Returns the face and vertex counts saved when `options.optimize` is set.
+pub fn export_obj(scene: &Scene, path: &Path, options: &ObjExportOptions) -> Result<Option<OptimizeReport>, String> {
+    let export = ExportTilesets::new(scene);
+    let tilesets = export.tilesets();
+    // (name, faces in world space, each tagged with its tileset)
+    let mut objects: Vec<(String, Vec<Face>)> = Vec::new();
+    let mut report = OptimizeReport::default();
 
     for layer in &scene.layers {
         if !layer.visible { continue; }
         for object in &layer.objects {
-            let mut face_refs = Vec::new();
-            for face in &object.faces {
-                let base_v = positions.len();
-                let base_vt = texcoords.len();
-                positions.extend_from_slice(&face.positions);
-                texcoords.extend_from_slice(&face.uvs);
-                face_refs.push((base_v, base_vt));
-            }
-            if !face_refs.is_empty() {
-                objects.push((object.name.clone(), face_refs));
+            let mut visible_faces = export.faces(layer, object);
+            if visible_faces.is_empty() { continue; }
+            if options.optimize {
+                let (merged, counts) = export.optimize(&visible_faces, options.tileset_atlas);
+                // Flattened instances repeat the object's geometry
+                (0..=object.instances.len()).for_each(|_| report.add(counts));
+                visible_faces = merged;
             }
+            objects.push((object.name.clone(), visible_faces.clone()));
 
             // Flatten instances
             for inst in &object.instances {
                 let m = inst.model_matrix();
-                let mut inst_refs = Vec::new();
-                for face in &object.faces {
-                    let base_v = positions.len();
-                    let base_vt = texcoords.len();
-                    let transformed: Vec<Vec3> = face.positions.iter()
-                        .map(|&p| m.transform_point3(p))
-                        .collect();
-                    positions.extend_from_slice(&transformed);
-                    texcoords.extend_from_slice(&face.uvs);
-                    inst_refs.push((base_v, base_vt));
-                }
-                if !inst_refs.is_empty() {
-                    objects.push((inst.name.clone(), inst_refs));
-                }
+                let transformed = visible_faces.iter().map(|f| {
+                    let mut f = f.clone();
+                    for p in &mut f.positions {
+                        *p = m.transform_point3(*p);
+                    }
+                    f
+                }).collect();
+                objects.push((inst.name.clone(), transformed));
+            }
+        }
+    }
+
+    let stem = path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "scene".to_string());
+    let mtl_name = format!("{stem}.mtl");
+    let atlas = options.tileset_atlas.then(|| TilesetAtlas::build(tilesets));
+    let material_names: Vec<String> = tilesets.iter().enumerate()
+        .map(|(i, ts)| format!("{}_{i}", sanitize_file_name(&ts.name)))
+        .collect();
+    let material_of = |face: &Face| match (&atlas, face.tileset) {
+        (Some(_), _) => "atlas".to_string(),
+        (None, Some(i)) => material_names[i].clone(),
+        (None, None) => "default".to_string(),
+    };
+    let all_faces = || objects.iter().flat_map(|(_, faces)| faces);
+
+    // Material library and tileset textures
+    let mut mtl = String::new();
+    writeln!(mtl, "# Exported from Cracktile 3D").unwrap();
+    if let Some(atlas) = &atlas {
+        writeln!(mtl, "\nnewmtl atlas").unwrap();
+        writeln!(mtl, "Kd 1 1 1\nKs 0 0 0\nd 1\nillum 1").unwrap();
+        let png_name = format!("{stem}_atlas.png");
+        fs::write(path.with_file_name(&png_name), atlas.encode_png()?)
+            .map_err(|e| format!("Write failed: {e}"))?;
+        writeln!(mtl, "map_Kd -clamp on {png_name}").unwrap();
+        match combined_alpha(tilesets) {
+            (crate::tile::AlphaMode::Opaque, _) => {}
+            (crate::tile::AlphaMode::AlphaTest, cutoff) => {
+                writeln!(mtl, "# alpha cutoff {cutoff}").unwrap();
+                writeln!(mtl, "map_d -clamp on {png_name}").unwrap();
+            }
+            (crate::tile::AlphaMode::AlphaBlend, _) => {
+                writeln!(mtl, "map_d -clamp on {png_name}").unwrap();
             }
         }
+    } else if all_faces().any(|f| f.tileset.is_none()) {
+        writeln!(mtl, "\nnewmtl default").unwrap();
+        writeln!(mtl, "Kd 1 1 1\nKs 0 0 0\nd 1\nillum 1").unwrap();
     }
+    for (i, ts) in tilesets.iter().enumerate() {
+        if atlas.is_some() || !all_faces().any(|f| f.tileset == Some(i)) { continue; }
+        writeln!(mtl, "\nnewmtl {}", material_names[i]).unwrap();
+        writeln!(mtl, "Kd 1 1 1\nKs 0 0 0\nd 1\nillum 1").unwrap();
+        if ts.image_data.is_none() { continue; }
+
+        let png_name = format!("{stem}_{}.png", material_names[i]);
+        let png = if options.padded_atlas { ts.encode_gpu_png()? } else { ts.encode_png()? };
+        fs::write(path.with_file_name(&png_name), png)
+            .map_err(|e| format!("Write failed: {e}"))?;
+        let clamp = if ts.material.wrap == crate::tile::WrapMode::ClampToEdge { "-clamp on " } else { "" };
+        writeln!(mtl, "map_Kd {clamp}{png_name}").unwrap();
+        for channel in MapChannel::ALL {
+            if ts.gpu_map(channel).is_none() { continue; }
+            let suffix = channel.label().to_lowercase();
+            let map_name = format!("{stem}_{}_{suffix}.png", material_names[i]);
+            let map_png = if options.padded_atlas { ts.encode_gpu_map_png(channel)? } else { ts.encode_map_png(channel)? };
+            fs::write(path.with_file_name(&map_name), map_png)
+                .map_err(|e| format!("Write failed: {e}"))?;
+            match channel {
+                MapChannel::Normal => writeln!(mtl, "map_Bump {clamp}-bm {} {map_name}", ts.material.normal_strength),
+                MapChannel::Emissive => writeln!(mtl, "Ke 1 1 1\nmap_Ke {clamp}{map_name}"),
+                MapChannel::Specular => writeln!(mtl, "Ks 1 1 1\nNs {}\nmap_Ks {clamp}{map_name}", ts.material.specular_power),
+            }.unwrap();
+        }
+        match ts.material.alpha_mode {
+            crate::tile::AlphaMode::Opaque => {}
+            crate::tile::AlphaMode::AlphaTest => {
+                writeln!(mtl, "# alpha cutoff {}", ts.material.alpha_cutoff).unwrap();
+                writeln!(mtl, "map_d {clamp}{png_name}").unwrap();
+            }
+            crate::tile::AlphaMode::AlphaBlend => {
+                writeln!(mtl, "map_d {clamp}{png_name}").unwrap();
+            }
+        }
+    }
+    fs::write(path.with_file_name(&mtl_name), &mtl).map_err(|e| format!("Write failed: {e}"))?;
 
     let mut out = String::new();
     writeln!(out, "# Exported from Cracktile 3D").unwrap();
+    writeln!(out, "mtllib {mtl_name}").unwrap();
     writeln!(out).unwrap();
 
-    for p in &positions {
-        writeln!(out, "v {} {} {}", p.x, p.y, p.z).unwrap();
+    // 1-based v/vt index of each face corner; a corner's position and texcoord share an index,
+    // and identical corners share a vertex when optimizing.
+    let mut corners: Vec<[usize; 4]> = Vec::new();
+    let mut welded: std::collections::HashMap<[u32; 9], usize> = std::collections::HashMap::new();
+    let mut texcoords = String::new();
+    let mut vertex_count = 0;
+    for face in all_faces() {
+        let uvs = match (&atlas, face.tileset) {
+            (Some(atlas), ts) => atlas.remap_uvs(ts, face.uvs),
+            (None, Some(i)) if options.padded_atlas => tilesets[i].padded_uvs(face.uvs),
+            _ => face.uvs,
+        };
+        let mut face_corners = [0; 4];
+        for (i, uv) in uvs.iter().enumerate() {
+            let (p, c) = (face.positions[i], face.colors[i]);
+            let next = vertex_count + 1;
+            face_corners[i] = if options.optimize {
+                *welded.entry(optimize::vertex_key(p, *uv, c)).or_insert(next)
+            } else {
+                next
+            };
+            if face_corners[i] != next { continue; }
+            vertex_count += 1;
+            writeln!(out, "v {} {} {} {} {} {}", p.x, p.y, p.z, c.x, c.y, c.z).unwrap();
+            writeln!(texcoords, "vt {} {}", uv.x, 1.0 - uv.y).unwrap();
+        }
+        corners.push(face_corners);
     }
     writeln!(out).unwrap();
+    out.push_str(&texcoords);
+    writeln!(out).unwrap();
 
-    for uv in &texcoords {
-        writeln!(out, "vt {} {}", uv.x, uv.y).unwrap();
+    for face in all_faces() {
+        let e1 = face.positions[1] - face.positions[0];
+        let e2 = face.positions[3] - face.positions[0];
+        let n = e1.cross(e2).normalize_or(Vec3::Y);
+        writeln!(out, "vn {} {} {}", n.x, n.y, n.z).unwrap();
     }
     writeln!(out).unwrap();
 
-    for (name, face_refs) in &objects {
+    // OBJ is 1-indexed; normals advance by 1 per face.
+    let mut face_index = 0;
+    for (name, faces) in &objects {
         writeln!(out, "o {name}").unwrap();
-        for &(base_v, base_vt) in face_refs {
-            let v = base_v + 1; // OBJ is 1-indexed
-            let vt = base_vt + 1;
-            writeln!(
-                out,
-                "f {}/{} {}/{} {}/{} {}/{}",
-                v, vt, v + 1, vt + 1, v + 2, vt + 2, v + 3, vt + 3,
-            ).unwrap();
+        let mut current_material = None;
+        for face in faces {
+            let material = material_of(face);
+            if current_material.as_ref() != Some(&material) {
+                writeln!(out, "usemtl {material}").unwrap();
+                current_material = Some(material);
+            }
+            let [a, b, c, d] = corners[face_index];
+            let vn = face_index + 1;
+            writeln!(out, "f {a}/{a}/{vn} {b}/{b}/{vn} {c}/{c}/{vn} {d}/{d}/{vn}").unwrap();
+            face_index += 1;
         }
     }
 
-    fs::write(path, &out).map_err(|e| format!("Write failed: {e}"))
+    fs::write(path, &out).map_err(|e| format!("Write failed: {e}"))?;
+    Ok(options.optimize.then_some(report))
+}
+
+/// Options for OBJ export.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ObjExportOptions {
+    /// Write tilesets with edge extrusion as their padded copy and remap UVs into it.
+    pub padded_atlas: bool,
+    /// Pack every tileset into one atlas texture and material, remapping UVs into it.
+    pub tileset_atlas: bool,
+    /// Merge coplanar same-tile faces (see `optimize::merge_coplanar`) and weld shared vertices.
+    pub optimize: bool,
 }
 
 /// Path to the recent files config file.
@@ -167,16 +579,34 @@ pub fn save_recent_files(files: &[PathBuf]) {
     let _ = fs::write(path, json);
 }
 
-/// Import a Wavefront OBJ file. Returns a list of (faces, optional_name) per object.
-pub fn import_obj(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, String> {
+/// Import a Wavefront OBJ file. Materials from `mtllib` become tilesets (their `map_Kd` image),
+/// and each object is split per `usemtl` group since an object holds a single tileset.
+/// Reads `v x y z r g b` vertex colors and splits n-gons into quads (ear clipping if concave).
+pub fn import_obj(path: &Path) -> Result<ImportedScene, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Read failed: {e}"))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
 
     let mut positions: Vec<Vec3> = Vec::new();
+    let mut colors: Vec<Vec4> = Vec::new();
     let mut texcoords: Vec<Vec2> = Vec::new();
+    let mut materials: std::collections::HashMap<String, ObjMaterial> = std::collections::HashMap::new();
+    let mut result = ImportedScene::default();
+    // Texture path → tileset index, so materials sharing an image share a tileset.
+    let mut loaded_textures: std::collections::HashMap<PathBuf, Option<usize>> = std::collections::HashMap::new();
+
     let mut current_name: Option<String> = None;
+    let mut current_tileset: Option<usize> = None;
     let mut current_faces: Vec<Face> = Vec::new();
-    let mut objects: Vec<(Vec<Face>, Option<String>)> = Vec::new();
+
+    let flush = |result: &mut ImportedScene, faces: &mut Vec<Face>, name: &Option<String>, tileset: Option<usize>| {
+        if faces.is_empty() { return; }
+        let faces = std::mem::take(faces);
+        match result.objects.iter_mut().find(|o| o.name == *name && o.tileset == tileset) {
+            Some(existing) => existing.faces.extend(faces),
+            None => result.objects.push(ImportedObject { name: name.clone(), faces, tileset, layer: None }),
+        }
+    };
 
     for line in content.lines() {
         let line = line.trim();
@@ -187,584 +617,311 @@ pub fn import_obj(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, Strin
 
         match parts[0] {
             "v" if parts.len() >= 4 => {
-                let x: f32 = parts[1].parse().unwrap_or(0.0);
-                let y: f32 = parts[2].parse().unwrap_or(0.0);
-                let z: f32 = parts[3].parse().unwrap_or(0.0);
-                positions.push(Vec3::new(x, y, z));
+                let n: Vec<f32> = parts[1..].iter().map(|p| p.parse().unwrap_or(0.0)).collect();
+                positions.push(Vec3::new(n[0], n[1], n[2]));
+                // `v x y z r g b` vertex color extension
+                colors.push(if n.len() >= 6 { Vec4::new(n[3], n[4], n[5], 1.0) } else { Vec4::ONE });
             }
             "vt" if parts.len() >= 3 => {
                 let u: f32 = parts[1].parse().unwrap_or(0.0);
                 let v: f32 = parts[2].parse().unwrap_or(0.0);
-                texcoords.push(Vec2::new(u, v));
+                // OBJ V runs bottom-up; tileset UVs run top-down (export flips the same way).
+                texcoords.push(Vec2::new(u, 1.0 - v));
             }
-            "o" | "g" => {
-                if !current_faces.is_empty() {
-                    objects.push((std::mem::take(&mut current_faces), current_name.take()));
+            "mtllib" if parts.len() >= 2 => {
+                let file = line["mtllib".len()..].trim();
+                let mtl_path = base_dir.join(file);
+                let mtl_dir = mtl_path.parent().unwrap_or(base_dir);
+                match fs::read_to_string(&mtl_path) {
+                    Ok(mtl) => materials.extend(parse_mtl(&mtl, mtl_dir)),
+                    Err(e) => log::warn!("Could not read material library {file}: {e}"),
                 }
+            }
+            "usemtl" => {
+                flush(&mut result, &mut current_faces, &current_name, current_tileset);
+                let name = line["usemtl".len()..].trim();
+                current_tileset = match materials.get(name) {
+                    Some(mat) => mat.texture.as_ref().and_then(|tex_path| {
+                        *loaded_textures.entry(tex_path.clone()).or_insert_with(|| {
+                            load_obj_texture(tex_path, mat, &mut result.tilesets)
+                        })
+                    }),
+                    None => {
+                        log::warn!("Material '{name}' not found in any mtllib");
+                        None
+                    }
+                };
+            }
+            "o" | "g" => {
+                flush(&mut result, &mut current_faces, &current_name, current_tileset);
                 current_name = parts.get(1).map(|s| s.to_string());
             }
             "f" if parts.len() >= 4 => {
-                // Parse face indices (v/vt/vn format)
-                let mut face_verts: Vec<(usize, usize)> = Vec::new();
+                // Parse face indices (v/vt/vn format, negative = relative to the end)
+                let resolve = |s: &str, len: usize| -> Option<usize> {
+                    let i: i64 = s.parse().ok()?;
+                    if i < 0 { len.checked_sub(i.unsigned_abs() as usize) } else { (i as usize).checked_sub(1) }
+                };
+                let mut corner_pos = Vec::new();
+                let mut corner_uv = Vec::new();
+                let mut corner_color = Vec::new();
                 for &part in &parts[1..] {
-                    let indices: Vec<&str> = part.split('/').collect();
-                    let vi: usize = indices[0].parse::<usize>().unwrap_or(1) - 1;
-                    let ti: usize = if indices.len() > 1 && !indices[1].is_empty() {
-                        indices[1].parse::<usize>().unwrap_or(1) - 1
-                    } else {
-                        0
-                    };
-                    face_verts.push((vi, ti));
-                }
-
-                // Handle quads directly, triangulate n-gons into quads
-                if face_verts.len() == 4 {
-                    let get_pos = |i: usize| positions.get(face_verts[i].0).copied().unwrap_or(Vec3::ZERO);
-                    let get_uv = |i: usize| texcoords.get(face_verts[i].1).copied().unwrap_or(Vec2::ZERO);
-                    current_faces.push(Face {
-                        positions: [get_pos(0), get_pos(1), get_pos(2), get_pos(3)],
-                        uvs: [get_uv(0), get_uv(1), get_uv(2), get_uv(3)],
-                        colors: [Vec4::ONE; 4],
-                        hidden: false,
-                    });
-                } else if face_verts.len() == 3 {
-                    // Triangle → degenerate quad (duplicate last vertex)
-                    let get_pos = |i: usize| positions.get(face_verts[i].0).copied().unwrap_or(Vec3::ZERO);
-                    let get_uv = |i: usize| texcoords.get(face_verts[i].1).copied().unwrap_or(Vec2::ZERO);
-                    current_faces.push(Face {
-                        positions: [get_pos(0), get_pos(1), get_pos(2), get_pos(2)],
-                        uvs: [get_uv(0), get_uv(1), get_uv(2), get_uv(2)],
-                        colors: [Vec4::ONE; 4],
-                        hidden: false,
-                    });
-                } else if face_verts.len() > 4 {
-                    // Fan triangulate into quads where possible
-                    let get_pos = |i: usize| positions.get(face_verts[i].0).copied().unwrap_or(Vec3::ZERO);
-                    let get_uv = |i: usize| texcoords.get(face_verts[i].1).copied().unwrap_or(Vec2::ZERO);
-                    let mut i = 1;
-                    while i + 2 < face_verts.len() {
-                        if i + 3 <= face_verts.len() {
-                            // Make a quad
-                            let i2 = i + 1;
-                            let i3 = if i + 2 < face_verts.len() { i + 2 } else { 0 };
-                            current_faces.push(Face {
-                                positions: [get_pos(0), get_pos(i), get_pos(i2), get_pos(i3)],
-                                uvs: [get_uv(0), get_uv(i), get_uv(i2), get_uv(i3)],
-                                colors: [Vec4::ONE; 4],
-                                hidden: false,
-                            });
-                            i += 3;
-                        } else {
-                            // Remaining triangle
-                            let i2 = i + 1;
-                            current_faces.push(Face {
-                                positions: [get_pos(0), get_pos(i), get_pos(i2), get_pos(i2)],
-                                uvs: [get_uv(0), get_uv(i), get_uv(i2), get_uv(i2)],
-                                colors: [Vec4::ONE; 4],
-                                hidden: false,
-                            });
-                            i += 2;
-                        }
-                    }
+                    let mut indices = part.split('/');
+                    let vi = indices.next().and_then(|s| resolve(s, positions.len()));
+                    let ti = indices.next().filter(|s| !s.is_empty()).and_then(|s| resolve(s, texcoords.len()));
+                    corner_pos.push(vi.and_then(|i| positions.get(i)).copied().unwrap_or(Vec3::ZERO));
+                    corner_color.push(vi.and_then(|i| colors.get(i)).copied().unwrap_or(Vec4::ONE));
+                    corner_uv.push(ti.and_then(|i| texcoords.get(i)).copied().unwrap_or(Vec2::ZERO));
                 }
+                current_faces.extend(polygon_to_faces(&corner_pos, &corner_uv, &corner_color));
             }
             _ => {}
         }
     }
 
     // Push last object
-    if !current_faces.is_empty() {
-        objects.push((current_faces, current_name));
-    }
+    flush(&mut result, &mut current_faces, &current_name, current_tileset);
 
-    if objects.is_empty() {
+    if result.objects.is_empty() {
         return Err("No geometry found in OBJ file".to_string());
     }
 
-    Ok(objects)
+    Ok(result)
 }
 
-/// Import a GLB (binary glTF 2.0) file. Returns a list of (faces, optional_name) per mesh.
-pub fn import_glb(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, String> {
-    let data = fs::read(path)
-        .map_err(|e| format!("Read failed: {e}"))?;
-
-    if data.len() < 12 {
-        return Err("File too small for GLB".to_string());
-    }
-
-    // GLB header
-    let magic = &data[0..4];
-    if magic != b"glTF" {
-        return Err("Not a GLB file (bad magic)".to_string());
-    }
-    let _version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-
-    // Parse chunks
-    let mut offset = 12;
-    let mut json_data: Option<&[u8]> = None;
-    let mut bin_data: Option<&[u8]> = None;
-
-    while offset + 8 <= data.len() {
-        let chunk_len = u32::from_le_bytes([data[offset], data[offset+1], data[offset+2], data[offset+3]]) as usize;
-        let chunk_type = u32::from_le_bytes([data[offset+4], data[offset+5], data[offset+6], data[offset+7]]);
-        offset += 8;
-
-        if offset + chunk_len > data.len() { break; }
+/// The parts of an MTL material that map onto a tileset.
+#[derive(Default)]
+struct ObjMaterial {
+    /// `map_Kd` image path, resolved against the .mtl's directory.
+    texture: Option<PathBuf>,
+    /// `-clamp on` on the `map_Kd` statement.
+    clamp: bool,
+    /// Dissolve (`d`, or `1 - Tr`).
+    dissolve: f32,
+    has_alpha_map: bool,
+}
 
-        if chunk_type == 0x4E4F534A { // JSON
-            json_data = Some(&data[offset..offset + chunk_len]);
-        } else if chunk_type == 0x004E4942 { // BIN
-            bin_data = Some(&data[offset..offset + chunk_len]);
+fn parse_mtl(content: &str, mtl_dir: &Path) -> Vec<(String, ObjMaterial)> {
+    let mut materials: Vec<(String, ObjMaterial)> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        let Some((key, rest)) = line.split_once(char::is_whitespace) else { continue };
+        let rest = rest.trim();
+        if key == "newmtl" {
+            materials.push((rest.to_string(), ObjMaterial { dissolve: 1.0, ..Default::default() }));
+            continue;
         }
-        offset += chunk_len;
-    }
-
-    let json_bytes = json_data.ok_or("No JSON chunk in GLB")?;
-    let bin = bin_data.unwrap_or(&[]);
-    let json_str = std::str::from_utf8(json_bytes)
-        .map_err(|e| format!("Invalid JSON UTF-8: {e}"))?;
-
-    // Minimal JSON parsing for glTF — extract meshes, accessors, bufferViews
-    // We use a simple approach: find arrays by key and parse them
-    let mut objects: Vec<(Vec<Face>, Option<String>)> = Vec::new();
-
-    // Extract buffer views: [{byteOffset, byteLength}, ...]
-    let buffer_views = parse_glb_buffer_views(json_str);
-    let accessors = parse_glb_accessors(json_str);
-    let meshes = parse_glb_meshes(json_str);
-
-    for mesh in &meshes {
-        let mut faces = Vec::new();
-
-        if let (Some(pos_acc), Some(idx_acc)) = (mesh.position_accessor, mesh.indices_accessor) {
-            let positions = read_accessor_vec3(&accessors, &buffer_views, bin, pos_acc);
-            let texcoords = mesh.texcoord_accessor
-                .map(|acc| read_accessor_vec2(&accessors, &buffer_views, bin, acc))
-                .unwrap_or_default();
-            let indices = read_accessor_indices(&accessors, &buffer_views, bin, idx_acc);
-
-            // Convert indexed triangles to quads
-            let mut i = 0;
-            while i + 2 < indices.len() {
-                let i0 = indices[i] as usize;
-                let i1 = indices[i + 1] as usize;
-                let i2 = indices[i + 2] as usize;
-
-                let get_pos = |idx: usize| positions.get(idx).copied().unwrap_or(Vec3::ZERO);
-                let get_uv = |idx: usize| texcoords.get(idx).copied().unwrap_or(Vec2::ZERO);
-
-                // Try to pair adjacent triangles into quads
-                if i + 5 < indices.len() {
-                    let i3 = indices[i + 3] as usize;
-                    let i4 = indices[i + 4] as usize;
-                    let i5 = indices[i + 5] as usize;
-
-                    // Check if two triangles share an edge and are coplanar
-                    let n1 = (get_pos(i1) - get_pos(i0)).cross(get_pos(i2) - get_pos(i0));
-                    let n2 = (get_pos(i4) - get_pos(i3)).cross(get_pos(i5) - get_pos(i3));
-                    let coplanar = n1.normalize_or_zero().dot(n2.normalize_or_zero()) > 0.99;
-
-                    // Check shared edge: i0==i3 && i2==i4 (common strip pattern)
-                    let shared = (i0 == i3 && i2 == i4) || (i0 == i5 && i2 == i3) || (i1 == i3 && i2 == i5);
-
-                    if coplanar && shared {
-                        // Find the unique fourth vertex
-                        let quad_verts = if i0 == i3 && i2 == i4 {
-                            [i0, i1, i2, i5]
-                        } else if i0 == i5 && i2 == i3 {
-                            [i0, i1, i2, i4]
-                        } else {
-                            [i0, i1, i5, i2]
-                        };
-                        faces.push(Face {
-                            positions: [get_pos(quad_verts[0]), get_pos(quad_verts[1]), get_pos(quad_verts[2]), get_pos(quad_verts[3])],
-                            uvs: [get_uv(quad_verts[0]), get_uv(quad_verts[1]), get_uv(quad_verts[2]), get_uv(quad_verts[3])],
-                            colors: [Vec4::ONE; 4],
-                            hidden: false,
-                        });
-                        i += 6;
-                        continue;
-                    }
-                }
-
-                // Single triangle → degenerate quad
-                faces.push(Face {
-                    positions: [get_pos(i0), get_pos(i1), get_pos(i2), get_pos(i2)],
-                    uvs: [get_uv(i0), get_uv(i1), get_uv(i2), get_uv(i2)],
-                    colors: [Vec4::ONE; 4],
-                    hidden: false,
-                });
-                i += 3;
+        let Some((_, mat)) = materials.last_mut() else { continue };
+        match key {
+            "map_Kd" => {
+                let (file, clamp) = strip_mtl_map_options(rest);
+                mat.texture = Some(mtl_dir.join(file));
+                mat.clamp = clamp;
             }
-        }
-
-        if !faces.is_empty() {
-            objects.push((faces, mesh.name.clone()));
+            "map_d" => mat.has_alpha_map = true,
+            "d" => mat.dissolve = rest.parse().unwrap_or(1.0),
+            "Tr" => mat.dissolve = 1.0 - rest.parse().unwrap_or(0.0),
+            _ => {}
         }
     }
-
-    if objects.is_empty() {
-        return Err("No geometry found in GLB file".to_string());
-    }
-
-    Ok(objects)
+    materials
 }
 
-// --- GLB parsing helpers ---
-
-struct GlbBufferView {
-    byte_offset: usize,
-    _byte_length: usize,
-}
-
-struct GlbAccessor {
-    buffer_view: usize,
-    component_type: u32,
-    count: usize,
-    _accessor_type: String,
-}
-
-struct GlbMesh {
-    name: Option<String>,
-    position_accessor: Option<usize>,
-    texcoord_accessor: Option<usize>,
-    indices_accessor: Option<usize>,
-}
-
-fn parse_json_number(s: &str) -> usize {
-    s.trim().trim_matches(|c: char| !c.is_ascii_digit()).parse().unwrap_or(0)
+/// Split `[-option args...] file name` into the file name and whether `-clamp on` was given.
+fn strip_mtl_map_options(s: &str) -> (String, bool) {
+    let tokens: Vec<&str> = s.split_whitespace().collect();
+    let mut clamp = false;
+    let mut i = 0;
+    while i < tokens.len() && tokens[i].starts_with('-') {
+        let args = match tokens[i] {
+            "-blendu" | "-blendv" | "-cc" | "-clamp" | "-texres" | "-bm" | "-boost" | "-imfchan" => 1,
+            "-mm" => 2,
+            "-o" | "-s" | "-t" => 3,
+            _ => 0,
+        };
+        if tokens[i] == "-clamp" {
+            clamp = tokens.get(i + 1) == Some(&"on");
+        }
+        // -o/-s/-t take 1 to 3 numbers; stop at the first non-number.
+        let mut taken = 0;
+        while taken < args && tokens.get(i + 1 + taken).is_some_and(|t| t.parse::<f32>().is_ok() || args == 1) {
+            taken += 1;
+        }
+        i += 1 + taken;
+    }
+    (tokens[i.min(tokens.len())..].join(" "), clamp)
 }
 
-fn parse_glb_buffer_views(json: &str) -> Vec<GlbBufferView> {
-    let mut views = Vec::new();
-    let Some(start) = json.find("\"bufferViews\"") else { return views };
-    let Some(arr_start) = json[start..].find('[') else { return views };
-    let json_slice = &json[start + arr_start..];
-    let Some(arr_end) = find_matching_bracket(json_slice) else { return views };
-    let arr = &json_slice[1..arr_end];
-
-    for obj in split_json_objects(arr) {
-        let byte_offset = extract_json_field(&obj, "byteOffset").map(|s| parse_json_number(&s)).unwrap_or(0);
-        let byte_length = extract_json_field(&obj, "byteLength").map(|s| parse_json_number(&s)).unwrap_or(0);
-        views.push(GlbBufferView { byte_offset, _byte_length: byte_length });
+/// Load an MTL texture as a CPU-only tileset. Returns its index, or None if unreadable.
+fn load_obj_texture(path: &Path, mat: &ObjMaterial, tilesets: &mut Vec<crate::tile::Tileset>) -> Option<usize> {
+    let img = match image::open(path) {
+        Ok(img) => img.to_rgba8(),
+        Err(e) => {
+            log::warn!("Could not load texture {:?}: {e}", path);
+            return None;
+        }
+    };
+    let (w, h) = img.dimensions();
+    let mut material = crate::tile::MaterialSettings {
+        wrap: if mat.clamp { crate::tile::WrapMode::ClampToEdge } else { crate::tile::WrapMode::Repeat },
+        ..Default::default()
+    };
+    if mat.dissolve < 1.0 {
+        material.alpha_mode = crate::tile::AlphaMode::AlphaBlend;
+    } else if !mat.has_alpha_map && !img.pixels().any(|p| p[3] < 255) {
+        material.alpha_mode = crate::tile::AlphaMode::Opaque;
     }
-    views
+    let name = path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (tile_w, tile_h) = imported_tile_size(w, h);
+    let mut ts = crate::tile::Tileset::from_rgba(name, w, h, Some(img.into_raw()), tile_w, tile_h, material);
+    ts.source = Some(crate::tile::TilesetSource::new(path, Default::default()));
+    tilesets.push(ts);
+    Some(tilesets.len() - 1)
 }
 
-fn parse_glb_accessors(json: &str) -> Vec<GlbAccessor> {
-    let mut accessors = Vec::new();
-    let Some(start) = json.find("\"accessors\"") else { return accessors };
-    let Some(arr_start) = json[start..].find('[') else { return accessors };
-    let json_slice = &json[start + arr_start..];
-    let Some(arr_end) = find_matching_bracket(json_slice) else { return accessors };
-    let arr = &json_slice[1..arr_end];
-
-    for obj in split_json_objects(arr) {
-        let buffer_view = extract_json_field(&obj, "bufferView").map(|s| parse_json_number(&s)).unwrap_or(0);
-        let component_type = extract_json_field(&obj, "componentType").map(|s| parse_json_number(&s) as u32).unwrap_or(0);
-        let count = extract_json_field(&obj, "count").map(|s| parse_json_number(&s)).unwrap_or(0);
-        let accessor_type = extract_json_string(&obj, "type").unwrap_or_default();
-        accessors.push(GlbAccessor { buffer_view, component_type, count, _accessor_type: accessor_type });
-    }
-    accessors
+/// Tile size for an imported texture. Textures are not tile grids; use 16px tiles when they
+/// divide evenly, else treat the whole image as one tile.
+fn imported_tile_size(w: u32, h: u32) -> (u32, u32) {
+    if w.is_multiple_of(16) && h.is_multiple_of(16) { (16, 16) } else { (w, h) }
 }
 
-fn parse_glb_meshes(json: &str) -> Vec<GlbMesh> {
-    let mut meshes = Vec::new();
-    let Some(start) = json.find("\"meshes\"") else { return meshes };
-    let Some(arr_start) = json[start..].find('[') else { return meshes };
-    let json_slice = &json[start + arr_start..];
-    let Some(arr_end) = find_matching_bracket(json_slice) else { return meshes };
-    let arr = &json_slice[1..arr_end];
-
-    for obj in split_json_objects(arr) {
-        let name = extract_json_string(&obj, "name");
-        let position_accessor = extract_json_field(&obj, "POSITION").map(|s| parse_json_number(&s));
-        let texcoord_accessor = extract_json_field(&obj, "TEXCOORD_0").map(|s| parse_json_number(&s));
-        let indices_accessor = extract_json_field(&obj, "indices").map(|s| parse_json_number(&s));
-        meshes.push(GlbMesh { name, position_accessor, texcoord_accessor, indices_accessor });
+/// Split a polygon into faces: triangles become degenerate quads, convex n-gons are fanned into
+/// quads, and concave polygons are ear-clipped and re-paired into quads where possible.
+fn polygon_to_faces(positions: &[Vec3], uvs: &[Vec2], colors: &[Vec4]) -> Vec<Face> {
+    let n = positions.len();
+    let face = |v: [usize; 4]| Face {
+        positions: v.map(|i| positions[i]),
+        uvs: v.map(|i| uvs[i]),
+        colors: v.map(|i| colors[i]),
+        hidden: false,
+        tileset: None,
+    };
+    if n < 3 { return Vec::new(); }
+    if n == 3 { return vec![face([0, 1, 2, 2])]; }
+
+    // Newell normal handles non-planar and concave polygons.
+    let mut normal = Vec3::ZERO;
+    for i in 0..n {
+        let (a, b) = (positions[i], positions[(i + 1) % n]);
+        normal += Vec3::new((a.y - b.y) * (a.z + b.z), (a.z - b.z) * (a.x + b.x), (a.x - b.x) * (a.y + b.y));
     }
-    meshes
-}
+    let is_reflex = |i: usize| {
+        let prev = positions[(i + n - 1) % n];
+        let next = positions[(i + 1) % n];
+        (positions[i] - prev).cross(next - positions[i]).dot(normal) < 0.0
+    };
+    let reflex: Vec<usize> = (0..n).filter(|&i| is_reflex(i)).collect();
 
-fn find_matching_bracket(s: &str) -> Option<usize> {
-    let open = s.as_bytes()[0];
-    let close = if open == b'[' { b']' } else { b'}' };
-    let mut depth = 0;
-    for (i, ch) in s.bytes().enumerate() {
-        if ch == open { depth += 1; }
-        if ch == close { depth -= 1; if depth == 0 { return Some(i); } }
+    if n == 4 {
+        // A quad renders as (0,1,2) + (0,2,3); start at the reflex corner so that diagonal is inside.
+        let start = reflex.first().copied().unwrap_or(0);
+        return vec![face([0, 1, 2, 3].map(|i| (i + start) % n))];
     }
-    None
-}
 
-fn split_json_objects(s: &str) -> Vec<String> {
-    let mut objects = Vec::new();
-    let mut depth = 0;
-    let mut start = None;
-    for (i, ch) in s.char_indices() {
-        if ch == '{' {
-            if depth == 0 { start = Some(i); }
-            depth += 1;
+    if reflex.is_empty() {
+        let mut faces = Vec::new();
+        let mut i = 1;
+        while i + 2 < n {
+            faces.push(face([0, i, i + 1, i + 2]));
+            i += 2;
         }
-        if ch == '}' {
-            depth -= 1;
-            if depth == 0
-                && let Some(s_idx) = start {
-                    objects.push(s[s_idx..=i].to_string());
-                }
+        if i + 1 < n {
+            faces.push(face([0, i, i + 1, i + 1]));
         }
+        return faces;
     }
-    objects
-}
 
-fn extract_json_field(obj: &str, key: &str) -> Option<String> {
-    let search = format!("\"{}\"", key);
-    let idx = obj.find(&search)?;
-    let after_key = &obj[idx + search.len()..];
-    let colon = after_key.find(':')?;
-    let value_start = &after_key[colon + 1..].trim_start();
-    // Read until comma, closing brace, or end
-    let end = value_start.find([',', '}', ']']).unwrap_or(value_start.len());
-    Some(value_start[..end].trim().to_string())
+    let triangles = ear_clip(positions, normal);
+    triangles_to_faces(&triangles, positions, uvs, colors)
 }
 
-fn extract_json_string(obj: &str, key: &str) -> Option<String> {
-    let field = extract_json_field(obj, key)?;
-    let trimmed = field.trim().trim_matches('"');
-    if trimmed.is_empty() { None } else { Some(trimmed.to_string()) }
-}
-
-fn read_accessor_vec3(accessors: &[GlbAccessor], views: &[GlbBufferView], bin: &[u8], acc_idx: usize) -> Vec<Vec3> {
-    let acc = match accessors.get(acc_idx) { Some(a) => a, None => return Vec::new() };
-    let view = match views.get(acc.buffer_view) { Some(v) => v, None => return Vec::new() };
-    let start = view.byte_offset;
-    let mut result = Vec::with_capacity(acc.count);
-    for i in 0..acc.count {
-        let off = start + i * 12;
-        if off + 12 > bin.len() { break; }
-        let x = f32::from_le_bytes([bin[off], bin[off+1], bin[off+2], bin[off+3]]);
-        let y = f32::from_le_bytes([bin[off+4], bin[off+5], bin[off+6], bin[off+7]]);
-        let z = f32::from_le_bytes([bin[off+8], bin[off+9], bin[off+10], bin[off+11]]);
-        result.push(Vec3::new(x, y, z));
+/// Ear-clipping triangulation of a simple polygon with the given normal.
+fn ear_clip(positions: &[Vec3], normal: Vec3) -> Vec<u32> {
+    let mut remaining: Vec<usize> = (0..positions.len()).collect();
+    let mut triangles = Vec::new();
+    let inside = |p: Vec3, a: Vec3, b: Vec3, c: Vec3| {
+        (b - a).cross(p - a).dot(normal) >= 0.0
+            && (c - b).cross(p - b).dot(normal) >= 0.0
+            && (a - c).cross(p - c).dot(normal) >= 0.0
+    };
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+            let (pa, pb, pc) = (positions[a], positions[b], positions[c]);
+            (pb - pa).cross(pc - pb).dot(normal) > 0.0
+                && !remaining.iter().any(|&j| j != a && j != b && j != c && inside(positions[j], pa, pb, pc))
+        });
+        // Degenerate input (self-intersecting): fall back to clipping the first corner.
+        let i = ear.unwrap_or(0);
+        triangles.extend([remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]].map(|v| v as u32));
+        remaining.remove(i);
     }
-    result
+    triangles.extend(remaining.iter().map(|&v| v as u32));
+    triangles
 }
 
-fn read_accessor_vec2(accessors: &[GlbAccessor], views: &[GlbBufferView], bin: &[u8], acc_idx: usize) -> Vec<Vec2> {
-    let acc = match accessors.get(acc_idx) { Some(a) => a, None => return Vec::new() };
-    let view = match views.get(acc.buffer_view) { Some(v) => v, None => return Vec::new() };
-    let start = view.byte_offset;
-    let mut result = Vec::with_capacity(acc.count);
-    for i in 0..acc.count {
-        let off = start + i * 8;
-        if off + 8 > bin.len() { break; }
-        let u = f32::from_le_bytes([bin[off], bin[off+1], bin[off+2], bin[off+3]]);
-        let v = f32::from_le_bytes([bin[off+4], bin[off+5], bin[off+6], bin[off+7]]);
-        result.push(Vec2::new(u, v));
-    }
-    result
+/// Import a GLB (binary glTF 2.0) file, including its textures as new tilesets.
+pub fn import_glb(path: &Path) -> Result<ImportedScene, String> {
+    gltf_import::import(path)
 }
 
-fn read_accessor_indices(accessors: &[GlbAccessor], views: &[GlbBufferView], bin: &[u8], acc_idx: usize) -> Vec<u32> {
-    let acc = match accessors.get(acc_idx) { Some(a) => a, None => return Vec::new() };
-    let view = match views.get(acc.buffer_view) { Some(v) => v, None => return Vec::new() };
-    let start = view.byte_offset;
-    let mut result = Vec::with_capacity(acc.count);
-    match acc.component_type {
-        5125 => { // UNSIGNED_INT
-            for i in 0..acc.count {
-                let off = start + i * 4;
-                if off + 4 > bin.len() { break; }
-                result.push(u32::from_le_bytes([bin[off], bin[off+1], bin[off+2], bin[off+3]]));
-            }
-        }
-        5123 => { // UNSIGNED_SHORT
-            for i in 0..acc.count {
-                let off = start + i * 2;
-                if off + 2 > bin.len() { break; }
-                result.push(u16::from_le_bytes([bin[off], bin[off+1]]) as u32);
-            }
-        }
-        5121 => { // UNSIGNED_BYTE
-            for i in 0..acc.count {
-                let off = start + i;
-                if off >= bin.len() { break; }
-                result.push(bin[off] as u32);
-            }
-        }
-        _ => {}
-    }
-    result
-}
-
-/// Export the scene as a GLB (binary glTF 2.0) file.
-pub fn export_glb(scene: &Scene, path: &Path) -> Result<(), String> {
-    // Collect per-object geometry into a single binary buffer
-    let mut bin: Vec<u8> = Vec::new();
-
-    // JSON building blocks
-    let mut json_accessors = Vec::new();
-    let mut json_buffer_views = Vec::new();
-    let mut json_meshes = Vec::new();
-    let mut json_nodes = Vec::new();
-    let mut node_indices = Vec::new();
-
-    // Helper: emit one set of faces as a GLB mesh node
-    let emit_glb_object = |bin: &mut Vec<u8>,
-                                json_accessors: &mut Vec<String>,
-                                json_buffer_views: &mut Vec<String>,
-                                json_meshes: &mut Vec<String>,
-                                json_nodes: &mut Vec<String>,
-                                node_indices: &mut Vec<usize>,
-                                name: &str,
-                                faces: &[&Face]| {
-        if faces.is_empty() { return; }
-
-        let vertex_count = faces.len() * 4;
-        let index_count = faces.len() * 6;
-
-        let mut positions: Vec<f32> = Vec::with_capacity(vertex_count * 3);
-        let mut texcoords: Vec<f32> = Vec::with_capacity(vertex_count * 2);
-        let mut colors: Vec<f32> = Vec::with_capacity(vertex_count * 4);
-        let mut indices: Vec<u32> = Vec::with_capacity(index_count);
-
-        let mut min_pos = [f32::MAX; 3];
-        let mut max_pos = [f32::MIN; 3];
-
-        for face in faces {
-            let base = (positions.len() / 3) as u32;
-            for i in 0..4 {
-                let p = face.positions[i];
-                positions.extend_from_slice(&[p.x, p.y, p.z]);
-                min_pos[0] = min_pos[0].min(p.x);
-                min_pos[1] = min_pos[1].min(p.y);
-                min_pos[2] = min_pos[2].min(p.z);
-                max_pos[0] = max_pos[0].max(p.x);
-                max_pos[1] = max_pos[1].max(p.y);
-                max_pos[2] = max_pos[2].max(p.z);
-                texcoords.extend_from_slice(&[face.uvs[i].x, face.uvs[i].y]);
-                let c = face.colors[i];
-                colors.extend_from_slice(&[c.x, c.y, c.z, c.w]);
-            }
-            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
-        }
-
-        let append = |bin: &mut Vec<u8>, data: &[u8]| -> (usize, usize) {
-            let offset = bin.len();
-            bin.extend_from_slice(data);
-            let len = data.len();
-            while !bin.len().is_multiple_of(4) { bin.push(0); }
-            (offset, len)
-        };
-
-        let (pos_off, pos_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&positions));
-        let pos_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            pos_off, pos_len
-        ));
-        let pos_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
-            pos_bv, vertex_count,
-            min_pos[0], min_pos[1], min_pos[2],
-            max_pos[0], max_pos[1], max_pos[2],
-        ));
-
-        let (tc_off, tc_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&texcoords));
-        let tc_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            tc_off, tc_len
-        ));
-        let tc_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC2"}}"#,
-            tc_bv, vertex_count,
-        ));
-
-        let (col_off, col_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&colors));
-        let col_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            col_off, col_len
-        ));
-        let col_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC4"}}"#,
-            col_bv, vertex_count,
-        ));
-
-        let (idx_off, idx_len) = append(bin, bytemuck::cast_slice::<u32, u8>(&indices));
-        let idx_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}"#,
-            idx_off, idx_len
-        ));
-        let idx_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5125,"count":{},"type":"SCALAR"}}"#,
-            idx_bv, index_count,
-        ));
-
-        let mesh_idx = json_meshes.len();
-        let escaped_name = name.replace('\\', "\\\\").replace('"', "\\\"");
-        json_meshes.push(format!(
-            r#"{{"name":"{}","primitives":[{{"attributes":{{"POSITION":{},"TEXCOORD_0":{},"COLOR_0":{}}},"indices":{},"mode":4}}]}}"#,
-            escaped_name, pos_acc, tc_acc, col_acc, idx_acc,
-        ));
-
-        let node_idx = json_nodes.len();
-        json_nodes.push(format!(
-            r#"{{"name":"{}","mesh":{}}}"#,
-            escaped_name, mesh_idx,
-        ));
-        node_indices.push(node_idx);
+/// Pair triangles into quads where two consecutive triangles share an edge and are coplanar;
+/// leftover triangles become degenerate quads (last vertex repeated).
+fn triangles_to_faces(indices: &[u32], positions: &[Vec3], uvs: &[Vec2], colors: &[Vec4]) -> Vec<Face> {
+    let get_pos = |idx: u32| positions.get(idx as usize).copied().unwrap_or(Vec3::ZERO);
+    let get_uv = |idx: u32| uvs.get(idx as usize).copied().unwrap_or(Vec2::ZERO);
+    let get_color = |idx: u32| colors.get(idx as usize).copied().unwrap_or(Vec4::ONE);
+    let make_face = |v: [u32; 4]| Face {
+        positions: v.map(get_pos),
+        uvs: v.map(get_uv),
+        colors: v.map(get_color),
+        hidden: false,
+        tileset: None,
     };
 
-    for layer in &scene.layers {
-        if !layer.visible { continue; }
-        for object in &layer.objects {
-            // Source object
-            let visible_faces: Vec<_> = object.faces.iter().filter(|f| !f.hidden).collect();
-            emit_glb_object(&mut bin, &mut json_accessors, &mut json_buffer_views,
-                &mut json_meshes, &mut json_nodes, &mut node_indices,
-                &object.name, &visible_faces);
-
-            // Flatten instances
-            for inst in &object.instances {
-                let m = inst.model_matrix();
-                let transformed: Vec<Face> = object.faces.iter().filter(|f| !f.hidden).map(|f| {
-                    let mut nf = f.clone();
-                    for p in &mut nf.positions {
-                        *p = m.transform_point3(*p);
-                    }
-                    nf
-                }).collect();
-                let refs: Vec<_> = transformed.iter().collect();
-                emit_glb_object(&mut bin, &mut json_accessors, &mut json_buffer_views,
-                    &mut json_meshes, &mut json_nodes, &mut node_indices,
-                    &inst.name, &refs);
+    let mut faces = Vec::new();
+    let mut i = 0;
+    while i + 2 < indices.len() {
+        let (i0, i1, i2) = (indices[i], indices[i + 1], indices[i + 2]);
+
+        if i + 5 < indices.len() {
+            let (i3, i4, i5) = (indices[i + 3], indices[i + 4], indices[i + 5]);
+
+            // Check if two triangles share an edge and are coplanar
+            let n1 = (get_pos(i1) - get_pos(i0)).cross(get_pos(i2) - get_pos(i0));
+            let n2 = (get_pos(i4) - get_pos(i3)).cross(get_pos(i5) - get_pos(i3));
+            let coplanar = n1.normalize_or_zero().dot(n2.normalize_or_zero()) > 0.99;
+            let shared = (i0 == i3 && i2 == i4) || (i0 == i5 && i2 == i3) || (i1 == i3 && i2 == i5);
+
+            if coplanar && shared {
+                // Find the unique fourth vertex
+                let quad = if i0 == i3 && i2 == i4 {
+                    [i0, i1, i2, i5]
+                } else if i0 == i5 && i2 == i3 {
+                    [i0, i1, i2, i4]
+                } else {
+                    [i0, i1, i4, i2]
+                };
+                faces.push(make_face(quad));
+                i += 6;
+                continue;
             }
         }
-    }
 
-    if json_meshes.is_empty() {
-        return Err("No visible geometry to export".to_string());
+        // Single triangle → degenerate quad
+        faces.push(make_face([i0, i1, i2, i2]));
+        i += 3;
     }
+    faces
+}
 
-    // Build JSON string
-    let node_list: Vec<String> = node_indices.iter().map(|i| i.to_string()).collect();
-    let mut json = String::new();
-    write!(json, r#"{{"asset":{{"version":"2.0","generator":"Cracktile 3D"}}"#).unwrap();
-    write!(json, r#","scene":0,"scenes":[{{"nodes":[{}]}}]"#, node_list.join(",")).unwrap();
-    write!(json, r#","nodes":[{}]"#, json_nodes.join(",")).unwrap();
-    write!(json, r#","meshes":[{}]"#, json_meshes.join(",")).unwrap();
-    write!(json, r#","accessors":[{}]"#, json_accessors.join(",")).unwrap();
-    write!(json, r#","bufferViews":[{}]"#, json_buffer_views.join(",")).unwrap();
-    write!(json, r#","buffers":[{{"byteLength":{}}}]}}"#, bin.len()).unwrap();
+/// Export the scene as a GLB (binary glTF 2.0) file. Returns the optimize report when
+/// `options.optimize` is set.
+pub fn export_glb(scene: &Scene, path: &Path, options: &GltfExportOptions) -> Result<Option<OptimizeReport>, String> {
+    let (json, mut bin, report) = build_gltf_json_and_bin(scene, None, options)?;
 
     // Pad JSON to 4-byte alignment
     let mut json_bytes = json.into_bytes();
@@ -794,33 +951,167 @@ pub fn export_glb(scene: &Scene, path: &Path) -> Result<(), String> {
     file.write_all(&0x004E4942u32.to_le_bytes()).map_err(|e| format!("Write failed: {e}"))?; // "BIN\0"
     file.write_all(&bin).map_err(|e| format!("Write failed: {e}"))?;
 
-    Ok(())
+    Ok(report)
 }
 
-/// Export the scene as a glTF 2.0 (JSON + separate .bin) file.
-pub fn export_gltf(scene: &Scene, path: &Path) -> Result<(), String> {
-    let (json, bin) = build_gltf_json_and_bin(scene)?;
-
+/// Export the scene as a glTF 2.0 (JSON + separate .bin) file. Returns the optimize report
+/// when `options.optimize` is set.
+pub fn export_gltf(scene: &Scene, path: &Path, options: &GltfExportOptions) -> Result<Option<OptimizeReport>, String> {
     // Determine .bin path (same name, .bin extension)
     let bin_filename = path.file_stem()
         .map(|s| format!("{}.bin", s.to_string_lossy()))
         .unwrap_or_else(|| "scene.bin".to_string());
     let bin_path = path.with_file_name(&bin_filename);
 
-    // Modify JSON to reference external bin file
-    let json_with_uri = json.replace(
-        &format!(r#""byteLength":{}}}}}"#, bin.len()),
-        &format!(r#""byteLength":{},"uri":"{}"}}}}"#, bin.len(), bin_filename),
-    );
+    let (json, bin, report) = build_gltf_json_and_bin(scene, Some(&bin_filename), options)?;
 
-    fs::write(path, &json_with_uri).map_err(|e| format!("Write JSON failed: {e}"))?;
+    fs::write(path, &json).map_err(|e| format!("Write JSON failed: {e}"))?;
     fs::write(&bin_path, &bin).map_err(|e| format!("Write BIN failed: {e}"))?;
 
-    Ok(())
+    Ok(report)
+}
+
+/// Options shared by GLB and glTF export.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GltfExportOptions {
+    /// Write each Object as one mesh and each Instance as a node referencing it,
+    /// instead of baking instances into duplicated geometry.
+    pub keep_instances: bool,
+    /// With `keep_instances`, objects with at least `GPU_INSTANCING_MIN_INSTANCES` instances get a
+    /// single `EXT_mesh_gpu_instancing` node instead of one node per instance.
+    pub gpu_instancing: bool,
+    /// Embed tilesets with edge extrusion as their padded copy and remap UVs into it.
+    pub padded_atlas: bool,
+    /// Pack every tileset into one embedded atlas texture and material, remapping UVs into it.
+    pub tileset_atlas: bool,
+    /// Merge coplanar same-tile faces (see `optimize::merge_coplanar`) and weld shared vertices.
+    pub optimize: bool,
+}
+
+/// Instance count at which `GltfExportOptions::gpu_instancing` switches to EXT_mesh_gpu_instancing.
+pub const GPU_INSTANCING_MIN_INSTANCES: usize = 8;
+
+/// Append a material (and its embedded PNG textures, if any) to the glTF arrays.
+/// `maps` holds companion map PNGs with their strength; normal and emissive maps become
+/// `normalTexture`/`emissiveTexture` (glTF core has no specular slot, so those are skipped).
+/// Returns the material index.
+#[allow(clippy::too_many_arguments)]
+fn push_gltf_material(
+    bin: &mut Vec<u8>,
+    json_buffer_views: &mut Vec<String>,
+    json_images: &mut Vec<String>,
+    json_samplers: &mut Vec<String>,
+    json_textures: &mut Vec<String>,
+    json_materials: &mut Vec<String>,
+    name: &str,
+    png: Option<&[u8]>,
+    maps: &[(MapChannel, Vec<u8>, f32)],
+    filter: crate::tile::FilterMode,
+    wrap: crate::tile::WrapMode,
+    alpha_mode: crate::tile::AlphaMode,
+    alpha_cutoff: f32,
+) -> usize {
+    let escaped_name = gltf_escape(name);
+    let filter = match filter {
+        crate::tile::FilterMode::Nearest => 9728, // NEAREST
+        crate::tile::FilterMode::Linear => 9729,  // LINEAR
+    };
+    let wrap = match wrap {
+        crate::tile::WrapMode::ClampToEdge => 33071,
+        crate::tile::WrapMode::Repeat => 10497,
+        crate::tile::WrapMode::MirroredRepeat => 33648,
+    };
+    // One sampler shared by all of the material's textures
+    let mut sampler_idx = None;
+    let mut push_texture = |png: &[u8], image_name: &str| -> usize {
+        let offset = bin.len();
+        bin.extend_from_slice(png);
+        while !bin.len().is_multiple_of(4) { bin.push(0); }
+        let bv = json_buffer_views.len();
+        json_buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{}}}"#,
+            offset, png.len()
+        ));
+        let image_idx = json_images.len();
+        json_images.push(format!(
+            r#"{{"name":"{}","mimeType":"image/png","bufferView":{}}}"#,
+            gltf_escape(image_name), bv
+        ));
+        let sampler = *sampler_idx.get_or_insert_with(|| {
+            json_samplers.push(format!(
+                r#"{{"magFilter":{filter},"minFilter":{filter},"wrapS":{wrap},"wrapT":{wrap}}}"#,
+            ));
+            json_samplers.len() - 1
+        });
+        json_textures.push(format!(
+            r#"{{"sampler":{},"source":{}}}"#,
+            sampler, image_idx
+        ));
+        json_textures.len() - 1
+    };
+
+    let mut pbr = String::from(r#""metallicFactor":0,"roughnessFactor":1"#);
+    if let Some(png) = png {
+        let texture_idx = push_texture(png, name);
+        pbr = format!(r#""baseColorTexture":{{"index":{}}},{}"#, texture_idx, pbr);
+    }
+    let mut extra = String::new();
+    for (channel, map_png, strength) in maps {
+        let map_name = format!("{name}_{}", channel.label().to_lowercase());
+        match channel {
+            MapChannel::Normal => {
+                let texture_idx = push_texture(map_png, &map_name);
+                write!(extra, r#","normalTexture":{{"index":{texture_idx},"scale":{strength}}}"#).unwrap();
+            }
+            MapChannel::Emissive => {
+                let texture_idx = push_texture(map_png, &map_name);
+                // Core glTF caps emissiveFactor at 1.
+                let factor = strength.clamp(0.0, 1.0);
+                write!(extra, r#","emissiveTexture":{{"index":{texture_idx}}},"emissiveFactor":[{factor},{factor},{factor}]"#).unwrap();
+            }
+            MapChannel::Specular => {}
+        }
+    }
+
+    let alpha = match alpha_mode {
+        crate::tile::AlphaMode::Opaque => r#""alphaMode":"OPAQUE""#.to_string(),
+        crate::tile::AlphaMode::AlphaTest => {
+            format!(r#""alphaMode":"MASK","alphaCutoff":{}"#, alpha_cutoff)
+        }
+        crate::tile::AlphaMode::AlphaBlend => r#""alphaMode":"BLEND""#.to_string(),
+    };
+    json_materials.push(format!(
+        r#"{{"name":"{}","pbrMetallicRoughness":{{{}}}{},{}}}"#,
+        escaped_name, pbr, extra, alpha
+    ));
+    json_materials.len() - 1
+}
+
+/// A tileset's normal and emissive maps as PNGs with their strength, laid out like its exported
+/// base color image (padded when `padded` is set).
+fn gltf_material_maps(ts: &crate::tile::Tileset, padded: bool) -> Result<Vec<(MapChannel, Vec<u8>, f32)>, String> {
+    let mut maps = Vec::new();
+    for (channel, strength) in [
+        (MapChannel::Normal, ts.material.normal_strength),
+        (MapChannel::Emissive, ts.material.emissive_strength),
+    ] {
+        if ts.gpu_map(channel).is_none() { continue; }
+        let png = if padded { ts.encode_gpu_map_png(channel)? } else { ts.encode_map_png(channel)? };
+        maps.push((channel, png, strength));
+    }
+    Ok(maps)
 }
 
 /// Build glTF JSON string and binary buffer from scene (shared by GLB and glTF export).
-fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
+/// `bin_uri` names the external buffer file for .gltf; GLB passes None (buffer in the BIN chunk).
+/// Also returns the optimize report when `options.optimize` is set.
+fn build_gltf_json_and_bin(
+    scene: &Scene,
+    bin_uri: Option<&str>,
+    options: &GltfExportOptions,
+) -> Result<(String, Vec<u8>, Option<OptimizeReport>), String> {
+    let export = ExportTilesets::new(scene);
+    let tilesets = export.tilesets();
     let mut bin: Vec<u8> = Vec::new();
     let mut json_accessors = Vec::new();
     let mut json_buffer_views = Vec::new();
@@ -828,7 +1119,48 @@ fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
     let mut json_nodes = Vec::new();
     let mut node_indices = Vec::new();
 
-    // Helper: emit one set of faces as a glTF mesh node
+    // One material per tileset used by a visible face (or a single one for the tileset atlas);
+    // textures are embedded as PNG.
+    let mut json_images = Vec::new();
+    let mut json_samplers = Vec::new();
+    let mut json_textures = Vec::new();
+    let mut json_materials = Vec::new();
+    let mut uses_gpu_instancing = false;
+    let mut report = OptimizeReport::default();
+    let mut tileset_materials: Vec<Option<usize>> = vec![None; tilesets.len()];
+    let atlas = options.tileset_atlas.then(|| TilesetAtlas::build(tilesets));
+    let mut atlas_material = None;
+    if let Some(atlas) = &atlas {
+        let (alpha_mode, cutoff) = combined_alpha(tilesets);
+        atlas_material = Some(push_gltf_material(
+            &mut bin, &mut json_buffer_views, &mut json_images, &mut json_samplers, &mut json_textures, &mut json_materials,
+            "atlas", Some(&atlas.encode_png()?), &[], atlas.filter, crate::tile::WrapMode::ClampToEdge, alpha_mode, cutoff,
+        ));
+    } else {
+        for layer in scene.layers.iter().filter(|l| l.visible) {
+            for object in &layer.objects {
+                for face in export.faces(layer, object) {
+                    let Some(ts_idx) = face.tileset else { continue };
+                    let ts = &tilesets[ts_idx];
+                    if tileset_materials[ts_idx].is_some() { continue; }
+
+                    let png = match ts.image_data {
+                        Some(_) if options.padded_atlas => Some(ts.encode_gpu_png()?),
+                        Some(_) => Some(ts.encode_png()?),
+                        None => None,
+                    };
+                    let maps = gltf_material_maps(ts, options.padded_atlas)?;
+                    tileset_materials[ts_idx] = Some(push_gltf_material(
+                        &mut bin, &mut json_buffer_views, &mut json_images, &mut json_samplers, &mut json_textures, &mut json_materials,
+                        &ts.name, png.as_deref(), &maps, ts.material.filter, ts.material.wrap, ts.material.alpha_mode, ts.material.alpha_cutoff,
+                    ));
+                }
+            }
+        }
+    }
+
+    // Helper: emit one set of faces (tagged with their tileset, see `ExportTilesets::faces`) as a glTF
+    // mesh node, with one primitive per material
     let emit_gltf_object = |bin: &mut Vec<u8>,
                                  json_accessors: &mut Vec<String>,
                                  json_buffer_views: &mut Vec<String>,
@@ -836,35 +1168,21 @@ fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
                                  json_nodes: &mut Vec<String>,
                                  node_indices: &mut Vec<usize>,
                                  name: &str,
-                                 faces: &[&Face]| {
-        if faces.is_empty() { return; }
-
-        let vertex_count = faces.len() * 4;
-        let index_count = faces.len() * 6;
-
-        let mut positions: Vec<f32> = Vec::with_capacity(vertex_count * 3);
-        let mut texcoords: Vec<f32> = Vec::with_capacity(vertex_count * 2);
-        let mut colors: Vec<f32> = Vec::with_capacity(vertex_count * 4);
-        let mut indices: Vec<u32> = Vec::with_capacity(index_count);
-        let mut min_pos = [f32::MAX; 3];
-        let mut max_pos = [f32::MIN; 3];
+                                 faces: &[Face]|
+     -> Option<usize> {
+        if faces.is_empty() { return None; }
 
+        // Faces per material, in first-seen order
+        let mut groups: Vec<(Option<usize>, Vec<&Face>)> = Vec::new();
         for face in faces {
-            let base = (positions.len() / 3) as u32;
-            for i in 0..4 {
-                let p = face.positions[i];
-                positions.extend_from_slice(&[p.x, p.y, p.z]);
-                min_pos[0] = min_pos[0].min(p.x);
-                min_pos[1] = min_pos[1].min(p.y);
-                min_pos[2] = min_pos[2].min(p.z);
-                max_pos[0] = max_pos[0].max(p.x);
-                max_pos[1] = max_pos[1].max(p.y);
-                max_pos[2] = max_pos[2].max(p.z);
-                texcoords.extend_from_slice(&[face.uvs[i].x, face.uvs[i].y]);
-                let c = face.colors[i];
-                colors.extend_from_slice(&[c.x, c.y, c.z, c.w]);
+            let material = match atlas_material {
+                Some(m) => Some(m),
+                None => face.tileset.and_then(|i| tileset_materials[i]),
+            };
+            match groups.iter_mut().find(|(m, _)| *m == material) {
+                Some((_, group)) => group.push(face),
+                None => groups.push((material, vec![face])),
             }
-            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
         }
 
         let append = |bin: &mut Vec<u8>, data: &[u8]| -> (usize, usize) {
@@ -875,61 +1193,121 @@ fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
             (offset, len)
         };
 
-        let (pos_off, pos_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&positions));
-        let pos_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            pos_off, pos_len
-        ));
-        let pos_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
-            pos_bv, vertex_count,
-            min_pos[0], min_pos[1], min_pos[2],
-            max_pos[0], max_pos[1], max_pos[2],
-        ));
-
-        let (tc_off, tc_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&texcoords));
-        let tc_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            tc_off, tc_len
-        ));
-        let tc_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC2"}}"#,
-            tc_bv, vertex_count,
-        ));
-
-        let (col_off, col_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&colors));
-        let col_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
-            col_off, col_len
-        ));
-        let col_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC4"}}"#,
-            col_bv, vertex_count,
-        ));
-
-        let (idx_off, idx_len) = append(bin, bytemuck::cast_slice::<u32, u8>(&indices));
-        let idx_bv = json_buffer_views.len();
-        json_buffer_views.push(format!(
-            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}"#,
-            idx_off, idx_len
-        ));
-        let idx_acc = json_accessors.len();
-        json_accessors.push(format!(
-            r#"{{"bufferView":{},"componentType":5125,"count":{},"type":"SCALAR"}}"#,
-            idx_bv, index_count,
-        ));
+        let mut primitives = Vec::with_capacity(groups.len());
+        for (material, faces) in &groups {
+            let index_count = faces.len() * 6;
+
+            let mut positions: Vec<f32> = Vec::with_capacity(faces.len() * 12);
+            let mut texcoords: Vec<f32> = Vec::with_capacity(faces.len() * 8);
+            let mut colors: Vec<f32> = Vec::with_capacity(faces.len() * 16);
+            let mut indices: Vec<u32> = Vec::with_capacity(index_count);
+            let mut min_pos = [f32::MAX; 3];
+            let mut max_pos = [f32::MIN; 3];
+            // Faces (quad i = triangles 2i and 2i+1) grouped by the tile metadata they were built from
+            let mut tile_faces: std::collections::BTreeMap<(usize, u32, u32), (&TileProperties, Vec<usize>)> =
+                std::collections::BTreeMap::new();
+            // Vertex index of each distinct corner when welding
+            let mut welded: std::collections::HashMap<[u32; 9], u32> = std::collections::HashMap::new();
+
+            for (face_idx, face) in faces.iter().enumerate() {
+                let tileset = face.tileset.map(|i| &tilesets[i]);
+                if let Some((ts_idx, ((col, row), props))) = face.tileset.zip(tileset.and_then(|ts| ts.face_properties(&face.uvs))) {
+                    tile_faces.entry((ts_idx, col, row)).or_insert((props, Vec::new())).1.push(face_idx);
+                }
+                let uvs = match (&atlas, tileset) {
+                    (Some(atlas), _) => atlas.remap_uvs(face.tileset, face.uvs),
+                    (None, Some(ts)) if options.padded_atlas => ts.padded_uvs(face.uvs),
+                    _ => face.uvs,
+                };
+                let mut corners = [0u32; 4];
+                for (i, uv) in uvs.iter().enumerate() {
+                    let p = face.positions[i];
+                    let next = (positions.len() / 3) as u32;
+                    corners[i] = if options.optimize {
+                        *welded.entry(optimize::vertex_key(p, *uv, face.colors[i])).or_insert(next)
+                    } else {
+                        next
+                    };
+                    if corners[i] != next { continue; }
+                    positions.extend_from_slice(&[p.x, p.y, p.z]);
+                    min_pos[0] = min_pos[0].min(p.x);
+                    min_pos[1] = min_pos[1].min(p.y);
+                    min_pos[2] = min_pos[2].min(p.z);
+                    max_pos[0] = max_pos[0].max(p.x);
+                    max_pos[1] = max_pos[1].max(p.y);
+                    max_pos[2] = max_pos[2].max(p.z);
+                    texcoords.extend_from_slice(&[uv.x, uv.y]);
+                    let c = face.colors[i];
+                    colors.extend_from_slice(&[c.x, c.y, c.z, c.w]);
+                }
+                let [a, b, c, d] = corners;
+                indices.extend_from_slice(&[a, b, c, a, c, d]);
+            }
+            let vertex_count = positions.len() / 3;
+
+            let (pos_off, pos_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&positions));
+            let pos_bv = json_buffer_views.len();
+            json_buffer_views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
+                pos_off, pos_len
+            ));
+            let pos_acc = json_accessors.len();
+            json_accessors.push(format!(
+                r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+                pos_bv, vertex_count,
+                min_pos[0], min_pos[1], min_pos[2],
+                max_pos[0], max_pos[1], max_pos[2],
+            ));
+
+            let (tc_off, tc_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&texcoords));
+            let tc_bv = json_buffer_views.len();
+            json_buffer_views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
+                tc_off, tc_len
+            ));
+            let tc_acc = json_accessors.len();
+            json_accessors.push(format!(
+                r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC2"}}"#,
+                tc_bv, vertex_count,
+            ));
+
+            let (col_off, col_len) = append(bin, bytemuck::cast_slice::<f32, u8>(&colors));
+            let col_bv = json_buffer_views.len();
+            json_buffer_views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}"#,
+                col_off, col_len
+            ));
+            let col_acc = json_accessors.len();
+            json_accessors.push(format!(
+                r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC4"}}"#,
+                col_bv, vertex_count,
+            ));
+
+            let (idx_off, idx_len) = append(bin, bytemuck::cast_slice::<u32, u8>(&indices));
+            let idx_bv = json_buffer_views.len();
+            json_buffer_views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}"#,
+                idx_off, idx_len
+            ));
+            let idx_acc = json_accessors.len();
+            json_accessors.push(format!(
+                r#"{{"bufferView":{},"componentType":5125,"count":{},"type":"SCALAR"}}"#,
+                idx_bv, index_count,
+            ));
+
+            let material = material.map(|m| format!(r#","material":{m}"#)).unwrap_or_default();
+            let extras = if tile_faces.is_empty() { String::new() } else { gltf_tile_extras(tilesets, &tile_faces) };
+            primitives.push(format!(
+                r#"{{"attributes":{{"POSITION":{},"TEXCOORD_0":{},"COLOR_0":{}}},"indices":{}{},"mode":4{}}}"#,
+                pos_acc, tc_acc, col_acc, idx_acc, material, extras,
+            ));
+        }
 
         let mesh_idx = json_meshes.len();
-        let escaped_name = name.replace('\\', "\\\\").replace('"', "\\\"");
+        let escaped_name = gltf_escape(name);
         json_meshes.push(format!(
-            r#"{{"name":"{}","primitives":[{{"attributes":{{"POSITION":{},"TEXCOORD_0":{},"COLOR_0":{}}},"indices":{},"mode":4}}]}}"#,
-            escaped_name, pos_acc, tc_acc, col_acc, idx_acc,
+            r#"{{"name":"{}","primitives":[{}]}}"#,
+            escaped_name, primitives.join(","),
         ));
 
         let node_idx = json_nodes.len();
@@ -938,30 +1316,77 @@ fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
             escaped_name, mesh_idx,
         ));
         node_indices.push(node_idx);
+        Some(mesh_idx)
     };
 
     for layer in &scene.layers {
         if !layer.visible { continue; }
         for object in &layer.objects {
-            let visible_faces: Vec<_> = object.faces.iter().filter(|f| !f.hidden).collect();
-            emit_gltf_object(&mut bin, &mut json_accessors, &mut json_buffer_views,
+            let mut visible_faces = export.faces(layer, object);
+            if options.optimize && !visible_faces.is_empty() {
+                let (merged, counts) = export.optimize(&visible_faces, options.tileset_atlas);
+                // Flattened instances repeat the object's geometry
+                let copies = if options.keep_instances { 1 } else { 1 + object.instances.len() };
+                (0..copies).for_each(|_| report.add(counts));
+                visible_faces = merged;
+            }
+            let Some(mesh_idx) = emit_gltf_object(&mut bin, &mut json_accessors, &mut json_buffer_views,
                 &mut json_meshes, &mut json_nodes, &mut node_indices,
-                &object.name, &visible_faces);
+                &object.name, &visible_faces) else { continue };
+
+            if options.keep_instances {
+                if options.gpu_instancing && object.instances.len() >= GPU_INSTANCING_MIN_INSTANCES {
+                    // One node carrying per-instance TRS accessors.
+                    let translations: Vec<f32> = object.instances.iter().flat_map(|i| i.position.to_array()).collect();
+                    let rotations: Vec<f32> = object.instances.iter().flat_map(|i| i.rotation.to_array()).collect();
+                    let scales: Vec<f32> = object.instances.iter().flat_map(|i| i.scale.to_array()).collect();
+                    let mut accessor_ids = [0usize; 3];
+                    for (slot, (data, ty)) in [(&translations, "VEC3"), (&rotations, "VEC4"), (&scales, "VEC3")].into_iter().enumerate() {
+                        let offset = bin.len();
+                        bin.extend_from_slice(bytemuck::cast_slice::<f32, u8>(data));
+                        let bv = json_buffer_views.len();
+                        json_buffer_views.push(format!(
+                            r#"{{"buffer":0,"byteOffset":{},"byteLength":{}}}"#,
+                            offset, data.len() * 4
+                        ));
+                        accessor_ids[slot] = json_accessors.len();
+                        json_accessors.push(format!(
+                            r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"{}"}}"#,
+                            bv, object.instances.len(), ty
+                        ));
+                    }
+                    node_indices.push(json_nodes.len());
+                    json_nodes.push(format!(
+                        r#"{{"name":"{} instances","mesh":{},"extensions":{{"EXT_mesh_gpu_instancing":{{"attributes":{{"TRANSLATION":{},"ROTATION":{},"SCALE":{}}}}}}}}}"#,
+                        gltf_escape(&object.name), mesh_idx, accessor_ids[0], accessor_ids[1], accessor_ids[2],
+                    ));
+                    uses_gpu_instancing = true;
+                } else {
+                    for inst in &object.instances {
+                        let (t, r, sc) = (inst.position, inst.rotation, inst.scale);
+                        node_indices.push(json_nodes.len());
+                        json_nodes.push(format!(
+                            r#"{{"name":"{}","mesh":{},"translation":[{},{},{}],"rotation":[{},{},{},{}],"scale":[{},{},{}]}}"#,
+                            gltf_escape(&inst.name), mesh_idx, t.x, t.y, t.z, r.x, r.y, r.z, r.w, sc.x, sc.y, sc.z,
+                        ));
+                    }
+                }
+                continue;
+            }
 
             // Flatten instances
             for inst in &object.instances {
                 let m = inst.model_matrix();
-                let transformed: Vec<Face> = object.faces.iter().filter(|f| !f.hidden).map(|f| {
+                let transformed: Vec<Face> = visible_faces.iter().map(|f| {
                     let mut nf = f.clone();
                     for p in &mut nf.positions {
                         *p = m.transform_point3(*p);
                     }
                     nf
                 }).collect();
-                let refs: Vec<_> = transformed.iter().collect();
                 emit_gltf_object(&mut bin, &mut json_accessors, &mut json_buffer_views,
                     &mut json_meshes, &mut json_nodes, &mut node_indices,
-                    &inst.name, &refs);
+                    &inst.name, &transformed);
             }
         }
     }
@@ -973,14 +1398,32 @@ fn build_gltf_json_and_bin(scene: &Scene) -> Result<(String, Vec<u8>), String> {
     let node_list: Vec<String> = node_indices.iter().map(|i| i.to_string()).collect();
     let mut json = String::new();
     write!(json, r#"{{"asset":{{"version":"2.0","generator":"Cracktile 3D"}}"#).unwrap();
+    if uses_gpu_instancing {
+        // Without the extension only one copy would render, so require it.
+        write!(json, r#","extensionsUsed":["EXT_mesh_gpu_instancing"],"extensionsRequired":["EXT_mesh_gpu_instancing"]"#).unwrap();
+    }
     write!(json, r#","scene":0,"scenes":[{{"nodes":[{}]}}]"#, node_list.join(",")).unwrap();
     write!(json, r#","nodes":[{}]"#, json_nodes.join(",")).unwrap();
     write!(json, r#","meshes":[{}]"#, json_meshes.join(",")).unwrap();
+    if !json_materials.is_empty() {
+        write!(json, r#","materials":[{}]"#, json_materials.join(",")).unwrap();
+    }
+    if !json_textures.is_empty() {
+        write!(json, r#","textures":[{}]"#, json_textures.join(",")).unwrap();
+        write!(json, r#","samplers":[{}]"#, json_samplers.join(",")).unwrap();
+        write!(json, r#","images":[{}]"#, json_images.join(",")).unwrap();
+    }
     write!(json, r#","accessors":[{}]"#, json_accessors.join(",")).unwrap();
     write!(json, r#","bufferViews":[{}]"#, json_buffer_views.join(",")).unwrap();
-    write!(json, r#","buffers":[{{"byteLength":{}}}]}}"#, bin.len()).unwrap();
+    match bin_uri {
+        Some(uri) => {
+            let escaped_uri = gltf_escape(uri);
+            write!(json, r#","buffers":[{{"byteLength":{},"uri":"{}"}}]}}"#, bin.len(), escaped_uri).unwrap();
+        }
+        None => write!(json, r#","buffers":[{{"byteLength":{}}}]}}"#, bin.len()).unwrap(),
+    }
 
-    Ok((json, bin))
+    Ok((json, bin, options.optimize.then_some(report)))
 }
 
 /// Export the scene as a Collada DAE file.
@@ -1105,27 +1548,9 @@ pub fn export_dae(scene: &Scene, path: &Path) -> Result<(), String> {
     fs::write(path, &out).map_err(|e| format!("Write failed: {e}"))
 }
 
-/// Import a glTF 2.0 (JSON) file. Returns a list of (faces, optional_name) per mesh.
-pub fn import_gltf(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, String> {
-    let json_str = fs::read_to_string(path)
-        .map_err(|e| format!("Read failed: {e}"))?;
-
-    // Find the buffer URI to load the .bin file
-    let bin = if let Some(uri) = extract_json_string(&json_str, "uri") {
-        let bin_path = path.parent()
-            .map(|p| p.join(&uri))
-            .unwrap_or_else(|| PathBuf::from(&uri));
-        fs::read(&bin_path)
-            .map_err(|e| format!("Read .bin failed: {e}"))?
-    } else {
-        Vec::new()
-    };
-
-    let buffer_views = parse_glb_buffer_views(&json_str);
-    let accessors = parse_glb_accessors(&json_str);
-    let meshes = parse_glb_meshes(&json_str);
-
-    import_from_gltf_data(&meshes, &accessors, &buffer_views, &bin)
+/// Import a glTF 2.0 (JSON) file with external or embedded buffers and images.
+pub fn import_gltf(path: &Path) -> Result<ImportedScene, String> {
+    gltf_import::import(path)
 }
 
 /// Import a Collada DAE file. Returns a list of (faces, optional_name) per geometry.
@@ -1212,6 +1637,7 @@ pub fn import_dae(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, Strin
                             uvs: [get_uv(qt[0]), get_uv(qt[1]), get_uv(qt[2]), get_uv(qt[3])],
                             colors: [Vec4::ONE; 4],
                             hidden: false,
+                            tileset: None,
                         });
                         i += stride * 6;
                         continue;
@@ -1224,6 +1650,7 @@ pub fn import_dae(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, Strin
                     uvs: [get_uv(ti0), get_uv(ti1), get_uv(ti2), get_uv(ti2)],
                     colors: [Vec4::ONE; 4],
                     hidden: false,
+                    tileset: None,
                 });
                 i += stride * 3;
             }
@@ -1241,82 +1668,37 @@ pub fn import_dae(path: &Path) -> Result<Vec<(Vec<Face>, Option<String>)>, Strin
     Ok(objects)
 }
 
-/// Shared glTF mesh import logic (used by both GLB and glTF importers).
-fn import_from_gltf_data(
-    meshes: &[GlbMesh],
-    accessors: &[GlbAccessor],
-    buffer_views: &[GlbBufferView],
-    bin: &[u8],
-) -> Result<Vec<(Vec<Face>, Option<String>)>, String> {
-    let mut objects: Vec<(Vec<Face>, Option<String>)> = Vec::new();
-
-    for mesh in meshes {
-        let mut faces = Vec::new();
-
-        if let (Some(pos_acc), Some(idx_acc)) = (mesh.position_accessor, mesh.indices_accessor) {
-            let positions = read_accessor_vec3(accessors, buffer_views, bin, pos_acc);
-            let texcoords = mesh.texcoord_accessor
-                .map(|acc| read_accessor_vec2(accessors, buffer_views, bin, acc))
-                .unwrap_or_default();
-            let indices = read_accessor_indices(accessors, buffer_views, bin, idx_acc);
-
-            let mut i = 0;
-            while i + 2 < indices.len() {
-                let i0 = indices[i] as usize;
-                let i1 = indices[i + 1] as usize;
-                let i2 = indices[i + 2] as usize;
-
-                let get_pos = |idx: usize| positions.get(idx).copied().unwrap_or(Vec3::ZERO);
-                let get_uv = |idx: usize| texcoords.get(idx).copied().unwrap_or(Vec2::ZERO);
-
-                if i + 5 < indices.len() {
-                    let i3 = indices[i + 3] as usize;
-                    let i4 = indices[i + 4] as usize;
-                    let i5 = indices[i + 5] as usize;
-
-                    let n1 = (get_pos(i1) - get_pos(i0)).cross(get_pos(i2) - get_pos(i0));
-                    let n2 = (get_pos(i4) - get_pos(i3)).cross(get_pos(i5) - get_pos(i3));
-                    let coplanar = n1.normalize_or_zero().dot(n2.normalize_or_zero()) > 0.99;
-                    let shared = (i0 == i3 && i2 == i4) || (i0 == i5 && i2 == i3) || (i1 == i3 && i2 == i5);
-
-                    if coplanar && shared {
-                        let quad_verts = if i0 == i3 && i2 == i4 {
-                            [i0, i1, i2, i5]
-                        } else if i0 == i5 && i2 == i3 {
-                            [i0, i1, i2, i4]
-                        } else {
-                            [i0, i1, i5, i2]
-                        };
-                        faces.push(Face {
-                            positions: [get_pos(quad_verts[0]), get_pos(quad_verts[1]), get_pos(quad_verts[2]), get_pos(quad_verts[3])],
-                            uvs: [get_uv(quad_verts[0]), get_uv(quad_verts[1]), get_uv(quad_verts[2]), get_uv(quad_verts[3])],
-                            colors: [Vec4::ONE; 4],
-                            hidden: false,
-                        });
-                        i += 6;
-                        continue;
-                    }
-                }
-
-                faces.push(Face {
-                    positions: [get_pos(i0), get_pos(i1), get_pos(i2), get_pos(i2)],
-                    uvs: [get_uv(i0), get_uv(i1), get_uv(i2), get_uv(i2)],
-                    colors: [Vec4::ONE; 4],
-                    hidden: false,
-                });
-                i += 3;
-            }
-        }
+/// Primitive `extras` listing the tile metadata of its faces, e.g.
+/// `"tileProperties":[{"tileset":"walls","tile":[2,0],"faces":[0,3],"collision":"solid","tags":["wall"],"properties":{"k":"v"}}]`.
+/// Face `i` is the quad made of triangles `2i` and `2i + 1`.
+fn gltf_tile_extras(
+    tilesets: &[crate::tile::Tileset],
+    tile_faces: &std::collections::BTreeMap<(usize, u32, u32), (&TileProperties, Vec<usize>)>,
+) -> String {
+    let entries: Vec<String> = tile_faces.iter().map(|(&(ts, col, row), (props, face_ids))| {
+        let faces: Vec<String> = face_ids.iter().map(|i| i.to_string()).collect();
+        let tags: Vec<String> = props.tags.iter().map(|t| json_string(t)).collect();
+        let custom: Vec<String> = props.custom.iter()
+            .filter(|(k, _)| !k.is_empty())
+            .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+            .collect();
+        format!(
+            r#"{{"tileset":{},"tile":[{},{}],"faces":[{}],"collision":"{}","tags":[{}],"properties":{{{}}}}}"#,
+            json_string(&tilesets[ts].name), col, row, faces.join(","), props.collision.name(), tags.join(","), custom.join(","),
+        )
+    }).collect();
+    format!(r#","extras":{{"tileProperties":[{}]}}"#, entries.join(","))
+}
 
-        if !faces.is_empty() {
-            objects.push((faces, mesh.name.clone()));
-        }
-    }
+/// A quoted JSON string literal, with quotes, backslashes and control characters escaped.
+fn json_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
 
-    if objects.is_empty() {
-        return Err("No geometry found in glTF file".to_string());
-    }
-    Ok(objects)
+/// Escape a string for embedding between quotes in glTF JSON.
+fn gltf_escape(s: &str) -> String {
+    let quoted = json_string(s);
+    quoted[1..quoted.len() - 1].to_string()
 }
 
 fn xml_escape(s: &str) -> String {
@@ -1365,3 +1747,53 @@ fn count_dae_triangle_inputs(geo_block: &str) -> usize {
     let tri_block = &geo_block[tri_start..tri_start + tri_end];
     tri_block.matches("<input ").count()
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tileset_sources_are_relative_to_the_scene_when_they_share_a_root() {
+        let base = Path::new("/home/user/project");
+        assert_eq!(relative_path(Path::new("/home/user/project/art/tiles.png"), base).as_deref(), Some("art/tiles.png"));
+        assert_eq!(relative_path(Path::new("/home/user/shared/tiles.png"), base).as_deref(), Some("../shared/tiles.png"));
+        assert_eq!(relative_path(Path::new("/opt/tiles.png"), base), None);
+        assert_eq!(relative_path(Path::new("tiles.png"), base), None);
+    }
+
+    #[test]
+    fn obj_export_import_round_trips_uvs() {
+        let uvs = [Vec2::new(0.25, 0.5), Vec2::new(0.5, 0.5), Vec2::new(0.5, 0.25), Vec2::new(0.25, 0.25)];
+        let face = Face::new_quad(Vec3::new(0.5, 0.0, 0.5), Vec3::Y, 0.5, uvs);
+        let mut scene = Scene::new();
+        let mut object = crate::scene::Object::new("Quad".to_string());
+        object.faces.push(face.clone());
+        scene.layers[0].objects.push(object);
+
+        let dir = std::env::temp_dir().join(format!("cracktile3d_obj_round_trip_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("quad.obj");
+        export_obj(&scene, &path, &ObjExportOptions::default()).unwrap();
+        let imported = import_obj(&path);
+        let _ = fs::remove_dir_all(&dir);
+
+        let imported = imported.unwrap();
+        assert_eq!(imported.objects.len(), 1);
+        let faces = &imported.objects[0].faces;
+        assert_eq!(faces.len(), 1);
+        for c in 0..4 {
+            assert!(faces[0].positions[c].abs_diff_eq(face.positions[c], 1e-5), "corner {c} position");
+            assert!(faces[0].uvs[c].abs_diff_eq(face.uvs[c], 1e-5), "corner {c} uv: {} vs {}", faces[0].uvs[c], face.uvs[c]);
+        }
+    }
+
+    #[test]
+    fn text_scene_image_paths_must_stay_in_the_scene_folder() {
+        assert!(is_contained_path("scene_tilesets/00_grass.png"));
+        assert!(is_contained_path("./scene_tilesets/00_grass.png"));
+        assert!(!is_contained_path("/etc/passwd"));
+        assert!(!is_contained_path("../secret.png"));
+        assert!(!is_contained_path("scene_tilesets/../../secret.png"));
+        assert!(!is_contained_path(""));
+    }
+}
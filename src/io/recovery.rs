@@ -0,0 +1,288 @@
+//! Autosave snapshots for crash recovery.
+//!
+//! Snapshots are regular .ct3d files written to `~/.config/cracktile3d/recovery/`, each with a
+//! small JSON sidecar recording which scene file (if any) it was taken from. File names carry
+//! the session that wrote them (`autosave_<session>_<stamp>.ct3d`), so several running
+//! instances share the directory without rotating or clearing each other's snapshots.
+//!
+//! Each session holds an exclusive lock on `session_<session>.lock` while it runs. The OS
+//! releases it when the process dies, so only snapshots of sessions whose lock is free are
+//! offered for recovery or pruned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use serde::{Serialize, Deserialize};
+use crate::scene::Scene;
+use crate::tile::Tileset;
+
+/// An autosave snapshot found in the recovery directory.
+pub struct Snapshot {
+    /// Path of the snapshot .ct3d file.
+    pub path: PathBuf,
+    /// Scene file the snapshot was taken from (None = never saved).
+    pub source: Option<PathBuf>,
+    /// When the snapshot was written.
+    pub modified: SystemTime,
+    /// Session that wrote the snapshot (None = written before snapshots were tagged).
+    pub session: Option<String>,
+}
+
+impl Snapshot {
+    /// Whether this snapshot holds work newer than its source file on disk.
+    pub fn is_newer_than_source(&self) -> bool {
+        let Some(ref source) = self.source else { return true };
+        match fs::metadata(source).and_then(|m| m.modified()) {
+            Ok(saved) => self.modified > saved,
+            Err(_) => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotMeta {
+    source: Option<PathBuf>,
+}
+
+/// Directory holding autosave snapshots, next to the settings file.
+pub fn recovery_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".config/cracktile3d/recovery")
+}
+
+/// Orphaned sessions whose newest snapshot is older than this are deleted at startup unless
+/// their snapshot is the one offered, so crashes can't pile up snapshots forever.
+const MAX_ORPHAN_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// Tag of this process's snapshots: process id and start time, unique across restarts.
+/// The first call also takes the session's lock, held until the process exits.
+fn session_id() -> &'static str {
+    static SESSION: OnceLock<(String, Option<fs::File>)> = OnceLock::new();
+    let (id, _lock) = SESSION.get_or_init(|| {
+        let start = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let id = format!("{}-{start}", std::process::id());
+        let lock = fs::create_dir_all(recovery_dir())
+            .and_then(|()| fs::File::create(lock_path(&id)))
+            .and_then(|file| file.try_lock().map_err(std::io::Error::from).map(|()| file));
+        if let Err(ref e) = lock {
+            log::warn!("Could not lock autosave session {id}: {e}");
+        }
+        (id, lock.ok())
+    });
+    id
+}
+
+fn lock_path(session: &str) -> PathBuf {
+    recovery_dir().join(format!("session_{session}.lock"))
+}
+
+/// Whether the session that owns `session`'s snapshots is still running (holds its lock).
+fn session_alive(session: &str) -> bool {
+    if session == session_id() {
+        return true;
+    }
+    match fs::File::open(lock_path(session)) {
+        Ok(file) => matches!(file.try_lock(), Err(fs::TryLockError::WouldBlock)),
+        Err(_) => false,
+    }
+}
+
+/// Session of a recovery file named `autosave_<session>_<stamp>.<ext>` (any extensions,
+/// including in-progress `.tmp` writes).
+fn session_of(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.split('.').next()?;
+    let (session, _stamp) = stem.strip_prefix("autosave_")?.rsplit_once('_')?;
+    Some(session.to_string())
+}
+
+/// Write an autosave snapshot of `scene`. Geometry is converted and tileset images are copied
+/// on the calling thread; PNG encoding and disk I/O run on a background thread. Keeps at most
+/// `keep` of this session's snapshots.
+pub fn write_snapshot(
+    scene: &Scene,
+    source: Option<&Path>,
+    keep: usize,
+) -> Result<std::thread::JoinHandle<()>, String> {
+    let data = super::schema::SceneDataV10::from_scene(scene);
+    let tilesets: Vec<Tileset> = scene.tilesets.iter().map(Tileset::cpu_copy).collect();
+    let meta = SnapshotMeta { source: source.map(Path::to_path_buf) };
+    let dir = recovery_dir();
+
+    let handle = std::thread::Builder::new()
+        .name("autosave".to_string())
+        .spawn(move || {
+            let stamp = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let path = dir.join(format!("autosave_{}_{stamp}.ct3d", session_id()));
+            // Both files go to temporary names first and are renamed into place sidecar first,
+            // so a crash mid-write never leaves a truncated snapshot to be offered
+            let result = fs::create_dir_all(&dir)
+                .map_err(|e| format!("Create recovery directory failed: {e}"))
+                .and_then(|()| super::schema::SceneLatest::from_parts(data, &tilesets))
+                .and_then(|latest| super::encode_binary(&latest))
+                .and_then(|data| {
+                    let json = serde_json::to_string(&meta).map_err(|e| e.to_string())?;
+                    write_atomic(&path.with_extension("json"), json.as_bytes())?;
+                    write_atomic(&path, &data)
+                });
+            match result {
+                Ok(()) => {
+                    log::info!("Autosaved to {:?}", path);
+                    rotate_snapshots(keep);
+                }
+                Err(e) => log::error!("Autosave failed: {e}"),
+            }
+        })
+        .map_err(|e| format!("Failed to start autosave thread: {e}"))?;
+    Ok(handle)
+}
+
+/// Write `data` to `<path>.tmp`, then rename it over `path`.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Write failed: {e}"))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Rename failed: {e}"))
+}
+
+/// List snapshots, newest first.
+pub fn list_snapshots() -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(recovery_dir()) else { return Vec::new() };
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ct3d"))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            let source = fs::read_to_string(path.with_extension("json"))
+                .ok()
+                .and_then(|s| serde_json::from_str::<SnapshotMeta>(&s).ok())
+                .and_then(|m| m.source);
+            let session = session_of(&path);
+            Some(Snapshot { path, source, modified, session })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.modified));
+    snapshots
+}
+
+/// Choose the snapshot to offer at startup: the newest readable one holding unsaved work, from
+/// the most recently active session that is no longer running. Orphaned sessions with nothing
+/// to recover are deleted, as are other orphans past `MAX_ORPHAN_AGE`; younger ones wait to be
+/// offered on a later start. Snapshots of running instances are never touched.
+pub fn recoverable_snapshot() -> Option<Snapshot> {
+    // Sessions in order of their newest snapshot; untagged (legacy) snapshots count as one
+    let mut sessions: Vec<(Option<String>, Vec<Snapshot>)> = Vec::new();
+    for snapshot in list_snapshots() {
+        if snapshot.session.as_deref().is_some_and(session_alive) {
+            continue;
+        }
+        match sessions.iter_mut().find(|(s, _)| *s == snapshot.session) {
+            Some((_, group)) => group.push(snapshot),
+            None => sessions.push((snapshot.session.clone(), vec![snapshot])),
+        }
+    }
+
+    let mut offered = None;
+    for (_, mut group) in sessions {
+        if offered.is_none() {
+            let candidate = group.iter()
+                .position(|s| s.is_newer_than_source() && super::load_scene(&s.path).is_ok());
+            if let Some(i) = candidate {
+                offered = Some(group.swap_remove(i));
+                continue;
+            }
+        } else {
+            let age = group[0].modified.elapsed().unwrap_or_default();
+            if age < MAX_ORPHAN_AGE {
+                continue;
+            }
+        }
+        group.iter().for_each(remove_snapshot);
+    }
+    remove_orphaned_files(offered.as_ref().and_then(|s| s.session.as_deref()));
+    offered
+}
+
+/// Delete the lock files and unfinished `.tmp` writes of sessions that are no longer running
+/// (except `keep`, whose snapshot is being offered).
+fn remove_orphaned_files(keep: Option<&str>) {
+    let Ok(entries) = fs::read_dir(recovery_dir()) else { return };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let session = match name.strip_prefix("session_").and_then(|n| n.strip_suffix(".lock")) {
+            Some(session) => session.to_string(),
+            None if name.ends_with(".tmp") => match session_of(&path) {
+                Some(session) => session,
+                None => continue,
+            },
+            None => continue,
+        };
+        if Some(session.as_str()) != keep && !session_alive(&session) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// This session's snapshots, newest first.
+fn own_snapshots() -> Vec<Snapshot> {
+    list_snapshots().into_iter().filter(|s| s.session.as_deref() == Some(session_id())).collect()
+}
+
+/// Delete all but the newest `keep` of this session's snapshots.
+pub fn rotate_snapshots(keep: usize) {
+    for snapshot in own_snapshots().into_iter().skip(keep.max(1)) {
+        remove_snapshot(&snapshot);
+    }
+}
+
+/// Delete this session's snapshots (after a successful manual save, or on a clean exit).
+pub fn clear_snapshots() {
+    for snapshot in own_snapshots() {
+        remove_snapshot(&snapshot);
+    }
+}
+
+/// Delete `snapshot` and every other snapshot from the session that wrote it (when recovery
+/// is declined or the work was already saved).
+pub fn discard_session(snapshot: &Snapshot) {
+    if snapshot.session.is_none() {
+        remove_snapshot(snapshot);
+        return;
+    }
+    for other in list_snapshots().into_iter().filter(|s| s.session == snapshot.session) {
+        remove_snapshot(&other);
+    }
+}
+
+/// Take over the snapshots of `snapshot`'s session after restoring from it, so they are
+/// rotated and cleared with this session's own and the restored work stays covered until
+/// it is saved or autosaved again.
+pub fn adopt_session(snapshot: &Snapshot) {
+    let snapshots: Vec<Snapshot> = match snapshot.session {
+        Some(_) => list_snapshots().into_iter().filter(|s| s.session == snapshot.session).collect(),
+        None => list_snapshots().into_iter().filter(|s| s.path == snapshot.path).collect(),
+    };
+    for other in snapshots {
+        let stamp = other.modified
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = other.path.with_file_name(format!("autosave_{}_{stamp}.ct3d", session_id()));
+        if fs::rename(other.path.with_extension("json"), path.with_extension("json")).is_ok() {
+            let _ = fs::rename(&other.path, &path);
+        }
+    }
+}
+
+fn remove_snapshot(snapshot: &Snapshot) {
+    let _ = fs::remove_file(&snapshot.path);
+    let _ = fs::remove_file(snapshot.path.with_extension("json"));
+}
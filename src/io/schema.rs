@@ -0,0 +1,1979 @@
+//! Versioned on-disk schema for .ct3d scene files.
+//!
+//! Runtime types (`Scene`, `Object`, `Face`, ...) are free to change; the structs here are
+//! frozen per file version. Loading deserializes the payload into the schema matching the
+//! file's version, then migrates it forward one version at a time (v1 → v2 → ...) until it
+//! reaches the latest schema, which is finally converted into a runtime `Scene`.
+//!
+//! To add a new format version: copy the latest schema structs that change into new `*V{n}`
+//! types, add a `migrate_v{n-1}` step with its arm in `VersionedScene::upgrade`, add a decode
+//! arm for the binary and text formats, and bump `VERSION` in `io`.
+
+use glam::{Quat, Vec2, Vec3, Vec4};
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use crate::bones::{Bone, Skeleton};
+use crate::scene::{Instance, Layer, Object, Prefab, Scene};
+use crate::scene::mesh::Face;
+use crate::tile::{AlphaMode, FilterMode, MaterialSettings, MipFilter, Tileset, TilesetSource, WrapMode};
+use crate::tile::aseprite::AsepriteSelection;
+use crate::tile::indexed::{IndexedColors, PaletteVariant};
+use crate::tile::maps::MapChannel;
+use crate::tile::properties::{CollisionType, TileProperties};
+use crate::tile::palette::{Palette, PaletteEntry, PaletteMode};
+
+// --- v1: scene geometry, prefabs, skeleton, palettes (no tilesets) ---
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneV1 {
+    pub layers: Vec<LayerV1>,
+    pub crosshair_pos: [f32; 3],
+    pub grid_cell_size: f32,
+    pub grid_preset_index: usize,
+    pub active_layer: usize,
+    pub prefabs: Vec<PrefabV1>,
+    pub skeleton: Vec<BoneV1>,
+    pub palettes: Vec<PaletteV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LayerV1 {
+    pub name: String,
+    pub visible: bool,
+    pub objects: Vec<ObjectV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectV1 {
+    pub name: String,
+    pub faces: Vec<FaceV1>,
+    pub tileset_index: Option<usize>,
+    pub instances: Vec<InstanceV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FaceV1 {
+    pub positions: [[f32; 3]; 4],
+    pub uvs: [[f32; 2]; 4],
+    pub colors: [[f32; 4]; 4],
+    pub hidden: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InstanceV1 {
+    pub name: String,
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PrefabV1 {
+    pub name: String,
+    pub faces: Vec<FaceV1>,
+    pub origin: [f32; 3],
+    pub tileset_index: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BoneV1 {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub head: [f32; 3],
+    pub tail: [f32; 3],
+    pub pose_rotation: [f32; 4],
+    pub pose_translation: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaletteV1 {
+    pub name: String,
+    pub entries: Vec<PaletteEntryV1>,
+    pub mode: PaletteModeV1,
+    pub random_rotation: bool,
+    pub random_flip_h: bool,
+    pub random_flip_v: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaletteEntryV1 {
+    pub tileset_index: usize,
+    pub col: u32,
+    pub row: u32,
+    pub weight: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum PaletteModeV1 {
+    Random,
+    Sequence,
+}
+
+// --- v2: v1 scene followed by a tileset section with embedded PNG images ---
+
+pub struct SceneV2 {
+    pub scene: SceneV1,
+    pub tilesets: Vec<TilesetV2>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV2 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MaterialSettingsV2 {
+    pub filter: FilterModeV2,
+    pub wrap: WrapModeV2,
+    pub alpha_mode: AlphaModeV2,
+    pub alpha_cutoff: f32,
+    pub decal: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum FilterModeV2 {
+    Nearest,
+    Linear,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum WrapModeV2 {
+    ClampToEdge,
+    Repeat,
+    MirroredRepeat,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum AlphaModeV2 {
+    AlphaTest,
+    AlphaBlend,
+    Opaque,
+}
+
+// --- v3: tilesets remember the image file they were loaded from (for hot-reload) ---
+
+pub struct SceneV3 {
+    pub scene: SceneV1,
+    pub tilesets: Vec<TilesetV3>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV3 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV2,
+    pub source: Option<TilesetSourceV3>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetSourceV3 {
+    /// Source file, relative to the scene file when saved next to it (absolute otherwise).
+    pub path: String,
+    pub aseprite_frame: usize,
+    pub aseprite_layer: Option<usize>,
+}
+
+// --- v4: tilesets with an outer margin and spacing between tiles ---
+
+pub struct SceneV4 {
+    pub scene: SceneV1,
+    pub tilesets: Vec<TilesetV4>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV4 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV2,
+    pub source: Option<TilesetSourceV3>,
+}
+
+// --- v5: material edge extrusion (padded GPU copy against texture bleeding) ---
+
+pub struct SceneV5 {
+    pub scene: SceneV1,
+    pub tilesets: Vec<TilesetV5>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV5 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV5,
+    pub source: Option<TilesetSourceV3>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MaterialSettingsV5 {
+    pub filter: FilterModeV2,
+    pub wrap: WrapModeV2,
+    pub alpha_mode: AlphaModeV2,
+    pub alpha_cutoff: f32,
+    pub decal: bool,
+    pub extrude: u32,
+}
+
+// --- v6: per-tile metadata (tags, collision type, custom properties) ---
+
+pub struct SceneV6 {
+    pub scene: SceneV1,
+    pub tilesets: Vec<TilesetV6>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV6 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV5,
+    pub source: Option<TilesetSourceV3>,
+    /// Only tiles that carry metadata are listed.
+    pub tile_properties: Vec<TilePropertiesV6>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilePropertiesV6 {
+    pub col: u32,
+    pub row: u32,
+    pub tags: Vec<String>,
+    pub collision: CollisionTypeV6,
+    pub custom: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum CollisionTypeV6 {
+    None,
+    Solid,
+    Platform,
+    Water,
+    Ladder,
+    Hazard,
+    Trigger,
+}
+
+// --- v7: faces record their source tileset (objects mixing tilesets via the scene atlas) ---
+
+pub struct SceneV7 {
+    pub scene: SceneDataV7,
+    pub tilesets: Vec<TilesetV6>,
+}
+
+/// The v1 scene section with `FaceV7` faces.
+#[derive(Serialize, Deserialize)]
+pub struct SceneDataV7 {
+    pub layers: Vec<LayerV7>,
+    pub crosshair_pos: [f32; 3],
+    pub grid_cell_size: f32,
+    pub grid_preset_index: usize,
+    pub active_layer: usize,
+    pub prefabs: Vec<PrefabV7>,
+    pub skeleton: Vec<BoneV1>,
+    pub palettes: Vec<PaletteV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LayerV7 {
+    pub name: String,
+    pub visible: bool,
+    pub objects: Vec<ObjectV7>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectV7 {
+    pub name: String,
+    pub faces: Vec<FaceV7>,
+    pub tileset_index: Option<usize>,
+    pub instances: Vec<InstanceV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FaceV7 {
+    pub positions: [[f32; 3]; 4],
+    pub uvs: [[f32; 2]; 4],
+    pub colors: [[f32; 4]; 4],
+    pub hidden: bool,
+    /// Source tileset when it differs from the object's.
+    pub tileset: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PrefabV7 {
+    pub name: String,
+    pub faces: Vec<FaceV7>,
+    pub origin: [f32; 3],
+    pub tileset_index: Option<usize>,
+}
+
+// --- v8: tilesets with normal/emissive/specular companion maps ---
+
+pub struct SceneV8 {
+    pub scene: SceneDataV7,
+    pub tilesets: Vec<TilesetV8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV8 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV8,
+    pub source: Option<TilesetSourceV3>,
+    /// Only tiles that carry metadata are listed.
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// PNG-encoded companion maps, same size as `png`. Empty when the map is not set.
+    pub normal_png: Vec<u8>,
+    pub emissive_png: Vec<u8>,
+    pub specular_png: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MaterialSettingsV8 {
+    pub filter: FilterModeV2,
+    pub wrap: WrapModeV2,
+    pub alpha_mode: AlphaModeV2,
+    pub alpha_cutoff: f32,
+    pub decal: bool,
+    pub extrude: u32,
+    pub normal_strength: f32,
+    pub emissive_strength: f32,
+    pub specular_strength: f32,
+    pub specular_power: f32,
+}
+
+// --- v9: material mipmaps and anisotropic filtering ---
+
+pub struct SceneV9 {
+    pub scene: SceneDataV7,
+    pub tilesets: Vec<TilesetV9>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV9 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image. Empty when the tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV9,
+    pub source: Option<TilesetSourceV3>,
+    /// Only tiles that carry metadata are listed.
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// PNG-encoded companion maps, same size as `png`. Empty when the map is not set.
+    pub normal_png: Vec<u8>,
+    pub emissive_png: Vec<u8>,
+    pub specular_png: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MaterialSettingsV9 {
+    pub filter: FilterModeV2,
+    pub wrap: WrapModeV2,
+    pub alpha_mode: AlphaModeV2,
+    pub alpha_cutoff: f32,
+    pub decal: bool,
+    pub extrude: u32,
+    pub mip_filter: MipFilterV9,
+    pub anisotropy: u16,
+    pub normal_strength: f32,
+    pub emissive_strength: f32,
+    pub specular_strength: f32,
+    pub specular_power: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum MipFilterV9 {
+    Off,
+    Nearest,
+    Linear,
+}
+
+// --- v10: indexed-color tilesets with palette variants, chosen per layer and object ---
+
+pub struct SceneV10 {
+    pub scene: SceneDataV10,
+    pub tilesets: Vec<TilesetV10>,
+}
+
+/// The v7 scene section with palette variant names on layers and objects.
+#[derive(Serialize, Deserialize)]
+pub struct SceneDataV10 {
+    pub layers: Vec<LayerV10>,
+    pub crosshair_pos: [f32; 3],
+    pub grid_cell_size: f32,
+    pub grid_preset_index: usize,
+    pub active_layer: usize,
+    pub prefabs: Vec<PrefabV7>,
+    pub skeleton: Vec<BoneV1>,
+    pub palettes: Vec<PaletteV1>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LayerV10 {
+    pub name: String,
+    pub visible: bool,
+    pub objects: Vec<ObjectV10>,
+    pub palette_variant: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectV10 {
+    pub name: String,
+    pub faces: Vec<FaceV7>,
+    pub tileset_index: Option<usize>,
+    pub instances: Vec<InstanceV1>,
+    pub palette_variant: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TilesetV10 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG-encoded image (resolved with the base color table when indexed). Empty when the
+    /// tileset had no pixel data.
+    pub png: Vec<u8>,
+    pub material: MaterialSettingsV9,
+    pub source: Option<TilesetSourceV3>,
+    /// Only tiles that carry metadata are listed.
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// PNG-encoded companion maps, same size as `png`. Empty when the map is not set.
+    pub normal_png: Vec<u8>,
+    pub emissive_png: Vec<u8>,
+    pub specular_png: Vec<u8>,
+    pub indexed: Option<IndexedColorsV10>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct IndexedColorsV10 {
+    /// 8-bit grayscale PNG of color table indices, same size as the image.
+    pub index_png: Vec<u8>,
+    pub colors: Vec<[u8; 4]>,
+    pub variants: Vec<PaletteVariantV10>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaletteVariantV10 {
+    pub name: String,
+    pub colors: Vec<[u8; 4]>,
+}
+
+/// The newest schema; what `save_scene` writes.
+pub type SceneLatest = SceneV10;
+
+// --- Migrations ---
+
+/// v1 → v2: older files carry no tilesets; objects keep their indices and render with the
+/// placeholder texture until tilesets are loaded again.
+pub fn migrate_v1(v1: SceneV1) -> SceneV2 {
+    SceneV2 { scene: v1, tilesets: Vec::new() }
+}
+
+/// v2 → v3: tilesets have no known source file, so they stay embedded-only.
+pub fn migrate_v2(v2: SceneV2) -> SceneV3 {
+    SceneV3 {
+        scene: v2.scene,
+        tilesets: v2.tilesets.into_iter().map(TilesetV3::from_v2).collect(),
+    }
+}
+
+/// v3 → v4: tiles were packed edge to edge, i.e. no margin and no spacing.
+pub fn migrate_v3(v3: SceneV3) -> SceneV4 {
+    SceneV4 {
+        scene: v3.scene,
+        tilesets: v3.tilesets.into_iter().map(TilesetV4::from_v3).collect(),
+    }
+}
+
+/// v4 → v5: tilesets were uploaded without extrusion.
+pub fn migrate_v4(v4: SceneV4) -> SceneV5 {
+    SceneV5 {
+        scene: v4.scene,
+        tilesets: v4.tilesets.into_iter().map(TilesetV5::from_v4).collect(),
+    }
+}
+
+/// v5 → v6: no tile had metadata.
+pub fn migrate_v5(v5: SceneV5) -> SceneV6 {
+    SceneV6 {
+        scene: v5.scene,
+        tilesets: v5.tilesets.into_iter().map(TilesetV6::from_v5).collect(),
+    }
+}
+
+/// v6 → v7: every face used its object's tileset.
+pub fn migrate_v6(v6: SceneV6) -> SceneV7 {
+    SceneV7 {
+        scene: SceneDataV7::from_v1(v6.scene),
+        tilesets: v6.tilesets,
+    }
+}
+
+/// v7 → v8: no tileset had companion maps.
+pub fn migrate_v7(v7: SceneV7) -> SceneV8 {
+    SceneV8 {
+        scene: v7.scene,
+        tilesets: v7.tilesets.into_iter().map(TilesetV8::from_v6).collect(),
+    }
+}
+
+/// v8 → v9: tilesets had no mipmaps or anisotropic filtering.
+pub fn migrate_v8(v8: SceneV8) -> SceneV9 {
+    SceneV9 {
+        scene: v8.scene,
+        tilesets: v8.tilesets.into_iter().map(TilesetV9::from_v8).collect(),
+    }
+}
+
+/// v9 → v10: no tileset was indexed and every layer and object drew the base colors.
+pub fn migrate_v9(v9: SceneV9) -> SceneV10 {
+    SceneV10 {
+        scene: SceneDataV10::from_v7(v9.scene),
+        tilesets: v9.tilesets.into_iter().map(TilesetV10::from_v9).collect(),
+    }
+}
+
+/// A decoded scene in the schema of the file's version.
+pub enum VersionedScene {
+    V1(SceneV1),
+    V2(SceneV2),
+    V3(SceneV3),
+    V4(SceneV4),
+    V5(SceneV5),
+    V6(SceneV6),
+    V7(SceneV7),
+    V8(SceneV8),
+    V9(SceneV9),
+    V10(SceneV10),
+}
+
+impl VersionedScene {
+    /// Migrate one version at a time until the latest schema is reached.
+    pub fn upgrade(mut self) -> SceneLatest {
+        loop {
+            self = match self {
+                Self::V1(v1) => Self::V2(migrate_v1(v1)),
+                Self::V2(v2) => Self::V3(migrate_v2(v2)),
+                Self::V3(v3) => Self::V4(migrate_v3(v3)),
+                Self::V4(v4) => Self::V5(migrate_v4(v4)),
+                Self::V5(v5) => Self::V6(migrate_v5(v5)),
+                Self::V6(v6) => Self::V7(migrate_v6(v6)),
+                Self::V7(v7) => Self::V8(migrate_v7(v7)),
+                Self::V8(v8) => Self::V9(migrate_v8(v8)),
+                Self::V9(v9) => Self::V10(migrate_v9(v9)),
+                Self::V10(latest) => return latest,
+            };
+        }
+    }
+}
+
+// --- Payload encoding ---
+
+/// Decode a payload written with format `version` and migrate it to the latest schema.
+pub fn decode(version: u32, mut payload: &[u8]) -> Result<SceneLatest, String> {
+    let payload = &mut payload;
+    let scene = match version {
+        1 => VersionedScene::V1(read_section(payload, "scene", version)?),
+        2 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V2(SceneV2 { scene, tilesets }) }
+        3 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V3(SceneV3 { scene, tilesets }) }
+        4 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V4(SceneV4 { scene, tilesets }) }
+        5 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V5(SceneV5 { scene, tilesets }) }
+        6 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V6(SceneV6 { scene, tilesets }) }
+        7 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V7(SceneV7 { scene, tilesets }) }
+        8 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V8(SceneV8 { scene, tilesets }) }
+        9 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V9(SceneV9 { scene, tilesets }) }
+        10 => { let (scene, tilesets) = read_sections(payload, version)?; VersionedScene::V10(SceneV10 { scene, tilesets }) }
+        _ => return Err(format!("Unknown file version {version}")),
+    };
+    Ok(scene.upgrade())
+}
+
+/// Encode a scene using the latest schema.
+pub fn encode(latest: &SceneLatest, out: &mut Vec<u8>) -> Result<(), String> {
+    write_section(out, &latest.scene, "scene")?;
+    write_section(out, &latest.tilesets, "tileset")?;
+    Ok(())
+}
+
+fn read_section<T: DeserializeOwned>(payload: &mut &[u8], section: &str, version: u32) -> Result<T, String> {
+    bincode::deserialize_from(payload)
+        .map_err(|e| format!("Failed to read {section} section (format v{version}): {e}"))
+}
+
+/// The scene and tileset sections every version since v2 starts with.
+fn read_sections<S: DeserializeOwned, T: DeserializeOwned>(payload: &mut &[u8], version: u32) -> Result<(S, T), String> {
+    Ok((read_section(payload, "scene", version)?, read_section(payload, "tileset", version)?))
+}
+
+fn write_section<T: Serialize>(out: &mut Vec<u8>, value: &T, section: &str) -> Result<(), String> {
+    bincode::serialize_into(out, value)
+        .map_err(|e| format!("Failed to write {section} section: {e}"))
+}
+
+// --- Text (.ct3dj) encoding ---
+//
+// The text format stores the same versioned schema as pretty-printed JSON so scenes can be
+// diffed and merged. Object keys are written in sorted order (serde_json's default map), so
+// output is deterministic.
+// Tileset images are written as PNG files next to the scene and referenced by relative path.
+
+/// Identifies a JSON document as a Cracktile 3D scene.
+pub const TEXT_FORMAT_TAG: &str = "cracktile3d-scene";
+
+/// A v2 tileset in the text format: image stored externally instead of embedded.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV2 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TextSceneV10 {
+    pub format: String,
+    pub version: u32,
+    pub scene: SceneDataV10,
+    pub tilesets: Vec<TextTilesetV10>,
+}
+
+/// A v3 tileset in the text format.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV3 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV2,
+    pub source: Option<TilesetSourceV3>,
+}
+
+/// A v4 tileset in the text format.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV4 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV2,
+    pub source: Option<TilesetSourceV3>,
+}
+
+/// A v5 tileset in the text format.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV5 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV5,
+    pub source: Option<TilesetSourceV3>,
+}
+
+/// A v6 tileset in the text format.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV6 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV5,
+    pub source: Option<TilesetSourceV3>,
+    pub tile_properties: Vec<TilePropertiesV6>,
+}
+
+/// A v8 tileset in the text format: companion maps are stored externally like the image.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV8 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV8,
+    pub source: Option<TilesetSourceV3>,
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// Companion map PNG paths relative to the scene file. Empty when the map is not set.
+    pub normal_image: String,
+    pub emissive_image: String,
+    pub specular_image: String,
+}
+
+/// A v9 tileset in the text format.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV9 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV9,
+    pub source: Option<TilesetSourceV3>,
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// Companion map PNG paths relative to the scene file. Empty when the map is not set.
+    pub normal_image: String,
+    pub emissive_image: String,
+    pub specular_image: String,
+}
+
+/// A v10 tileset in the text format: the index image is stored externally like the image.
+#[derive(Serialize, Deserialize)]
+pub struct TextTilesetV10 {
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub margin: u32,
+    pub spacing: u32,
+    /// PNG path relative to the scene file. Empty when the tileset had no pixel data.
+    pub image: String,
+    pub material: MaterialSettingsV9,
+    pub source: Option<TilesetSourceV3>,
+    pub tile_properties: Vec<TilePropertiesV6>,
+    /// Companion map PNG paths relative to the scene file. Empty when the map is not set.
+    pub normal_image: String,
+    pub emissive_image: String,
+    pub specular_image: String,
+    pub indexed: Option<TextIndexedColorsV10>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TextIndexedColorsV10 {
+    /// Index PNG path relative to the scene file.
+    pub index_image: String,
+    pub colors: Vec<[u8; 4]>,
+    pub variants: Vec<PaletteVariantV10>,
+}
+
+/// Decode a text scene and migrate it to the latest schema.
+/// `read_image` resolves a tileset's relative image path to PNG bytes.
+pub fn decode_text(
+    json: &str,
+    max_version: u32,
+    mut read_image: impl FnMut(&str) -> Result<Vec<u8>, String>,
+) -> Result<SceneLatest, String> {
+    let mut root: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Invalid JSON: {e}"))?;
+    if root.get("format").and_then(|f| f.as_str()) != Some(TEXT_FORMAT_TAG) {
+        return Err("Not a Cracktile 3D text scene (missing format tag)".to_string());
+    }
+    let version = root.get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("Missing format version")? as u32;
+    if version > max_version {
+        return Err(format!("File version {version} is newer than supported ({max_version})"));
+    }
+
+    let mut read_png = |name: &str, image: &str| -> Result<Vec<u8>, String> {
+        if image.is_empty() {
+            Ok(Vec::new())
+        } else {
+            read_image(image).map_err(|e| format!("Tileset '{name}': {e}"))
+        }
+    };
+
+    // All versions share the document layout of `TextSceneV10`; only the entries differ.
+    let root = &mut root;
+    let scene = match version {
+        2 => VersionedScene::V2(SceneV2 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV2>(root, version, &mut read_png)?,
+        }),
+        3 => VersionedScene::V3(SceneV3 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV3>(root, version, &mut read_png)?,
+        }),
+        4 => VersionedScene::V4(SceneV4 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV4>(root, version, &mut read_png)?,
+        }),
+        5 => VersionedScene::V5(SceneV5 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV5>(root, version, &mut read_png)?,
+        }),
+        6 => VersionedScene::V6(SceneV6 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV6>(root, version, &mut read_png)?,
+        }),
+        7 => VersionedScene::V7(SceneV7 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV6>(root, version, &mut read_png)?,
+        }),
+        8 => VersionedScene::V8(SceneV8 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV8>(root, version, &mut read_png)?,
+        }),
+        9 => VersionedScene::V9(SceneV9 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV9>(root, version, &mut read_png)?,
+        }),
+        10 => VersionedScene::V10(SceneV10 {
+            scene: read_text_section(root, "scene", version)?,
+            tilesets: read_text_tilesets::<TextTilesetV10>(root, version, &mut read_png)?,
+        }),
+        _ => return Err(format!("Unknown text file version {version}")),
+    };
+    Ok(scene.upgrade())
+}
+
+/// Reads the PNG a text tileset references: (tileset name, relative path) → bytes, empty for
+/// an empty path.
+type ReadPng<'a> = dyn FnMut(&str, &str) -> Result<Vec<u8>, String> + 'a;
+
+/// A text-format tileset entry, resolved into its version's binary schema by reading the PNG
+/// files it references.
+trait TextTileset: DeserializeOwned {
+    type Tileset;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<Self::Tileset, String>;
+}
+
+fn read_text_tilesets<T: TextTileset>(
+    root: &mut serde_json::Value,
+    version: u32,
+    read_png: &mut ReadPng,
+) -> Result<Vec<T::Tileset>, String> {
+    read_text_section::<Vec<T>>(root, "tilesets", version)?
+        .into_iter()
+        .map(|ts| ts.resolve(read_png))
+        .collect()
+}
+
+impl TextTileset for TextTilesetV2 {
+    type Tileset = TilesetV2;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV2, String> {
+        Ok(TilesetV2 {
+            png: read_png(&self.name, &self.image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            material: self.material,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV3 {
+    type Tileset = TilesetV3;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV3, String> {
+        Ok(TilesetV3 {
+            png: read_png(&self.name, &self.image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            material: self.material,
+            source: self.source,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV4 {
+    type Tileset = TilesetV4;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV4, String> {
+        Ok(TilesetV4 {
+            png: read_png(&self.name, &self.image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV5 {
+    type Tileset = TilesetV5;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV5, String> {
+        Ok(TilesetV5 {
+            png: read_png(&self.name, &self.image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV6 {
+    type Tileset = TilesetV6;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV6, String> {
+        Ok(TilesetV6 {
+            png: read_png(&self.name, &self.image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+            tile_properties: self.tile_properties,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV8 {
+    type Tileset = TilesetV8;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV8, String> {
+        Ok(TilesetV8 {
+            png: read_png(&self.name, &self.image)?,
+            normal_png: read_png(&self.name, &self.normal_image)?,
+            emissive_png: read_png(&self.name, &self.emissive_image)?,
+            specular_png: read_png(&self.name, &self.specular_image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+            tile_properties: self.tile_properties,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV9 {
+    type Tileset = TilesetV9;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV9, String> {
+        Ok(TilesetV9 {
+            png: read_png(&self.name, &self.image)?,
+            normal_png: read_png(&self.name, &self.normal_image)?,
+            emissive_png: read_png(&self.name, &self.emissive_image)?,
+            specular_png: read_png(&self.name, &self.specular_image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+            tile_properties: self.tile_properties,
+        })
+    }
+}
+
+impl TextTileset for TextTilesetV10 {
+    type Tileset = TilesetV10;
+    fn resolve(self, read_png: &mut ReadPng) -> Result<TilesetV10, String> {
+        let indexed = match self.indexed {
+            Some(ix) => Some(IndexedColorsV10 {
+                index_png: read_png(&self.name, &ix.index_image)?,
+                colors: ix.colors,
+                variants: ix.variants,
+            }),
+            None => None,
+        };
+        Ok(TilesetV10 {
+            png: read_png(&self.name, &self.image)?,
+            normal_png: read_png(&self.name, &self.normal_image)?,
+            emissive_png: read_png(&self.name, &self.emissive_image)?,
+            specular_png: read_png(&self.name, &self.specular_image)?,
+            name: self.name,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            material: self.material,
+            source: self.source,
+            tile_properties: self.tile_properties,
+            indexed,
+        })
+    }
+}
+
+/// Encode a scene as pretty-printed JSON using the latest schema.
+/// `write_image(index, name, png)` stores the PNG bytes of tileset `index` (its image, or a
+/// companion map or index image named after the tileset with a `_normal`/`_emissive`/
+/// `_specular`/`_indices` suffix)
+/// and returns the relative path to reference.
+pub fn encode_text(
+    latest: SceneLatest,
+    version: u32,
+    mut write_image: impl FnMut(usize, &str, &[u8]) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut tilesets = Vec::with_capacity(latest.tilesets.len());
+    for (i, ts) in latest.tilesets.into_iter().enumerate() {
+        let mut write_png = |suffix: &str, png: &[u8]| -> Result<String, String> {
+            if png.is_empty() { Ok(String::new()) } else { write_image(i, &format!("{}{suffix}", ts.name), png) }
+        };
+        let image = write_png("", &ts.png)?;
+        let normal_image = write_png("_normal", &ts.normal_png)?;
+        let emissive_image = write_png("_emissive", &ts.emissive_png)?;
+        let specular_image = write_png("_specular", &ts.specular_png)?;
+        let indexed = match ts.indexed {
+            Some(ix) => Some(TextIndexedColorsV10 {
+                index_image: write_png("_indices", &ix.index_png)?,
+                colors: ix.colors,
+                variants: ix.variants,
+            }),
+            None => None,
+        };
+        tilesets.push(TextTilesetV10 {
+            name: ts.name,
+            tile_width: ts.tile_width,
+            tile_height: ts.tile_height,
+            margin: ts.margin,
+            spacing: ts.spacing,
+            image,
+            material: ts.material,
+            source: ts.source,
+            tile_properties: ts.tile_properties,
+            normal_image,
+            emissive_image,
+            specular_image,
+            indexed,
+        });
+    }
+    let text = TextSceneV10 {
+        format: TEXT_FORMAT_TAG.to_string(),
+        version,
+        scene: latest.scene,
+        tilesets,
+    };
+    // Round-trip through a compact string so f32 values keep their shortest representation.
+    let compact = serde_json::to_string(&text)
+        .map_err(|e| format!("Failed to write text scene: {e}"))?;
+    let value: serde_json::Value = serde_json::from_str(&compact)
+        .map_err(|e| format!("Failed to write text scene: {e}"))?;
+    let mut json = String::new();
+    write_pretty_json(&value, 0, &mut json);
+    json.push('\n');
+    Ok(json)
+}
+
+/// Pretty-print JSON with two-space indentation, keeping arrays of scalars (vectors, colors,
+/// UVs) on a single line so each face stays compact and diffs stay readable.
+fn write_pretty_json(value: &serde_json::Value, indent: usize, out: &mut String) {
+    use serde_json::Value;
+    let pad = |out: &mut String, n: usize| out.extend(std::iter::repeat_n("  ", n));
+    match value {
+        Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 { out.push_str(", "); }
+                out.push_str(&item.to_string());
+            }
+            out.push(']');
+        }
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                pad(out, indent + 1);
+                write_pretty_json(item, indent + 1, out);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            pad(out, indent);
+            out.push(']');
+        }
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Object(map) => {
+            out.push_str("{\n");
+            for (i, (key, item)) in map.iter().enumerate() {
+                pad(out, indent + 1);
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push_str(": ");
+                write_pretty_json(item, indent + 1, out);
+                out.push_str(if i + 1 < map.len() { ",\n" } else { "\n" });
+            }
+            pad(out, indent);
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+fn read_text_section<T: DeserializeOwned>(root: &mut serde_json::Value, section: &str, version: u32) -> Result<T, String> {
+    let value = root.get_mut(section)
+        .map(serde_json::Value::take)
+        .ok_or_else(|| format!("Missing {section} section (format v{version})"))?;
+    serde_json::from_value(value)
+        .map_err(|e| format!("Failed to read {section} section (format v{version}): {e}"))
+}
+
+// --- Runtime conversion (latest schema only) ---
+
+impl SceneLatest {
+    pub fn from_scene(scene: &Scene) -> Result<Self, String> {
+        Self::from_parts(SceneDataV10::from_scene(scene), &scene.tilesets)
+    }
+
+    /// Combine an already converted scene section with its tilesets, PNG-encoding their
+    /// images. Split from `from_scene` so the encoding can run off the UI thread.
+    pub fn from_parts(scene: SceneDataV10, tilesets: &[Tileset]) -> Result<Self, String> {
+        let tilesets = tilesets.iter()
+            .map(TilesetV10::from_tileset)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { scene, tilesets })
+    }
+
+    /// Convert into a runtime scene. GPU resources are left empty.
+    pub fn into_scene(self) -> Result<Scene, String> {
+        let data = self.scene;
+        let mut scene = Scene::new();
+        scene.layers = data.layers.into_iter().map(LayerV10::into_layer).collect();
+        if scene.layers.is_empty() {
+            return Err("Scene section contains no layers".to_string());
+        }
+        scene.crosshair_pos = data.crosshair_pos.into();
+        scene.grid_cell_size = data.grid_cell_size;
+        scene.grid_preset_index = data.grid_preset_index;
+        scene.active_layer = data.active_layer.min(scene.layers.len() - 1);
+        scene.prefabs = data.prefabs.into_iter().map(PrefabV7::into_prefab).collect();
+        scene.skeleton = Skeleton { bones: data.skeleton.into_iter().map(BoneV1::into_bone).collect() };
+        scene.palettes = data.palettes.into_iter().map(PaletteV1::into_palette).collect();
+        scene.tilesets = self.tilesets.into_iter()
+            .map(TilesetV10::into_tileset)
+            .collect::<Result<Vec<_>, _>>()?;
+        scene.active_tileset = if scene.tilesets.is_empty() { None } else { Some(0) };
+        Ok(scene)
+    }
+}
+
+impl SceneDataV7 {
+    fn from_v1(v1: SceneV1) -> Self {
+        Self {
+            layers: v1.layers.into_iter().map(LayerV7::from_v1).collect(),
+            crosshair_pos: v1.crosshair_pos,
+            grid_cell_size: v1.grid_cell_size,
+            grid_preset_index: v1.grid_preset_index,
+            active_layer: v1.active_layer,
+            prefabs: v1.prefabs.into_iter().map(PrefabV7::from_v1).collect(),
+            skeleton: v1.skeleton,
+            palettes: v1.palettes,
+        }
+    }
+}
+
+impl LayerV7 {
+    fn from_v1(v1: LayerV1) -> Self {
+        Self {
+            name: v1.name,
+            visible: v1.visible,
+            objects: v1.objects.into_iter().map(ObjectV7::from_v1).collect(),
+        }
+    }
+}
+
+impl ObjectV7 {
+    fn from_v1(v1: ObjectV1) -> Self {
+        Self {
+            name: v1.name,
+            faces: v1.faces.into_iter().map(FaceV7::from_v1).collect(),
+            tileset_index: v1.tileset_index,
+            instances: v1.instances,
+        }
+    }
+}
+
+impl SceneDataV10 {
+    /// Scene section of a runtime scene: everything but the tilesets.
+    pub fn from_scene(scene: &Scene) -> Self {
+        Self {
+            layers: scene.layers.iter().map(LayerV10::from_layer).collect(),
+            crosshair_pos: scene.crosshair_pos.into(),
+            grid_cell_size: scene.grid_cell_size,
+            grid_preset_index: scene.grid_preset_index,
+            active_layer: scene.active_layer,
+            prefabs: scene.prefabs.iter().map(PrefabV7::from_prefab).collect(),
+            skeleton: scene.skeleton.bones.iter().map(BoneV1::from_bone).collect(),
+            palettes: scene.palettes.iter().map(PaletteV1::from_palette).collect(),
+        }
+    }
+
+    fn from_v7(v7: SceneDataV7) -> Self {
+        Self {
+            layers: v7.layers.into_iter().map(LayerV10::from_v7).collect(),
+            crosshair_pos: v7.crosshair_pos,
+            grid_cell_size: v7.grid_cell_size,
+            grid_preset_index: v7.grid_preset_index,
+            active_layer: v7.active_layer,
+            prefabs: v7.prefabs,
+            skeleton: v7.skeleton,
+            palettes: v7.palettes,
+        }
+    }
+}
+
+impl LayerV10 {
+    fn from_v7(v7: LayerV7) -> Self {
+        Self {
+            name: v7.name,
+            visible: v7.visible,
+            objects: v7.objects.into_iter().map(ObjectV10::from_v7).collect(),
+            palette_variant: None,
+        }
+    }
+
+    fn from_layer(layer: &Layer) -> Self {
+        Self {
+            name: layer.name.clone(),
+            visible: layer.visible,
+            objects: layer.objects.iter().map(ObjectV10::from_object).collect(),
+            palette_variant: layer.palette_variant.clone(),
+        }
+    }
+
+    fn into_layer(self) -> Layer {
+        Layer {
+            name: self.name,
+            visible: self.visible,
+            objects: self.objects.into_iter().map(ObjectV10::into_object).collect(),
+            palette_variant: self.palette_variant,
+        }
+    }
+}
+
+impl ObjectV10 {
+    fn from_v7(v7: ObjectV7) -> Self {
+        Self {
+            name: v7.name,
+            faces: v7.faces,
+            tileset_index: v7.tileset_index,
+            instances: v7.instances,
+            palette_variant: None,
+        }
+    }
+
+    fn from_object(obj: &Object) -> Self {
+        Self {
+            name: obj.name.clone(),
+            faces: obj.faces.iter().map(FaceV7::from_face).collect(),
+            tileset_index: obj.tileset_index,
+            instances: obj.instances.iter().map(InstanceV1::from_instance).collect(),
+            palette_variant: obj.palette_variant.clone(),
+        }
+    }
+
+    fn into_object(self) -> Object {
+        let mut obj = Object::new(self.name);
+        obj.faces = self.faces.into_iter().map(FaceV7::into_face).collect();
+        obj.tileset_index = self.tileset_index;
+        obj.instances = self.instances.into_iter().map(InstanceV1::into_instance).collect();
+        obj.palette_variant = self.palette_variant;
+        obj
+    }
+}
+
+impl FaceV7 {
+    fn from_v1(v1: FaceV1) -> Self {
+        Self {
+            positions: v1.positions,
+            uvs: v1.uvs,
+            colors: v1.colors,
+            hidden: v1.hidden,
+            tileset: None,
+        }
+    }
+
+    fn from_face(face: &Face) -> Self {
+        Self {
+            positions: face.positions.map(Into::into),
+            uvs: face.uvs.map(Into::into),
+            colors: face.colors.map(Into::into),
+            hidden: face.hidden,
+            tileset: face.tileset,
+        }
+    }
+
+    fn into_face(self) -> Face {
+        Face {
+            positions: self.positions.map(Vec3::from),
+            uvs: self.uvs.map(Vec2::from),
+            colors: self.colors.map(Vec4::from),
+            hidden: self.hidden,
+            tileset: self.tileset,
+        }
+    }
+}
+
+impl InstanceV1 {
+    fn from_instance(inst: &Instance) -> Self {
+        Self {
+            name: inst.name.clone(),
+            position: inst.position.into(),
+            rotation: inst.rotation.to_array(),
+            scale: inst.scale.into(),
+        }
+    }
+
+    fn into_instance(self) -> Instance {
+        Instance {
+            name: self.name,
+            position: self.position.into(),
+            rotation: Quat::from_array(self.rotation),
+            scale: self.scale.into(),
+        }
+    }
+}
+
+impl PrefabV7 {
+    fn from_v1(v1: PrefabV1) -> Self {
+        Self {
+            name: v1.name,
+            faces: v1.faces.into_iter().map(FaceV7::from_v1).collect(),
+            origin: v1.origin,
+            tileset_index: v1.tileset_index,
+        }
+    }
+
+    fn from_prefab(prefab: &Prefab) -> Self {
+        Self {
+            name: prefab.name.clone(),
+            faces: prefab.faces.iter().map(FaceV7::from_face).collect(),
+            origin: prefab.origin.into(),
+            tileset_index: prefab.tileset_index,
+        }
+    }
+
+    fn into_prefab(self) -> Prefab {
+        Prefab {
+            name: self.name,
+            faces: self.faces.into_iter().map(FaceV7::into_face).collect(),
+            origin: self.origin.into(),
+            tileset_index: self.tileset_index,
+        }
+    }
+}
+
+impl BoneV1 {
+    fn from_bone(bone: &Bone) -> Self {
+        Self {
+            name: bone.name.clone(),
+            parent: bone.parent,
+            head: bone.head.into(),
+            tail: bone.tail.into(),
+            pose_rotation: bone.pose_rotation.to_array(),
+            pose_translation: bone.pose_translation.into(),
+        }
+    }
+
+    fn into_bone(self) -> Bone {
+        let mut bone = Bone::new(self.name, self.head.into(), self.tail.into(), self.parent);
+        bone.pose_rotation = Quat::from_array(self.pose_rotation);
+        bone.pose_translation = self.pose_translation.into();
+        bone
+    }
+}
+
+impl PaletteV1 {
+    fn from_palette(palette: &Palette) -> Self {
+        Self {
+            name: palette.name.clone(),
+            entries: palette.entries.iter().map(|e| PaletteEntryV1 {
+                tileset_index: e.tileset_index,
+                col: e.col,
+                row: e.row,
+                weight: e.weight,
+            }).collect(),
+            mode: match palette.mode {
+                PaletteMode::Random => PaletteModeV1::Random,
+                PaletteMode::Sequence => PaletteModeV1::Sequence,
+            },
+            random_rotation: palette.random_rotation,
+            random_flip_h: palette.random_flip_h,
+            random_flip_v: palette.random_flip_v,
+        }
+    }
+
+    fn into_palette(self) -> Palette {
+        let mut palette = Palette::new(self.name);
+        palette.entries = self.entries.into_iter().map(|e| PaletteEntry {
+            tileset_index: e.tileset_index,
+            col: e.col,
+            row: e.row,
+            weight: e.weight,
+        }).collect();
+        palette.mode = match self.mode {
+            PaletteModeV1::Random => PaletteMode::Random,
+            PaletteModeV1::Sequence => PaletteMode::Sequence,
+        };
+        palette.random_rotation = self.random_rotation;
+        palette.random_flip_h = self.random_flip_h;
+        palette.random_flip_v = self.random_flip_v;
+        palette
+    }
+}
+
+impl TilesetV3 {
+    fn from_v2(v2: TilesetV2) -> Self {
+        Self {
+            name: v2.name,
+            tile_width: v2.tile_width,
+            tile_height: v2.tile_height,
+            png: v2.png,
+            material: v2.material,
+            source: None,
+        }
+    }
+}
+
+impl TilesetV4 {
+    fn from_v3(v3: TilesetV3) -> Self {
+        Self {
+            name: v3.name,
+            tile_width: v3.tile_width,
+            tile_height: v3.tile_height,
+            margin: 0,
+            spacing: 0,
+            png: v3.png,
+            material: v3.material,
+            source: v3.source,
+        }
+    }
+}
+
+impl TilesetV5 {
+    fn from_v4(v4: TilesetV4) -> Self {
+        Self {
+            name: v4.name,
+            tile_width: v4.tile_width,
+            tile_height: v4.tile_height,
+            margin: v4.margin,
+            spacing: v4.spacing,
+            png: v4.png,
+            material: MaterialSettingsV5::from_v2(v4.material),
+            source: v4.source,
+        }
+    }
+}
+
+impl TilesetV6 {
+    fn from_v5(v5: TilesetV5) -> Self {
+        Self {
+            name: v5.name,
+            tile_width: v5.tile_width,
+            tile_height: v5.tile_height,
+            margin: v5.margin,
+            spacing: v5.spacing,
+            png: v5.png,
+            material: v5.material,
+            source: v5.source,
+            tile_properties: Vec::new(),
+        }
+    }
+}
+
+impl TilesetV8 {
+    fn from_v6(v6: TilesetV6) -> Self {
+        Self {
+            name: v6.name,
+            tile_width: v6.tile_width,
+            tile_height: v6.tile_height,
+            margin: v6.margin,
+            spacing: v6.spacing,
+            png: v6.png,
+            material: MaterialSettingsV8::from_v5(v6.material),
+            source: v6.source,
+            tile_properties: v6.tile_properties,
+            normal_png: Vec::new(),
+            emissive_png: Vec::new(),
+            specular_png: Vec::new(),
+        }
+    }
+}
+
+impl TilesetV9 {
+    fn from_v8(v8: TilesetV8) -> Self {
+        Self {
+            name: v8.name,
+            tile_width: v8.tile_width,
+            tile_height: v8.tile_height,
+            margin: v8.margin,
+            spacing: v8.spacing,
+            png: v8.png,
+            material: MaterialSettingsV9::from_v8(v8.material),
+            source: v8.source,
+            tile_properties: v8.tile_properties,
+            normal_png: v8.normal_png,
+            emissive_png: v8.emissive_png,
+            specular_png: v8.specular_png,
+        }
+    }
+}
+
+impl TilesetV10 {
+    fn from_v9(v9: TilesetV9) -> Self {
+        Self {
+            name: v9.name,
+            tile_width: v9.tile_width,
+            tile_height: v9.tile_height,
+            margin: v9.margin,
+            spacing: v9.spacing,
+            png: v9.png,
+            material: v9.material,
+            source: v9.source,
+            tile_properties: v9.tile_properties,
+            normal_png: v9.normal_png,
+            emissive_png: v9.emissive_png,
+            specular_png: v9.specular_png,
+            indexed: None,
+        }
+    }
+
+    fn from_tileset(ts: &Tileset) -> Result<Self, String> {
+        Ok(Self {
+            name: ts.name.clone(),
+            tile_width: ts.tile_width,
+            tile_height: ts.tile_height,
+            margin: ts.margin,
+            spacing: ts.spacing,
+            png: ts.encode_png()?,
+            material: MaterialSettingsV9::from_material(&ts.material),
+            source: ts.source.as_ref().map(|src| TilesetSourceV3 {
+                path: src.path.to_string_lossy().into_owned(),
+                aseprite_frame: src.aseprite.frame,
+                aseprite_layer: src.aseprite.layer,
+            }),
+            tile_properties: ts.tile_properties.iter()
+                .filter(|(_, props)| !props.is_empty())
+                .map(|(&(col, row), props)| TilePropertiesV6::from_properties(col, row, props))
+                .collect(),
+            normal_png: ts.encode_map_png(MapChannel::Normal)?,
+            emissive_png: ts.encode_map_png(MapChannel::Emissive)?,
+            specular_png: ts.encode_map_png(MapChannel::Specular)?,
+            indexed: match ts.indexed {
+                Some(ref indexed) => Some(IndexedColorsV10 {
+                    index_png: ts.encode_index_png()?,
+                    colors: indexed.colors.clone(),
+                    variants: indexed.variants.iter()
+                        .map(|v| PaletteVariantV10 { name: v.name.clone(), colors: v.colors.clone() })
+                        .collect(),
+                }),
+                None => None,
+            },
+        })
+    }
+
+    /// Decode into a CPU-only tileset. GPU resources are created by the caller.
+    /// The source's modification time is left unknown, so hot-reload picks up edits made
+    /// to the image while the scene was closed.
+    fn into_tileset(self) -> Result<Tileset, String> {
+        let (w, h, data) = if self.png.is_empty() {
+            (0, 0, None)
+        } else {
+            let (w, h, rgba) = Tileset::decode_png(&self.png)
+                .map_err(|e| format!("Tileset '{}': {e}", self.name))?;
+            (w, h, Some(rgba))
+        };
+        let material = self.material.into_material();
+        let mut ts = Tileset::from_rgba(self.name, w, h, data, self.tile_width, self.tile_height, material);
+        ts.margin = self.margin;
+        ts.spacing = self.spacing;
+        ts.source = self.source.map(|src| TilesetSource {
+            path: src.path.into(),
+            aseprite: AsepriteSelection { frame: src.aseprite_frame, layer: src.aseprite_layer },
+            modified: None,
+        });
+        ts.tile_properties = self.tile_properties.into_iter()
+            .map(TilePropertiesV6::into_entry)
+            .collect();
+        for (channel, png) in [
+            (MapChannel::Normal, self.normal_png),
+            (MapChannel::Emissive, self.emissive_png),
+            (MapChannel::Specular, self.specular_png),
+        ] {
+            if png.is_empty() { continue; }
+            let (mw, mh, rgba) = Tileset::decode_png(&png)
+                .map_err(|e| format!("Tileset '{}' {} map: {e}", ts.name, channel.label()))?;
+            if (mw, mh) != (w, h) {
+                log::warn!("Tileset '{}': {} map is {mw}x{mh}, not {w}x{h}; dropped", ts.name, channel.label());
+                continue;
+            }
+            *ts.maps.get_mut(channel) = Some(rgba);
+        }
+        if let Some(ix) = self.indexed {
+            let (iw, ih, indices) = Tileset::decode_index_png(&ix.index_png)
+                .map_err(|e| format!("Tileset '{}' indices: {e}", ts.name))?;
+            if (iw, ih) != (w, h) {
+                log::warn!("Tileset '{}': index image is {iw}x{ih}, not {w}x{h}; kept as RGBA", ts.name);
+            } else {
+                ts.indexed = Some(IndexedColors {
+                    indices,
+                    colors: ix.colors,
+                    variants: ix.variants.into_iter()
+                        .map(|v| PaletteVariant { name: v.name, colors: v.colors })
+                        .collect(),
+                });
+                ts.apply_indexed();
+            }
+        }
+        Ok(ts)
+    }
+}
+
+impl TilePropertiesV6 {
+    fn from_properties(col: u32, row: u32, props: &TileProperties) -> Self {
+        Self {
+            col,
+            row,
+            tags: props.tags.clone(),
+            collision: match props.collision {
+                CollisionType::None => CollisionTypeV6::None,
+                CollisionType::Solid => CollisionTypeV6::Solid,
+                CollisionType::Platform => CollisionTypeV6::Platform,
+                CollisionType::Water => CollisionTypeV6::Water,
+                CollisionType::Ladder => CollisionTypeV6::Ladder,
+                CollisionType::Hazard => CollisionTypeV6::Hazard,
+                CollisionType::Trigger => CollisionTypeV6::Trigger,
+            },
+            custom: props.custom.clone(),
+        }
+    }
+
+    fn into_entry(self) -> ((u32, u32), TileProperties) {
+        let props = TileProperties {
+            tags: self.tags,
+            collision: match self.collision {
+                CollisionTypeV6::None => CollisionType::None,
+                CollisionTypeV6::Solid => CollisionType::Solid,
+                CollisionTypeV6::Platform => CollisionType::Platform,
+                CollisionTypeV6::Water => CollisionType::Water,
+                CollisionTypeV6::Ladder => CollisionType::Ladder,
+                CollisionTypeV6::Hazard => CollisionType::Hazard,
+                CollisionTypeV6::Trigger => CollisionType::Trigger,
+            },
+            custom: self.custom,
+        };
+        ((self.col, self.row), props)
+    }
+}
+
+impl MaterialSettingsV5 {
+    fn from_v2(v2: MaterialSettingsV2) -> Self {
+        Self {
+            filter: v2.filter,
+            wrap: v2.wrap,
+            alpha_mode: v2.alpha_mode,
+            alpha_cutoff: v2.alpha_cutoff,
+            decal: v2.decal,
+            extrude: 0,
+        }
+    }
+}
+
+impl MaterialSettingsV8 {
+    fn from_v5(v5: MaterialSettingsV5) -> Self {
+        let defaults = MaterialSettings::default();
+        Self {
+            filter: v5.filter,
+            wrap: v5.wrap,
+            alpha_mode: v5.alpha_mode,
+            alpha_cutoff: v5.alpha_cutoff,
+            decal: v5.decal,
+            extrude: v5.extrude,
+            normal_strength: defaults.normal_strength,
+            emissive_strength: defaults.emissive_strength,
+            specular_strength: defaults.specular_strength,
+            specular_power: defaults.specular_power,
+        }
+    }
+}
+
+impl MaterialSettingsV9 {
+    fn from_v8(v8: MaterialSettingsV8) -> Self {
+        Self {
+            filter: v8.filter,
+            wrap: v8.wrap,
+            alpha_mode: v8.alpha_mode,
+            alpha_cutoff: v8.alpha_cutoff,
+            decal: v8.decal,
+            extrude: v8.extrude,
+            mip_filter: MipFilterV9::Off,
+            anisotropy: 1,
+            normal_strength: v8.normal_strength,
+            emissive_strength: v8.emissive_strength,
+            specular_strength: v8.specular_strength,
+            specular_power: v8.specular_power,
+        }
+    }
+
+    fn from_material(m: &MaterialSettings) -> Self {
+        Self {
+            filter: match m.filter {
+                FilterMode::Nearest => FilterModeV2::Nearest,
+                FilterMode::Linear => FilterModeV2::Linear,
+            },
+            wrap: match m.wrap {
+                WrapMode::ClampToEdge => WrapModeV2::ClampToEdge,
+                WrapMode::Repeat => WrapModeV2::Repeat,
+                WrapMode::MirroredRepeat => WrapModeV2::MirroredRepeat,
+            },
+            alpha_mode: match m.alpha_mode {
+                AlphaMode::AlphaTest => AlphaModeV2::AlphaTest,
+                AlphaMode::AlphaBlend => AlphaModeV2::AlphaBlend,
+                AlphaMode::Opaque => AlphaModeV2::Opaque,
+            },
+            alpha_cutoff: m.alpha_cutoff,
+            decal: m.decal,
+            extrude: m.extrude,
+            mip_filter: match m.mip_filter {
+                MipFilter::Off => MipFilterV9::Off,
+                MipFilter::Nearest => MipFilterV9::Nearest,
+                MipFilter::Linear => MipFilterV9::Linear,
+            },
+            anisotropy: m.anisotropy,
+            normal_strength: m.normal_strength,
+            emissive_strength: m.emissive_strength,
+            specular_strength: m.specular_strength,
+            specular_power: m.specular_power,
+        }
+    }
+
+    fn into_material(self) -> MaterialSettings {
+        MaterialSettings {
+            filter: match self.filter {
+                FilterModeV2::Nearest => FilterMode::Nearest,
+                FilterModeV2::Linear => FilterMode::Linear,
+            },
+            wrap: match self.wrap {
+                WrapModeV2::ClampToEdge => WrapMode::ClampToEdge,
+                WrapModeV2::Repeat => WrapMode::Repeat,
+                WrapModeV2::MirroredRepeat => WrapMode::MirroredRepeat,
+            },
+            alpha_mode: match self.alpha_mode {
+                AlphaModeV2::AlphaTest => AlphaMode::AlphaTest,
+                AlphaModeV2::AlphaBlend => AlphaMode::AlphaBlend,
+                AlphaModeV2::Opaque => AlphaMode::Opaque,
+            },
+            alpha_cutoff: self.alpha_cutoff,
+            decal: self.decal,
+            extrude: self.extrude,
+            mip_filter: match self.mip_filter {
+                MipFilterV9::Off => MipFilter::Off,
+                MipFilterV9::Nearest => MipFilter::Nearest,
+                MipFilterV9::Linear => MipFilter::Linear,
+            },
+            anisotropy: self.anisotropy,
+            normal_strength: self.normal_strength,
+            emissive_strength: self.emissive_strength,
+            specular_strength: self.specular_strength,
+            specular_power: self.specular_power,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn sample_v1() -> SceneV1 {
+        let face = FaceV1 {
+            positions: [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+            uvs: [[0.0, 0.5], [0.5, 0.5], [0.5, 0.0], [0.0, 0.0]],
+            colors: [[1.0, 0.5, 0.25, 1.0]; 4],
+            hidden: false,
+        };
+        SceneV1 {
+            layers: vec![LayerV1 {
+                name: "Layer 1".to_string(),
+                visible: true,
+                objects: vec![ObjectV1 {
+                    name: "Object".to_string(),
+                    faces: vec![face],
+                    tileset_index: Some(0),
+                    instances: vec![InstanceV1 {
+                        name: "Instance".to_string(),
+                        position: [2.0, 0.0, 0.0],
+                        rotation: [0.0, 0.0, 0.0, 1.0],
+                        scale: [1.0; 3],
+                    }],
+                }],
+            }],
+            crosshair_pos: [1.0, 2.0, 3.0],
+            grid_cell_size: 0.5,
+            grid_preset_index: 2,
+            active_layer: 0,
+            prefabs: Vec::new(),
+            skeleton: Vec::new(),
+            palettes: Vec::new(),
+        }
+    }
+
+    fn sample_tileset_v2() -> TilesetV2 {
+        TilesetV2 {
+            name: "tiles".to_string(),
+            tile_width: 16,
+            tile_height: 8,
+            png: Vec::new(),
+            material: MaterialSettingsV2 {
+                filter: FilterModeV2::Linear,
+                wrap: WrapModeV2::Repeat,
+                alpha_mode: AlphaModeV2::AlphaBlend,
+                alpha_cutoff: 0.25,
+                decal: true,
+            },
+        }
+    }
+
+    fn encode_bytes(latest: &SceneLatest) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(latest, &mut out).unwrap();
+        out
+    }
+
+    /// Encode as text, keeping images in memory; returns the JSON and the stored images.
+    fn encode_text_in_memory(latest: SceneLatest) -> (String, HashMap<String, Vec<u8>>) {
+        let mut images = HashMap::new();
+        let json = encode_text(latest, super::super::VERSION, |i, name, png| {
+            let path = format!("{i}_{name}.png");
+            images.insert(path.clone(), png.to_vec());
+            Ok(path)
+        }).unwrap();
+        (json, images)
+    }
+
+    #[test]
+    fn binary_v1_migrates_to_latest() {
+        let mut payload = Vec::new();
+        write_section(&mut payload, &sample_v1(), "scene").unwrap();
+        let latest = decode(1, &payload).unwrap();
+
+        assert!(latest.tilesets.is_empty());
+        assert_eq!(latest.scene.crosshair_pos, [1.0, 2.0, 3.0]);
+        let layer = &latest.scene.layers[0];
+        assert!(layer.palette_variant.is_none());
+        let object = &layer.objects[0];
+        assert_eq!(object.tileset_index, Some(0));
+        assert!(object.palette_variant.is_none());
+        assert_eq!(object.instances[0].position, [2.0, 0.0, 0.0]);
+        assert_eq!(object.faces[0].positions[2], [1.0, 1.0, 0.0]);
+        assert_eq!(object.faces[0].colors[0], [1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(object.faces[0].tileset, None);
+    }
+
+    #[test]
+    fn binary_v2_tilesets_get_defaults_for_later_fields() {
+        let mut payload = Vec::new();
+        write_section(&mut payload, &sample_v1(), "scene").unwrap();
+        write_section(&mut payload, &vec![sample_tileset_v2()], "tileset").unwrap();
+        let latest = decode(2, &payload).unwrap();
+
+        let ts = &latest.tilesets[0];
+        assert_eq!((ts.name.as_str(), ts.tile_width, ts.tile_height), ("tiles", 16, 8));
+        assert_eq!((ts.margin, ts.spacing), (0, 0));
+        assert!(ts.source.is_none());
+        assert!(ts.tile_properties.is_empty());
+        assert!(ts.normal_png.is_empty() && ts.emissive_png.is_empty() && ts.specular_png.is_empty());
+        assert!(ts.indexed.is_none());
+        let m = &ts.material;
+        assert!(matches!(m.filter, FilterModeV2::Linear));
+        assert!(matches!(m.wrap, WrapModeV2::Repeat));
+        assert!(matches!(m.alpha_mode, AlphaModeV2::AlphaBlend));
+        assert_eq!((m.alpha_cutoff, m.decal, m.extrude), (0.25, true, 0));
+        assert!(matches!(m.mip_filter, MipFilterV9::Off));
+        assert_eq!(m.anisotropy, 1);
+    }
+
+    #[test]
+    fn binary_latest_round_trips() {
+        let mut payload = Vec::new();
+        write_section(&mut payload, &sample_v1(), "scene").unwrap();
+        write_section(&mut payload, &vec![sample_tileset_v2()], "tileset").unwrap();
+        let first = encode_bytes(&decode(2, &payload).unwrap());
+        let second = encode_bytes(&decode(super::super::VERSION, &first).unwrap());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert!(decode(0, &[]).is_err());
+        assert!(decode(super::super::VERSION + 1, &[]).is_err());
+    }
+
+    #[test]
+    fn text_latest_round_trips() {
+        let mut payload = Vec::new();
+        write_section(&mut payload, &sample_v1(), "scene").unwrap();
+        write_section(&mut payload, &vec![sample_tileset_v2()], "tileset").unwrap();
+        let (json, images) = encode_text_in_memory(decode(2, &payload).unwrap());
+
+        let read = |path: &str| images.get(path).cloned().ok_or_else(|| format!("missing {path}"));
+        let decoded = decode_text(&json, super::super::VERSION, read).unwrap();
+        let (again, _) = encode_text_in_memory(decoded);
+        assert_eq!(json, again);
+    }
+
+    #[test]
+    fn text_v2_migrates_to_latest() {
+        let scene = serde_json::to_value(sample_v1()).unwrap();
+        let json = serde_json::json!({
+            "format": TEXT_FORMAT_TAG,
+            "version": 2,
+            "scene": scene,
+            "tilesets": [{
+                "name": "tiles",
+                "tile_width": 16,
+                "tile_height": 8,
+                "image": "",
+                "material": {
+                    "filter": "Nearest",
+                    "wrap": "ClampToEdge",
+                    "alpha_mode": "AlphaTest",
+                    "alpha_cutoff": 0.5,
+                    "decal": false
+                }
+            }]
+        }).to_string();
+        let latest = decode_text(&json, super::super::VERSION, |path| Err(format!("unexpected read of {path}"))).unwrap();
+
+        assert_eq!(latest.scene.layers[0].objects[0].faces.len(), 1);
+        let ts = &latest.tilesets[0];
+        assert_eq!((ts.tile_width, ts.tile_height, ts.margin, ts.spacing), (16, 8, 0, 0));
+        assert!(ts.png.is_empty());
+        assert!(matches!(ts.material.wrap, WrapModeV2::ClampToEdge));
+        assert!(ts.indexed.is_none());
+    }
+
+    #[test]
+    fn text_newer_than_supported_is_rejected() {
+        let json = serde_json::json!({ "format": TEXT_FORMAT_TAG, "version": super::super::VERSION + 1 }).to_string();
+        assert!(decode_text(&json, super::super::VERSION, |_| Ok(Vec::new())).is_err());
+    }
+}
@@ -0,0 +1,835 @@
+//! Tiled (.tmx/.tmj) and LDtk (.ldtk) map import.
+//!
+//! Every tile layer becomes a new Cracktile layer of floor quads on the XZ plane: map +X is
+//! world +X, map +Y (down) is world +Z, and one map cell is one grid cell. A layer sits at its
+//! z-order among tile layers (bottom = 0) in grid cells, unless a Tiled layer or group has a
+//! numeric `height` property. Tile flip flags go through `transform_tile_uvs`, like the tilebrush.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use glam::{Vec2, Vec3};
+use serde_json::Value;
+use crate::scene::mesh::Face;
+use crate::tile::{MaterialSettings, Tileset, TilesetSource};
+use crate::tools::draw::transform_tile_uvs;
+use super::{ImportedLayer, ImportedObject, ImportedScene};
+
+const FLIP_H: u32 = 0x8000_0000;
+const FLIP_V: u32 = 0x4000_0000;
+const FLIP_D: u32 = 0x2000_0000;
+const ROTATE_HEX: u32 = 0x1000_0000;
+const GID_MASK: u32 = !(FLIP_H | FLIP_V | FLIP_D | ROTATE_HEX);
+
+/// `Face::new_quad` facing +Y puts the tile's top edge at +Z; two quarter turns put it at -Z so
+/// the floor reads the same from above as the map does in the editor.
+const FLOOR_ROTATION: u8 = 2;
+
+/// A tileset image as described by the map (before loading).
+struct SheetDef {
+    image: PathBuf,
+    /// Color key (`trans` / `transparentcolor`) made fully transparent on load.
+    trans: Option<[u8; 3]>,
+    tile_w: u32,
+    tile_h: u32,
+    margin: u32,
+    spacing: u32,
+    /// Tiles per row; 0 = derive from the image width.
+    columns: u32,
+}
+
+/// A loaded tileset image with the grid needed to locate tiles in it.
+struct Sheet {
+    /// Index into `ImportedScene::tilesets`.
+    tileset: usize,
+    tile_w: u32,
+    tile_h: u32,
+    margin: u32,
+    spacing: u32,
+    columns: u32,
+    image_w: u32,
+    image_h: u32,
+}
+
+impl Sheet {
+    /// Pixel origin of tile `id`.
+    fn tile_origin(&self, id: u32) -> [u32; 2] {
+        let columns = self.columns.max(1);
+        [
+            self.margin + (id % columns) * (self.tile_w + self.spacing),
+            self.margin + (id / columns) * (self.tile_h + self.spacing),
+        ]
+    }
+}
+
+/// Loads each tileset image once, however many maps/tilesets reference it.
+#[derive(Default)]
+struct Sheets {
+    sheets: Vec<Sheet>,
+    tilesets: Vec<Tileset>,
+    by_image: HashMap<PathBuf, usize>,
+}
+
+impl Sheets {
+    /// Load a sheet, returning its index, or None if the image cannot be read.
+    fn load(&mut self, def: SheetDef) -> Option<usize> {
+        let key = def.image.clone();
+        if let Some(&i) = self.by_image.get(&key)
+            && self.sheets[i].tile_w == def.tile_w && self.sheets[i].tile_h == def.tile_h
+        {
+            return Some(i);
+        }
+        let mut img = match image::open(&def.image) {
+            Ok(img) => img.to_rgba8(),
+            Err(e) => {
+                log::warn!("Could not load tileset image {:?}: {e}", def.image);
+                return None;
+            }
+        };
+        if let Some(key) = def.trans {
+            for p in img.pixels_mut().filter(|p| p.0[..3] == key) {
+                p.0[3] = 0;
+            }
+        }
+        let (w, h) = img.dimensions();
+        let columns = if def.columns > 0 {
+            def.columns
+        } else {
+            (w.saturating_sub(2 * def.margin) + def.spacing) / (def.tile_w + def.spacing).max(1)
+        };
+        let name = def.image.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut tileset = Tileset::from_rgba(
+            name, w, h, Some(img.into_raw()), def.tile_w.max(1), def.tile_h.max(1), MaterialSettings::default(),
+        );
+        tileset.margin = def.margin;
+        tileset.spacing = def.spacing;
+        // A color key is applied at import time, so only unkeyed sheets can be reloaded as-is.
+        if def.trans.is_none() {
+            tileset.source = Some(TilesetSource::new(&def.image, Default::default()));
+        }
+        self.tilesets.push(tileset);
+        self.sheets.push(Sheet {
+            tileset: self.tilesets.len() - 1,
+            tile_w: def.tile_w,
+            tile_h: def.tile_h,
+            margin: def.margin,
+            spacing: def.spacing,
+            columns,
+            image_w: w,
+            image_h: h,
+        });
+        self.by_image.insert(key, self.sheets.len() - 1);
+        Some(self.sheets.len() - 1)
+    }
+}
+
+/// One tile to place.
+struct PlacedTile {
+    /// Cell position of the tile's top-left corner (fractional for pixel offsets).
+    cell: Vec2,
+    sheet: usize,
+    /// Pixel origin in the sheet image.
+    src: [u32; 2],
+    flip_h: bool,
+    flip_v: bool,
+    /// Tiled's anti-diagonal flip (swap x and y), applied before the other flips.
+    flip_d: bool,
+}
+
+/// A tile layer, flattened out of any groups.
+struct MapLayer {
+    name: String,
+    visible: bool,
+    /// Height in grid cells.
+    height: f32,
+    /// Edge length of one tile in grid cells.
+    tile_size: f32,
+    tiles: Vec<PlacedTile>,
+}
+
+/// Import a Tiled map (.tmx XML or .tmj JSON).
+pub fn import_tiled(path: &Path, cell_size: f32) -> Result<ImportedScene, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Read failed: {e}"))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let is_json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tmj") || e.eq_ignore_ascii_case("json"));
+    let map = if is_json {
+        let doc: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Parse failed: {e}"))?;
+        TiledMap::from_json(&doc, base_dir)?
+    } else {
+        TiledMap::from_xml(&parse_xml(&content)?, base_dir)?
+    };
+    if map.orientation != "orthogonal" {
+        log::warn!("Tiled map orientation '{}' imported as orthogonal", map.orientation);
+    }
+
+    let mut sheets = Sheets::default();
+    // (firstgid, sheet), sorted so a gid belongs to the last tileset with firstgid <= gid.
+    let mut gid_ranges: Vec<(u32, Option<usize>)> = map.tilesets.into_iter()
+        .map(|(firstgid, def)| (firstgid, def.and_then(|d| sheets.load(d))))
+        .collect();
+    gid_ranges.sort_by_key(|(firstgid, _)| *firstgid);
+
+    let mut layers = Vec::new();
+    let mut z = 0.0;
+    for layer in map.layers {
+        let mut tiles = Vec::new();
+        for chunk in &layer.chunks {
+            for (i, &raw) in chunk.gids.iter().enumerate() {
+                let gid = raw & GID_MASK;
+                if gid == 0 { continue; }
+                let Some(&(firstgid, Some(sheet))) = gid_ranges.iter().rev().find(|(first, _)| *first <= gid) else {
+                    continue;
+                };
+                let (x, y) = (chunk.x + (i as i32 % chunk.width.max(1)), chunk.y + (i as i32 / chunk.width.max(1)));
+                tiles.push(PlacedTile {
+                    cell: Vec2::new(x as f32, y as f32) + layer.offset / Vec2::new(map.tile_w, map.tile_h),
+                    sheet,
+                    src: sheets.sheets[sheet].tile_origin(gid - firstgid),
+                    flip_h: raw & FLIP_H != 0,
+                    flip_v: raw & FLIP_V != 0,
+                    flip_d: raw & FLIP_D != 0,
+                });
+            }
+        }
+        layers.push(MapLayer {
+            name: layer.name,
+            visible: layer.visible,
+            height: layer.height.unwrap_or(z),
+            tile_size: 1.0,
+            tiles,
+        });
+        z += 1.0;
+    }
+
+    build_scene(layers, sheets, cell_size)
+}
+
+/// Import an LDtk project (.ldtk). Levels are placed at their world positions; layers with the
+/// same identifier across levels share one Cracktile layer.
+pub fn import_ldtk(path: &Path, cell_size: f32) -> Result<ImportedScene, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Read failed: {e}"))?;
+    let doc: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Parse failed: {e}"))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let default_grid = doc["defaultGridSize"].as_f64().unwrap_or(16.0).max(1.0) as f32;
+
+    let mut sheets = Sheets::default();
+    let mut sheet_by_uid = HashMap::new();
+    for ts in doc["defs"]["tilesets"].as_array().into_iter().flatten() {
+        let Some(rel) = ts["relPath"].as_str() else {
+            log::warn!("Skipping LDtk tileset '{}' without an image", ts["identifier"].as_str().unwrap_or("?"));
+            continue;
+        };
+        let grid = json_u32(&ts["tileGridSize"]);
+        let def = SheetDef {
+            image: base_dir.join(rel),
+            trans: None,
+            tile_w: grid,
+            tile_h: grid,
+            margin: json_u32(&ts["padding"]),
+            spacing: json_u32(&ts["spacing"]),
+            columns: json_u32(&ts["__cWid"]),
+        };
+        if let Some(sheet) = sheets.load(def) {
+            sheet_by_uid.insert(ts["uid"].as_i64().unwrap_or(-1), sheet);
+        }
+    }
+
+    // Z-order from the layer definitions (listed top-most first), counting only layers that
+    // can hold tiles.
+    let tile_layer_defs: Vec<&str> = doc["defs"]["layers"].as_array().into_iter().flatten()
+        .filter(|l| l["__type"] == "Tiles" || !l["tilesetDefUid"].is_null() || !l["autoTilesetDefUid"].is_null())
+        .filter_map(|l| l["identifier"].as_str())
+        .collect();
+    let z_of = |identifier: &str| {
+        tile_layer_defs.iter().position(|&l| l == identifier)
+            .map_or(0.0, |i| (tile_layer_defs.len() - 1 - i) as f32)
+    };
+
+    let mut layers: Vec<MapLayer> = Vec::new();
+    for level in doc["levels"].as_array().into_iter().flatten() {
+        let external;
+        let instances = match level["layerInstances"].as_array() {
+            Some(instances) => instances,
+            None => {
+                let rel = level["externalRelPath"].as_str().ok_or("LDtk level has no layer data")?;
+                let level_path = base_dir.join(rel);
+                let data = fs::read_to_string(&level_path)
+                    .map_err(|e| format!("Read {:?} failed: {e}", level_path))?;
+                external = serde_json::from_str::<Value>(&data)
+                    .map_err(|e| format!("Parse {:?} failed: {e}", level_path))?;
+                external["layerInstances"].as_array().ok_or("LDtk level has no layer data")?
+            }
+        };
+        let world = Vec2::new(level["worldX"].as_f64().unwrap_or(0.0) as f32, level["worldY"].as_f64().unwrap_or(0.0) as f32);
+
+        for li in instances {
+            let uid = li["overrideTilesetUid"].as_i64().or_else(|| li["__tilesetDefUid"].as_i64());
+            let Some(&sheet) = uid.and_then(|uid| sheet_by_uid.get(&uid)) else { continue };
+            let identifier = li["__identifier"].as_str().unwrap_or("Layer");
+            let grid = li["__gridSize"].as_f64().unwrap_or(default_grid as f64) as f32;
+            let offset = world + Vec2::new(
+                li["pxTotalOffsetX"].as_f64().unwrap_or(0.0) as f32,
+                li["pxTotalOffsetY"].as_f64().unwrap_or(0.0) as f32,
+            );
+
+            let index = match layers.iter().position(|l| l.name == identifier) {
+                Some(i) => i,
+                None => {
+                    layers.push(MapLayer {
+                        name: identifier.to_string(),
+                        visible: false,
+                        height: z_of(identifier),
+                        tile_size: grid / default_grid,
+                        tiles: Vec::new(),
+                    });
+                    layers.len() - 1
+                }
+            };
+            let layer = &mut layers[index];
+            layer.visible |= li["visible"].as_bool().unwrap_or(true);
+
+            let tiles = li["gridTiles"].as_array().into_iter().flatten()
+                .chain(li["autoLayerTiles"].as_array().into_iter().flatten());
+            for tile in tiles {
+                let px = Vec2::new(tile["px"][0].as_f64().unwrap_or(0.0) as f32, tile["px"][1].as_f64().unwrap_or(0.0) as f32);
+                let flags = tile["f"].as_u64().unwrap_or(0);
+                layer.tiles.push(PlacedTile {
+                    cell: (offset + px) / default_grid,
+                    sheet,
+                    src: [json_u32(&tile["src"][0]), json_u32(&tile["src"][1])],
+                    flip_h: flags & 1 != 0,
+                    flip_v: flags & 2 != 0,
+                    flip_d: false,
+                });
+            }
+        }
+    }
+    // Bottom layer first, as in the Tiled importer.
+    layers.sort_by(|a, b| a.height.total_cmp(&b.height));
+
+    build_scene(layers, sheets, cell_size)
+}
+
+/// Turn flattened map layers into imported layers, with one object per (layer, tileset).
+fn build_scene(layers: Vec<MapLayer>, sheets: Sheets, cell_size: f32) -> Result<ImportedScene, String> {
+    let mut result = ImportedScene::default();
+    for layer in layers.into_iter().filter(|l| !l.tiles.is_empty()) {
+        let layer_index = result.layers.len();
+        let mut by_tileset: Vec<(usize, Vec<Face>)> = Vec::new();
+        for tile in &layer.tiles {
+            let sheet = &sheets.sheets[tile.sheet];
+            let face = tile_face(tile, sheet, layer.height, layer.tile_size, cell_size);
+            match by_tileset.iter_mut().find(|(ts, _)| *ts == sheet.tileset) {
+                Some((_, faces)) => faces.push(face),
+                None => by_tileset.push((sheet.tileset, vec![face])),
+            }
+        }
+        for (tileset, faces) in by_tileset {
+            result.objects.push(ImportedObject {
+                name: Some(layer.name.clone()),
+                faces,
+                tileset: Some(tileset),
+                layer: Some(layer_index),
+            });
+        }
+        result.layers.push(ImportedLayer { name: layer.name, visible: layer.visible });
+    }
+    if result.objects.is_empty() {
+        return Err("No tiles found in map".to_string());
+    }
+    result.tilesets = sheets.tilesets;
+    Ok(result)
+}
+
+/// Floor quad for one tile.
+fn tile_face(tile: &PlacedTile, sheet: &Sheet, height: f32, tile_size: f32, cell_size: f32) -> Face {
+    let half = tile_size * cell_size * 0.5;
+    let corner = tile.cell * cell_size;
+    let center = Vec3::new(corner.x + half, height * cell_size, corner.y + half);
+
+    let (w, h) = (sheet.image_w.max(1) as f32, sheet.image_h.max(1) as f32);
+    let u0 = tile.src[0] as f32 / w;
+    let v0 = tile.src[1] as f32 / h;
+    let u1 = (tile.src[0] + sheet.tile_w) as f32 / w;
+    let v1 = (tile.src[1] + sheet.tile_h) as f32 / h;
+    let uvs = [Vec2::new(u0, v1), Vec2::new(u1, v1), Vec2::new(u1, v0), Vec2::new(u0, v0)];
+
+    // A diagonal flip (transpose) is a clockwise quarter turn (three UV cycles) followed by a
+    // horizontal flip; the map's own horizontal/vertical flips then apply on top.
+    let rotation = (FLOOR_ROTATION + if tile.flip_d { 3 } else { 0 }) % 4;
+    let uvs = transform_tile_uvs(uvs, rotation, tile.flip_h ^ tile.flip_d, tile.flip_v);
+    Face::new_quad(center, Vec3::Y, half, uvs)
+}
+
+fn json_u32(v: &Value) -> u32 {
+    v.as_u64().unwrap_or(0) as u32
+}
+
+// --- Tiled ---
+
+/// Tile data for a rectangle of cells (the whole layer, or one chunk of an infinite map).
+struct TiledChunk {
+    x: i32,
+    y: i32,
+    width: i32,
+    gids: Vec<u32>,
+}
+
+struct TiledLayer {
+    name: String,
+    visible: bool,
+    /// `height` custom property (grid cells), inherited from enclosing groups.
+    height: Option<f32>,
+    /// Pixel offset, including enclosing groups.
+    offset: Vec2,
+    chunks: Vec<TiledChunk>,
+}
+
+struct TiledMap {
+    orientation: String,
+    tile_w: f32,
+    tile_h: f32,
+    /// (firstgid, image tileset; None for image-collection tilesets)
+    tilesets: Vec<(u32, Option<SheetDef>)>,
+    layers: Vec<TiledLayer>,
+}
+
+/// Group state inherited by nested layers.
+#[derive(Clone)]
+struct TiledGroup {
+    prefix: String,
+    visible: bool,
+    height: Option<f32>,
+    offset: Vec2,
+}
+
+impl TiledGroup {
+    fn root() -> Self {
+        Self { prefix: String::new(), visible: true, height: None, offset: Vec2::ZERO }
+    }
+
+    fn nest(&self, name: &str, visible: bool, height: Option<f32>, offset: Vec2) -> Self {
+        Self {
+            prefix: format!("{}{name}/", self.prefix),
+            visible: self.visible && visible,
+            height: height.or(self.height),
+            offset: self.offset + offset,
+        }
+    }
+}
+
+impl TiledMap {
+    fn from_xml(map: &XmlNode, base_dir: &Path) -> Result<Self, String> {
+        if map.name != "map" {
+            return Err("Not a Tiled map (missing <map>)".to_string());
+        }
+        let mut tilesets = Vec::new();
+        for ts in map.children.iter().filter(|c| c.name == "tileset") {
+            let firstgid = ts.attr_u32("firstgid");
+            tilesets.push((firstgid, tileset_from_xml_ref(ts, base_dir)?));
+        }
+        let mut layers = Vec::new();
+        collect_xml_layers(map, &TiledGroup::root(), map.attr_u32("width") as i32, &mut layers)?;
+        Ok(Self {
+            orientation: map.attr("orientation").unwrap_or("orthogonal").to_string(),
+            tile_w: map.attr_u32("tilewidth").max(1) as f32,
+            tile_h: map.attr_u32("tileheight").max(1) as f32,
+            tilesets,
+            layers,
+        })
+    }
+
+    fn from_json(map: &Value, base_dir: &Path) -> Result<Self, String> {
+        if map["type"] != "map" && map["layers"].is_null() {
+            return Err("Not a Tiled map".to_string());
+        }
+        let mut tilesets = Vec::new();
+        for ts in map["tilesets"].as_array().into_iter().flatten() {
+            let firstgid = json_u32(&ts["firstgid"]);
+            tilesets.push((firstgid, tileset_from_json_ref(ts, base_dir)?));
+        }
+        let mut layers = Vec::new();
+        collect_json_layers(map, &TiledGroup::root(), json_u32(&map["width"]) as i32, &mut layers)?;
+        Ok(Self {
+            orientation: map["orientation"].as_str().unwrap_or("orthogonal").to_string(),
+            tile_w: json_u32(&map["tilewidth"]).max(1) as f32,
+            tile_h: json_u32(&map["tileheight"]).max(1) as f32,
+            tilesets,
+            layers,
+        })
+    }
+}
+
+/// A `<tileset>` in a map: inline, or a reference to an external .tsx/.tsj file.
+fn tileset_from_xml_ref(ts: &XmlNode, base_dir: &Path) -> Result<Option<SheetDef>, String> {
+    let Some(source) = ts.attr("source") else {
+        return Ok(tileset_from_xml(ts, base_dir));
+    };
+    let path = base_dir.join(source);
+    let dir = path.parent().unwrap_or(base_dir).to_path_buf();
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Read {:?} failed: {e}", path))?;
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsj") || e.eq_ignore_ascii_case("json")) {
+        let doc: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Parse {:?} failed: {e}", path))?;
+        Ok(tileset_from_json(&doc, &dir))
+    } else {
+        Ok(tileset_from_xml(&parse_xml(&content)?, &dir))
+    }
+}
+
+fn tileset_from_xml(ts: &XmlNode, base_dir: &Path) -> Option<SheetDef> {
+    let Some(image) = ts.child("image") else {
+        log::warn!("Skipping Tiled image-collection tileset '{}'", ts.attr("name").unwrap_or("?"));
+        return None;
+    };
+    Some(SheetDef {
+        image: base_dir.join(image.attr("source")?),
+        trans: image.attr("trans").and_then(parse_hex_color),
+        tile_w: ts.attr_u32("tilewidth"),
+        tile_h: ts.attr_u32("tileheight"),
+        margin: ts.attr_u32("margin"),
+        spacing: ts.attr_u32("spacing"),
+        columns: ts.attr_u32("columns"),
+    })
+}
+
+fn tileset_from_json_ref(ts: &Value, base_dir: &Path) -> Result<Option<SheetDef>, String> {
+    let Some(source) = ts["source"].as_str() else {
+        return Ok(tileset_from_json(ts, base_dir));
+    };
+    let path = base_dir.join(source);
+    let dir = path.parent().unwrap_or(base_dir).to_path_buf();
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Read {:?} failed: {e}", path))?;
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsx")) {
+        Ok(tileset_from_xml(&parse_xml(&content)?, &dir))
+    } else {
+        let doc: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Parse {:?} failed: {e}", path))?;
+        Ok(tileset_from_json(&doc, &dir))
+    }
+}
+
+fn tileset_from_json(ts: &Value, base_dir: &Path) -> Option<SheetDef> {
+    let Some(image) = ts["image"].as_str() else {
+        log::warn!("Skipping Tiled image-collection tileset '{}'", ts["name"].as_str().unwrap_or("?"));
+        return None;
+    };
+    Some(SheetDef {
+        image: base_dir.join(image),
+        trans: ts["transparentcolor"].as_str().and_then(parse_hex_color),
+        tile_w: json_u32(&ts["tilewidth"]),
+        tile_h: json_u32(&ts["tileheight"]),
+        margin: json_u32(&ts["margin"]),
+        spacing: json_u32(&ts["spacing"]),
+        columns: json_u32(&ts["columns"]),
+    })
+}
+
+/// Parse `#rrggbb` or `rrggbb`.
+fn parse_hex_color(s: &str) -> Option<[u8; 3]> {
+    let s = s.trim_start_matches('#');
+    let s = if s.len() == 8 { &s[2..] } else { s };
+    if s.len() != 6 { return None; }
+    let byte = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?])
+}
+
+fn collect_xml_layers(parent: &XmlNode, group: &TiledGroup, map_width: i32, out: &mut Vec<TiledLayer>) -> Result<(), String> {
+    for node in &parent.children {
+        let name = node.attr("name").unwrap_or("Layer");
+        let visible = node.attr("visible") != Some("0");
+        let offset = Vec2::new(node.attr_f32("offsetx"), node.attr_f32("offsety"));
+        let height = xml_height_property(node);
+        match node.name.as_str() {
+            "group" => {
+                collect_xml_layers(node, &group.nest(name, visible, height, offset), map_width, out)?;
+            }
+            "layer" => {
+                let Some(data) = node.child("data") else { continue };
+                let width = match node.attr_u32("width") { 0 => map_width, w => w as i32 };
+                let chunks = if data.children.iter().any(|c| c.name == "chunk") {
+                    data.children.iter()
+                        .filter(|c| c.name == "chunk")
+                        .map(|c| Ok(TiledChunk {
+                            x: c.attr_i32("x"),
+                            y: c.attr_i32("y"),
+                            width: c.attr_u32("width") as i32,
+                            gids: xml_data_gids(data, c)?,
+                        }))
+                        .collect::<Result<_, String>>()?
+                } else {
+                    vec![TiledChunk { x: 0, y: 0, width, gids: xml_data_gids(data, data)? }]
+                };
+                let layer = group.nest(name, visible, height, offset);
+                out.push(TiledLayer {
+                    name: format!("{}{name}", group.prefix),
+                    visible: layer.visible,
+                    height: layer.height,
+                    offset: layer.offset,
+                    chunks,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn xml_height_property(node: &XmlNode) -> Option<f32> {
+    node.child("properties")?.children.iter()
+        .find(|p| p.name == "property" && p.attr("name") == Some("height"))
+        .and_then(|p| p.attr("value").or(Some(p.text.trim())))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Gids of a `<data>` element (or one of its `<chunk>`s, which share the encoding).
+fn xml_data_gids(data: &XmlNode, content: &XmlNode) -> Result<Vec<u32>, String> {
+    match data.attr("encoding") {
+        None => Ok(content.children.iter()
+            .filter(|c| c.name == "tile")
+            .map(|t| t.attr("gid").and_then(|g| g.parse().ok()).unwrap_or(0))
+            .collect()),
+        Some("csv") => parse_csv_gids(&content.text),
+        Some("base64") => decode_gids(&content.text, data.attr("compression")),
+        Some(other) => Err(format!("Unsupported Tiled layer encoding '{other}'")),
+    }
+}
+
+fn collect_json_layers(parent: &Value, group: &TiledGroup, map_width: i32, out: &mut Vec<TiledLayer>) -> Result<(), String> {
+    for layer in parent["layers"].as_array().into_iter().flatten() {
+        let name = layer["name"].as_str().unwrap_or("Layer");
+        let visible = layer["visible"].as_bool().unwrap_or(true);
+        let offset = Vec2::new(
+            layer["offsetx"].as_f64().unwrap_or(0.0) as f32,
+            layer["offsety"].as_f64().unwrap_or(0.0) as f32,
+        );
+        let height = layer["properties"].as_array().into_iter().flatten()
+            .find(|p| p["name"] == "height")
+            .and_then(|p| p["value"].as_f64().map(|v| v as f32).or_else(|| p["value"].as_str()?.trim().parse().ok()));
+        match layer["type"].as_str() {
+            Some("group") => {
+                collect_json_layers(layer, &group.nest(name, visible, height, offset), map_width, out)?;
+            }
+            Some("tilelayer") => {
+                let compression = layer["compression"].as_str().filter(|c| !c.is_empty());
+                let base64 = layer["encoding"] == "base64";
+                let gids = |data: &Value| -> Result<Vec<u32>, String> {
+                    match data {
+                        Value::String(s) if base64 => decode_gids(s, compression),
+                        Value::Array(a) => Ok(a.iter().map(|g| g.as_u64().unwrap_or(0) as u32).collect()),
+                        _ => Ok(Vec::new()),
+                    }
+                };
+                let chunks = match layer["chunks"].as_array() {
+                    Some(chunks) => chunks.iter()
+                        .map(|c| Ok(TiledChunk {
+                            x: c["x"].as_i64().unwrap_or(0) as i32,
+                            y: c["y"].as_i64().unwrap_or(0) as i32,
+                            width: json_u32(&c["width"]) as i32,
+                            gids: gids(&c["data"])?,
+                        }))
+                        .collect::<Result<_, String>>()?,
+                    None => {
+                        let width = match json_u32(&layer["width"]) { 0 => map_width, w => w as i32 };
+                        vec![TiledChunk { x: 0, y: 0, width, gids: gids(&layer["data"])? }]
+                    }
+                };
+                let nested = group.nest(name, visible, height, offset);
+                out.push(TiledLayer {
+                    name: format!("{}{name}", group.prefix),
+                    visible: nested.visible,
+                    height: nested.height,
+                    offset: nested.offset,
+                    chunks,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_csv_gids(text: &str) -> Result<Vec<u32>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|e| format!("Invalid tile id '{s}': {e}")))
+        .collect()
+}
+
+/// Decode base64 layer data, optionally zlib/gzip compressed, into little-endian gids.
+fn decode_gids(text: &str, compression: Option<&str>) -> Result<Vec<u32>, String> {
+    let raw = super::gltf_import::decode_base64(text.trim())?;
+    let bytes = match compression {
+        None => raw,
+        Some("zlib") => miniz_oxide::inflate::decompress_to_vec_zlib(&raw)
+            .map_err(|e| format!("zlib decompression failed: {e:?}"))?,
+        Some("gzip") => miniz_oxide::inflate::decompress_to_vec(gzip_payload(&raw)?)
+            .map_err(|e| format!("gzip decompression failed: {e:?}"))?,
+        Some(other) => return Err(format!("Unsupported Tiled layer compression '{other}'")),
+    };
+    Ok(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+/// Skip a gzip member header, returning the raw deflate stream.
+fn gzip_payload(data: &[u8]) -> Result<&[u8], String> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+    if data.len() < 10 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return Err("Invalid gzip header".to_string());
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2).ok_or("Truncated gzip header")?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            pos += data.get(pos..).and_then(|d| d.iter().position(|&b| b == 0)).ok_or("Truncated gzip header")? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    data.get(pos..).ok_or_else(|| "Truncated gzip header".to_string())
+}
+
+// --- Minimal XML ---
+
+/// An XML element: enough of the format for Tiled's .tmx/.tsx files.
+struct XmlNode {
+    name: String,
+    attrs: HashMap<String, String>,
+    children: Vec<XmlNode>,
+    text: String,
+}
+
+impl XmlNode {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), attrs: HashMap::new(), children: Vec::new(), text: String::new() }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+
+    fn attr_u32(&self, name: &str) -> u32 {
+        self.attr(name).and_then(|v| v.trim().parse().ok()).unwrap_or(0)
+    }
+
+    fn attr_i32(&self, name: &str) -> i32 {
+        self.attr(name).and_then(|v| v.trim().parse().ok()).unwrap_or(0)
+    }
+
+    fn attr_f32(&self, name: &str) -> f32 {
+        self.attr(name).and_then(|v| v.trim().parse().ok()).unwrap_or(0.0)
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.name == name)
+    }
+}
+
+/// Parse an XML document into its root element.
+fn parse_xml(src: &str) -> Result<XmlNode, String> {
+    let mut stack = vec![XmlNode::new("")];
+    let mut rest = src;
+    while let Some(lt) = rest.find('<') {
+        let text = &rest[..lt];
+        rest = &rest[lt..];
+        let top = stack.last_mut().ok_or("Malformed XML")?;
+        top.text.push_str(&xml_unescape(text));
+
+        if let Some(body) = rest.strip_prefix("<!--") {
+            let end = body.find("-->").ok_or("Unterminated XML comment")?;
+            rest = &body[end + 3..];
+        } else if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let end = body.find("]]>").ok_or("Unterminated CDATA section")?;
+            top.text.push_str(&body[..end]);
+            rest = &body[end + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest.find('>').ok_or("Malformed XML declaration")?;
+            rest = &rest[end + 1..];
+        } else {
+            let end = tag_end(rest).ok_or("Unterminated XML tag")?;
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            if let Some(name) = tag.strip_prefix('/') {
+                let node = stack.pop().ok_or("Malformed XML")?;
+                if node.name != name.trim() {
+                    return Err(format!("Mismatched XML tag </{}> (expected </{}>)", name.trim(), node.name));
+                }
+                stack.last_mut().ok_or("Malformed XML")?.children.push(node);
+            } else {
+                let (tag, self_closing) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let node = parse_start_tag(tag)?;
+                if self_closing {
+                    top.children.push(node);
+                } else {
+                    stack.push(node);
+                }
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err("Unclosed XML element".to_string());
+    }
+    stack.pop().and_then(|doc| doc.children.into_iter().next()).ok_or_else(|| "Empty XML document".to_string())
+}
+
+/// Index of the `>` closing the tag at the start of `s`, skipping quoted attribute values.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_start_tag(tag: &str) -> Result<XmlNode, String> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let mut node = XmlNode::new(&tag[..name_end]);
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or_else(|| format!("Malformed attribute in <{}>", node.name))?;
+        let key = rest[..eq].trim().to_string();
+        let value_part = rest[eq + 1..].trim_start();
+        let quote = value_part.chars().next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("Unquoted attribute in <{}>", node.name))?;
+        let close = value_part[1..].find(quote).ok_or_else(|| format!("Unterminated attribute in <{}>", node.name))?;
+        node.attrs.insert(key, xml_unescape(&value_part[1..1 + close]));
+        rest = value_part[close + 2..].trim_start();
+    }
+    Ok(node)
+}
+
+fn xml_unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,551 @@
+//! MagicaVoxel (.vox) import and export.
+//!
+//! MagicaVoxel is Z-up; a voxel at (x, y, z) maps to Cracktile (x, z, -y), scaled by the grid
+//! cell size. Import emits only the exposed faces of each model and UV-maps them onto a
+//! generated 16x16 palette tileset (one pixel per palette entry). Export voxelizes axis-aligned
+//! faces: every grid cell directly behind a face becomes a voxel.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use glam::{IVec3, Mat3, Vec2, Vec3, Vec4};
+use crate::scene::Scene;
+use crate::scene::mesh::Face;
+use crate::tile::{AlphaMode, MaterialSettings, Tileset};
+use super::{ImportedObject, ImportedScene};
+
+const VOX_VERSION: i32 = 150;
+/// Largest model MagicaVoxel accepts, per axis.
+const MAX_MODEL_SIZE: i32 = 256;
+
+struct VoxModel {
+    size: IVec3,
+    /// (x, y, z, palette index 1-255)
+    voxels: Vec<[u8; 4]>,
+}
+
+/// A shape placed by the scene graph: model index, world transform, optional name.
+struct Placement {
+    model: usize,
+    rotation: Mat3,
+    translation: Vec3,
+    name: Option<String>,
+}
+
+/// Scene graph node (only the parts needed to place models).
+enum Node {
+    Transform { child: i32, rotation: Mat3, translation: Vec3, name: Option<String> },
+    Group { children: Vec<i32> },
+    Shape { models: Vec<usize> },
+}
+
+/// Import a .vox file. Each model becomes one object; `cell_size` is the world size of a voxel.
+pub fn import_vox(path: &Path, cell_size: f32) -> Result<ImportedScene, String> {
+    let data = fs::read(path)
+        .map_err(|e| format!("Read failed: {e}"))?;
+    if data.len() < 8 || &data[0..4] != b"VOX " {
+        return Err("Not a MagicaVoxel file (bad magic)".to_string());
+    }
+
+    let mut models = Vec::new();
+    let mut pending_size = None;
+    let mut palette = default_palette();
+    let mut nodes: HashMap<i32, Node> = HashMap::new();
+
+    let mut reader = Reader { data: &data, pos: 8 };
+    // MAIN wraps everything; its children follow its (empty) content directly.
+    let (main_id, main_content, _) = reader.chunk_header()?;
+    if &main_id != b"MAIN" {
+        return Err("Missing MAIN chunk".to_string());
+    }
+    reader.skip(main_content)?;
+
+    while reader.pos < data.len() {
+        let (id, content_len, children_len) = reader.chunk_header()?;
+        let mut chunk = Reader { data: reader.bytes(content_len)?, pos: 0 };
+        reader.skip(children_len)?;
+        match &id {
+            b"SIZE" => {
+                pending_size = Some(IVec3::new(chunk.i32()?, chunk.i32()?, chunk.i32()?));
+            }
+            b"XYZI" => {
+                let size = pending_size.take().ok_or("XYZI chunk without SIZE")?;
+                let count = chunk.i32()?.max(0) as usize;
+                let voxels = chunk.bytes(count * 4)?
+                    .chunks_exact(4)
+                    .map(|v| [v[0], v[1], v[2], v[3]])
+                    .collect();
+                models.push(VoxModel { size, voxels });
+            }
+            b"RGBA" => {
+                // Entry i holds the color of palette index i + 1.
+                for i in 0..255 {
+                    let c = chunk.bytes(4)?;
+                    palette[i + 1] = [c[0], c[1], c[2], c[3]];
+                }
+            }
+            b"nTRN" => {
+                let id = chunk.i32()?;
+                let attrs = chunk.dict()?;
+                let child = chunk.i32()?;
+                chunk.i32()?; // reserved
+                chunk.i32()?; // layer
+                let frames = chunk.i32()?;
+                let frame = if frames > 0 { chunk.dict()? } else { HashMap::new() };
+                nodes.insert(id, Node::Transform {
+                    child,
+                    rotation: frame.get("_r").and_then(|r| r.parse().ok()).map(decode_rotation).unwrap_or(Mat3::IDENTITY),
+                    translation: frame.get("_t").map(|t| parse_translation(t)).unwrap_or(Vec3::ZERO),
+                    name: attrs.get("_name").cloned(),
+                });
+            }
+            b"nGRP" => {
+                let id = chunk.i32()?;
+                chunk.dict()?;
+                let count = chunk.i32()?.max(0);
+                let children = (0..count).map(|_| chunk.i32()).collect::<Result<_, _>>()?;
+                nodes.insert(id, Node::Group { children });
+            }
+            b"nSHP" => {
+                let id = chunk.i32()?;
+                chunk.dict()?;
+                let count = chunk.i32()?.max(0);
+                let mut shape_models = Vec::new();
+                for _ in 0..count {
+                    shape_models.push(chunk.i32()?.max(0) as usize);
+                    chunk.dict()?;
+                }
+                nodes.insert(id, Node::Shape { models: shape_models });
+            }
+            _ => {}
+        }
+    }
+
+    if models.is_empty() {
+        return Err("No voxel models found in .vox file".to_string());
+    }
+
+    // Files without a scene graph (pre-0.99) place every model at the origin.
+    let mut placements = Vec::new();
+    if nodes.contains_key(&0) {
+        collect_placements(&nodes, 0, Mat3::IDENTITY, Vec3::ZERO, None, 0, &mut placements);
+    }
+    if placements.is_empty() {
+        placements = (0..models.len())
+            .map(|model| Placement { model, rotation: Mat3::IDENTITY, translation: Vec3::ZERO, name: None })
+            .collect();
+    }
+
+    let palette_tileset = palette_tileset(&palette);
+    let mut result = ImportedScene { tilesets: vec![palette_tileset], ..Default::default() };
+    for (i, placement) in placements.iter().enumerate() {
+        let Some(model) = models.get(placement.model) else { continue };
+        let faces = model_faces(model, placement, cell_size, &result.tilesets[0]);
+        if faces.is_empty() { continue; }
+        result.objects.push(ImportedObject {
+            name: Some(placement.name.clone().unwrap_or_else(|| format!("vox_model_{i}"))),
+            faces,
+            tileset: Some(0),
+            layer: None,
+        });
+    }
+    if result.objects.is_empty() {
+        return Err("No voxels found in .vox file".to_string());
+    }
+    Ok(result)
+}
+
+/// Walk the scene graph from `id`, accumulating transforms down to shape nodes.
+fn collect_placements(
+    nodes: &HashMap<i32, Node>,
+    id: i32,
+    rotation: Mat3,
+    translation: Vec3,
+    name: Option<String>,
+    depth: usize,
+    out: &mut Vec<Placement>,
+) {
+    if depth > 64 { return; }
+    match nodes.get(&id) {
+        Some(Node::Transform { child, rotation: r, translation: t, name: n }) => {
+            let name = n.clone().or(name);
+            collect_placements(nodes, *child, rotation * *r, translation + rotation * *t, name, depth + 1, out);
+        }
+        Some(Node::Group { children }) => {
+            for &child in children {
+                collect_placements(nodes, child, rotation, translation, name.clone(), depth + 1, out);
+            }
+        }
+        Some(Node::Shape { models }) => {
+            for &model in models {
+                out.push(Placement { model, rotation, translation, name: name.clone() });
+            }
+        }
+        None => {}
+    }
+}
+
+/// Exposed faces of one model, in Cracktile space.
+fn model_faces(model: &VoxModel, placement: &Placement, cell_size: f32, palette: &Tileset) -> Vec<Face> {
+    let occupied: std::collections::HashSet<[u8; 3]> = model.voxels.iter().map(|v| [v[0], v[1], v[2]]).collect();
+    // MagicaVoxel pivots a model around floor(size / 2).
+    let pivot = (model.size / 2).as_vec3();
+    let half = cell_size * 0.5;
+
+    let mut faces = Vec::new();
+    for v in &model.voxels {
+        let (col, row) = (v[3] as u32 % 16, v[3] as u32 / 16);
+        let uvs = palette_uvs(palette, col, row);
+        for dir in [IVec3::X, IVec3::NEG_X, IVec3::Y, IVec3::NEG_Y, IVec3::Z, IVec3::NEG_Z] {
+            let n = IVec3::new(v[0] as i32, v[1] as i32, v[2] as i32) + dir;
+            let inside = n.cmpge(IVec3::ZERO).all() && n.cmplt(IVec3::splat(256)).all();
+            if inside && occupied.contains(&[n.x as u8, n.y as u8, n.z as u8]) { continue; }
+
+            let local = Vec3::new(v[0] as f32, v[1] as f32, v[2] as f32) + 0.5 - pivot;
+            let center = vox_to_world(placement.translation + placement.rotation * local) * cell_size;
+            let normal = vox_to_world(placement.rotation * dir.as_vec3()).round();
+            faces.push(Face::new_quad(center + normal * half, normal, half, uvs));
+        }
+    }
+    faces
+}
+
+/// UVs for a palette cell, inset to its center so sampling never bleeds into neighbours.
+fn palette_uvs(palette: &Tileset, col: u32, row: u32) -> [Vec2; 4] {
+    let uvs = palette.tile_uvs(col, row);
+    let center = (uvs[0] + uvs[2]) * 0.5;
+    uvs.map(|uv| center + (uv - center) * 0.5)
+}
+
+/// MagicaVoxel (Z-up) to Cracktile (Y-up).
+fn vox_to_world(v: Vec3) -> Vec3 {
+    Vec3::new(v.x, v.z, -v.y)
+}
+
+/// Cracktile (Y-up) grid cell to MagicaVoxel (Z-up) voxel. The negated axis shifts by one since
+/// a cell is indexed by its minimum corner.
+fn cell_to_vox(c: IVec3) -> IVec3 {
+    IVec3::new(c.x, -c.z - 1, c.y)
+}
+
+/// Decode the packed `_r` rotation byte of an nTRN frame.
+fn decode_rotation(r: u8) -> Mat3 {
+    let first = (r & 3) as usize;
+    let second = ((r >> 2) & 3) as usize;
+    let third = 3usize.saturating_sub(first + second);
+    let mut rows = [Vec3::ZERO; 3];
+    for (row, (index, sign_bit)) in rows.iter_mut().zip([(first, 4), (second, 5), (third, 6)]) {
+        let sign = if r & (1 << sign_bit) != 0 { -1.0 } else { 1.0 };
+        row[index.min(2)] = sign;
+    }
+    Mat3::from_cols(rows[0], rows[1], rows[2]).transpose()
+}
+
+fn parse_translation(s: &str) -> Vec3 {
+    let v: Vec<f32> = s.split_whitespace().filter_map(|t| t.parse().ok()).collect();
+    if v.len() == 3 { Vec3::new(v[0], v[1], v[2]) } else { Vec3::ZERO }
+}
+
+/// 16x16 palette image (index = row * 16 + col) as a tileset of 1-pixel tiles.
+fn palette_tileset(palette: &[[u8; 4]; 256]) -> Tileset {
+    let pixels: Vec<u8> = palette.iter().flatten().copied().collect();
+    let material = MaterialSettings { alpha_mode: AlphaMode::Opaque, ..Default::default() };
+    Tileset::from_rgba("vox_palette".to_string(), 16, 16, Some(pixels), 1, 1, material)
+}
+
+/// MagicaVoxel's built-in palette, used when a file has no RGBA chunk.
+fn default_palette() -> [[u8; 4]; 256] {
+    // The default palette is a 6x6x6 color cube followed by ramps; a plain cube is close enough
+    // for files that rely on it, which is rare since the editor always writes RGBA.
+    let mut palette = [[0, 0, 0, 0]; 256];
+    for (i, entry) in palette.iter_mut().enumerate().skip(1) {
+        let i = (i - 1) as u32;
+        let channel = |d: u32| (255 - (i / d % 6) * 51) as u8;
+        *entry = [channel(1), channel(6), channel(36), 255];
+    }
+    palette
+}
+
+/// Export the scene as a single MagicaVoxel model, voxelizing axis-aligned faces at
+/// `scene.grid_cell_size`. Non-axis-aligned faces are skipped.
+pub fn export_vox(scene: &Scene, path: &Path) -> Result<(), String> {
+    let cell = scene.grid_cell_size;
+    let export = super::ExportTilesets::new(scene);
+    let mut voxels: HashMap<IVec3, [u8; 4]> = HashMap::new();
+    let mut skipped = 0;
+
+    for layer in scene.layers.iter().filter(|l| l.visible) {
+        for object in &layer.objects {
+            let faces = export.faces(layer, object);
+            let transforms = std::iter::once(glam::Mat4::IDENTITY)
+                .chain(object.instances.iter().map(|i| i.model_matrix()));
+            for m in transforms {
+                for face in &faces {
+                    let positions = face.positions.map(|p| m.transform_point3(p));
+                    let normal = (positions[1] - positions[0]).cross(positions[3] - positions[0]).normalize_or_zero();
+                    let axis = dominant_axis(normal);
+                    if normal[axis].abs() < 0.999 {
+                        skipped += 1;
+                        continue;
+                    }
+                    let tileset = face.tileset.map(|i| &export.tilesets()[i]);
+                    let color = face_color(face, tileset);
+                    let min = positions.iter().fold(Vec3::MAX, |a, &p| a.min(p));
+                    let max = positions.iter().fold(Vec3::MIN, |a, &p| a.max(p));
+                    // Cells whose center lies inside the face rectangle, one cell behind the face.
+                    let lo = ((min / cell) - 0.5).ceil().as_ivec3();
+                    let hi = ((max / cell) - 0.5).floor().as_ivec3();
+                    let plane = (positions[0][axis] / cell).round() as i32;
+                    let behind = if normal[axis] > 0.0 { plane - 1 } else { plane };
+                    for x in lo.x..=hi.x.max(lo.x) {
+                        for y in lo.y..=hi.y.max(lo.y) {
+                            for z in lo.z..=hi.z.max(lo.z) {
+                                let mut c = IVec3::new(x, y, z);
+                                c[axis] = behind;
+                                voxels.entry(c).or_insert(color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if skipped > 0 {
+        log::warn!("Skipped {skipped} non-axis-aligned face(s) during .vox export");
+    }
+    if voxels.is_empty() {
+        return Err("No axis-aligned geometry to voxelize".to_string());
+    }
+
+    let vox_voxels: Vec<(IVec3, [u8; 4])> = voxels.into_iter().map(|(c, col)| (cell_to_vox(c), col)).collect();
+    let min = vox_voxels.iter().fold(IVec3::MAX, |a, (c, _)| a.min(*c));
+    let max = vox_voxels.iter().fold(IVec3::MIN, |a, (c, _)| a.max(*c));
+    let size = max - min + IVec3::ONE;
+    if size.max_element() > MAX_MODEL_SIZE {
+        return Err(format!(
+            "Scene is {}x{}x{} voxels; a .vox model is limited to {MAX_MODEL_SIZE} per axis",
+            size.x, size.y, size.z,
+        ));
+    }
+
+    let (palette, indices) = build_palette(vox_voxels.iter().map(|(_, c)| *c));
+
+    let mut size_chunk = Vec::new();
+    write_i32s(&mut size_chunk, &size.to_array());
+    let mut xyzi = (vox_voxels.len() as i32).to_le_bytes().to_vec();
+    for ((c, _), index) in vox_voxels.iter().zip(&indices) {
+        let local = *c - min;
+        xyzi.extend_from_slice(&[local.x as u8, local.y as u8, local.z as u8, *index]);
+    }
+    let rgba: Vec<u8> = palette[1..].iter().chain(std::iter::once(&[0, 0, 0, 0])).flatten().copied().collect();
+
+    // Minimal scene graph (root transform -> group -> transform -> shape) whose translation
+    // undoes MagicaVoxel's model centering, so re-importing lands on the original cells.
+    let t = min + size / 2;
+    let mut children = Vec::new();
+    write_chunk(&mut children, b"SIZE", &size_chunk);
+    write_chunk(&mut children, b"XYZI", &xyzi);
+    write_chunk(&mut children, b"nTRN", &transform_node(0, 1, None));
+    write_chunk(&mut children, b"nGRP", &group_node(1, &[2]));
+    write_chunk(&mut children, b"nTRN", &transform_node(2, 3, Some(format!("{} {} {}", t.x, t.y, t.z))));
+    write_chunk(&mut children, b"nSHP", &shape_node(3, 0));
+    write_chunk(&mut children, b"RGBA", &rgba);
+
+    let mut out = b"VOX ".to_vec();
+    out.extend_from_slice(&VOX_VERSION.to_le_bytes());
+    out.extend_from_slice(b"MAIN");
+    out.extend_from_slice(&0i32.to_le_bytes());
+    out.extend_from_slice(&(children.len() as i32).to_le_bytes());
+    out.extend_from_slice(&children);
+
+    fs::write(path, &out).map_err(|e| format!("Write failed: {e}"))
+}
+
+/// Index of the largest absolute component.
+fn dominant_axis(v: Vec3) -> usize {
+    let a = v.abs();
+    if a.x >= a.y && a.x >= a.z { 0 } else if a.y >= a.z { 1 } else { 2 }
+}
+
+/// Face color: tileset texel under the UV centroid (if any) times the average vertex color.
+fn face_color(face: &Face, tileset: Option<&Tileset>) -> [u8; 4] {
+    let mut color = face.colors.iter().sum::<Vec4>() / 4.0;
+    if let Some(ts) = tileset
+        && let Some(ref data) = ts.image_data
+        && ts.image_width > 0 && ts.image_height > 0
+    {
+        let uv = face.uvs.iter().sum::<Vec2>() / 4.0;
+        let x = ((uv.x.rem_euclid(1.0) * ts.image_width as f32) as u32).min(ts.image_width - 1);
+        let y = ((uv.y.rem_euclid(1.0) * ts.image_height as f32) as u32).min(ts.image_height - 1);
+        let i = ((y * ts.image_width + x) * 4) as usize;
+        let texel = Vec4::new(data[i] as f32, data[i + 1] as f32, data[i + 2] as f32, data[i + 3] as f32) / 255.0;
+        color *= texel;
+    }
+    (color.clamp(Vec4::ZERO, Vec4::ONE) * 255.0).round().to_array().map(|c| c as u8)
+}
+
+/// Palette (index 0 unused) and per-voxel indices. Beyond 255 distinct colors, voxels snap to
+/// the nearest already-allocated entry.
+fn build_palette(colors: impl Iterator<Item = [u8; 4]>) -> ([[u8; 4]; 256], Vec<u8>) {
+    let mut palette = [[0u8; 4]; 256];
+    let mut lookup: HashMap<[u8; 4], u8> = HashMap::new();
+    let mut used = 0usize;
+    let mut indices = Vec::new();
+    for c in colors {
+        let index = match lookup.get(&c) {
+            Some(&i) => i,
+            None if used < 255 => {
+                used += 1;
+                palette[used] = c;
+                lookup.insert(c, used as u8);
+                used as u8
+            }
+            None => {
+                let dist = |p: &[u8; 4]| p.iter().zip(&c).map(|(a, b)| (*a as i32 - *b as i32).pow(2)).sum::<i32>();
+                (1..=255).min_by_key(|&i| dist(&palette[i])).unwrap_or(1) as u8
+            }
+        };
+        indices.push(index);
+    }
+    if used == 255 && lookup.len() < indices.len() {
+        log::warn!("More than 255 colors; extra colors were mapped to the nearest palette entry");
+    }
+    (palette, indices)
+}
+
+fn transform_node(id: i32, child: i32, translation: Option<String>) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_i32s(&mut out, &[id, 0, child, -1, 0, 1]);
+    match translation {
+        Some(t) => write_dict(&mut out, &[("_t", &t)]),
+        None => write_dict(&mut out, &[]),
+    }
+    out
+}
+
+fn group_node(id: i32, children: &[i32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_i32s(&mut out, &[id, 0, children.len() as i32]);
+    write_i32s(&mut out, children);
+    out
+}
+
+fn shape_node(id: i32, model: i32) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_i32s(&mut out, &[id, 0, 1, model, 0]);
+    out
+}
+
+fn write_i32s(out: &mut Vec<u8>, values: &[i32]) {
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+fn write_dict(out: &mut Vec<u8>, entries: &[(&str, &str)]) {
+    write_i32s(out, &[entries.len() as i32]);
+    for (key, value) in entries {
+        for s in [key, value] {
+            write_i32s(out, &[s.len() as i32]);
+            out.extend_from_slice(s.as_bytes());
+        }
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as i32).to_le_bytes());
+    out.extend_from_slice(&0i32.to_le_bytes());
+    out.extend_from_slice(content);
+}
+
+/// Little-endian cursor over chunk data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let out = self.data.get(self.pos..self.pos + len).ok_or("Unexpected end of .vox data")?;
+        self.pos += len;
+        Ok(out)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let b = self.bytes(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.i32()?.max(0) as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<HashMap<String, String>, String> {
+        let count = self.i32()?.max(0);
+        (0..count).map(|_| Ok((self.string()?, self.string()?))).collect()
+    }
+
+    /// Chunk id, content length, children length.
+    fn chunk_header(&mut self) -> Result<([u8; 4], usize, usize), String> {
+        let id = self.bytes(4)?;
+        let content = self.i32()?.max(0) as usize;
+        let children = self.i32()?.max(0) as usize;
+        Ok(([id[0], id[1], id[2], id[3]], content, children))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// (cell, color) of every voxel a set of cube faces encloses: each face sits half a cell in
+    /// front of the cell it belongs to.
+    fn cells_of(faces: &[Face], tileset: Option<&Tileset>) -> HashSet<(IVec3, [u8; 4])> {
+        faces.iter().map(|f| {
+            let center = f.positions.iter().sum::<Vec3>() / 4.0;
+            let normal = (f.positions[1] - f.positions[0]).cross(f.positions[3] - f.positions[0]).normalize();
+            ((center - normal * 0.5 - 0.5).round().as_ivec3(), face_color(f, tileset))
+        }).collect()
+    }
+
+    #[test]
+    fn export_import_round_trips_cells_and_colors() {
+        let cells = [
+            (IVec3::new(0, 0, 0), Vec4::new(1.0, 0.0, 0.0, 1.0)),
+            (IVec3::new(3, 1, -2), Vec4::new(0.0, 1.0, 0.0, 1.0)),
+            (IVec3::new(-4, 2, 5), Vec4::new(0.2, 0.4, 1.0, 1.0)),
+        ];
+        let mut object = crate::scene::Object::new("Voxels".to_string());
+        for (cell, color) in cells {
+            let center = cell.as_vec3() + 0.5;
+            for normal in [Vec3::X, Vec3::NEG_X, Vec3::Y, Vec3::NEG_Y, Vec3::Z, Vec3::NEG_Z] {
+                let mut face = Face::new_quad(center + normal * 0.5, normal, 0.5, [Vec2::ZERO; 4]);
+                face.colors = [color; 4];
+                object.faces.push(face);
+            }
+        }
+        let mut scene = Scene::new();
+        scene.grid_cell_size = 1.0;
+        let expected = cells_of(&object.faces, None);
+        assert_eq!(expected.len(), cells.len());
+        scene.layers[0].objects.push(object);
+
+        let path = std::env::temp_dir().join(format!("cracktile3d_vox_round_trip_{}.vox", std::process::id()));
+        export_vox(&scene, &path).unwrap();
+        let imported = import_vox(&path, 1.0);
+        let _ = fs::remove_file(&path);
+
+        let imported = imported.unwrap();
+        let faces: Vec<Face> = imported.objects.into_iter().flat_map(|o| o.faces).collect();
+        assert_eq!(faces.len(), 18);
+        assert_eq!(cells_of(&faces, imported.tilesets.first()), expected);
+    }
+}
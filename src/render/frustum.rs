@@ -0,0 +1,27 @@
+use glam::{Mat4, Vec3, Vec4};
+
+/// The six clip planes of a view-projection matrix, for culling bounding boxes.
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Planes of a clip-from-space matrix (wgpu depth range 0..1). Passing the view-projection
+    /// times a model matrix gives planes in that model's local space, so local bounds can be
+    /// tested without transforming them.
+    pub fn from_matrix(m: Mat4) -> Self {
+        let (x, y, z, w) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        Self { planes: [w + x, w - x, w + y, w - y, z, w - z] }
+    }
+
+    /// Whether the box `min..=max` may be visible. Conservative: boxes just outside a corner
+    /// of the frustum can pass.
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner farthest along the plane normal is the last one to leave
+            let normal = plane.truncate();
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), max, min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
@@ -1,5 +1,6 @@
 mod renderer;
 pub mod camera;
+mod frustum;
 pub mod gizmo;
 mod grid;
 pub mod skybox;
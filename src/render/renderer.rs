@@ -2,19 +2,17 @@ use std::sync::Arc;
 use wgpu::util::DeviceExt;
 
 use crate::input::InputState;
-use crate::render::camera::Camera;
+use crate::render::camera::{Camera, Projection};
+use crate::render::frustum::Frustum;
 use crate::render::grid::GridRenderer;
 use crate::render::skybox::SkyboxRenderer;
-use crate::render::vertex::{LineVertex, Vertex};
+use crate::render::vertex::{InstanceRaw, LineVertex, Vertex};
 use crate::scene::Scene;
 use crate::scene::mesh::Face;
 use crate::tools::edit::Selection;
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
-/// Maximum number of model matrix slots in the dynamic uniform buffer.
-const MAX_MODEL_SLOTS: u64 = 512;
-
 pub struct Renderer {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
@@ -54,13 +52,10 @@ pub struct Renderer {
     /// Ambient light color RGB.
     pub ambient_color: [f32; 3],
 
-    // Model matrix dynamic uniform buffer (for instances)
-    model_buffer: wgpu::Buffer,
+    // Identity model matrix for line overlays (drawn in world space)
     model_bind_group: wgpu::BindGroup,
-    /// Alignment requirement for dynamic offsets into the model buffer.
-    model_buffer_alignment: u32,
-    /// Number of model matrix slots used this frame (reset in prepare_frame).
-    model_slot_count: u32,
+    /// Single identity transform, the instance buffer of objects without instances.
+    identity_instance_buffer: wgpu::Buffer,
 }
 
 impl Renderer {
@@ -168,13 +163,77 @@ impl Renderer {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    // Tile grid of the padded texture copy (3 vec4s = 48 bytes)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Scene atlas placement of each source tileset (info + white + 64 rects = 1056 bytes)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Normal, emissive and specular maps
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 5,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 6,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    // Map enable flags + strengths (2 vec4s = 32 bytes)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 7,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             });
 
-        // Light uniform (3 vec4s = 48 bytes)
+        // Light uniform (4 vec4s = 64 bytes)
         let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("light_uniform"),
-            size: 48,
+            size: 64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -203,15 +262,18 @@ impl Renderer {
             }],
         });
 
-        // Model matrix dynamic uniform buffer
-        let model_buffer_alignment = device.limits().min_uniform_buffer_offset_alignment;
-        let model_buffer_size = model_buffer_alignment as u64 * MAX_MODEL_SLOTS;
-
-        let model_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        // Identity model matrix uniform for line overlays
+        let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("model_uniform"),
-            size: model_buffer_size,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
+            contents: bytemuck::cast_slice(&glam::Mat4::IDENTITY.to_cols_array()),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // Tile geometry reads its transforms per instance; plain objects draw one identity instance
+        let identity_instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("identity_instance"),
+            contents: bytemuck::bytes_of(&InstanceRaw::IDENTITY),
+            usage: wgpu::BufferUsages::VERTEX,
         });
 
         let model_bind_group_layout =
@@ -222,7 +284,7 @@ impl Renderer {
                     visibility: wgpu::ShaderStages::VERTEX,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: true,
+                        has_dynamic_offset: false,
                         min_binding_size: wgpu::BufferSize::new(64), // mat4x4<f32>
                     },
                     count: None,
@@ -234,15 +296,11 @@ impl Renderer {
             layout: &model_bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                    buffer: &model_buffer,
-                    offset: 0,
-                    size: wgpu::BufferSize::new(64), // mat4x4<f32>
-                }),
+                resource: model_buffer.as_entire_binding(),
             }],
         });
 
-        // Tile pipeline — bind groups: [camera, tileset, light, model]
+        // Tile pipeline — bind groups: [camera, tileset, light]; vertex buffers: [vertices, instance transforms]
         let tile_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("tile_shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("shaders/tile.wgsl").into()),
@@ -250,7 +308,7 @@ impl Renderer {
 
         let tile_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("tile_pipeline_layout"),
-            bind_group_layouts: &[&camera_bind_group_layout, &tile_bind_group_layout, &light_bind_group_layout, &model_bind_group_layout],
+            bind_group_layouts: &[&camera_bind_group_layout, &tile_bind_group_layout, &light_bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -260,7 +318,7 @@ impl Renderer {
             vertex: wgpu::VertexState {
                 module: &tile_shader,
                 entry_point: Some("vs_main"),
-                buffers: &[Vertex::LAYOUT],
+                buffers: &[Vertex::LAYOUT, InstanceRaw::LAYOUT],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -296,7 +354,7 @@ impl Renderer {
             vertex: wgpu::VertexState {
                 module: &tile_shader,
                 entry_point: Some("vs_main"),
-                buffers: &[Vertex::LAYOUT],
+                buffers: &[Vertex::LAYOUT, InstanceRaw::LAYOUT],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -438,6 +496,24 @@ impl Renderer {
             min_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
+        // All zeros: padding disabled
+        let placeholder_padding = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("placeholder_padding"),
+            contents: &[0; 48],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        // All zeros: atlas lookup disabled
+        let placeholder_atlas = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("placeholder_atlas"),
+            contents: &[0; crate::tile::atlas::ATLAS_TABLE_SIZE],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        // All zeros: no companion maps
+        let placeholder_maps = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("placeholder_maps"),
+            contents: &[0; crate::tile::maps::MAPS_UNIFORM_SIZE],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
         let placeholder_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("placeholder_bg"),
             layout: &tile_bind_group_layout,
@@ -450,6 +526,30 @@ impl Renderer {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&placeholder_sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: placeholder_padding.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: placeholder_atlas.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&placeholder_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&placeholder_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::TextureView(&placeholder_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: placeholder_maps.as_entire_binding(),
+                },
             ],
         });
 
@@ -479,10 +579,8 @@ impl Renderer {
             light_color: [1.0, 1.0, 0.95],
             light_intensity: 0.8,
             ambient_color: [0.3, 0.3, 0.35],
-            model_buffer,
             model_bind_group,
-            model_buffer_alignment,
-            model_slot_count: 0,
+            identity_instance_buffer,
         }
     }
 
@@ -497,18 +595,8 @@ impl Renderer {
         self.camera.set_aspect(width as f32, height as f32);
     }
 
-    /// Allocate a model matrix slot and upload the matrix. Returns the dynamic offset.
-    fn upload_model_matrix(&mut self, matrix: glam::Mat4) -> u32 {
-        let slot = self.model_slot_count;
-        self.model_slot_count += 1;
-        let offset = slot * self.model_buffer_alignment;
-        let data: [f32; 16] = matrix.to_cols_array();
-        self.queue.write_buffer(&self.model_buffer, offset as u64, bytemuck::cast_slice(&data));
-        offset
-    }
-
-    /// Upload per-frame data (camera, grid, skybox, lighting, model identity) before the render pass begins.
-    pub fn prepare_frame(&mut self, scene: &Scene) {
+    /// Upload per-frame data (camera, grid, skybox, lighting, instance transforms) before the render pass begins.
+    pub fn prepare_frame(&mut self, scene: &mut Scene) {
         let vp = self.camera.view_projection();
         let vp_raw: [f32; 16] = vp.to_cols_array();
         self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&vp_raw));
@@ -520,37 +608,31 @@ impl Renderer {
             self.skybox.prepare(&self.queue, inv_vp);
         }
 
-        // Upload light uniform: direction(xyz) + enabled(w), color(rgb) + intensity(a), ambient(rgb) + pad
+        // Upload light uniform: direction(xyz) + enabled(w), color(rgb) + intensity(a), ambient(rgb) + pad,
+        // eye position(xyz) + perspective(w) for specular highlights; orthographic views store
+        // the direction toward the camera instead, since every view ray is parallel
         let enabled_f = if self.lighting_enabled { 1.0f32 } else { 0.0 };
-        let light_data: [f32; 12] = [
+        let (eye, perspective_f) = match self.camera.projection {
+            Projection::Perspective => (self.camera.position, 1.0f32),
+            Projection::Orthographic => ((self.camera.position - self.camera.target).normalize_or_zero(), 0.0),
+        };
+        let light_data: [f32; 16] = [
             self.light_direction[0], self.light_direction[1], self.light_direction[2], enabled_f,
             self.light_color[0], self.light_color[1], self.light_color[2], self.light_intensity,
             self.ambient_color[0], self.ambient_color[1], self.ambient_color[2], 0.0,
+            eye.x, eye.y, eye.z, perspective_f,
         ];
         self.queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&light_data));
 
-        // Reset model slot counter and upload identity matrix at slot 0
-        self.model_slot_count = 0;
-        self.upload_model_matrix(glam::Mat4::IDENTITY);
-
-        // Upload instance model matrices
-        for layer in &scene.layers {
+        // Per-object instance buffers (only changed transforms are rewritten)
+        for layer in &mut scene.layers {
             if !layer.visible { continue; }
-            for object in &layer.objects {
-                for inst in &object.instances {
-                    if self.model_slot_count < MAX_MODEL_SLOTS as u32 {
-                        self.upload_model_matrix(inst.model_matrix());
-                    }
-                }
+            for object in &mut layer.objects {
+                object.sync_gpu_instances(&self.device, &self.queue);
             }
         }
     }
 
-    /// Get the dynamic offset for the identity model matrix (slot 0).
-    fn identity_offset(&self) -> u32 {
-        0
-    }
-
     pub fn render_scene<'a>(
         &'a self,
         pass: &mut wgpu::RenderPass<'a>,
@@ -564,7 +646,7 @@ impl Renderer {
         // Draw grid (uses line pipeline with identity model)
         pass.set_pipeline(&self.line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, self.grid.vertex_buffer.slice(..));
         pass.draw(0..self.grid.vertex_count, 0..1);
 
@@ -586,41 +668,63 @@ impl Renderer {
             pass.set_pipeline(pipeline);
             pass.set_bind_group(0, &self.camera_bind_group, &[]);
             pass.set_bind_group(2, &self.light_bind_group, &[]);
-            pass.set_bind_group(3, &self.model_bind_group, &[self.identity_offset()]);
-
-            // Track model slot index for instances (slot 0 = identity, then instances)
-            let mut inst_slot: u32 = 1;
 
+            let view_projection = self.camera.view_projection();
             for layer in &scene.layers {
                 if !layer.visible {
                     continue;
                 }
                 for object in &layer.objects {
                     if let Some(ref gpu_mesh) = object.gpu_mesh {
-                        let bind_group = object.tileset_index
+                        let atlas = scene.atlas.as_ref()
+                            .filter(|_| gpu_mesh.mixed)
+                            .and_then(|a| a.bind_group.as_ref());
+                        // Palette variants swap the tileset's colors; the scene atlas has none.
+                        let bind_group = atlas.or_else(|| object.tileset_index
                             .and_then(|idx| scene.tilesets.get(idx))
-                            .and_then(|ts| ts.bind_group.as_ref())
+                            .and_then(|ts| ts.variant_bind_group(layer.object_variant(object))))
                             .unwrap_or(&self.placeholder_bind_group);
+                        // Frustum of each on-screen copy (the source object, then its instances),
+                        // in that copy's local space
+                        let visible: Vec<(u32, Frustum)> = std::iter::once(glam::Mat4::IDENTITY)
+                            .chain(object.instances.iter().map(|inst| inst.model_matrix()))
+                            .enumerate()
+                            .map(|(i, model)| (i as u32, Frustum::from_matrix(view_projection * model)))
+                            .filter(|(_, frustum)| frustum.intersects_aabb(gpu_mesh.bounds.0, gpu_mesh.bounds.1))
+                            .collect();
+                        if visible.is_empty() {
+                            continue;
+                        }
                         pass.set_bind_group(1, bind_group, &[]);
+                        let instances = match object.gpu_instances {
+                            Some(ref gpu) => &gpu.buffer,
+                            None => &self.identity_instance_buffer,
+                        };
                         pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-                        pass.set_index_buffer(gpu_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-
-                        // Draw source object with identity model
-                        pass.set_bind_group(3, &self.model_bind_group, &[self.identity_offset()]);
-                        pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
-
-                        // Draw instances with their model matrices
-                        for _inst in &object.instances {
-                            if inst_slot < MAX_MODEL_SLOTS as u32 {
-                                let offset = inst_slot * self.model_buffer_alignment;
-                                pass.set_bind_group(3, &self.model_bind_group, &[offset]);
-                                pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
+                        pass.set_vertex_buffer(1, instances.slice(..));
+                        // Visible copies are drawn instanced, one draw per run of consecutive ones
+                        match gpu_mesh.chunks {
+                            Some(ref chunks) => {
+                                for chunk in chunks.chunks.iter().filter(|c| c.index_count > 0) {
+                                    let ids = visible.iter()
+                                        .filter(|(_, frustum)| frustum.intersects_aabb(chunk.bounds.0, chunk.bounds.1))
+                                        .map(|(i, _)| *i);
+                                    let mut runs = instance_runs(ids).peekable();
+                                    if runs.peek().is_some() {
+                                        pass.set_index_buffer(chunk.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                                    }
+                                    for run in runs {
+                                        pass.draw_indexed(0..chunk.index_count, 0, run);
+                                    }
+                                }
+                            }
+                            None => {
+                                pass.set_index_buffer(gpu_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                                for run in instance_runs(visible.iter().map(|(i, _)| *i)) {
+                                    pass.draw_indexed(0..gpu_mesh.index_count, 0, run);
+                                }
                             }
-                            inst_slot += 1;
                         }
-                    } else {
-                        // Skip instance slots even if no gpu_mesh
-                        inst_slot += object.instances.len() as u32;
                     }
                 }
             }
@@ -675,7 +779,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..line_verts.len() as u32, 0..1);
     }
@@ -787,7 +891,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.selection_line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..line_verts.len() as u32, 0..1);
     }
@@ -824,7 +928,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.selection_line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..line_verts.len() as u32, 0..1);
     }
@@ -860,7 +964,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.selection_line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..line_verts.len() as u32, 0..1);
     }
@@ -883,7 +987,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.selection_line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..gizmo_verts.len() as u32, 0..1);
     }
@@ -935,7 +1039,7 @@ impl Renderer {
 
         pass.set_pipeline(&self.selection_line_pipeline);
         pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        pass.set_bind_group(1, &self.model_bind_group, &[self.identity_offset()]);
+        pass.set_bind_group(1, &self.model_bind_group, &[]);
         pass.set_vertex_buffer(0, buffer.slice(..));
         pass.draw(0..verts.len() as u32, 0..1);
     }
@@ -1057,3 +1161,16 @@ impl Renderer {
         texture.create_view(&Default::default())
     }
 }
+
+/// Group ascending instance indices into runs of consecutive ones, one instanced draw each.
+fn instance_runs(ids: impl Iterator<Item = u32>) -> impl Iterator<Item = std::ops::Range<u32>> {
+    let mut ids = ids.peekable();
+    std::iter::from_fn(move || {
+        let start = ids.next()?;
+        let mut end = start + 1;
+        while ids.next_if_eq(&end).is_some() {
+            end += 1;
+        }
+        Some(start..end)
+    })
+}
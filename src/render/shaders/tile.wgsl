@@ -6,23 +6,54 @@ struct LightUniform {
     direction: vec4<f32>,   // xyz = direction (toward light), w = enabled (1.0 or 0.0)
     color: vec4<f32>,       // rgb = light color, a = intensity
     ambient: vec4<f32>,     // rgb = ambient color, a = unused
+    eye: vec4<f32>,         // xyz = camera position (w = 1.0) or direction toward camera (w = 0.0, orthographic)
 };
 
-struct ModelUniform {
-    model: mat4x4<f32>,
+// Grid of the tileset's padded texture copy, in source-image pixels.
+struct TilePadding {
+    grid: vec4<f32>,   // x = tile width, y = tile height, z = margin, w = spacing
+    cells: vec4<f32>,  // x = columns, y = rows, z = extrusion, w = enabled (1.0 or 0.0)
+    sizes: vec4<f32>,  // xy = source image size, zw = padded texture size
+};
+
+// Where each source tileset sits inside the scene atlas, for objects that mix tilesets.
+struct AtlasTable {
+    info: vec4<u32>,           // x = tileset count, y = enabled (1 or 0)
+    white: vec4<f32>,          // xy = UV of a white texel for faces without a tileset
+    rects: array<vec4<f32>, 64>, // xy = UV offset, zw = UV scale
+};
+
+// Companion maps of the tileset, sampled on the same UVs as the albedo texture.
+struct MaterialMaps {
+    enabled: vec4<f32>,   // x = normal, y = emissive, z = specular (1.0 or 0.0)
+    strength: vec4<f32>,  // x = normal, y = emissive, z = specular, w = specular power
 };
 
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
 @group(1) @binding(0) var t_tileset: texture_2d<f32>;
 @group(1) @binding(1) var s_tileset: sampler;
+@group(1) @binding(2) var<uniform> padding: TilePadding;
+@group(1) @binding(3) var<uniform> atlas: AtlasTable;
+@group(1) @binding(4) var t_normal: texture_2d<f32>;
+@group(1) @binding(5) var t_emissive: texture_2d<f32>;
+@group(1) @binding(6) var t_specular: texture_2d<f32>;
+@group(1) @binding(7) var<uniform> maps: MaterialMaps;
 @group(2) @binding(0) var<uniform> light: LightUniform;
-@group(3) @binding(0) var<uniform> model: ModelUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) uv: vec2<f32>,
     @location(3) color: vec4<f32>,
+    @location(4) tileset: u32,
+};
+
+// Model matrix of the instance being drawn (identity for the source object), one column each.
+struct InstanceInput {
+    @location(5) model_0: vec4<f32>,
+    @location(6) model_1: vec4<f32>,
+    @location(7) model_2: vec4<f32>,
+    @location(8) model_3: vec4<f32>,
 };
 
 struct VertexOutput {
@@ -30,6 +61,7 @@ struct VertexOutput {
     @location(0) uv: vec2<f32>,
     @location(1) color: vec4<f32>,
     @location(2) normal: vec3<f32>,
+    @location(3) world_pos: vec3<f32>,
 };
 
 // Compute the cofactor (adjugate) of a 3x3 matrix — transpose of this is the
@@ -42,22 +74,77 @@ fn cofactor3(m: mat3x3<f32>) -> mat3x3<f32> {
 }
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
     var out: VertexOutput;
-    let world_pos = model.model * vec4<f32>(in.position, 1.0);
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let world_pos = model * vec4<f32>(in.position, 1.0);
     out.clip_position = camera.view_proj * world_pos;
+    out.world_pos = world_pos.xyz;
     out.uv = in.uv;
+    if atlas.info.y == 1u {
+        if in.tileset < atlas.info.x {
+            let rect = atlas.rects[in.tileset];
+            out.uv = rect.xy + in.uv * rect.zw;
+        } else {
+            out.uv = atlas.white.xy;
+        }
+    }
     out.color = in.color;
     // Normal matrix = cofactor of upper-left 3x3 (equivalent to transpose(inverse(M)) * det(M))
     // The determinant factor cancels after normalize(), so cofactor alone is sufficient.
-    let model3 = mat3x3<f32>(model.model[0].xyz, model.model[1].xyz, model.model[2].xyz);
+    let model3 = mat3x3<f32>(model[0].xyz, model[1].xyz, model[2].xyz);
     out.normal = normalize(cofactor3(model3) * in.normal);
     return out;
 }
 
+// Map a source-image UV into the padded copy, where each tile sits `extrusion` pixels inside
+// its own cell surrounded by repeats of its border pixels.
+fn padded_uv(uv: vec2<f32>) -> vec2<f32> {
+    let tile = padding.grid.xy;
+    let step = tile + vec2<f32>(padding.grid.w);
+    let px = uv * padding.sizes.xy - vec2<f32>(padding.grid.z);
+    let cell = clamp(floor(px / step), vec2<f32>(0.0), padding.cells.xy - 1.0);
+    let offset = clamp(px - cell * step, vec2<f32>(0.0), tile);
+    let padded = cell * (tile + 2.0 * padding.cells.z) + padding.cells.z + offset;
+    return padded / padding.sizes.zw;
+}
+
+// Tilt a surface normal by a tangent-space normal map sample (green = up in the image). The
+// tangent frame is rebuilt from screen-space derivatives, so meshes need no stored tangents.
+fn perturb_normal(n: vec3<f32>, dp1: vec3<f32>, dp2: vec3<f32>, duv1: vec2<f32>, duv2: vec2<f32>, sample: vec3<f32>) -> vec3<f32> {
+    let dp2perp = cross(dp2, n);
+    let dp1perp = cross(n, dp1);
+    let t = dp2perp * duv1.x + dp1perp * duv2.x;
+    let b = dp2perp * duv1.y + dp1perp * duv2.y;
+    let len2 = max(dot(t, t), dot(b, b));
+    if len2 <= 0.0 {
+        return n;
+    }
+    let inv = inverseSqrt(len2);
+    // Image V grows downward, so the map's +Y points along -b.
+    var tangent_n = sample * 2.0 - 1.0;
+    tangent_n = vec3<f32>(tangent_n.xy * maps.strength.x, tangent_n.z);
+    return normalize(t * inv * tangent_n.x - b * inv * tangent_n.y + n * tangent_n.z);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let tex_color = textureSample(t_tileset, s_tileset, in.uv);
+    // Position derivatives for the normal map's tangent frame, taken before any discard.
+    let dp1 = dpdx(in.world_pos);
+    let dp2 = dpdy(in.world_pos);
+    // Gradients come from the unremapped UV so mip selection stays smooth across tile seams.
+    var uv = in.uv;
+    let duv1 = dpdx(in.uv);
+    let duv2 = dpdy(in.uv);
+    var ddx = duv1;
+    var ddy = duv2;
+    if padding.cells.w > 0.5 {
+        let scale = padding.sizes.xy / padding.sizes.zw;
+        uv = padded_uv(in.uv);
+        ddx *= scale;
+        ddy *= scale;
+    }
+    let tex_color = textureSampleGrad(t_tileset, s_tileset, uv, ddx, ddy);
     if tex_color.a < 0.01 {
         discard;
     }
@@ -66,14 +153,35 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
     // Apply lighting if enabled
     if light.direction.w > 0.5 {
-        let n = normalize(in.normal);
+        var v = normalize(light.eye.xyz);
+        if light.eye.w > 0.5 {
+            v = normalize(light.eye.xyz - in.world_pos);
+        }
+        var n = normalize(in.normal);
+        if maps.enabled.x > 0.5 {
+            // Face the viewer first so back faces tilt the same way as front faces.
+            let facing = select(-1.0, 1.0, dot(n, v) >= 0.0);
+            let sample = textureSampleGrad(t_normal, s_tileset, uv, ddx, ddy).rgb;
+            n = perturb_normal(n * facing, dp1, dp2, duv1, duv2, sample);
+        }
         let l = normalize(light.direction.xyz);
         let ndotl = max(dot(n, l), 0.0);
         // Also light the back face (two-sided lighting for tiles)
         let ndotl_back = max(dot(-n, l), 0.0);
         let diffuse = max(ndotl, ndotl_back);
-        let lit = light.ambient.rgb + light.color.rgb * light.color.a * diffuse;
-        base_color = vec4<f32>(base_color.rgb * lit, base_color.a);
+        var lit = base_color.rgb * (light.ambient.rgb + light.color.rgb * light.color.a * diffuse);
+        if maps.enabled.z > 0.5 {
+            let h = normalize(l + v);
+            // Use the side of the surface the viewer sees, matching two-sided diffuse.
+            let ns = n * select(-1.0, 1.0, dot(n, v) >= 0.0);
+            let spec_mask = textureSampleGrad(t_specular, s_tileset, uv, ddx, ddy).r * maps.strength.z;
+            let spec = pow(max(dot(ns, h), 0.0), maps.strength.w) * step(0.0, dot(ns, l));
+            lit += light.color.rgb * light.color.a * spec * spec_mask;
+        }
+        if maps.enabled.y > 0.5 {
+            lit += textureSampleGrad(t_emissive, s_tileset, uv, ddx, ddy).rgb * maps.strength.y;
+        }
+        base_color = vec4<f32>(lit, base_color.a);
     }
 
     return base_color;
@@ -8,9 +8,15 @@ pub struct Vertex {
     pub normal: [f32; 3],
     pub uv: [f32; 2],
     pub color: [f32; 4],
+    /// Source tileset of the face, looked up in the scene atlas when the object mixes
+    /// tilesets. NO_TILESET = sample the bound texture directly.
+    pub tileset: u32,
 }
 
 impl Vertex {
+    /// Vertex tileset slot meaning the face uses its object's texture as-is.
+    pub const NO_TILESET: u32 = u32::MAX;
+
     pub const LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
         array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
         step_mode: wgpu::VertexStepMode::Vertex,
@@ -19,6 +25,34 @@ impl Vertex {
             1 => Float32x3,  // normal
             2 => Float32x2,  // uv
             3 => Float32x4,  // color
+            4 => Uint32,     // tileset
+        ],
+    };
+}
+
+/// Per-instance data for instanced tile drawing: the model matrix, one column per attribute.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct InstanceRaw {
+    pub model: [[f32; 4]; 4],
+}
+
+impl InstanceRaw {
+    pub const IDENTITY: InstanceRaw = InstanceRaw { model: glam::Mat4::IDENTITY.to_cols_array_2d() };
+
+    pub fn from_matrix(m: glam::Mat4) -> Self {
+        Self { model: m.to_cols_array_2d() }
+    }
+
+    /// Follows `Vertex::LAYOUT` in the tile pipeline, at locations 5..=8.
+    pub const LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![
+            5 => Float32x4,  // model column 0
+            6 => Float32x4,  // model column 1
+            7 => Float32x4,  // model column 2
+            8 => Float32x4,  // model column 3
         ],
     };
 }
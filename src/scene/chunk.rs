@@ -0,0 +1,125 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+
+use glam::{IVec3, Vec3};
+use crate::scene::mesh::Face;
+
+/// Objects with fewer faces are drawn whole; culling their parts isn't worth the draw calls.
+pub const CHUNK_MIN_FACES: usize = 4096;
+/// Edge length of the world-space cells a large object's faces are grouped by.
+const CHUNK_SIZE: f32 = 32.0;
+/// `face_chunk` entry of a face not yet placed in a chunk.
+const UNASSIGNED: usize = usize::MAX;
+
+/// A spatial cell of a large object, drawn through its own index buffer into the object's
+/// shared vertex buffer so off-screen parts of the object can be skipped.
+pub struct MeshChunk {
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    /// Local-space bounds of the chunk's faces.
+    pub bounds: (Vec3, Vec3),
+    /// Indices the buffer has room for; grown by doubling.
+    capacity: usize,
+}
+
+/// Chunk membership of every face of an object, kept in step with face edits so only the
+/// chunks that gained or lost faces are rebuilt, each from its own members.
+#[derive(Default)]
+pub struct MeshChunks {
+    pub chunks: Vec<MeshChunk>,
+    cells: HashMap<IVec3, usize>,
+    /// Chunk of each face.
+    face_chunk: Vec<usize>,
+    /// Faces of each chunk, in face order.
+    members: Vec<BTreeSet<usize>>,
+}
+
+fn cell_of(face: &Face) -> IVec3 {
+    let center = (face.positions[0] + face.positions[1] + face.positions[2] + face.positions[3]) * 0.25;
+    (center / CHUNK_SIZE).floor().as_ivec3()
+}
+
+impl MeshChunks {
+    /// Reassign the faces in `dirty` (and any removed from the end) to their cells and rebuild
+    /// the index buffers of every chunk whose faces changed.
+    pub fn update(&mut self, faces: &[Face], dirty: &[Range<usize>], device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut changed = vec![false; self.chunks.len()];
+        for (fi, &c) in self.face_chunk.iter().enumerate().skip(faces.len()) {
+            if c != UNASSIGNED {
+                self.members[c].remove(&fi);
+                changed[c] = true;
+            }
+        }
+        // Faces past the old end are new, whether or not a dirty range covers them
+        let grown = self.face_chunk.len()..faces.len();
+        self.face_chunk.resize(faces.len(), UNASSIGNED);
+
+        for range in dirty.iter().chain([&grown]) {
+            let range = range.start.min(faces.len())..range.end.min(faces.len());
+            for (fi, face) in faces[range.clone()].iter().enumerate().map(|(i, f)| (range.start + i, f)) {
+                let cell = cell_of(face);
+                let chunk = *self.cells.entry(cell).or_insert_with(|| {
+                    self.chunks.push(MeshChunk::new(device));
+                    self.members.push(BTreeSet::new());
+                    changed.push(false);
+                    self.chunks.len() - 1
+                });
+                let old = std::mem::replace(&mut self.face_chunk[fi], chunk);
+                if old != UNASSIGNED {
+                    self.members[old].remove(&fi);
+                    changed[old] = true;
+                }
+                self.members[chunk].insert(fi);
+                changed[chunk] = true;
+            }
+        }
+
+        for chunk in (0..changed.len()).filter(|&c| changed[c]) {
+            let members = &self.members[chunk];
+            let mut indices = Vec::with_capacity(members.len() * 6);
+            let (mut min, mut max) = (Vec3::MAX, Vec3::MIN);
+            for &fi in members {
+                let face = &faces[fi];
+                if face.hidden { continue; }
+                indices.extend_from_slice(&Face::indices((fi * 4) as u32));
+                for p in face.positions {
+                    min = min.min(p);
+                    max = max.max(p);
+                }
+            }
+            self.chunks[chunk].upload(device, queue, &indices, (min, max));
+        }
+    }
+}
+
+impl MeshChunk {
+    fn new(device: &wgpu::Device) -> Self {
+        Self {
+            index_buffer: Self::create_buffer(device, 0),
+            index_count: 0,
+            bounds: (Vec3::ZERO, Vec3::ZERO),
+            capacity: 0,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("object_chunk_ib"),
+            size: (capacity.max(6) * std::mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, indices: &[u32], bounds: (Vec3, Vec3)) {
+        if indices.len() > self.capacity {
+            self.capacity = indices.len().next_power_of_two();
+            self.index_buffer = Self::create_buffer(device, self.capacity);
+        }
+        if !indices.is_empty() {
+            queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(indices));
+        }
+        self.index_count = indices.len() as u32;
+        self.bounds = bounds;
+    }
+}
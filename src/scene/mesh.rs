@@ -10,6 +10,10 @@ pub struct Face {
     pub colors: [Vec4; 4],
     #[serde(default)]
     pub hidden: bool,
+    /// Tileset this face's UVs refer to when it differs from its object's; objects that mix
+    /// tilesets render through the scene atlas. None = the object's tileset.
+    #[serde(default)]
+    pub tileset: Option<usize>,
 }
 
 impl Face {
@@ -30,6 +34,7 @@ impl Face {
             uvs,
             colors: [Vec4::ONE; 4],
             hidden: false,
+            tileset: None,
         }
     }
 
@@ -49,16 +54,19 @@ impl Face {
             uvs,
             colors: [Vec4::ONE; 4],
             hidden: false,
+            tileset: None,
         }
     }
 
-    pub fn vertices(&self) -> [Vertex; 4] {
+    /// GPU vertices for this quad, tagged with the atlas slot of its tileset.
+    pub fn vertices(&self, tileset: u32) -> [Vertex; 4] {
         let n: [f32; 3] = self.normal().into();
         std::array::from_fn(|i| Vertex {
             position: self.positions[i].into(),
             normal: n,
             uv: self.uvs[i].into(),
             color: self.colors[i].into(),
+            tileset,
         })
     }
 
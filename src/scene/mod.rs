@@ -1,4 +1,6 @@
+mod chunk;
 mod object;
+pub mod optimize;
 pub mod mesh;
 
 pub use object::{Object, Instance};
@@ -8,6 +10,7 @@ use crate::tile::Tileset;
 use crate::scene::mesh::Face;
 use crate::bones::Skeleton;
 use crate::tile::palette::Palette;
+use crate::tile::atlas::TilesetAtlas;
 
 pub const GRID_PRESETS: &[f32] = &[0.125, 0.25, 0.5, 1.0, 2.0, 4.0];
 
@@ -63,9 +66,6 @@ pub struct Scene {
     pub tilesets: Vec<Tileset>,
     #[serde(skip)]
     pub active_tileset: Option<usize>,
-    /// Objects that need GPU mesh rebuild after property edits. Cleared each frame by app.
-    #[serde(skip)]
-    pub dirty_objects: Vec<(usize, usize)>,
     /// Prefab library — reusable geometry templates.
     #[serde(default)]
     pub prefabs: Vec<Prefab>,
@@ -81,6 +81,12 @@ pub struct Scene {
     /// Active palette index (None = direct tile selection).
     #[serde(skip)]
     pub active_palette: Option<usize>,
+    /// All tilesets packed together, built while any object mixes tilesets.
+    #[serde(skip)]
+    pub atlas: Option<TilesetAtlas>,
+    /// Tileset pixels or the tileset list changed since the atlas was built.
+    #[serde(skip)]
+    pub atlas_dirty: bool,
 }
 
 fn default_grid_preset_index() -> usize { 3 }
@@ -90,6 +96,16 @@ pub struct Layer {
     pub name: String,
     pub visible: bool,
     pub objects: Vec<Object>,
+    /// Palette variant name used by this layer's objects on indexed tilesets. None = base colors.
+    #[serde(default)]
+    pub palette_variant: Option<String>,
+}
+
+impl Layer {
+    /// Palette variant an object draws with: its own override, else the layer's.
+    pub fn object_variant<'a>(&'a self, obj: &'a Object) -> Option<&'a str> {
+        obj.palette_variant.as_deref().or(self.palette_variant.as_deref())
+    }
 }
 
 impl Scene {
@@ -108,12 +124,45 @@ impl Scene {
         (total, objects_with)
     }
 
+    /// Tileset indices referenced by any object or face.
+    pub fn used_tilesets(&self) -> std::collections::HashSet<usize> {
+        let mut used = std::collections::HashSet::new();
+        for obj in self.layers.iter().flat_map(|l| &l.objects) {
+            used.extend(obj.tileset_index);
+            used.extend(obj.faces.iter().filter_map(|f| f.tileset));
+        }
+        used
+    }
+
+    /// Rewrite object and face tileset references after the tileset list changed
+    /// (`remap` returns None for removed tilesets). Objects whose faces referenced a
+    /// tileset are queued for a full GPU re-upload, since the index is baked into their vertices.
+    pub fn remap_tileset_refs(&mut self, remap: impl Fn(usize) -> Option<usize>) {
+        for layer in &mut self.layers {
+            for obj in &mut layer.objects {
+                obj.tileset_index = obj.tileset_index.and_then(&remap);
+                let mut touched = false;
+                for face in &mut obj.faces {
+                    if let Some(ti) = face.tileset {
+                        face.tileset = remap(ti);
+                        touched = true;
+                    }
+                }
+                if touched {
+                    obj.mark_faces_dirty_from(0);
+                }
+            }
+        }
+        self.atlas_dirty = true;
+    }
+
     pub fn new() -> Self {
         Self {
             layers: vec![Layer {
                 name: "Layer 1".to_string(),
                 visible: true,
                 objects: Vec::new(),
+                palette_variant: None,
             }],
             crosshair_pos: Vec3::ZERO,
             grid_cell_size: 1.0,
@@ -121,12 +170,13 @@ impl Scene {
             active_layer: 0,
             tilesets: Vec::new(),
             active_tileset: None,
-            dirty_objects: Vec::new(),
             prefabs: Vec::new(),
             active_prefab: None,
             skeleton: Skeleton::new(),
             palettes: Vec::new(),
             active_palette: None,
+            atlas: None,
+            atlas_dirty: false,
         }
     }
 }
@@ -1,7 +1,9 @@
 use glam::{Mat4, Quat, Vec3};
 use serde::{Serialize, Deserialize};
-use wgpu::util::DeviceExt;
+use crate::render::vertex::{InstanceRaw, Vertex};
+use crate::scene::chunk::{CHUNK_MIN_FACES, MeshChunks};
 use crate::scene::mesh::Face;
+use crate::util::bvh::FaceBvh;
 
 /// A lightweight reference to a source object with an independent transform.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -42,12 +44,48 @@ pub struct Object {
     /// Lightweight instances that re-render this object's geometry with independent transforms.
     #[serde(default)]
     pub instances: Vec<Instance>,
+    /// Palette variant name on indexed tilesets, overriding the layer's. None = inherit.
+    #[serde(default)]
+    pub palette_variant: Option<String>,
+    /// Instance transforms on the GPU. None while the object has no instances.
+    #[serde(skip)]
+    pub gpu_instances: Option<GpuInstances>,
+    /// Face hierarchy for picking and volume queries, built on first use (see `bvh`).
+    #[serde(skip)]
+    pub bvh: std::cell::OnceCell<FaceBvh>,
+    /// Face ranges changed since the last `sync_gpu_mesh`, sorted and non-overlapping.
+    #[serde(skip)]
+    dirty_faces: Vec<std::ops::Range<usize>>,
 }
 
+/// Dirty ranges an object tracks before collapsing them into one span.
+const MAX_DIRTY_RANGES: usize = 32;
+
 pub struct GpuMesh {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub index_count: u32,
+    /// Faces reference more than one tileset, so the mesh samples the scene atlas.
+    pub mixed: bool,
+    /// Local-space bounds of the faces, for frustum culling. Widened by the faces each sync
+    /// uploads and recomputed only when the buffers are rebuilt, so it may be loose after
+    /// faces moved inward or were removed.
+    pub bounds: (Vec3, Vec3),
+    /// Spatial chunks drawn instead of `index_buffer` on large objects. None = draw whole;
+    /// while chunked, `index_buffer` is a placeholder and never written.
+    pub chunks: Option<MeshChunks>,
+    /// Faces the buffers have room for; grown by doubling.
+    capacity: usize,
+}
+
+/// Per-instance transforms for one instanced draw of an object: the object itself (identity)
+/// followed by each of its instances.
+pub struct GpuInstances {
+    pub buffer: wgpu::Buffer,
+    /// Instances the buffer has room for; grown by doubling.
+    capacity: usize,
+    /// Last uploaded contents, so unchanged transforms aren't rewritten every frame.
+    uploaded: Vec<InstanceRaw>,
 }
 
 impl Object {
@@ -58,42 +96,193 @@ impl Object {
             gpu_mesh: None,
             tileset_index: None,
             instances: Vec::new(),
+            palette_variant: None,
+            gpu_instances: None,
+            bvh: std::cell::OnceCell::new(),
+            dirty_faces: Vec::new(),
         }
     }
 
-    /// Rebuild GPU buffers from CPU face data.
-    pub fn rebuild_gpu_mesh(&mut self, device: &wgpu::Device) {
-        if self.faces.is_empty() {
-            self.gpu_mesh = None;
+    /// The face BVH, built on first use. None while it is out of step with `faces` (faces were
+    /// edited since the last `sync_gpu_mesh`); callers then test every face.
+    pub fn bvh(&self) -> Option<&FaceBvh> {
+        if !self.dirty_faces.is_empty() {
+            return None;
+        }
+        let bvh = self.bvh.get_or_init(|| FaceBvh::build(&self.faces));
+        (bvh.face_count() == self.faces.len()).then_some(bvh)
+    }
+
+    /// Tileset a face's UVs refer to: its own override, else the object's.
+    pub fn face_tileset(&self, face: &Face) -> Option<usize> {
+        face.tileset.or(self.tileset_index)
+    }
+
+    /// Whether any face uses a different tileset than the object.
+    pub fn is_mixed(&self) -> bool {
+        self.faces.iter().any(|f| f.tileset.is_some_and(|t| Some(t) != self.tileset_index))
+    }
+
+    /// Queue faces `range` for upload on the next `sync_gpu_mesh`.
+    pub fn mark_faces_dirty(&mut self, range: std::ops::Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        // Keep the list sorted and merged; past a handful of ranges one span is cheaper to
+        // upload than many small writes.
+        let at = self.dirty_faces.partition_point(|r| r.end < range.start);
+        let mut merged = range;
+        while let Some(next) = self.dirty_faces.get(at).filter(|r| r.start <= merged.end) {
+            merged = merged.start.min(next.start)..merged.end.max(next.end);
+            self.dirty_faces.remove(at);
+        }
+        self.dirty_faces.insert(at, merged);
+        if self.dirty_faces.len() > MAX_DIRTY_RANGES {
+            let span = self.dirty_faces[0].start..self.dirty_faces[self.dirty_faces.len() - 1].end;
+            self.dirty_faces = vec![span];
+        }
+    }
+
+    /// Queue one face for upload.
+    pub fn mark_face_dirty(&mut self, fi: usize) {
+        self.mark_faces_dirty(fi..fi + 1);
+    }
+
+    /// Queue every face from `start` on, for insertions and removals that shift later faces.
+    /// `mark_faces_dirty_from(0)` re-uploads the whole object.
+    pub fn mark_faces_dirty_from(&mut self, start: usize) {
+        self.mark_faces_dirty(start..usize::MAX);
+    }
+
+    /// Upload dirty faces into the GPU buffers and bring the face BVH up to date.
+    ///
+    /// Face `i` always occupies vertices `4i..4i+4` and indices `6i..6i+6`, so a dirty range
+    /// maps to one write per buffer. The buffers are reallocated only when the face count
+    /// outgrows their capacity or the tileset slots baked into every vertex change.
+    /// With `chunking`, objects of at least `CHUNK_MIN_FACES` faces are instead drawn through
+    /// spatial chunks that can be culled separately, and the whole-object indices are skipped.
+    pub fn sync_gpu_mesh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, chunking: bool) {
+        let missing = self.gpu_mesh.is_none() != self.faces.is_empty();
+        let wants_chunks = chunking && self.faces.len() >= CHUNK_MIN_FACES;
+        let rechunk = self.gpu_mesh.as_ref().is_some_and(|m| m.chunks.is_some() != wants_chunks);
+        if self.dirty_faces.is_empty() && !missing && !rechunk {
             return;
         }
+        let dirty = std::mem::take(&mut self.dirty_faces);
 
-        let mut vertices = Vec::with_capacity(self.faces.len() * 4);
-        let mut indices = Vec::with_capacity(self.faces.len() * 6);
+        // Keep the BVH in step: refit when faces only moved, otherwise rebuild on next query
+        let refitted = match self.bvh.get_mut() {
+            Some(bvh) if bvh.face_count() == self.faces.len() => bvh.refit(&self.faces),
+            _ => false,
+        };
+        if !refitted {
+            self.bvh.take();
+        }
 
-        for face in &self.faces {
-            if face.hidden { continue; }
-            let base = vertices.len() as u32;
-            vertices.extend_from_slice(&face.vertices());
-            indices.extend_from_slice(&Face::indices(base));
+        if self.faces.is_empty() {
+            self.gpu_mesh = None;
+            return;
         }
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("object_vb"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
+        let mixed = self.is_mixed();
+        let face_count = self.faces.len();
+        // Switching between whole and chunked drawing rebuilds everything, since the whole-object
+        // index buffer is only kept up to date while unchunked
+        let fits = self.gpu_mesh.as_ref().is_some_and(|m| {
+            m.capacity >= face_count && m.mixed == mixed && m.chunks.is_some() == wants_chunks
         });
+        if !fits {
+            let capacity = face_count.next_power_of_two();
+            let buffer = |label, faces: usize, size, usage| device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: (faces * size) as u64,
+                usage: usage | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let index_faces = if wants_chunks { 1 } else { capacity };
+            self.gpu_mesh = Some(GpuMesh {
+                vertex_buffer: buffer("object_vb", capacity, 4 * std::mem::size_of::<Vertex>(), wgpu::BufferUsages::VERTEX),
+                index_buffer: buffer("object_ib", index_faces, 6 * std::mem::size_of::<u32>(), wgpu::BufferUsages::INDEX),
+                index_count: 0,
+                mixed,
+                bounds: (Vec3::MAX, Vec3::MIN),
+                chunks: None,
+                capacity,
+            });
+        }
+        let ranges: Vec<_> = if fits { dirty } else { std::iter::once(0..face_count).collect() };
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("object_ib"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+        let Some(mesh) = &mut self.gpu_mesh else { return };
+        for range in &ranges {
+            let range = range.start.min(face_count)..range.end.min(face_count);
+            if range.is_empty() {
+                continue;
+            }
+            let mut vertices = Vec::with_capacity(range.len() * 4);
+            let mut indices = Vec::with_capacity(if wants_chunks { 0 } else { range.len() * 6 });
+            for (fi, face) in self.faces[range.clone()].iter().enumerate() {
+                let base = ((range.start + fi) * 4) as u32;
+                let slot = match face.tileset.or(self.tileset_index) {
+                    Some(ts) if mixed => ts as u32,
+                    _ => Vertex::NO_TILESET,
+                };
+                vertices.extend_from_slice(&face.vertices(slot));
+                for p in face.positions {
+                    mesh.bounds = (mesh.bounds.0.min(p), mesh.bounds.1.max(p));
+                }
+                if !wants_chunks {
+                    // Hidden faces keep their slot but draw degenerate triangles
+                    indices.extend_from_slice(&if face.hidden { [base; 6] } else { Face::indices(base) });
+                }
+            }
+            let vertex_offset = range.start * 4 * std::mem::size_of::<Vertex>();
+            queue.write_buffer(&mesh.vertex_buffer, vertex_offset as u64, bytemuck::cast_slice(&vertices));
+            if !wants_chunks {
+                let index_offset = range.start * 6 * std::mem::size_of::<u32>();
+                queue.write_buffer(&mesh.index_buffer, index_offset as u64, bytemuck::cast_slice(&indices));
+            }
+        }
+        mesh.index_count = if wants_chunks { 0 } else { (face_count * 6) as u32 };
 
-        self.gpu_mesh = Some(GpuMesh {
-            vertex_buffer,
-            index_buffer,
-            index_count: indices.len() as u32,
-        });
+        if !wants_chunks {
+            mesh.chunks = None;
+        } else if let Some(chunks) = &mut mesh.chunks {
+            chunks.update(&self.faces, &ranges, device, queue);
+        } else {
+            let mut chunks = MeshChunks::default();
+            chunks.update(&self.faces, &[], device, queue);
+            mesh.chunks = Some(chunks);
+        }
+    }
+
+    /// Bring the instance buffer in line with `instances`, reallocating only when it outgrew its
+    /// capacity and writing only when a transform changed.
+    pub fn sync_gpu_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.instances.is_empty() {
+            self.gpu_instances = None;
+            return;
+        }
+        let raw: Vec<InstanceRaw> = std::iter::once(InstanceRaw::IDENTITY)
+            .chain(self.instances.iter().map(|inst| InstanceRaw::from_matrix(inst.model_matrix())))
+            .collect();
+        match &mut self.gpu_instances {
+            Some(gpu) if gpu.capacity >= raw.len() => {
+                if gpu.uploaded != raw {
+                    queue.write_buffer(&gpu.buffer, 0, bytemuck::cast_slice(&raw));
+                    gpu.uploaded = raw;
+                }
+            }
+            _ => {
+                let capacity = raw.len().next_power_of_two();
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("object_instances"),
+                    size: (capacity * std::mem::size_of::<InstanceRaw>()) as u64,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                queue.write_buffer(&buffer, 0, bytemuck::cast_slice(&raw));
+                self.gpu_instances = Some(GpuInstances { buffer, capacity, uploaded: raw });
+            }
+        }
     }
 }
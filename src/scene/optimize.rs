@@ -0,0 +1,320 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::ops::{Add, Mul, Sub};
+
+use glam::{Vec2, Vec3, Vec4};
+use crate::scene::mesh::Face;
+use crate::tile::{Tileset, WrapMode};
+
+/// Scale at which lengths are snapped to compare faces; finer than any grid preset.
+const QUANTIZE: f32 = 1024.0;
+/// Tolerance for a quad being a parallelogram (positions) with matching affine UVs.
+const EPSILON: f32 = 1e-4;
+
+/// How a face's texture continues past its UVs, which decides whether it can grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiling {
+    /// No texture: UVs are free to extend.
+    Untextured,
+    /// The image repeats unpadded, so UVs may step by whole images.
+    Repeat,
+    /// Sampling past the tile would show its neighbours; the face keeps its size.
+    Clamp,
+}
+
+impl Tiling {
+    /// Tiling of faces drawing from `tileset`. `atlas` = the image is packed into a shared
+    /// atlas (mixed-tileset objects, atlas export), where only untextured faces can grow.
+    pub fn of(tileset: Option<&Tileset>, atlas: bool) -> Self {
+        match tileset {
+            None => Tiling::Untextured,
+            Some(ts) if ts.image_data.is_none() => Tiling::Untextured,
+            Some(_) if atlas => Tiling::Clamp,
+            Some(ts) if ts.material.wrap == WrapMode::Repeat && ts.padded_size().is_none() => Tiling::Repeat,
+            Some(_) => Tiling::Clamp,
+        }
+    }
+}
+
+/// Face and vertex counts of a mesh before and after `merge_coplanar`. Vertices are counted
+/// as exporters write them: four per face before, shared corners welded after.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OptimizeReport {
+    pub faces_before: usize,
+    pub faces_after: usize,
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+}
+
+impl OptimizeReport {
+    pub fn new(before: &[Face], after: &[Face]) -> Self {
+        Self {
+            faces_before: before.len(),
+            faces_after: after.len(),
+            vertices_before: before.len() * 4,
+            vertices_after: welded_vertex_count(after),
+        }
+    }
+
+    /// Accumulate another mesh's counts.
+    pub fn add(&mut self, other: Self) {
+        self.faces_before += other.faces_before;
+        self.faces_after += other.faces_after;
+        self.vertices_before += other.vertices_before;
+        self.vertices_after += other.vertices_after;
+    }
+}
+
+impl std::fmt::Display for OptimizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "faces {} → {}, vertices {} → {}",
+            self.faces_before, self.faces_after, self.vertices_before, self.vertices_after,
+        )
+    }
+}
+
+/// Faces that may merge: same plane, edge vectors, lattice phase, color, tileset and UVs.
+#[derive(PartialEq, Eq, Hash)]
+struct GroupKey {
+    edges: [i64; 6],
+    plane: i64,
+    phase: [i64; 2],
+    color: [u32; 4],
+    tileset: Option<usize>,
+    uvs: [u32; 8],
+}
+
+fn quantize(x: f32) -> i64 {
+    (x * QUANTIZE).round() as i64
+}
+
+fn is_whole(x: f32) -> bool {
+    (x - x.round()).abs() < EPSILON
+}
+
+/// Group and lattice cell of a face that can merge with its neighbours: a visible
+/// parallelogram with one color and affine UVs that the face's tiling lets extend. Cells
+/// count edge vectors from the origin, so faces laid out tile by tile land on adjacent cells.
+fn lattice_cell(face: &Face, tiling: Tiling) -> Option<(GroupKey, (i64, i64))> {
+    if face.hidden || tiling == Tiling::Clamp || face.colors.iter().any(|&c| c != face.colors[0]) {
+        return None;
+    }
+    let p = face.positions;
+    let (e0, e1) = (p[1] - p[0], p[3] - p[0]);
+    let n = e0.cross(e1);
+    if n.length_squared() < EPSILON * EPSILON || (p[2] - p[1] - e1).length() > EPSILON {
+        return None;
+    }
+    let uv = face.uvs;
+    let (du, dv) = (uv[1] - uv[0], uv[3] - uv[0]);
+    if (uv[2] - uv[1] - dv).length() > EPSILON {
+        return None;
+    }
+    if tiling == Tiling::Repeat && ![du.x, du.y, dv.x, dv.y].into_iter().all(is_whole) {
+        return None;
+    }
+
+    // Coordinates of the corner in the (e0, e1) basis, via the dual vectors of the plane
+    let (d0, d1) = (e1.cross(n), n.cross(e0));
+    let (i, j) = (p[0].dot(d0) / e0.dot(d0), p[0].dot(d1) / e1.dot(d1));
+    let key = GroupKey {
+        edges: [e0.x, e0.y, e0.z, e1.x, e1.y, e1.z].map(quantize),
+        plane: quantize(n.normalize().dot(p[0])),
+        phase: [quantize(i - i.round()), quantize(j - j.round())],
+        color: face.colors[0].to_array().map(f32::to_bits),
+        tileset: face.tileset,
+        uvs: [uv[0].x, uv[0].y, uv[1].x, uv[1].y, uv[2].x, uv[2].y, uv[3].x, uv[3].y].map(f32::to_bits),
+    };
+    Some((key, (i.round() as i64, j.round() as i64)))
+}
+
+/// Greedily merge runs of adjacent coplanar faces into larger quads, keeping the order of
+/// the faces that survive. Merged faces span `w × h` of the originals with their UVs
+/// extended to match, so textured faces only grow where `tiling` says the image repeats.
+/// Faces that can't merge are copied unchanged. The result may have T-junctions where a
+/// merged edge meets smaller neighbours.
+pub fn merge_coplanar(faces: &[Face], tiling: impl Fn(&Face) -> Tiling) -> Vec<Face> {
+    let mut groups: HashMap<GroupKey, HashMap<(i64, i64), usize>> = HashMap::new();
+    // (index of the first original face, face), sorted back into order at the end
+    let mut out: Vec<(usize, Face)> = Vec::with_capacity(faces.len());
+    for (fi, face) in faces.iter().enumerate() {
+        let Some((key, cell)) = lattice_cell(face, tiling(face)) else {
+            out.push((fi, face.clone()));
+            continue;
+        };
+        match groups.entry(key).or_default().entry(cell) {
+            // Duplicate face on the same cell
+            Entry::Occupied(_) => out.push((fi, face.clone())),
+            Entry::Vacant(slot) => { slot.insert(fi); }
+        }
+    }
+
+    for cells in groups.into_values() {
+        let mut order: Vec<(i64, i64)> = cells.keys().copied().collect();
+        order.sort_unstable_by_key(|&(i, j)| (j, i));
+        let mut used: HashSet<(i64, i64)> = HashSet::with_capacity(cells.len());
+        let free = |cell: (i64, i64), used: &HashSet<(i64, i64)>| cells.contains_key(&cell) && !used.contains(&cell);
+        for (i, j) in order {
+            if used.contains(&(i, j)) { continue; }
+            // Grow along e0 first, then add whole rows along e1
+            let mut w = 1;
+            while free((i + w, j), &used) { w += 1; }
+            let mut h = 1;
+            while (i..i + w).all(|ci| free((ci, j + h), &used)) { h += 1; }
+            for cj in j..j + h {
+                for ci in i..i + w {
+                    used.insert((ci, cj));
+                }
+            }
+
+            let seed = &faces[cells[&(i, j)]];
+            let first = (j..j + h).flat_map(|cj| (i..i + w).map(move |ci| (ci, cj))).map(|c| cells[&c]).min().unwrap_or(0);
+            out.push((first, stretch(seed, w as f32, h as f32)));
+        }
+    }
+
+    out.sort_by_key(|(fi, _)| *fi);
+    out.into_iter().map(|(_, f)| f).collect()
+}
+
+/// A face grown to `w × h` copies of itself along its first and last edges.
+fn stretch(face: &Face, w: f32, h: f32) -> Face {
+    fn grow<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>>(c: [T; 4], w: f32, h: f32) -> [T; 4] {
+        let (e0, e1) = ((c[1] - c[0]) * w, (c[3] - c[0]) * h);
+        [c[0], c[0] + e0, c[0] + e0 + e1, c[0] + e1]
+    }
+    let mut out = face.clone();
+    out.positions = grow(face.positions, w, h);
+    out.uvs = grow(face.uvs, w, h);
+    out
+}
+
+/// Identity of a vertex for welding: exact position, UV and color.
+pub fn vertex_key(position: Vec3, uv: Vec2, color: Vec4) -> [u32; 9] {
+    [position.x, position.y, position.z, uv.x, uv.y, color.x, color.y, color.z, color.w].map(f32::to_bits)
+}
+
+/// Vertices left once corners with identical position, UV and color are shared.
+pub fn welded_vertex_count(faces: &[Face]) -> usize {
+    faces.iter()
+        .flat_map(|f| (0..4).map(move |c| vertex_key(f.positions[c], f.uvs[c], f.colors[c])))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIT_UVS: [Vec2; 4] = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)];
+
+    /// Unit floor tile with its corner at (x, 0, z).
+    fn tile(x: f32, z: f32) -> Face {
+        let p = Vec3::new(x, 0.0, z);
+        Face {
+            positions: [p, p + Vec3::X, p + Vec3::X + Vec3::Z, p + Vec3::Z],
+            uvs: UNIT_UVS,
+            colors: [Vec4::ONE; 4],
+            hidden: false,
+            tileset: None,
+        }
+    }
+
+    fn untextured(faces: &[Face]) -> Vec<Face> {
+        merge_coplanar(faces, |_| Tiling::Untextured)
+    }
+
+    #[test]
+    fn adjacent_tiles_merge_into_one_stretched_face() {
+        let faces: Vec<Face> = (0..2).flat_map(|z| (0..3).map(move |x| tile(x as f32, z as f32))).collect();
+        let merged = untextured(&faces);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].positions, [Vec3::ZERO, Vec3::new(3.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 2.0), Vec3::new(0.0, 0.0, 2.0)]);
+        assert_eq!(merged[0].uvs, [Vec2::ZERO, Vec2::new(3.0, 0.0), Vec2::new(3.0, 2.0), Vec2::new(0.0, 2.0)]);
+
+        let report = OptimizeReport::new(&faces, &merged);
+        assert_eq!((report.faces_before, report.faces_after), (6, 1));
+        assert_eq!((report.vertices_before, report.vertices_after), (24, 4));
+    }
+
+    #[test]
+    fn faces_off_the_lattice_or_with_other_uvs_or_colors_stay_apart() {
+        // Half a tile out of phase
+        assert_eq!(untextured(&[tile(0.0, 0.0), tile(1.0, 0.0), tile(2.5, 0.0)]).len(), 2);
+
+        let mut flipped = tile(1.0, 0.0);
+        flipped.uvs = [UNIT_UVS[1], UNIT_UVS[0], UNIT_UVS[3], UNIT_UVS[2]];
+        assert_eq!(untextured(&[tile(0.0, 0.0), flipped]).len(), 2);
+
+        let mut red = tile(1.0, 0.0);
+        red.colors = [Vec4::new(1.0, 0.0, 0.0, 1.0); 4];
+        assert_eq!(untextured(&[tile(0.0, 0.0), red]).len(), 2);
+
+        // One corner off-color: the face can't grow at all
+        let mut shaded = tile(1.0, 0.0);
+        shaded.colors[2] = Vec4::ZERO;
+        assert_eq!(untextured(&[tile(0.0, 0.0), shaded, tile(2.0, 0.0)]).len(), 3);
+    }
+
+    #[test]
+    fn repeating_textures_only_grow_by_whole_images() {
+        let whole = [tile(0.0, 0.0), tile(1.0, 0.0)];
+        assert_eq!(merge_coplanar(&whole, |_| Tiling::Repeat).len(), 1);
+
+        let half: Vec<Face> = whole.iter().cloned().map(|mut f| { f.uvs = f.uvs.map(|uv| uv * 0.5); f }).collect();
+        assert_eq!(merge_coplanar(&half, |_| Tiling::Repeat).len(), 2);
+        assert_eq!(untextured(&half).len(), 1);
+    }
+
+    #[test]
+    fn clamped_faces_keep_their_size() {
+        let faces = [tile(0.0, 0.0), tile(1.0, 0.0), tile(2.0, 0.0)];
+        let merged = merge_coplanar(&faces, |_| Tiling::Clamp);
+        assert_eq!(merged.len(), 3);
+        for (a, b) in faces.iter().zip(&merged) {
+            assert_eq!(a.positions, b.positions);
+            assert_eq!(a.uvs, b.uvs);
+        }
+
+        // A clamped face between two growable ones splits the run
+        let mut mixed = faces.clone();
+        mixed[1].tileset = Some(1);
+        let merged = merge_coplanar(&mixed, |f| if f.tileset.is_some() { Tiling::Clamp } else { Tiling::Untextured });
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1].positions, mixed[1].positions);
+    }
+
+    #[test]
+    fn duplicate_faces_on_one_cell_are_kept() {
+        let merged = untextured(&[tile(0.0, 0.0), tile(0.0, 0.0), tile(1.0, 0.0)]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].positions[1], Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(merged[1].positions, tile(0.0, 0.0).positions);
+    }
+
+    #[test]
+    fn output_follows_the_order_of_the_first_original_face() {
+        let mut hidden = tile(5.0, 5.0);
+        hidden.hidden = true;
+        let mut last = tile(-3.0, 0.0);
+        last.hidden = true;
+        let merged = untextured(&[hidden.clone(), tile(1.0, 0.0), tile(0.0, 0.0), last.clone()]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].positions, hidden.positions);
+        assert_eq!(merged[1].positions[0], Vec3::ZERO);
+        assert_eq!(merged[1].positions[2], Vec3::new(2.0, 0.0, 1.0));
+        assert_eq!(merged[2].positions, last.positions);
+    }
+
+    #[test]
+    fn report_counts_welded_vertices_and_adds_up() {
+        let faces = [tile(0.0, 0.0), tile(5.0, 0.0)];
+        let merged = untextured(&faces);
+        let mut report = OptimizeReport::new(&faces, &merged);
+        assert_eq!((report.faces_after, report.vertices_after), (2, 8));
+        report.add(OptimizeReport::new(&faces, &faces));
+        assert_eq!((report.faces_before, report.faces_after), (4, 4));
+        assert_eq!((report.vertices_before, report.vertices_after), (16, 16));
+        assert_eq!(report.to_string(), "faces 4 → 4, vertices 16 → 16");
+    }
+}
@@ -8,6 +8,7 @@ pub struct Settings {
     pub display: DisplaySettings,
     pub draw: DrawSettings,
     pub edit: EditSettings,
+    pub file: FileSettings,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -53,6 +54,8 @@ pub struct DisplaySettings {
     pub preview_color: [f32; 4],
     pub vertex_size: f32,
     pub undo_limit: usize,
+    /// Split objects with many faces into spatial chunks so off-screen parts aren't drawn.
+    pub chunk_large_objects: bool,
 }
 
 impl Default for DisplaySettings {
@@ -68,6 +71,7 @@ impl Default for DisplaySettings {
             preview_color: [0.3, 1.0, 0.5, 1.0],
             vertex_size: 0.15,
             undo_limit: 100,
+            chunk_large_objects: true,
         }
     }
 }
@@ -108,6 +112,64 @@ impl Default for EditSettings {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FileSettings {
+    pub autosave_enabled: bool,
+    /// Minutes between autosave snapshots.
+    pub autosave_interval_minutes: f32,
+    /// Number of snapshots kept in the recovery directory.
+    pub autosave_keep: usize,
+    /// glTF/GLB export: write instances as nodes referencing one mesh per object.
+    pub gltf_keep_instances: bool,
+    /// glTF/GLB export: use EXT_mesh_gpu_instancing for objects with many instances.
+    pub gltf_gpu_instancing: bool,
+    /// OBJ/glTF export: write tilesets with edge extrusion as their padded copy, with UVs remapped.
+    pub export_padded_atlas: bool,
+    /// OBJ/glTF export: pack all tilesets into one atlas texture and material.
+    pub export_tileset_atlas: bool,
+    /// OBJ/glTF export: merge coplanar same-tile faces and weld shared vertices.
+    pub export_optimize_meshes: bool,
+    /// Reload tileset images when their source file changes on disk.
+    pub tileset_hot_reload: bool,
+}
+
+impl Default for FileSettings {
+    fn default() -> Self {
+        Self {
+            autosave_enabled: true,
+            autosave_interval_minutes: 2.0,
+            autosave_keep: 5,
+            gltf_keep_instances: false,
+            gltf_gpu_instancing: false,
+            export_padded_atlas: false,
+            export_tileset_atlas: false,
+            export_optimize_meshes: false,
+            tileset_hot_reload: true,
+        }
+    }
+}
+
+impl FileSettings {
+    pub fn gltf_export_options(&self) -> crate::io::GltfExportOptions {
+        crate::io::GltfExportOptions {
+            keep_instances: self.gltf_keep_instances,
+            gpu_instancing: self.gltf_gpu_instancing,
+            padded_atlas: self.export_padded_atlas,
+            tileset_atlas: self.export_tileset_atlas,
+            optimize: self.export_optimize_meshes,
+        }
+    }
+
+    pub fn obj_export_options(&self) -> crate::io::ObjExportOptions {
+        crate::io::ObjExportOptions {
+            padded_atlas: self.export_padded_atlas,
+            tileset_atlas: self.export_tileset_atlas,
+            optimize: self.export_optimize_meshes,
+        }
+    }
+}
+
 impl Settings {
     /// Load settings from config file. Falls back to defaults on error.
     pub fn load() -> Self {
@@ -145,4 +207,5 @@ pub enum SettingsTab {
     Display,
     Draw,
     Edit,
+    File,
 }
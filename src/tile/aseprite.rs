@@ -0,0 +1,555 @@
+//! Native Aseprite (.ase/.aseprite) reader.
+//!
+//! Decodes layers, cels (raw, linked, compressed and tilemap), palettes and tilesets, and
+//! composites one frame into the straight RGBA buffer `Tileset` expects. Layer blend modes other
+//! than Normal are composited as Normal.
+
+use std::path::Path;
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_TILESET: u16 = 0x2023;
+
+const LAYER_VISIBLE: u16 = 1;
+const HEADER_LAYER_OPACITY_VALID: u32 = 1;
+
+/// Which part of an Aseprite file to flatten into a tileset image.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AsepriteSelection {
+    pub frame: usize,
+    /// Flatten only this layer (even if hidden). None = all visible layers.
+    pub layer: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayerKind {
+    Image,
+    Group,
+    Tilemap { tileset: usize },
+}
+
+pub struct Layer {
+    pub name: String,
+    pub kind: LayerKind,
+    /// Visible, taking hidden parent groups into account.
+    pub visible: bool,
+    /// Nesting depth under groups (0 = top level).
+    pub depth: u16,
+    opacity: u8,
+}
+
+enum CelContent {
+    /// Straight RGBA pixels.
+    Image { width: u32, height: u32, pixels: Vec<u8> },
+    Tilemap { width: u32, height: u32, tiles: Vec<u32>, id_mask: u32, flip_x: u32, flip_y: u32, flip_d: u32 },
+    /// Same content as this layer's cel in another frame.
+    Linked(usize),
+}
+
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    z_index: i16,
+    content: CelContent,
+}
+
+struct AseTileset {
+    tile_width: u32,
+    tile_height: u32,
+    /// All tiles stacked vertically, straight RGBA.
+    pixels: Vec<u8>,
+    count: u32,
+}
+
+/// A decoded Aseprite file.
+pub struct AsepriteFile {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<Layer>,
+    /// Cels per frame.
+    frames: Vec<Vec<Cel>>,
+    /// Grid size from the sprite header (0 if unset).
+    grid: (u32, u32),
+    tilesets: Vec<Option<AseTileset>>,
+    layer_opacity_valid: bool,
+}
+
+impl AsepriteFile {
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Tile size for a tileset made from this sprite: the tile size of its first Aseprite
+    /// tileset if it has one, else the sprite grid, else 16x16.
+    pub fn tile_size(&self) -> (u32, u32) {
+        if let Some(ts) = self.tilesets.iter().flatten().next() {
+            return (ts.tile_width, ts.tile_height);
+        }
+        if self.grid.0 > 0 && self.grid.1 > 0 { self.grid } else { (16, 16) }
+    }
+
+    /// Composite a frame (one layer, or every visible layer) into straight RGBA.
+    pub fn flatten(&self, selection: AsepriteSelection) -> Result<Vec<u8>, String> {
+        let frame = self.frames.get(selection.frame)
+            .ok_or_else(|| format!("Frame {} out of range (sprite has {})", selection.frame + 1, self.frames.len()))?;
+        let mut canvas = vec![0u8; rgba_len(self.width, self.height, 1)?];
+
+        let mut cels: Vec<&Cel> = frame.iter()
+            .filter(|c| match selection.layer {
+                Some(l) => c.layer == l,
+                None => self.layers.get(c.layer).is_some_and(|l| l.visible),
+            })
+            .collect();
+        // Aseprite orders cels by layer index + z-index, breaking ties by z-index.
+        cels.sort_by_key(|c| (c.layer as i32 + c.z_index as i32, c.z_index));
+
+        for cel in cels {
+            let Some(layer) = self.layers.get(cel.layer) else { continue };
+            // A linked cel shows the source cel's content at the source cel's position.
+            let source = match cel.content {
+                CelContent::Linked(frame) => self.frames.get(frame)
+                    .and_then(|cels| cels.iter().find(|c| c.layer == cel.layer)),
+                _ => Some(cel),
+            };
+            let Some(source) = source else { continue };
+            let layer_opacity = if self.layer_opacity_valid { layer.opacity as u32 } else { 255 };
+            let opacity = cel.opacity as u32 * layer_opacity / 255;
+            match &source.content {
+                CelContent::Image { width, height, pixels } => {
+                    self.blit(&mut canvas, pixels, *width, *height, source.x, source.y, opacity);
+                }
+                CelContent::Tilemap { width, height, tiles, id_mask, flip_x, flip_y, flip_d } => {
+                    let LayerKind::Tilemap { tileset } = layer.kind else { continue };
+                    let Some(Some(ts)) = self.tilesets.get(tileset) else { continue };
+                    let (tw, th) = (ts.tile_width, ts.tile_height);
+                    let mut tile = vec![0u8; rgba_len(tw, th, 1)?];
+                    for (i, &value) in tiles.iter().enumerate() {
+                        let id = value & id_mask;
+                        // Tile 0 is always the empty tile.
+                        if id == 0 || id >= ts.count { continue; }
+                        let flags = (value & flip_x != 0, value & flip_y != 0, value & flip_d != 0);
+                        if flags.2 && tw != th { continue; }
+                        for ty in 0..th {
+                            for tx in 0..tw {
+                                let (mut sx, mut sy) = (tx, ty);
+                                if flags.2 { std::mem::swap(&mut sx, &mut sy); }
+                                if flags.0 { sx = tw - 1 - sx; }
+                                if flags.1 { sy = th - 1 - sy; }
+                                let src = ((id as usize * th as usize + sy as usize) * tw as usize + sx as usize) * 4;
+                                let dst = (ty as usize * tw as usize + tx as usize) * 4;
+                                tile[dst..dst + 4].copy_from_slice(&ts.pixels[src..src + 4]);
+                            }
+                        }
+                        let (col, row) = (i as u32 % width.max(&1), i as u32 / width.max(&1));
+                        if row >= *height { break; }
+                        let x = source.x + (col * tw) as i32;
+                        let y = source.y + (row * th) as i32;
+                        self.blit(&mut canvas, &tile, tw, th, x, y, opacity);
+                    }
+                }
+                CelContent::Linked(_) => {}
+            }
+        }
+        Ok(canvas)
+    }
+
+    /// Alpha-composite a straight-RGBA image over the canvas ("normal" blend).
+    #[allow(clippy::too_many_arguments)]
+    fn blit(&self, canvas: &mut [u8], pixels: &[u8], width: u32, height: u32, x: i32, y: i32, opacity: u32) {
+        for sy in 0..height as i32 {
+            let dy = y + sy;
+            if dy < 0 || dy >= self.height as i32 { continue; }
+            for sx in 0..width as i32 {
+                let dx = x + sx;
+                if dx < 0 || dx >= self.width as i32 { continue; }
+                let s = (sy as usize * width as usize + sx as usize) * 4;
+                let d = (dy as usize * self.width as usize + dx as usize) * 4;
+                let sa = pixels[s + 3] as u32 * opacity / 255;
+                if sa == 0 { continue; }
+                let da = canvas[d + 3] as u32;
+                let out_a = sa + da * (255 - sa) / 255;
+                for c in 0..3 {
+                    let sc = pixels[s + c] as u32;
+                    let dc = canvas[d + c] as u32;
+                    canvas[d + c] = ((sc * sa + dc * da * (255 - sa) / 255) / out_a.max(1)) as u8;
+                }
+                canvas[d + 3] = out_a as u8;
+            }
+        }
+    }
+}
+
+/// Read and decode an Aseprite file.
+pub fn read(path: &Path) -> Result<AsepriteFile, String> {
+    let data = std::fs::read(path)
+        .map_err(|e| format!("Read failed: {e}"))?;
+    parse(&data)
+}
+
+fn parse(data: &[u8]) -> Result<AsepriteFile, String> {
+    let mut header = Reader { data, pos: 0 };
+    header.u32()?; // file size
+    if header.u16()? != HEADER_MAGIC {
+        return Err("Not an Aseprite file (bad magic)".to_string());
+    }
+    let frame_count = header.u16()? as usize;
+    let width = header.u16()? as u32;
+    let height = header.u16()? as u32;
+    let depth = header.u16()?;
+    let flags = header.u32()?;
+    header.skip(2 + 4 + 4)?; // speed, reserved
+    let transparent_index = header.u8()?;
+    header.skip(3)?;
+    let color_count = header.u16()? as usize;
+    header.skip(2 + 4)?; // pixel ratio, grid origin
+    let grid = (header.u16()? as u32, header.u16()? as u32);
+    if !matches!(depth, 8 | 16 | 32) {
+        return Err(format!("Unsupported Aseprite color depth {depth}"));
+    }
+
+    let mut file = AsepriteFile {
+        width,
+        height,
+        layers: Vec::new(),
+        frames: Vec::with_capacity(frame_count),
+        grid,
+        tilesets: Vec::new(),
+        layer_opacity_valid: flags & HEADER_LAYER_OPACITY_VALID != 0,
+    };
+    let mut palette = vec![[0u8, 0, 0, 255]; color_count.max(256)];
+    let mut has_new_palette = false;
+    // Stack of (depth, visible) for the enclosing groups of the next layer.
+    let mut group_visibility: Vec<(u16, bool)> = Vec::new();
+
+    let mut pos = 128;
+    for _ in 0..frame_count {
+        let mut frame = Reader { data, pos };
+        let frame_size = frame.u32()? as usize;
+        if frame.u16()? != FRAME_MAGIC {
+            return Err("Corrupt Aseprite frame header".to_string());
+        }
+        let old_chunks = frame.u16()? as usize;
+        frame.skip(2 + 2)?; // duration, reserved
+        let new_chunks = frame.u32()? as usize;
+        let chunk_count = if new_chunks == 0 { old_chunks } else { new_chunks };
+
+        let mut cels = Vec::new();
+        for _ in 0..chunk_count {
+            let chunk_start = frame.pos;
+            let chunk_size = frame.u32()? as usize;
+            let chunk_type = frame.u16()?;
+            let end = chunk_start + chunk_size;
+            let mut chunk = Reader { data: data.get(..end).ok_or("Truncated Aseprite chunk")?, pos: frame.pos };
+            match chunk_type {
+                CHUNK_LAYER => {
+                    let layer_flags = chunk.u16()?;
+                    let kind = chunk.u16()?;
+                    let depth = chunk.u16()?;
+                    chunk.skip(2 + 2 + 2)?; // default size, blend mode
+                    let opacity = chunk.u8()?;
+                    chunk.skip(3)?;
+                    let name = chunk.string()?;
+                    let kind = match kind {
+                        1 => LayerKind::Group,
+                        2 => LayerKind::Tilemap { tileset: chunk.u32()? as usize },
+                        _ => LayerKind::Image,
+                    };
+                    group_visibility.retain(|(d, _)| *d < depth);
+                    let visible = layer_flags & LAYER_VISIBLE != 0 && group_visibility.iter().all(|(_, v)| *v);
+                    if kind == LayerKind::Group {
+                        group_visibility.push((depth, visible));
+                    }
+                    file.layers.push(Layer { name, kind, visible, depth, opacity });
+                }
+                CHUNK_CEL => {
+                    let cel = read_cel(&mut chunk, depth, &palette, transparent_index)?;
+                    cels.push(cel);
+                }
+                CHUNK_PALETTE => {
+                    let size = chunk.u32()? as usize;
+                    let first = chunk.u32()? as usize;
+                    let last = chunk.u32()? as usize;
+                    chunk.skip(8)?;
+                    if palette.len() < size { palette.resize(size, [0, 0, 0, 255]); }
+                    for entry in palette.iter_mut().take(last + 1).skip(first) {
+                        let entry_flags = chunk.u16()?;
+                        let rgba = chunk.bytes(4)?;
+                        *entry = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                        if entry_flags & 1 != 0 { chunk.string()?; }
+                    }
+                    has_new_palette = true;
+                }
+                CHUNK_OLD_PALETTE if !has_new_palette => {
+                    let packets = chunk.u16()?;
+                    let mut index = 0usize;
+                    for _ in 0..packets {
+                        index += chunk.u8()? as usize;
+                        let count = match chunk.u8()? { 0 => 256, n => n as usize };
+                        for _ in 0..count {
+                            let rgb = chunk.bytes(3)?;
+                            if let Some(entry) = palette.get_mut(index) {
+                                *entry = [rgb[0], rgb[1], rgb[2], 255];
+                            }
+                            index += 1;
+                        }
+                    }
+                }
+                CHUNK_TILESET => {
+                    let id = chunk.u32()? as usize;
+                    let tileset_flags = chunk.u32()?;
+                    let count = chunk.u32()?;
+                    let tile_width = chunk.u16()? as u32;
+                    let tile_height = chunk.u16()? as u32;
+                    chunk.skip(2 + 14)?; // base index, reserved
+                    chunk.string()?;
+                    if tileset_flags & 1 != 0 { chunk.skip(8)?; } // external file link
+                    let pixels = if tileset_flags & 2 != 0 {
+                        let len = chunk.u32()? as usize;
+                        let raw = inflate(chunk.bytes(len)?)?;
+                        to_rgba(&raw, depth, &palette, transparent_index, rgba_len(tile_width, tile_height, count)? / 4)?
+                    } else {
+                        log::warn!("Aseprite tileset {id} is stored externally; its tiles will be blank");
+                        vec![0; rgba_len(tile_width, tile_height, count)?]
+                    };
+                    if file.tilesets.len() <= id { file.tilesets.resize_with(id + 1, || None); }
+                    file.tilesets[id] = Some(AseTileset { tile_width, tile_height, pixels, count });
+                }
+                _ => {}
+            }
+            frame.pos = end;
+        }
+        file.frames.push(cels);
+        pos += frame_size;
+    }
+    Ok(file)
+}
+
+fn read_cel(chunk: &mut Reader, depth: u16, palette: &[[u8; 4]], transparent_index: u8) -> Result<Cel, String> {
+    let layer = chunk.u16()? as usize;
+    let x = chunk.i16()? as i32;
+    let y = chunk.i16()? as i32;
+    let opacity = chunk.u8()?;
+    let cel_type = chunk.u16()?;
+    let z_index = chunk.i16()?;
+    chunk.skip(5)?;
+    let content = match cel_type {
+        0 | 2 => {
+            let width = chunk.u16()? as u32;
+            let height = chunk.u16()? as u32;
+            let rest = chunk.rest();
+            let raw = if cel_type == 2 { inflate(rest)? } else { rest.to_vec() };
+            let pixels = to_rgba(&raw, depth, palette, transparent_index, width as usize * height as usize)?;
+            CelContent::Image { width, height, pixels }
+        }
+        1 => CelContent::Linked(chunk.u16()? as usize),
+        3 => {
+            let width = chunk.u16()? as u32;
+            let height = chunk.u16()? as u32;
+            let bits = chunk.u16()?;
+            let id_mask = chunk.u32()?;
+            let flip_x = chunk.u32()?;
+            let flip_y = chunk.u32()?;
+            let flip_d = chunk.u32()?;
+            chunk.skip(10)?;
+            if bits != 32 {
+                return Err(format!("Unsupported Aseprite tilemap with {bits} bits per tile"));
+            }
+            let raw = inflate(chunk.rest())?;
+            let tiles = raw.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+            CelContent::Tilemap { width, height, tiles, id_mask, flip_x, flip_y, flip_d }
+        }
+        other => return Err(format!("Unknown Aseprite cel type {other}")),
+    };
+    Ok(Cel { layer, x, y, opacity, z_index, content })
+}
+
+/// Byte length of `count` straight-RGBA images of `width × height`, checked since every
+/// factor comes from the file.
+fn rgba_len(width: u32, height: u32, count: u32) -> Result<usize, String> {
+    (width as usize).checked_mul(height as usize)
+        .and_then(|n| n.checked_mul(count as usize))
+        .and_then(|n| n.checked_mul(4))
+        .ok_or_else(|| format!("Aseprite image of {count} × {width}x{height} pixels is too large"))
+}
+
+/// Convert `count` pixels of the sprite's color depth to straight RGBA.
+fn to_rgba(raw: &[u8], depth: u16, palette: &[[u8; 4]], transparent_index: u8, count: usize) -> Result<Vec<u8>, String> {
+    let bpp = depth as usize / 8;
+    if raw.len() < count * bpp {
+        return Err("Truncated Aseprite pixel data".to_string());
+    }
+    let mut out = Vec::with_capacity(count * 4);
+    for px in raw[..count * bpp].chunks_exact(bpp) {
+        match depth {
+            32 => out.extend_from_slice(px),
+            16 => out.extend_from_slice(&[px[0], px[0], px[0], px[1]]),
+            _ if px[0] == transparent_index => out.extend_from_slice(&[0, 0, 0, 0]),
+            _ => out.extend_from_slice(palette.get(px[0] as usize).unwrap_or(&[0, 0, 0, 0])),
+        }
+    }
+    Ok(out)
+}
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    miniz_oxide::inflate::decompress_to_vec_zlib(data)
+        .map_err(|e| format!("Aseprite decompression failed: {e:?}"))
+}
+
+/// Little-endian cursor.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let out = self.data.get(self.pos..self.pos + len).ok_or("Unexpected end of Aseprite data")?;
+        self.pos += len;
+        Ok(out)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let out = self.data.get(self.pos..).unwrap_or(&[]);
+        self.pos = self.data.len();
+        out
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|v| v as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn chunk(chunk_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 6) as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(&chunk_type.to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn layer(name: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        for v in [LAYER_VISIBLE, 0, 0, 0, 0, 0] {
+            body.extend_from_slice(&v.to_le_bytes()); // flags, type, depth, default size, blend
+        }
+        body.extend_from_slice(&[255, 0, 0, 0]); // opacity, reserved
+        body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        body.extend_from_slice(name.as_bytes());
+        chunk(CHUNK_LAYER, &body)
+    }
+
+    fn cel(layer: u16, x: i16, y: i16, cel_type: u16, content: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&layer.to_le_bytes());
+        body.extend_from_slice(&x.to_le_bytes());
+        body.extend_from_slice(&y.to_le_bytes());
+        body.push(255);
+        body.extend_from_slice(&cel_type.to_le_bytes());
+        body.extend_from_slice(&[0; 2 + 5]); // z-index, reserved
+        body.extend_from_slice(content);
+        chunk(CHUNK_CEL, &body)
+    }
+
+    fn image(width: u16, height: u16, color: [u8; 4], compressed: bool) -> Vec<u8> {
+        let pixels: Vec<u8> = std::iter::repeat_n(color, width as usize * height as usize).flatten().collect();
+        let mut out = [width.to_le_bytes(), height.to_le_bytes()].concat();
+        if compressed {
+            out.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(&pixels, 6));
+        } else {
+            out.extend_from_slice(&pixels);
+        }
+        out
+    }
+
+    fn frame(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut out = ((body.len() + 16) as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(&FRAME_MAGIC.to_le_bytes());
+        out.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[100, 0, 0, 0]); // duration, reserved
+        out.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    /// A 2x2 RGBA sprite with two layers: frame 0 has a raw red cel filling "Base" and a
+    /// compressed blue pixel at (1, 1) on "Top"; frame 1 links "Base" back to frame 0.
+    fn sample() -> Vec<u8> {
+        let frames = [
+            frame(&[layer("Base"), layer("Top"), cel(0, 0, 0, 0, &image(2, 2, RED, false)), cel(1, 1, 1, 2, &image(1, 1, BLUE, true))]),
+            frame(&[cel(0, 0, 0, 1, &0u16.to_le_bytes())]),
+        ];
+        let mut header = vec![0u8; 128];
+        header[4..6].copy_from_slice(&HEADER_MAGIC.to_le_bytes());
+        header[6..8].copy_from_slice(&(frames.len() as u16).to_le_bytes());
+        header[8..10].copy_from_slice(&2u16.to_le_bytes()); // width
+        header[10..12].copy_from_slice(&2u16.to_le_bytes()); // height
+        header[12..14].copy_from_slice(&32u16.to_le_bytes()); // depth
+        header[14..18].copy_from_slice(&HEADER_LAYER_OPACITY_VALID.to_le_bytes());
+        let mut data = [header, frames.concat()].concat();
+        let len = data.len() as u32;
+        data[0..4].copy_from_slice(&len.to_le_bytes());
+        data
+    }
+
+    fn pixels(colors: [[u8; 4]; 4]) -> Vec<u8> {
+        colors.concat()
+    }
+
+    #[test]
+    fn raw_compressed_and_linked_cels_composite() {
+        let file = parse(&sample()).unwrap();
+        assert_eq!((file.width, file.height, file.frame_count(), file.layers.len()), (2, 2, 2, 2));
+
+        let all = file.flatten(AsepriteSelection { frame: 0, layer: None }).unwrap();
+        assert_eq!(all, pixels([RED, RED, RED, BLUE]));
+        let top = file.flatten(AsepriteSelection { frame: 0, layer: Some(1) }).unwrap();
+        assert_eq!(top, pixels([[0; 4], [0; 4], [0; 4], BLUE]));
+        let linked = file.flatten(AsepriteSelection { frame: 1, layer: None }).unwrap();
+        assert_eq!(linked, pixels([RED; 4]));
+    }
+
+    #[test]
+    fn truncated_files_are_errors() {
+        let data = sample();
+        for len in 0..data.len() {
+            assert!(parse(&data[..len]).is_err(), "file cut to {len} bytes parsed");
+        }
+    }
+}
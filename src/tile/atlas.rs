@@ -0,0 +1,288 @@
+//! Scene-wide texture atlas packing every tileset, used by objects that mix tilesets.
+
+use glam::Vec2;
+use image::ImageEncoder;
+use wgpu::util::DeviceExt;
+use super::tileset::{FilterMode, Tileset};
+
+/// Most tilesets the atlas lookup table (and so a mixed object) can address.
+pub const MAX_ATLAS_TILESETS: usize = 64;
+
+/// Byte size of the shader's AtlasTable uniform (info + white + one rect per tileset).
+pub const ATLAS_TABLE_SIZE: usize = 16 + 16 + MAX_ATLAS_TILESETS * 16;
+
+/// Largest atlas edge, in pixels.
+const MAX_ATLAS_SIZE: u32 = 8192;
+
+/// Pixels of each image's border repeated around it, so filtering never bleeds between tilesets.
+const GUTTER: u32 = 2;
+
+/// Edge of the solid white block sampled by faces that have no tileset.
+const WHITE_SIZE: u32 = 2;
+
+/// All tileset images packed into one texture, with each tileset's placement.
+pub struct TilesetAtlas {
+    pub width: u32,
+    pub height: u32,
+    /// UV offset (xy) and scale (zw) of each tileset, indexed like Scene.tilesets.
+    /// Tilesets without pixel data map onto the white block.
+    pub rects: Vec<[f32; 4]>,
+    /// UV of a white texel.
+    pub white: Vec2,
+    pub image_data: Vec<u8>,
+    /// Linear if any packed tileset filters linearly.
+    pub filter: FilterMode,
+    pub gpu_texture: Option<wgpu::Texture>,
+    pub bind_group: Option<wgpu::BindGroup>,
+}
+
+/// One image to place: its index (None = white block) and unpadded size.
+struct Item {
+    tileset: Option<usize>,
+    width: u32,
+    height: u32,
+}
+
+impl TilesetAtlas {
+    /// Pack the tilesets' images with simple shelf packing. Tilesets past MAX_ATLAS_TILESETS,
+    /// and the largest images when they do not all fit in the largest atlas, are left out and
+    /// sample the white block instead.
+    pub fn build(tilesets: &[Tileset]) -> Self {
+        let mut items = vec![Item { tileset: None, width: WHITE_SIZE, height: WHITE_SIZE }];
+        items.extend(tilesets.iter().enumerate()
+            .take(MAX_ATLAS_TILESETS)
+            .filter(|(_, ts)| ts.image_data.is_some())
+            .map(|(i, ts)| Item { tileset: Some(i), width: ts.image_width, height: ts.image_height }));
+        if tilesets.len() > MAX_ATLAS_TILESETS {
+            log::warn!("Tileset atlas holds at most {MAX_ATLAS_TILESETS} tilesets; the rest render untextured");
+        }
+        // Tallest first keeps the shelves tight.
+        items.sort_by(|a, b| b.height.cmp(&a.height).then(b.width.cmp(&a.width)));
+
+        let (width, height, positions) = loop {
+            if let Some(packed) = pack(&items) {
+                break packed;
+            }
+            let (largest, index) = items.iter().enumerate()
+                .filter_map(|(i, it)| Some((i, it.tileset?)))
+                .max_by_key(|&(i, _)| items[i].width as u64 * items[i].height as u64)
+                .expect("the white block alone always fits");
+            items.remove(largest);
+            log::warn!("Tileset '{}' does not fit in the tileset atlas", tilesets[index].name);
+        };
+
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+        let mut rects: Vec<Option<[f32; 4]>> = vec![None; tilesets.len()];
+        let mut white = Vec2::ZERO;
+        let size = Vec2::new(width as f32, height as f32);
+        for (item, &(x, y)) in items.iter().zip(&positions) {
+            let (ox, oy) = (x + GUTTER, y + GUTTER);
+            match item.tileset {
+                Some(i) => {
+                    let ts = &tilesets[i];
+                    let data = ts.image_data.as_deref().unwrap_or_default();
+                    blit_extruded(&mut image_data, width, x, y, data, item.width, item.height);
+                    rects[i] = Some([
+                        ox as f32 / size.x, oy as f32 / size.y,
+                        item.width as f32 / size.x, item.height as f32 / size.y,
+                    ]);
+                }
+                None => {
+                    let block = vec![255u8; (WHITE_SIZE * WHITE_SIZE * 4) as usize];
+                    blit_extruded(&mut image_data, width, x, y, &block, WHITE_SIZE, WHITE_SIZE);
+                    white = (Vec2::new(ox as f32, oy as f32) + WHITE_SIZE as f32 * 0.5) / size;
+                }
+            }
+        }
+        let rects = rects.into_iter().map(|r| r.unwrap_or([white.x, white.y, 0.0, 0.0])).collect();
+
+        let filter = if tilesets.iter().any(|ts| ts.material.filter == FilterMode::Linear) {
+            FilterMode::Linear
+        } else {
+            FilterMode::Nearest
+        };
+        Self { width, height, rects, white, image_data, filter, gpu_texture: None, bind_group: None }
+    }
+
+    /// Number of tilesets this atlas was packed from.
+    pub fn tileset_count(&self) -> usize {
+        self.rects.len()
+    }
+
+    /// Map a face's UVs from its tileset's image into the atlas (None = the white block).
+    pub fn remap_uvs(&self, tileset: Option<usize>, uvs: [Vec2; 4]) -> [Vec2; 4] {
+        match tileset.and_then(|i| self.rects.get(i)) {
+            Some(r) => uvs.map(|uv| Vec2::new(r[0], r[1]) + uv * Vec2::new(r[2], r[3])),
+            None => [self.white; 4],
+        }
+    }
+
+    /// Encode the atlas image as PNG bytes.
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(&self.image_data, self.width, self.height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| format!("PNG encode failed for tileset atlas: {e}"))?;
+        Ok(png)
+    }
+
+    /// Create the GPU texture, lookup table and bind group.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) {
+        let extent = wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 };
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("tileset_atlas"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &self.image_data,
+        );
+        let view = texture.create_view(&Default::default());
+
+        let wgpu_filter = match self.filter {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu_filter,
+            min_filter: wgpu_filter,
+            mipmap_filter: wgpu_filter,
+            ..Default::default()
+        });
+
+        // All zeros: atlas UVs are never remapped into a padded copy.
+        let padding_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("atlas_padding"),
+            contents: &[0; 48],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // All zeros: companion maps are not packed into the atlas.
+        let maps_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("atlas_maps"),
+            contents: &[0; super::maps::MAPS_UNIFORM_SIZE],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let mut table: Vec<u32> = Vec::with_capacity(ATLAS_TABLE_SIZE / 4);
+        table.extend([self.rects.len().min(MAX_ATLAS_TILESETS) as u32, 1, 0, 0]);
+        table.extend([self.white.x, self.white.y, 0.0, 0.0].map(f32::to_bits));
+        for i in 0..MAX_ATLAS_TILESETS {
+            let rect = self.rects.get(i).copied().unwrap_or([self.white.x, self.white.y, 0.0, 0.0]);
+            table.extend(rect.map(f32::to_bits));
+        }
+        let table_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("atlas_table"),
+            contents: bytemuck::cast_slice(&table),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("atlas_bg"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: padding_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: table_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: maps_buffer.as_entire_binding(),
+                },
+            ],
+        }));
+        self.gpu_texture = Some(texture);
+    }
+}
+
+/// Top-left corner of a packed item, in atlas pixels.
+type Position = (u32, u32);
+
+/// Find the narrowest power-of-two width whose shelf packing fits within MAX_ATLAS_SIZE.
+/// Returns (width, height, item positions).
+fn pack(items: &[Item]) -> Option<(u32, u32, Vec<Position>)> {
+    let area: u64 = items.iter()
+        .map(|it| (it.width + 2 * GUTTER) as u64 * (it.height + 2 * GUTTER) as u64)
+        .sum();
+    let widest = items.iter().map(|it| it.width + 2 * GUTTER).max().unwrap_or(1);
+    let mut width = ((area as f64).sqrt() as u32).max(widest).next_power_of_two();
+    while width <= MAX_ATLAS_SIZE {
+        let (positions, height) = shelf_pack(items, width);
+        if height <= MAX_ATLAS_SIZE {
+            return Some((width, height.next_power_of_two(), positions));
+        }
+        width *= 2;
+    }
+    None
+}
+
+/// Place items (with gutters) left to right in rows as tall as their first item.
+/// Returns each item's top-left corner and the total height used.
+fn shelf_pack(items: &[Item], width: u32) -> (Vec<Position>, u32) {
+    let mut positions = Vec::with_capacity(items.len());
+    let (mut x, mut y, mut shelf) = (0, 0, 0);
+    for item in items {
+        let (w, h) = (item.width + 2 * GUTTER, item.height + 2 * GUTTER);
+        if x + w > width {
+            x = 0;
+            y += shelf;
+            shelf = 0;
+        }
+        positions.push((x, y));
+        x += w;
+        shelf = shelf.max(h);
+    }
+    (positions, y + shelf)
+}
+
+/// Copy an image into the atlas at (x, y), repeating its border pixels over the gutter.
+fn blit_extruded(atlas: &mut [u8], atlas_width: u32, x: u32, y: u32, src: &[u8], width: u32, height: u32) {
+    if width == 0 || height == 0 || src.len() < (width * height * 4) as usize {
+        return;
+    }
+    for dy in 0..height + 2 * GUTTER {
+        let sy = dy.saturating_sub(GUTTER).min(height - 1);
+        for dx in 0..width + 2 * GUTTER {
+            let sx = dx.saturating_sub(GUTTER).min(width - 1);
+            let s = ((sy * width + sx) * 4) as usize;
+            let d = (((y + dy) * atlas_width + x + dx) * 4) as usize;
+            atlas[d..d + 4].copy_from_slice(&src[s..s + 4]);
+        }
+    }
+}
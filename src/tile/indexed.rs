@@ -0,0 +1,182 @@
+//! Indexed-color tilesets: each pixel stores an index into a color table, and palette-swap
+//! variants (seasons, team colors) swap the table without touching the pixels.
+
+/// Largest color table an indexed tileset can have (indices are one byte).
+pub const MAX_COLORS: usize = 256;
+
+/// Color table and per-pixel indices of an indexed tileset.
+#[derive(Debug, Clone)]
+pub struct IndexedColors {
+    /// Color table index of every image pixel, row-major.
+    pub indices: Vec<u8>,
+    /// Base color table.
+    pub colors: Vec<[u8; 4]>,
+    /// Alternative color tables, selected by name from layers and objects.
+    pub variants: Vec<PaletteVariant>,
+}
+
+/// A named alternative color table. Entries past its end fall back to the base table.
+#[derive(Debug, Clone)]
+pub struct PaletteVariant {
+    pub name: String,
+    pub colors: Vec<[u8; 4]>,
+}
+
+impl IndexedColors {
+    /// Index an RGBA image by its exact colors, in order of first appearance.
+    /// Fails when the image has more than `MAX_COLORS` distinct colors.
+    pub fn from_rgba(data: &[u8]) -> Result<Self, String> {
+        let mut colors: Vec<[u8; 4]> = Vec::new();
+        let mut lookup = std::collections::HashMap::new();
+        let mut indices = Vec::with_capacity(data.len() / 4);
+        for px in data.chunks_exact(4) {
+            let color = [px[0], px[1], px[2], px[3]];
+            let index = match lookup.get(&color) {
+                Some(&i) => i,
+                None => {
+                    if colors.len() == MAX_COLORS {
+                        return Err(format!("Image has more than {MAX_COLORS} colors; convert it with a palette file instead"));
+                    }
+                    colors.push(color);
+                    lookup.insert(color, (colors.len() - 1) as u8);
+                    (colors.len() - 1) as u8
+                }
+            };
+            indices.push(index);
+        }
+        Ok(Self { indices, colors, variants: Vec::new() })
+    }
+
+    /// Index an RGBA image against a fixed color table, mapping each pixel to the nearest color.
+    /// A transparent entry is appended when the image has transparency the table can't express.
+    pub fn quantize(data: &[u8], mut colors: Vec<[u8; 4]>) -> Self {
+        let transparent = data.chunks_exact(4).any(|px| px[3] == 0);
+        if transparent && !colors.iter().any(|c| c[3] == 0) {
+            colors.truncate(MAX_COLORS - 1);
+            colors.push([0, 0, 0, 0]);
+        }
+        colors.truncate(MAX_COLORS);
+        let mut indexed = Self { indices: Vec::new(), colors, variants: Vec::new() };
+        indexed.reindex(data);
+        indexed
+    }
+
+    /// Recompute the indices after the pixels were edited as RGBA (paint editor, hot reload),
+    /// snapping colors missing from the base table to their nearest entry.
+    pub fn reindex(&mut self, data: &[u8]) {
+        let mut cache = std::collections::HashMap::new();
+        self.indices = data.chunks_exact(4).map(|px| {
+            let color = [px[0], px[1], px[2], px[3]];
+            *cache.entry(color).or_insert_with(|| nearest(&self.colors, color))
+        }).collect();
+    }
+
+    /// Color table of a variant (None = base), with missing entries taken from the base table.
+    pub fn table(&self, variant: Option<usize>) -> Vec<[u8; 4]> {
+        let mut table = self.colors.clone();
+        if let Some(v) = variant.and_then(|v| self.variants.get(v)) {
+            for (entry, &color) in table.iter_mut().zip(&v.colors) {
+                *entry = color;
+            }
+        }
+        table
+    }
+
+    /// RGBA pixels with the base table (None) or a variant's table applied.
+    pub fn resolve(&self, variant: Option<usize>) -> Vec<u8> {
+        let table = self.table(variant);
+        self.indices.iter()
+            .flat_map(|&i| table.get(i as usize).copied().unwrap_or([0, 0, 0, 0]))
+            .collect()
+    }
+
+    /// Position of the variant with this name.
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|v| v.name == name)
+    }
+}
+
+/// Index of the table color closest to `color` (squared RGBA distance).
+fn nearest(colors: &[[u8; 4]], color: [u8; 4]) -> u8 {
+    let distance = |c: &[u8; 4]| -> u32 {
+        c.iter().zip(&color).map(|(&a, &b)| (a as i32 - b as i32).pow(2) as u32).sum()
+    };
+    colors.iter().enumerate()
+        .min_by_key(|(_, c)| distance(c))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Read a color table from a palette file: GIMP `.gpl`, Lospec-style `.hex` (one RRGGBB per
+/// line), or `.pal` (JASC text, RIFF, or raw 768-byte RGB). Colors are opaque.
+pub fn read_palette_file(path: &std::path::Path) -> Result<Vec<[u8; 4]>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let ext = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+    let colors = match ext.as_str() {
+        "gpl" => parse_gpl(&String::from_utf8_lossy(&data))?,
+        "hex" => parse_hex(&String::from_utf8_lossy(&data))?,
+        "pal" => parse_pal(&data)?,
+        _ => return Err(format!("Unsupported palette format '.{ext}' (expected .gpl, .hex or .pal)")),
+    };
+    if colors.is_empty() {
+        return Err(format!("{} contains no colors", path.display()));
+    }
+    Ok(colors.into_iter().take(MAX_COLORS).collect())
+}
+
+fn parse_gpl(text: &str) -> Result<Vec<[u8; 4]>, String> {
+    let mut lines = text.lines();
+    if lines.next().is_none_or(|l| l.trim() != "GIMP Palette") {
+        return Err("Not a GIMP palette (missing header)".to_string());
+    }
+    let mut colors = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.contains(':') {
+            continue; // comments and Name:/Columns: headers
+        }
+        let rgb: Vec<u8> = line.split_whitespace().take(3).filter_map(|t| t.parse().ok()).collect();
+        if rgb.len() == 3 {
+            colors.push([rgb[0], rgb[1], rgb[2], 255]);
+        }
+    }
+    Ok(colors)
+}
+
+fn parse_hex(text: &str) -> Result<Vec<[u8; 4]>, String> {
+    text.lines()
+        .map(|l| l.trim().trim_start_matches('#'))
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let value = u32::from_str_radix(l, 16)
+                .ok()
+                .filter(|_| l.len() == 6)
+                .ok_or_else(|| format!("Invalid hex color '{l}'"))?;
+            Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8, 255])
+        })
+        .collect()
+}
+
+fn parse_pal(data: &[u8]) -> Result<Vec<[u8; 4]>, String> {
+    if data.starts_with(b"JASC-PAL") {
+        let text = String::from_utf8_lossy(data);
+        // Header, version, color count, then one "R G B" line per color
+        return Ok(text.lines().skip(3).filter_map(|line| {
+            let rgb: Vec<u8> = line.split_whitespace().filter_map(|t| t.parse().ok()).collect();
+            (rgb.len() >= 3).then(|| [rgb[0], rgb[1], rgb[2], 255])
+        }).collect());
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"PAL ") {
+        // "data" chunk: version (2), count (2), then RGBX entries
+        let chunk = data.windows(4).position(|w| w == b"data")
+            .ok_or("RIFF palette has no data chunk")?;
+        let count = data.get(chunk + 10..chunk + 12)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or("Truncated RIFF palette")?;
+        let entries = data.get(chunk + 12..).unwrap_or_default();
+        return Ok(entries.chunks_exact(4).take(count).map(|e| [e[0], e[1], e[2], 255]).collect());
+    }
+    if data.len() == 768 {
+        return Ok(data.chunks_exact(3).map(|c| [c[0], c[1], c[2], 255]).collect());
+    }
+    Err("Unrecognized .pal format (expected JASC, RIFF or 768-byte raw RGB)".to_string())
+}
@@ -0,0 +1,76 @@
+//! Companion images (normal, emissive, specular) laid out on a tileset's tile grid.
+
+/// Size of the per-tileset map uniform (enabled flags + strengths = 2 vec4s).
+pub const MAPS_UNIFORM_SIZE: usize = 32;
+
+/// Which companion image of a tileset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapChannel {
+    /// Tangent-space normals, +Y (green) pointing up the image.
+    Normal,
+    /// Light emitted regardless of scene lighting.
+    Emissive,
+    /// Specular intensity (red channel).
+    Specular,
+}
+
+impl MapChannel {
+    pub const ALL: [MapChannel; 3] = [MapChannel::Normal, MapChannel::Emissive, MapChannel::Specular];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MapChannel::Normal => "Normal",
+            MapChannel::Emissive => "Emissive",
+            MapChannel::Specular => "Specular",
+        }
+    }
+
+    /// Emissive maps hold colors (sRGB); normal and specular maps hold linear data.
+    pub fn texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            MapChannel::Emissive => wgpu::TextureFormat::Rgba8UnormSrgb,
+            MapChannel::Normal | MapChannel::Specular => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+/// Optional companion images, RGBA8 at the same size as the tileset image.
+#[derive(Debug, Default, Clone)]
+pub struct MaterialMaps {
+    pub normal: Option<Vec<u8>>,
+    pub emissive: Option<Vec<u8>>,
+    pub specular: Option<Vec<u8>>,
+}
+
+impl MaterialMaps {
+    pub fn get(&self, channel: MapChannel) -> Option<&Vec<u8>> {
+        match channel {
+            MapChannel::Normal => self.normal.as_ref(),
+            MapChannel::Emissive => self.emissive.as_ref(),
+            MapChannel::Specular => self.specular.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, channel: MapChannel) -> &mut Option<Vec<u8>> {
+        match channel {
+            MapChannel::Normal => &mut self.normal,
+            MapChannel::Emissive => &mut self.emissive,
+            MapChannel::Specular => &mut self.specular,
+        }
+    }
+}
+
+/// Read a companion image, scaling it to the tileset image size when it differs.
+pub fn read_map_rgba(path: &std::path::Path, width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let img = image::open(path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?
+        .to_rgba8();
+    if img.dimensions() == (width, height) {
+        return Ok(img.into_raw());
+    }
+    log::warn!(
+        "{} is {}x{}, scaling to the tileset's {width}x{height}",
+        path.display(), img.width(), img.height(),
+    );
+    Ok(image::imageops::resize(&img, width, height, image::imageops::FilterType::Nearest).into_raw())
+}
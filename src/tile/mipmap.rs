@@ -0,0 +1,138 @@
+//! CPU mipmap generation for tileset textures.
+
+use super::tileset::MipFilter;
+
+/// Number of mip levels down to 1x1 for `filter` (1 when mipmaps are off).
+pub fn level_count(width: u32, height: u32, filter: MipFilter) -> u32 {
+    match filter {
+        MipFilter::Off => 1,
+        _ => 32 - width.max(height).max(1).leading_zeros(),
+    }
+}
+
+/// Halve an RGBA8 image (odd sizes round down, never below 1). Each output pixel comes from a
+/// 2x2 block of the source:
+/// - `Nearest` keeps the block's most opaque pixel (first in reading order on ties), so pixel art
+///   keeps its exact colors and transparent pixels never win over opaque ones.
+/// - `Linear` averages the block (in linear light when `srgb`), weighting colors by alpha so
+///   transparent pixels don't darken or tint the edges of cutouts.
+pub fn downsample(width: u32, height: u32, data: &[u8], filter: MipFilter, srgb: bool) -> (u32, u32, Vec<u8>) {
+    let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+    let mut out = vec![0u8; (w * h * 4) as usize];
+    let to_linear: [f32; 256] = if srgb {
+        srgb_to_linear_table()
+    } else {
+        std::array::from_fn(|i| i as f32 / 255.0)
+    };
+    for y in 0..h {
+        for x in 0..w {
+            let block = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| {
+                let sx = (x * 2 + dx).min(width - 1);
+                let sy = (y * 2 + dy).min(height - 1);
+                let i = ((sy * width + sx) * 4) as usize;
+                [data[i], data[i + 1], data[i + 2], data[i + 3]]
+            });
+            let pixel = match filter {
+                MipFilter::Linear => average(&block, &to_linear, srgb),
+                _ => block.into_iter().fold(block[0], |best, p| if p[3] > best[3] { p } else { best }),
+            };
+            let o = ((y * w + x) * 4) as usize;
+            out[o..o + 4].copy_from_slice(&pixel);
+        }
+    }
+    (w, h, out)
+}
+
+/// Alpha-weighted average of a block, decoded through `to_linear`.
+fn average(block: &[[u8; 4]; 4], to_linear: &[f32; 256], srgb: bool) -> [u8; 4] {
+    let mut rgb = [0.0f32; 3];
+    let mut alpha = 0.0f32;
+    for p in block {
+        let a = p[3] as f32 / 255.0;
+        for c in 0..3 {
+            rgb[c] += to_linear[p[c] as usize] * a;
+        }
+        alpha += a;
+    }
+    if alpha <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+    let color = rgb.map(|c| {
+        let c = c / alpha;
+        if srgb { linear_to_srgb(c) } else { (c.clamp(0.0, 1.0) * 255.0).round() as u8 }
+    });
+    [color[0], color[1], color[2], (alpha / 4.0 * 255.0).round() as u8]
+}
+
+fn srgb_to_linear_table() -> [f32; 256] {
+    std::array::from_fn(|i| {
+        let c = i as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    })
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let s = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (s.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Create a texture with the mip chain for `filter` and upload every level.
+#[allow(clippy::too_many_arguments)]
+pub fn create_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+    filter: MipFilter,
+) -> wgpu::Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: level_count(width, height, filter),
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    write_levels(queue, &texture, data, filter);
+    texture
+}
+
+/// Upload new level-0 pixels to a texture from `create_texture` and regenerate its other levels.
+pub fn write_levels(queue: &wgpu::Queue, texture: &wgpu::Texture, data: &[u8], filter: MipFilter) {
+    let (mut w, mut h) = (texture.width(), texture.height());
+    let mut level = std::borrow::Cow::Borrowed(data);
+    for mip in 0..texture.mip_level_count() {
+        if mip > 0 {
+            let (nw, nh, next) = downsample(w, h, &level, filter, texture.format().is_srgb());
+            (w, h, level) = (nw, nh, next.into());
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: mip,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &level,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * w),
+                rows_per_image: Some(h),
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
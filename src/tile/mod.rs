@@ -1,4 +1,10 @@
 mod tileset;
+pub mod aseprite;
+pub mod atlas;
+pub mod indexed;
+pub mod maps;
+pub mod mipmap;
 pub mod palette;
+pub mod properties;
 
-pub use tileset::{Tileset, FilterMode, WrapMode, AlphaMode};
+pub use tileset::{Tileset, FilterMode, MipFilter, WrapMode, AlphaMode, MaterialSettings, TilesetSource, is_aseprite, max_texture_dimension, read_image_rgba};
@@ -0,0 +1,62 @@
+//! Per-tile gameplay metadata attached to a tileset's grid cells.
+
+use serde::{Serialize, Deserialize};
+
+/// How game engines should treat geometry built from a tile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollisionType {
+    /// No collision.
+    #[default]
+    None,
+    /// Blocks movement from every side.
+    Solid,
+    /// Blocks movement from above only.
+    Platform,
+    Water,
+    Ladder,
+    /// Damages whatever touches it.
+    Hazard,
+    /// Non-blocking volume that fires events.
+    Trigger,
+}
+
+impl CollisionType {
+    pub const ALL: [CollisionType; 7] = [
+        CollisionType::None,
+        CollisionType::Solid,
+        CollisionType::Platform,
+        CollisionType::Water,
+        CollisionType::Ladder,
+        CollisionType::Hazard,
+        CollisionType::Trigger,
+    ];
+
+    /// Lowercase name used in exported metadata.
+    pub fn name(self) -> &'static str {
+        match self {
+            CollisionType::None => "none",
+            CollisionType::Solid => "solid",
+            CollisionType::Platform => "platform",
+            CollisionType::Water => "water",
+            CollisionType::Ladder => "ladder",
+            CollisionType::Hazard => "hazard",
+            CollisionType::Trigger => "trigger",
+        }
+    }
+}
+
+/// Metadata for one tile of a tileset.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileProperties {
+    pub tags: Vec<String>,
+    pub collision: CollisionType,
+    /// Free-form key/value pairs, in the order they were added.
+    pub custom: Vec<(String, String)>,
+}
+
+impl TileProperties {
+    /// Whether the tile carries no metadata at all (such entries are not stored).
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.collision == CollisionType::None && self.custom.is_empty()
+    }
+}
@@ -1,5 +1,11 @@
 use glam::Vec2;
+use image::ImageEncoder;
+use wgpu::util::DeviceExt;
 use serde::{Serialize, Deserialize};
+use super::aseprite::{self, AsepriteSelection};
+use super::properties::TileProperties;
+use super::maps::{MapChannel, MaterialMaps, MAPS_UNIFORM_SIZE};
+use super::indexed::IndexedColors;
 
 /// Texture filter mode per tileset.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -9,6 +15,18 @@ pub enum FilterMode {
     Linear,
 }
 
+/// How a tileset's mip levels are generated and sampled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MipFilter {
+    /// Single level (no mipmaps).
+    #[default]
+    Off,
+    /// Each level keeps the most opaque pixel of every 2x2 block; levels switch without blending.
+    Nearest,
+    /// Each level is an alpha-weighted 2x2 average; levels blend into each other (trilinear).
+    Linear,
+}
+
 /// Texture wrap/address mode per tileset.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WrapMode {
@@ -40,6 +58,22 @@ pub struct MaterialSettings {
     pub alpha_cutoff: f32,
     /// Whether this tileset renders as a decal (overlay with depth bias).
     pub decal: bool,
+    /// Pixels of each tile's border repeated around it in the GPU copy, so filtering near a
+    /// tile edge never samples the neighboring tile (0 = upload the image as-is).
+    pub extrude: u32,
+    /// Mipmap generation and sampling for distant/minified tiles.
+    pub mip_filter: MipFilter,
+    /// Maximum anisotropic filtering samples (1 = off). Only applies with linear filtering and
+    /// linear mipmaps.
+    pub anisotropy: u16,
+    /// Scale of the normal map's tilt (0 = flat, 1 = as authored).
+    pub normal_strength: f32,
+    /// Multiplier on the emissive map's color.
+    pub emissive_strength: f32,
+    /// Multiplier on the specular map's intensity.
+    pub specular_strength: f32,
+    /// Blinn-Phong exponent of specular highlights (higher = tighter).
+    pub specular_power: f32,
 }
 
 impl Default for MaterialSettings {
@@ -50,6 +84,13 @@ impl Default for MaterialSettings {
             alpha_mode: AlphaMode::AlphaTest,
             alpha_cutoff: 0.01,
             decal: false,
+            extrude: 0,
+            mip_filter: MipFilter::Off,
+            anisotropy: 1,
+            normal_strength: 1.0,
+            emissive_strength: 1.0,
+            specular_strength: 1.0,
+            specular_power: 32.0,
         }
     }
 }
@@ -61,6 +102,10 @@ pub struct Tileset {
     pub image_height: u32,
     pub tile_width: u32,
     pub tile_height: u32,
+    /// Pixels between the image edge and the first row/column of tiles.
+    pub margin: u32,
+    /// Pixels between adjacent tiles.
+    pub spacing: u32,
     pub gpu_texture: Option<wgpu::Texture>,
     pub bind_group: Option<wgpu::BindGroup>,
     /// egui texture ID for displaying in the tileset browser panel.
@@ -69,42 +114,117 @@ pub struct Tileset {
     pub image_data: Option<Vec<u8>>,
     /// Per-tileset material settings.
     pub material: MaterialSettings,
+    /// Image file this tileset was loaded from, watched for hot-reload. None = embedded only.
+    pub source: Option<TilesetSource>,
+    /// Gameplay metadata keyed by (col, row). Tiles without metadata have no entry.
+    pub tile_properties: std::collections::BTreeMap<(u32, u32), TileProperties>,
+    /// Optional normal/emissive/specular images on the same tile grid as `image_data`.
+    pub maps: MaterialMaps,
+    /// GPU copies of `maps`, indexed like `MapChannel::ALL`.
+    pub map_textures: [Option<wgpu::Texture>; 3],
+    /// Color table and palette-swap variants when the tileset is indexed. `image_data` always
+    /// holds the pixels resolved with the base table.
+    pub indexed: Option<IndexedColors>,
+    /// Color textures of each palette variant, parallel to `indexed.variants`.
+    pub variant_textures: Vec<wgpu::Texture>,
+    /// Bind groups of each palette variant, parallel to `variant_textures`.
+    pub variant_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+/// Where a tileset's pixels come from on disk.
+#[derive(Debug, Clone)]
+pub struct TilesetSource {
+    pub path: std::path::PathBuf,
+    /// Frame/layer to flatten when the source is an Aseprite sprite.
+    pub aseprite: AsepriteSelection,
+    /// Modification time of the file when it was last read (None = not read this session).
+    pub modified: Option<std::time::SystemTime>,
+}
+
+impl TilesetSource {
+    /// Source for a file that was just read.
+    pub fn new(path: &std::path::Path, aseprite: AsepriteSelection) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            aseprite,
+            modified: file_modified(path),
+        }
+    }
+
+    /// Remember the file's current modification time as seen.
+    pub fn mark_read(&mut self) {
+        self.modified = file_modified(&self.path);
+    }
+
+    /// Whether the file's modification time differs from when it was last read.
+    pub fn changed_on_disk(&self) -> bool {
+        file_modified(&self.path).is_some_and(|m| self.modified != Some(m))
+    }
+}
+
+/// Largest texture side the renderer's device accepts (it requests `wgpu::Limits::default()`).
+pub fn max_texture_dimension() -> u32 {
+    wgpu::Limits::default().max_texture_dimension_2d
+}
+
+/// Number of whole tiles along one axis of the image.
+fn grid_count(image: u32, tile: u32, margin: u32, spacing: u32) -> u32 {
+    if tile == 0 { return 0; }
+    (image.saturating_sub(2 * margin) + spacing) / (tile + spacing)
+}
+
+/// Tile index along one axis for a pixel position, or None inside the leading margin.
+fn grid_index(pos: f32, tile: u32, margin: u32, spacing: u32) -> Option<u32> {
+    let offset = pos - margin as f32;
+    if tile == 0 || offset < 0.0 { return None; }
+    Some((offset / (tile + spacing) as f32) as u32)
+}
+
+fn file_modified(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
 impl Tileset {
     pub fn cols(&self) -> u32 {
-        if self.tile_width == 0 { return 0; }
-        self.image_width / self.tile_width
+        grid_count(self.image_width, self.tile_width, self.margin, self.spacing)
     }
 
     pub fn rows(&self) -> u32 {
-        if self.tile_height == 0 { return 0; }
-        self.image_height / self.tile_height
+        grid_count(self.image_height, self.tile_height, self.margin, self.spacing)
+    }
+
+    /// Pixel position of the top-left corner of the tile at (col, row).
+    pub fn tile_origin(&self, col: u32, row: u32) -> (u32, u32) {
+        (
+            self.margin + col * (self.tile_width + self.spacing),
+            self.margin + row * (self.tile_height + self.spacing),
+        )
+    }
+
+    /// Tile under an image pixel position. Spacing belongs to the tile before it;
+    /// returns None in the margin or beyond the last tile.
+    pub fn tile_at_pixel(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        let col = grid_index(x, self.tile_width, self.margin, self.spacing)?;
+        let row = grid_index(y, self.tile_height, self.margin, self.spacing)?;
+        (col < self.cols() && row < self.rows()).then_some((col, row))
     }
 
     /// Compute UV coordinates for a tile at (col, row) in the tileset grid.
     /// Returns [bottom-left, bottom-right, top-right, top-left] UVs.
     pub fn tile_uvs(&self, col: u32, row: u32) -> [Vec2; 4] {
-        let u0 = col as f32 * self.tile_width as f32 / self.image_width as f32;
-        let v0 = row as f32 * self.tile_height as f32 / self.image_height as f32;
-        let u1 = u0 + self.tile_width as f32 / self.image_width as f32;
-        let v1 = v0 + self.tile_height as f32 / self.image_height as f32;
-
-        [
-            Vec2::new(u0, v1), // bottom-left
-            Vec2::new(u1, v1), // bottom-right
-            Vec2::new(u1, v0), // top-right
-            Vec2::new(u0, v0), // top-left
-        ]
+        self.tile_region_uvs(col, row, col, row)
     }
 
     /// Compute UV coordinates spanning a rectangular region of tiles.
     /// (col0, row0) is the top-left tile, (col1, row1) is the bottom-right tile (inclusive).
+    /// With spacing, the region also covers the gutters between its tiles.
     pub fn tile_region_uvs(&self, col0: u32, row0: u32, col1: u32, row1: u32) -> [Vec2; 4] {
-        let u0 = col0 as f32 * self.tile_width as f32 / self.image_width as f32;
-        let v0 = row0 as f32 * self.tile_height as f32 / self.image_height as f32;
-        let u1 = (col1 + 1) as f32 * self.tile_width as f32 / self.image_width as f32;
-        let v1 = (row1 + 1) as f32 * self.tile_height as f32 / self.image_height as f32;
+        let (x0, y0) = self.tile_origin(col0, row0);
+        let (x1, y1) = self.tile_origin(col1, row1);
+        let u0 = x0 as f32 / self.image_width as f32;
+        let v0 = y0 as f32 / self.image_height as f32;
+        let u1 = (x1 + self.tile_width) as f32 / self.image_width as f32;
+        let v1 = (y1 + self.tile_height) as f32 / self.image_height as f32;
 
         [
             Vec2::new(u0, v1), // bottom-left
@@ -114,7 +234,226 @@ impl Tileset {
         ]
     }
 
+    /// Tile under the center of a face's UVs, i.e. the tile the face was built from.
+    /// Faces spanning a region of tiles resolve to the tile at the region's center.
+    pub fn tile_at_uvs(&self, uvs: &[Vec2; 4]) -> Option<(u32, u32)> {
+        let center = uvs.iter().copied().sum::<Vec2>() / 4.0;
+        self.tile_at_pixel(center.x * self.image_width as f32, center.y * self.image_height as f32)
+    }
+
+    /// Metadata of the tile a face was built from, if that tile has any.
+    pub fn face_properties(&self, uvs: &[Vec2; 4]) -> Option<((u32, u32), &TileProperties)> {
+        let tile = self.tile_at_uvs(uvs)?;
+        self.tile_properties.get(&tile).map(|props| (tile, props))
+    }
+
+    /// Size of the padded copy, where every tile sits in its own cell `extrude` pixels larger
+    /// on each side. None when extrusion is off, the grid has no tiles, or the copy would
+    /// exceed the GPU's texture size limit (the unpadded image is uploaded instead).
+    pub fn padded_size(&self) -> Option<(u32, u32)> {
+        let max = max_texture_dimension();
+        self.unclamped_padded_size().filter(|&(w, h)| w <= max && h <= max)
+    }
+
+    /// Whether extrusion is on but the padded copy is too large for the GPU, so it is ignored.
+    pub fn padding_exceeds_limit(&self) -> bool {
+        self.unclamped_padded_size().is_some() && self.padded_size().is_none()
+    }
+
+    fn unclamped_padded_size(&self) -> Option<(u32, u32)> {
+        let pad = self.material.extrude;
+        let (cols, rows) = (self.cols(), self.rows());
+        if pad == 0 || cols == 0 || rows == 0 {
+            return None;
+        }
+        // Saturate rather than wrap so absurd sizes still compare as too large
+        let cell = |tile: u32| tile.saturating_add(pad.saturating_mul(2));
+        Some((cols.saturating_mul(cell(self.tile_width)), rows.saturating_mul(cell(self.tile_height))))
+    }
+
+    /// Build the padded copy of the image: tiles repacked into cells with their border pixels
+    /// extruded outward (margin and spacing are dropped). None when extrusion is off.
+    pub fn padded_image(&self) -> Option<(u32, u32, Vec<u8>)> {
+        self.pad_pixels(self.image_data.as_ref()?)
+    }
+
+    /// Repack an image laid out like the tileset image into the padded layout (see `padded_image`).
+    fn pad_pixels(&self, data: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+        let (pw, ph) = self.padded_size()?;
+        let pad = self.material.extrude;
+        let (cell_w, cell_h) = (self.tile_width + 2 * pad, self.tile_height + 2 * pad);
+        let mut out = vec![0u8; (pw * ph * 4) as usize];
+        for row in 0..self.rows() {
+            for col in 0..self.cols() {
+                let (sx, sy) = self.tile_origin(col, row);
+                for y in 0..cell_h {
+                    // Clamp into the tile so the border rows/columns repeat outward.
+                    let ty = y.saturating_sub(pad).min(self.tile_height - 1);
+                    for x in 0..cell_w {
+                        let tx = x.saturating_sub(pad).min(self.tile_width - 1);
+                        let src = (((sy + ty) * self.image_width + sx + tx) * 4) as usize;
+                        let dst = (((row * cell_h + y) * pw + col * cell_w + x) * 4) as usize;
+                        out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+                    }
+                }
+            }
+        }
+        Some((pw, ph, out))
+    }
+
+    /// Remap a face's UVs from the source image into the padded copy (see `padded_image`).
+    /// Each corner keeps its offset within its tile; faces spanning several tiles stretch
+    /// slightly over the extruded borders between them. Unchanged when extrusion is off.
+    pub fn padded_uvs(&self, uvs: [Vec2; 4]) -> [Vec2; 4] {
+        let Some((pw, ph)) = self.padded_size() else { return uvs };
+        let pad = self.material.extrude as f32;
+        let size = Vec2::new(self.image_width as f32, self.image_height as f32);
+        let px = uvs.map(|uv| uv * size);
+        let min = px.iter().fold(Vec2::MAX, |m, &p| m.min(p));
+        let max = px.iter().fold(Vec2::MIN, |m, &p| m.max(p));
+        // Tiles covered by the face; corners on a shared edge resolve into this range.
+        let inset = Vec2::splat(0.5).min((max - min) * 0.5);
+        let first = self.tile_at_pixel_clamped(min + inset);
+        let last = self.tile_at_pixel_clamped(max - inset);
+        px.map(|p| {
+            let (col, row) = self.tile_at_pixel_clamped(p);
+            let (col, row) = (col.clamp(first.0, last.0), row.clamp(first.1, last.1));
+            let (ox, oy) = self.tile_origin(col, row);
+            let offset = (p - Vec2::new(ox as f32, oy as f32))
+                .clamp(Vec2::ZERO, Vec2::new(self.tile_width as f32, self.tile_height as f32));
+            let cell = Vec2::new(
+                col as f32 * (self.tile_width as f32 + 2.0 * pad),
+                row as f32 * (self.tile_height as f32 + 2.0 * pad),
+            );
+            (cell + Vec2::splat(pad) + offset) / Vec2::new(pw as f32, ph as f32)
+        })
+    }
+
+    /// Tile under a pixel position, clamped into the grid (margins and overhang included).
+    fn tile_at_pixel_clamped(&self, p: Vec2) -> (u32, u32) {
+        let col = grid_index(p.x.max(self.margin as f32), self.tile_width, self.margin, self.spacing).unwrap_or(0);
+        let row = grid_index(p.y.max(self.margin as f32), self.tile_height, self.margin, self.spacing).unwrap_or(0);
+        (col.min(self.cols().saturating_sub(1)), row.min(self.rows().saturating_sub(1)))
+    }
+
+    /// Pixels to upload to the GPU: the padded copy when extrusion is on, else the image itself.
+    pub fn gpu_image(&self) -> Option<(u32, u32, std::borrow::Cow<'_, [u8]>)> {
+        if let Some((w, h, data)) = self.padded_image() {
+            return Some((w, h, data.into()));
+        }
+        let data = self.image_data.as_deref()?;
+        Some((self.image_width, self.image_height, data.into()))
+    }
+
+    /// Pixels of a companion map to upload, padded like `gpu_image`. None when the map is missing
+    /// or no longer matches the tileset image size (e.g. after the image was replaced).
+    pub fn gpu_map(&self, channel: MapChannel) -> Option<(u32, u32, std::borrow::Cow<'_, [u8]>)> {
+        let data = self.maps.get(channel)?;
+        if data.len() != (self.image_width * self.image_height * 4) as usize {
+            return None;
+        }
+        if let Some((w, h, padded)) = self.pad_pixels(data) {
+            return Some((w, h, padded.into()));
+        }
+        Some((self.image_width, self.image_height, data.as_slice().into()))
+    }
+
+    /// Pixels of a palette variant to upload, padded like `gpu_image`.
+    pub fn gpu_variant_image(&self, variant: usize) -> Option<(u32, u32, Vec<u8>)> {
+        let data = self.indexed.as_ref()?.resolve(Some(variant));
+        if data.len() != (self.image_width * self.image_height * 4) as usize {
+            return None;
+        }
+        self.pad_pixels(&data).or(Some((self.image_width, self.image_height, data)))
+    }
+
+    /// Upload every palette variant's colors, replacing any previous GPU copies.
+    /// Call before `rebuild_bind_group`.
+    pub fn upload_variants(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let count = self.indexed.as_ref().map_or(0, |ix| ix.variants.len());
+        self.variant_textures = (0..count)
+            .filter_map(|v| self.gpu_variant_image(v))
+            .map(|(width, height, data)| super::mipmap::create_texture(
+                device, queue, "tileset_variant_texture", wgpu::TextureFormat::Rgba8UnormSrgb, width, height, &data, self.material.mip_filter,
+            ))
+            .collect();
+    }
+
+    /// Bind group to draw with for a palette variant name; the base colors when the tileset
+    /// has no variant of that name.
+    pub fn variant_bind_group(&self, variant: Option<&str>) -> Option<&wgpu::BindGroup> {
+        variant
+            .and_then(|name| self.indexed.as_ref()?.variant_index(name))
+            .and_then(|v| self.variant_bind_groups.get(v))
+            .or(self.bind_group.as_ref())
+    }
+
+    /// Re-index `image_data` after it was edited as RGBA (paint editor, hot reload, replace),
+    /// snapping colors missing from the base table to their nearest entry.
+    pub fn sync_indexed(&mut self) {
+        if let (Some(indexed), Some(data)) = (&mut self.indexed, &self.image_data) {
+            indexed.reindex(data);
+            self.apply_indexed();
+        }
+    }
+
+    /// Rewrite `image_data` from the indexed base table (after the table was edited).
+    pub fn apply_indexed(&mut self) {
+        if let Some(indexed) = &self.indexed {
+            self.image_data = Some(indexed.resolve(None));
+        }
+    }
+
+    /// CPU-only copy (image, maps, metadata and color tables; no GPU or egui resources).
+    pub fn cpu_copy(&self) -> Tileset {
+        Tileset {
+            name: self.name.clone(),
+            image_width: self.image_width,
+            image_height: self.image_height,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            margin: self.margin,
+            spacing: self.spacing,
+            gpu_texture: None,
+            bind_group: None,
+            egui_texture_id: None,
+            image_data: self.image_data.clone(),
+            material: self.material.clone(),
+            source: self.source.clone(),
+            tile_properties: self.tile_properties.clone(),
+            maps: self.maps.clone(),
+            map_textures: Default::default(),
+            indexed: self.indexed.clone(),
+            variant_textures: Vec::new(),
+            variant_bind_groups: Vec::new(),
+        }
+    }
+
+    /// CPU-only copy with a palette variant's colors baked into its image, named after the variant.
+    pub fn variant_copy(&self, variant: usize) -> Option<Tileset> {
+        let indexed = self.indexed.as_ref()?;
+        let name = &indexed.variants.get(variant)?.name;
+        let mut copy = self.cpu_copy();
+        copy.name = format!("{}_{name}", self.name);
+        copy.image_data = Some(indexed.resolve(Some(variant)));
+        copy.indexed = None;
+        copy.source = None;
+        Some(copy)
+    }
+
+    /// Upload the companion maps, replacing any previous GPU copies. Call before `rebuild_bind_group`.
+    pub fn upload_maps(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for (i, channel) in MapChannel::ALL.into_iter().enumerate() {
+            self.map_textures[i] = self.gpu_map(channel).map(|(width, height, data)| {
+                super::mipmap::create_texture(
+                    device, queue, "tileset_map_texture", channel.texture_format(), width, height, &data, self.material.mip_filter,
+                )
+            });
+        }
+    }
+
     /// Load a tileset from an image file path. Creates GPU resources.
+    /// `aseprite` picks the frame/layer when the file is an Aseprite sprite.
     pub fn load(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -122,74 +461,16 @@ impl Tileset {
         path: &std::path::Path,
         tile_width: u32,
         tile_height: u32,
+        aseprite: AsepriteSelection,
     ) -> Result<Self, String> {
-        let img = image::open(path)
-            .map_err(|e| format!("Failed to load image: {e}"))?
-            .to_rgba8();
+        let (w, h, image_data) = read_image_rgba(path, aseprite)?;
 
-        let (w, h) = img.dimensions();
-
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("tileset_texture"),
-            size: wgpu::Extent3d {
-                width: w,
-                height: h,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &img,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * w),
-                rows_per_image: Some(h),
-            },
-            wgpu::Extent3d {
-                width: w,
-                height: h,
-                depth_or_array_layers: 1,
-            },
+        let material = MaterialSettings::default();
+        let texture = super::mipmap::create_texture(
+            device, queue, "tileset_texture", wgpu::TextureFormat::Rgba8UnormSrgb, w, h, &image_data, material.mip_filter,
         );
 
-        let view = texture.create_view(&Default::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("tileset_bg"),
-            layout: bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
-
-        let image_data = img.into_raw();
-
-        Ok(Self {
+        let mut tileset = Self {
             name: path
                 .file_stem()
                 .map(|s| s.to_string_lossy().to_string())
@@ -198,15 +479,122 @@ impl Tileset {
             image_height: h,
             tile_width,
             tile_height,
+            margin: 0,
+            spacing: 0,
             gpu_texture: Some(texture),
-            bind_group: Some(bind_group),
+            bind_group: None,
             egui_texture_id: None,
             image_data: Some(image_data),
-            material: MaterialSettings::default(),
-        })
+            material,
+            source: Some(TilesetSource::new(path, aseprite)),
+            tile_properties: Default::default(),
+            maps: MaterialMaps::default(),
+            map_textures: Default::default(),
+            indexed: None,
+            variant_textures: Vec::new(),
+            variant_bind_groups: Vec::new(),
+        };
+        tileset.rebuild_bind_group(device, bind_group_layout);
+        Ok(tileset)
+    }
+
+    /// Create a CPU-only tileset from raw RGBA pixels (no GPU resources yet).
+    /// Used when restoring tilesets embedded in a scene file.
+    pub fn from_rgba(
+        name: String,
+        image_width: u32,
+        image_height: u32,
+        image_data: Option<Vec<u8>>,
+        tile_width: u32,
+        tile_height: u32,
+        material: MaterialSettings,
+    ) -> Self {
+        Self {
+            name,
+            image_width,
+            image_height,
+            tile_width,
+            tile_height,
+            margin: 0,
+            spacing: 0,
+            gpu_texture: None,
+            bind_group: None,
+            egui_texture_id: None,
+            image_data,
+            material,
+            source: None,
+            tile_properties: Default::default(),
+            maps: MaterialMaps::default(),
+            map_textures: Default::default(),
+            indexed: None,
+            variant_textures: Vec::new(),
+            variant_bind_groups: Vec::new(),
+        }
+    }
+
+    /// Encode the tileset image as PNG bytes. Returns an empty buffer if there is no image data.
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let Some(ref data) = self.image_data else { return Ok(Vec::new()) };
+        self.encode_rgba_png(self.image_width, self.image_height, data)
+    }
+
+    /// Encode the image that is uploaded to the GPU (padded when extrusion is on) as PNG bytes.
+    pub fn encode_gpu_png(&self) -> Result<Vec<u8>, String> {
+        let Some((w, h, data)) = self.gpu_image() else { return Ok(Vec::new()) };
+        self.encode_rgba_png(w, h, &data)
+    }
+
+    /// Encode a companion map as PNG bytes. Returns an empty buffer if the map is missing.
+    pub fn encode_map_png(&self, channel: MapChannel) -> Result<Vec<u8>, String> {
+        let Some(data) = self.maps.get(channel) else { return Ok(Vec::new()) };
+        self.encode_rgba_png(self.image_width, self.image_height, data)
+    }
+
+    /// Encode the GPU copy of a companion map (padded when extrusion is on) as PNG bytes.
+    pub fn encode_gpu_map_png(&self, channel: MapChannel) -> Result<Vec<u8>, String> {
+        let Some((w, h, data)) = self.gpu_map(channel) else { return Ok(Vec::new()) };
+        self.encode_rgba_png(w, h, &data)
+    }
+
+    /// Encode the color table indices of an indexed tileset as an 8-bit grayscale PNG.
+    /// Returns an empty buffer if the tileset isn't indexed.
+    pub fn encode_index_png(&self) -> Result<Vec<u8>, String> {
+        let Some(ref indexed) = self.indexed else { return Ok(Vec::new()) };
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(&indexed.indices, self.image_width, self.image_height, image::ExtendedColorType::L8)
+            .map_err(|e| format!("PNG encode failed for tileset '{}' indices: {e}", self.name))?;
+        Ok(png)
     }
 
-    /// Rebuild the GPU sampler and bind group after material settings change.
+    /// Decode an index PNG from `encode_index_png` into (width, height, indices).
+    pub fn decode_index_png(png: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+        let img = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+            .map_err(|e| format!("PNG decode failed: {e}"))?
+            .to_luma8();
+        let (w, h) = img.dimensions();
+        Ok((w, h, img.into_raw()))
+    }
+
+    fn encode_rgba_png(&self, width: u32, height: u32, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(data, width, height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| format!("PNG encode failed for tileset '{}': {e}", self.name))?;
+        Ok(png)
+    }
+
+    /// Decode PNG bytes into (width, height, RGBA pixels).
+    pub fn decode_png(png: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+        let img = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+            .map_err(|e| format!("PNG decode failed: {e}"))?
+            .to_rgba8();
+        let (w, h) = img.dimensions();
+        Ok((w, h, img.into_raw()))
+    }
+
+    /// Rebuild the GPU sampler and bind groups (the tileset's and its palette variants') after
+    /// material settings change.
     pub fn rebuild_bind_group(
         &mut self,
         device: &wgpu::Device,
@@ -225,30 +613,109 @@ impl Tileset {
             WrapMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
         };
 
+        let mipmap_filter = match self.material.mip_filter {
+            MipFilter::Linear => wgpu::FilterMode::Linear,
+            MipFilter::Off | MipFilter::Nearest => wgpu::FilterMode::Nearest,
+        };
+        // wgpu only allows anisotropy when every filter is linear.
+        let anisotropy_clamp = if wgpu_filter == wgpu::FilterMode::Linear && mipmap_filter == wgpu::FilterMode::Linear {
+            self.material.anisotropy.clamp(1, 16)
+        } else {
+            1
+        };
+
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             mag_filter: wgpu_filter,
             min_filter: wgpu_filter,
-            mipmap_filter: wgpu_filter,
+            mipmap_filter,
             address_mode_u: wgpu_wrap,
             address_mode_v: wgpu_wrap,
             address_mode_w: wgpu_wrap,
+            anisotropy_clamp,
             ..Default::default()
         });
 
-        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("tileset_bg"),
+        // Only remap UVs if the uploaded texture really is the padded copy.
+        let padded = self.padded_size()
+            .filter(|&(w, h)| texture.width() == w && texture.height() == h);
+        let (pw, ph) = padded.unwrap_or((self.image_width, self.image_height));
+        let padding: [f32; 12] = [
+            self.tile_width as f32, self.tile_height as f32, self.margin as f32, self.spacing as f32,
+            self.cols() as f32, self.rows() as f32, self.material.extrude as f32,
+            if padded.is_some() { 1.0 } else { 0.0 },
+            self.image_width as f32, self.image_height as f32, pw as f32, ph as f32,
+        ];
+        let padding_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tileset_padding"),
+            contents: bytemuck::cast_slice(&padding),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        // All zeros: the tileset's own texture never goes through the atlas lookup.
+        let atlas_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tileset_atlas_table"),
+            contents: &[0; super::atlas::ATLAS_TABLE_SIZE],
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // Missing maps bind the albedo view; their enable flag keeps the shader from reading it.
+        let map_views: Vec<wgpu::TextureView> = self.map_textures.iter()
+            .map(|t| t.as_ref().unwrap_or(texture).create_view(&Default::default()))
+            .collect();
+        let enabled = self.map_textures.each_ref().map(|t| if t.is_some() { 1.0 } else { 0.0 });
+        let m = &self.material;
+        let maps_uniform: [f32; MAPS_UNIFORM_SIZE / 4] = [
+            enabled[0], enabled[1], enabled[2], 0.0,
+            m.normal_strength, m.emissive_strength, m.specular_strength, m.specular_power,
+        ];
+        let maps_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tileset_maps"),
+            contents: bytemuck::cast_slice(&maps_uniform),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let create = |label: &str, view: &wgpu::TextureView| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
             layout: bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&view),
+                    resource: wgpu::BindingResource::TextureView(view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: padding_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: atlas_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&map_views[0]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&map_views[1]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: wgpu::BindingResource::TextureView(&map_views[2]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: maps_buffer.as_entire_binding(),
+                },
             ],
-        }));
+        });
+        // Palette variants share everything but the color texture.
+        self.variant_bind_groups = self.variant_textures.iter()
+            .map(|t| create("tileset_variant_bg", &t.create_view(&Default::default())))
+            .collect();
+        self.bind_group = Some(create("tileset_bg", &view));
     }
 
     /// Register this tileset's image with the egui renderer for UI display.
@@ -313,3 +780,23 @@ impl Tileset {
         self.egui_texture_id = Some(id);
     }
 }
+
+/// Whether a path names an Aseprite sprite (.ase/.aseprite).
+pub fn is_aseprite(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("ase") || e.eq_ignore_ascii_case("aseprite"))
+}
+
+/// Read an image file as straight RGBA8. Aseprite sprites are flattened per `aseprite`;
+/// everything else goes through the `image` crate.
+pub fn read_image_rgba(path: &std::path::Path, aseprite: AsepriteSelection) -> Result<(u32, u32, Vec<u8>), String> {
+    if is_aseprite(path) {
+        let sprite = aseprite::read(path)?;
+        let pixels = sprite.flatten(aseprite)?;
+        return Ok((sprite.width, sprite.height, pixels));
+    }
+    let img = image::open(path)
+        .map_err(|e| format!("Failed to load image: {e}"))?
+        .to_rgba8();
+    let (w, h) = img.dimensions();
+    Ok((w, h, img.into_raw()))
+}
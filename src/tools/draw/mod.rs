@@ -75,6 +75,9 @@ pub struct DrawState {
     pub tileset_panel_floating: bool,
     /// Block tool subtract mode: when true, block removes overlapping faces instead of adding.
     pub block_subtract: bool,
+    /// Place tiles from any tileset into the layer's existing object instead of starting a
+    /// new object per tileset (mixed objects render through the scene atlas).
+    pub combine_tilesets: bool,
 }
 
 impl DrawState {
@@ -94,6 +97,7 @@ impl DrawState {
             tilebrush_flip_v: false,
             tileset_panel_floating: false,
             block_subtract: false,
+            combine_tilesets: false,
         }
     }
 
@@ -107,22 +111,8 @@ impl DrawState {
     }
 
     /// Transform tile UVs according to current tilebrush rotation and flip settings.
-    pub fn transform_tile_uvs(&self, mut uvs: [Vec2; 4]) -> [Vec2; 4] {
-        // Apply rotation (cycle UVs clockwise)
-        for _ in 0..self.tilebrush_rotation {
-            uvs = [uvs[3], uvs[0], uvs[1], uvs[2]];
-        }
-        // Apply horizontal flip: swap left↔right
-        if self.tilebrush_flip_h {
-            uvs.swap(0, 1);
-            uvs.swap(2, 3);
-        }
-        // Apply vertical flip: swap top↔bottom
-        if self.tilebrush_flip_v {
-            uvs.swap(0, 3);
-            uvs.swap(1, 2);
-        }
-        uvs
+    pub fn transform_tile_uvs(&self, uvs: [Vec2; 4]) -> [Vec2; 4] {
+        transform_tile_uvs(uvs, self.tilebrush_rotation, self.tilebrush_flip_h, self.tilebrush_flip_v)
     }
 
     /// Compute the face(s) to place and target location.
@@ -161,7 +151,7 @@ impl DrawState {
         let faces = prefab.instantiate_at(position);
         let ts_idx = prefab.tileset_index;
         let layer_idx = scene.active_layer;
-        let (object_idx, create_object) = find_target_object(scene, layer_idx, ts_idx);
+        let (object_idx, create_object) = find_target_object(scene, layer_idx, ts_idx, self.combine_tilesets);
 
         Some(PlacementResult {
             layer: layer_idx,
@@ -200,7 +190,7 @@ impl DrawState {
         };
 
         let layer_idx = scene.active_layer;
-        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset);
+        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset, self.combine_tilesets);
 
         Some(PlacementResult {
             layer: layer_idx,
@@ -227,6 +217,7 @@ impl DrawState {
             uvs: self.tile_uvs(scene),
             colors: [Vec4::ONE; 4],
             hidden: false,
+            tileset: None,
         };
 
         Some(PlacementResult {
@@ -273,7 +264,7 @@ impl DrawState {
         ];
 
         let layer_idx = scene.active_layer;
-        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset);
+        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset, self.combine_tilesets);
 
         Some(PlacementResult {
             layer: layer_idx,
@@ -416,7 +407,7 @@ impl DrawState {
         };
 
         let layer_idx = scene.active_layer;
-        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset);
+        let (object_idx, create_object) = find_target_object(scene, layer_idx, scene.active_tileset, self.combine_tilesets);
 
         Some(PlacementResult {
             layer: layer_idx,
@@ -501,14 +492,41 @@ fn closest_edge(positions: &[Vec3; 4], point: Vec3) -> usize {
         .unwrap()
 }
 
+/// Rotate tile UVs by `rotation` quarter turns (each cycling corners one step), then flip
+/// horizontally and/or vertically. Shared by the tilebrush and map importers.
+pub fn transform_tile_uvs(mut uvs: [Vec2; 4], rotation: u8, flip_h: bool, flip_v: bool) -> [Vec2; 4] {
+    // Apply rotation (cycle UVs clockwise)
+    for _ in 0..rotation {
+        uvs = [uvs[3], uvs[0], uvs[1], uvs[2]];
+    }
+    // Apply horizontal flip: swap left↔right
+    if flip_h {
+        uvs.swap(0, 1);
+        uvs.swap(2, 3);
+    }
+    // Apply vertical flip: swap top↔bottom
+    if flip_v {
+        uvs.swap(0, 3);
+        uvs.swap(1, 2);
+    }
+    uvs
+}
+
 /// Find an existing object in the layer that uses the same tileset, or signal to create a new one.
-pub fn find_target_object(scene: &Scene, layer_idx: usize, tileset_idx: Option<usize>) -> (usize, bool) {
+/// With `combine`, a layer object using another tileset is reused instead of creating one.
+pub fn find_target_object(scene: &Scene, layer_idx: usize, tileset_idx: Option<usize>, combine: bool) -> (usize, bool) {
     if let Some(layer) = scene.layers.get(layer_idx) {
         for (i, obj) in layer.objects.iter().enumerate() {
             if obj.tileset_index == tileset_idx {
                 return (i, false);
             }
         }
+        // Combining tilesets: join the first textured object; its faces remember their tileset.
+        if combine && tileset_idx.is_some()
+            && let Some(i) = layer.objects.iter().position(|o| o.tileset_index.is_some())
+        {
+            return (i, false);
+        }
         (layer.objects.len(), true)
     } else {
         (0, true)
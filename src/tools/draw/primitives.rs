@@ -30,19 +30,19 @@ pub fn generate_cylinder(center: Vec3, radius: f32, height: f32, segments: usize
         let br = center + Vec3::new(radius * s1, -half_h, radius * c1);
         let tr = center + Vec3::new(radius * s1, half_h, radius * c1);
         let tl = center + Vec3::new(radius * s0, half_h, radius * c0);
-        faces.push(Face { positions: [bl, br, tr, tl], uvs, colors: [Vec4::ONE; 4], hidden: false });
+        faces.push(Face { positions: [bl, br, tr, tl], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None });
 
         // Top cap quad (triangle as degenerate quad: center, p0, p1, center)
         let tc = center + Vec3::new(0.0, half_h, 0.0);
         let t0 = center + Vec3::new(radius * s0, half_h, radius * c0);
         let t1 = center + Vec3::new(radius * s1, half_h, radius * c1);
-        faces.push(Face { positions: [tc, t0, t1, tc], uvs, colors: [Vec4::ONE; 4], hidden: false });
+        faces.push(Face { positions: [tc, t0, t1, tc], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None });
 
         // Bottom cap quad (triangle as degenerate quad)
         let bc = center + Vec3::new(0.0, -half_h, 0.0);
         let b0 = center + Vec3::new(radius * s0, -half_h, radius * c0);
         let b1 = center + Vec3::new(radius * s1, -half_h, radius * c1);
-        faces.push(Face { positions: [bc, b1, b0, bc], uvs, colors: [Vec4::ONE; 4], hidden: false });
+        faces.push(Face { positions: [bc, b1, b0, bc], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None });
     }
 
     faces
@@ -63,11 +63,11 @@ pub fn generate_cone(center: Vec3, radius: f32, height: f32, segments: usize, uv
         // Side triangle (degenerate quad: apex shared at positions[2] and [3])
         let b0 = center + Vec3::new(radius * s0, -half_h, radius * c0);
         let b1 = center + Vec3::new(radius * s1, -half_h, radius * c1);
-        faces.push(Face { positions: [b0, b1, apex, apex], uvs, colors: [Vec4::ONE; 4], hidden: false });
+        faces.push(Face { positions: [b0, b1, apex, apex], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None });
 
         // Bottom cap
         let bc = center + Vec3::new(0.0, -half_h, 0.0);
-        faces.push(Face { positions: [bc, b1, b0, bc], uvs, colors: [Vec4::ONE; 4], hidden: false });
+        faces.push(Face { positions: [bc, b1, b0, bc], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None });
     }
 
     faces
@@ -99,6 +99,7 @@ pub fn generate_sphere(center: Vec3, radius: f32, rings: usize, segments: usize,
                 uvs,
                 colors: [Vec4::ONE; 4],
                 hidden: false,
+                tileset: None,
             });
         }
     }
@@ -122,14 +123,14 @@ pub fn generate_wedge(center: Vec3, half_size: Vec3, uvs: [Vec2; 4]) -> Vec<Face
 
     vec![
         // Bottom face
-        Face { positions: [bl_b, br_b, br_f, bl_f], uvs, colors: [Vec4::ONE; 4], hidden: false },
+        Face { positions: [bl_b, br_b, br_f, bl_f], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None },
         // Front face (vertical)
-        Face { positions: [bl_f, br_f, tr, tl], uvs, colors: [Vec4::ONE; 4], hidden: false },
+        Face { positions: [bl_f, br_f, tr, tl], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None },
         // Back/slope face
-        Face { positions: [br_b, bl_b, tl, tr], uvs, colors: [Vec4::ONE; 4], hidden: false },
+        Face { positions: [br_b, bl_b, tl, tr], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None },
         // Left triangular end (degenerate quad)
-        Face { positions: [bl_b, bl_f, tl, tl], uvs, colors: [Vec4::ONE; 4], hidden: false },
+        Face { positions: [bl_b, bl_f, tl, tl], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None },
         // Right triangular end (degenerate quad)
-        Face { positions: [br_f, br_b, tr, tr], uvs, colors: [Vec4::ONE; 4], hidden: false },
+        Face { positions: [br_f, br_b, tr, tr], uvs, colors: [Vec4::ONE; 4], hidden: false, tileset: None },
     ]
 }
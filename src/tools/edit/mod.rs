@@ -417,27 +417,37 @@ impl EditState {
     /// Select all faces that overlap (same position within epsilon) with another face.
     pub fn select_overlapping(&mut self, scene: &Scene) {
         self.selection.clear();
-        let eps = 1e-4;
-
-        // Collect face centroids for fast overlap detection
-        let mut face_data: Vec<(usize, usize, usize, Vec3)> = Vec::new();
-        for (li, layer) in scene.layers.iter().enumerate() {
-            if !layer.visible { continue; }
-            for (oi, object) in layer.objects.iter().enumerate() {
-                for (fi, face) in object.faces.iter().enumerate() {
-                    if face.hidden { continue; }
-                    let centroid = (face.positions[0] + face.positions[1] + face.positions[2] + face.positions[3]) * 0.25;
-                    face_data.push((li, oi, fi, centroid));
-                }
-            }
-        }
-
+        let eps: f32 = 1e-4;
+        let centroid = |face: &crate::scene::mesh::Face| {
+            (face.positions[0] + face.positions[1] + face.positions[2] + face.positions[3]) * 0.25
+        };
+        let objects: Vec<(usize, usize, &crate::scene::Object)> = scene.layers.iter().enumerate()
+            .filter(|(_, layer)| layer.visible)
+            .flat_map(|(li, layer)| layer.objects.iter().enumerate().map(move |(oi, obj)| (li, oi, obj)))
+            .collect();
+
+        // A face whose centroid lies within sqrt(eps) of another's has bounds overlapping the box
+        // of that radius around it, so each object's BVH narrows the candidates.
+        let radius = Vec3::splat(eps.sqrt());
         let mut overlap_set = std::collections::HashSet::new();
-        for i in 0..face_data.len() {
-            for j in (i + 1)..face_data.len() {
-                if (face_data[i].3 - face_data[j].3).length_squared() < eps {
-                    overlap_set.insert((face_data[i].0, face_data[i].1, face_data[i].2));
-                    overlap_set.insert((face_data[j].0, face_data[j].1, face_data[j].2));
+        for &(li, oi, object) in &objects {
+            for (fi, face) in object.faces.iter().enumerate() {
+                if face.hidden || overlap_set.contains(&(li, oi, fi)) { continue; }
+                let c = centroid(face);
+                for &(lj, oj, other) in &objects {
+                    let mut test = |fj: usize| {
+                        let candidate = &other.faces[fj];
+                        if (lj, oj, fj) != (li, oi, fi) && !candidate.hidden
+                            && (centroid(candidate) - c).length_squared() < eps
+                        {
+                            overlap_set.insert((li, oi, fi));
+                            overlap_set.insert((lj, oj, fj));
+                        }
+                    };
+                    match other.bvh() {
+                        Some(bvh) => bvh.query_aabb(c - radius, c + radius, &mut test),
+                        None => (0..other.faces.len()).for_each(&mut test),
+                    }
                 }
             }
         }
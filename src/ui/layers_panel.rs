@@ -20,6 +20,14 @@ pub fn draw_layers_panel(
     let mut action = LayerAction::None;
     let mut prop_commit = None;
 
+    // Palette variant names offered by any indexed tileset
+    let mut variant_names: Vec<String> = Vec::new();
+    for variant in scene.tilesets.iter().filter_map(|ts| ts.indexed.as_ref()).flat_map(|ix| &ix.variants) {
+        if !variant_names.contains(&variant.name) {
+            variant_names.push(variant.name.clone());
+        }
+    }
+
     egui::SidePanel::right("layers_panel").default_width(200.0).show(ctx, |ui| {
         ui.heading("Layers");
 
@@ -47,6 +55,7 @@ pub fn draw_layers_panel(
                     ui.label("Name:");
                     ui.text_edit_singleline(&mut scene.layers[i].name);
                 });
+                palette_variant_combo(ui, ("layer_variant", i), &variant_names, &mut scene.layers[i].palette_variant, "Base");
                 ui.separator();
                 if ui.button("Duplicate").clicked() {
                     action = LayerAction::DuplicateLayer(i);
@@ -96,6 +105,10 @@ pub fn draw_layers_panel(
                                         ui.label("Name:");
                                         ui.text_edit_singleline(&mut scene.layers[i].objects[oi].name);
                                     });
+                                    palette_variant_combo(
+                                        ui, ("object_variant", i, oi), &variant_names,
+                                        &mut scene.layers[i].objects[oi].palette_variant, "Layer's",
+                                    );
                                 });
                             });
 
@@ -159,3 +172,28 @@ pub fn draw_layers_panel(
 
     (action, prop_commit)
 }
+
+/// Palette variant picker for a layer or object; `none_label` names what None means.
+/// Hidden when no tileset has variants.
+fn palette_variant_combo(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    names: &[String],
+    variant: &mut Option<String>,
+    none_label: &str,
+) {
+    if names.is_empty() && variant.is_none() {
+        return;
+    }
+    ui.horizontal(|ui| {
+        ui.label("Palette:");
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(variant.as_deref().unwrap_or(none_label))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(variant, None, none_label);
+                for name in names {
+                    ui.selectable_value(variant, Some(name.clone()), name);
+                }
+            });
+    }).response.on_hover_text("Palette variant used on indexed tilesets that have one by this name");
+}
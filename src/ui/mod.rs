@@ -59,9 +59,13 @@ pub enum UiAction {
     ImportGlb,
     ImportGltf,
     ImportDae,
+    ImportVox,
+    ImportTiled,
+    ImportLdtk,
     // Export (additional formats)
     ExportGltf,
     ExportDae,
+    ExportVox,
     // Camera bookmarks
     SaveBookmark(usize),
     RecallBookmark(usize),
@@ -84,6 +88,15 @@ pub enum UiAction {
     OpenPaintEditor,
     // Material settings
     RebuildMaterial(usize),
+    TilePropertiesChanged,
+    LoadMaterialMap(usize, crate::tile::maps::MapChannel),
+    ClearMaterialMap(usize, crate::tile::maps::MapChannel),
+    // Indexed color
+    ConvertTilesetToIndexed(usize),
+    ConvertTilesetWithPalette(usize),
+    RevertTilesetToRgba(usize),
+    IndexedColorsChanged(usize),
+    ImportPaletteVariant(usize),
     // Prefab operations
     CreatePrefab,
     DeconstructPrefab,
@@ -116,6 +129,7 @@ pub enum UiAction {
     TriangleDivide(u8), // diagonal: 0 = 0→2, 1 = 1→3
     TriangleMerge,
     SelectTriangles,
+    OptimizeMesh,
     // Vertex alignment operations
     PushVertices,
     PullVertices,
@@ -125,6 +139,9 @@ pub enum UiAction {
     StraightenVertices,
     // Recent files
     OpenRecentFile(usize),
+    // Crash recovery
+    RestoreAutosave,
+    DiscardAutosave,
     // UV vertex drag from UV panel
     UvVertexDrag {
         /// (index into selection.faces, vertex 0-3)
@@ -257,6 +274,34 @@ pub fn draw_ui(
                         action = UiAction::ExportDae;
                         ui.close();
                     }
+                    if ui.button("MagicaVoxel (.vox)").clicked() {
+                        action = UiAction::ExportVox;
+                        ui.close();
+                    }
+                    ui.separator();
+                    let f = &mut settings.file;
+                    let mut changed = ui.checkbox(&mut f.gltf_keep_instances, "glTF: keep instances as nodes")
+                        .on_hover_text("Write one mesh per object and a node per instance instead of duplicating geometry")
+                        .changed();
+                    changed |= ui.add_enabled(
+                        f.gltf_keep_instances,
+                        egui::Checkbox::new(&mut f.gltf_gpu_instancing, "glTF: GPU instancing extension"),
+                    ).on_hover_text(format!(
+                        "Use EXT_mesh_gpu_instancing for objects with {}+ instances",
+                        crate::io::GPU_INSTANCING_MIN_INSTANCES,
+                    )).changed();
+                    changed |= ui.checkbox(&mut f.export_padded_atlas, "OBJ/glTF: padded tilesets")
+                        .on_hover_text("Write tilesets that use edge extrusion as their padded copy and remap UVs to match")
+                        .changed();
+                    changed |= ui.checkbox(&mut f.export_tileset_atlas, "OBJ/glTF: combined tileset atlas")
+                        .on_hover_text("Pack all tilesets into one texture and material instead of one per tileset")
+                        .changed();
+                    changed |= ui.checkbox(&mut f.export_optimize_meshes, "OBJ/glTF: optimize meshes")
+                        .on_hover_text("Merge adjacent coplanar faces with the same tile and color into larger quads and weld shared vertices")
+                        .changed();
+                    if changed {
+                        settings.save();
+                    }
                 });
                 ui.menu_button("Import", |ui| {
                     if ui.button("Wavefront OBJ (.obj)").clicked() {
@@ -275,6 +320,18 @@ pub fn draw_ui(
                         action = UiAction::ImportDae;
                         ui.close();
                     }
+                    if ui.button("MagicaVoxel (.vox)").clicked() {
+                        action = UiAction::ImportVox;
+                        ui.close();
+                    }
+                    if ui.button("Tiled map (.tmx, .tmj)").clicked() {
+                        action = UiAction::ImportTiled;
+                        ui.close();
+                    }
+                    if ui.button("LDtk project (.ldtk)").clicked() {
+                        action = UiAction::ImportLdtk;
+                        ui.close();
+                    }
                 });
                 ui.separator();
                 if ui.button("Screenshot  F12").clicked() {
@@ -331,6 +388,11 @@ pub fn draw_ui(
                     ui.close();
                 }
                 ui.separator();
+                if ui.button("Optimize Mesh").on_hover_text("Merge adjacent coplanar faces with the same tile and color into larger quads").clicked() {
+                    action = UiAction::OptimizeMesh;
+                    ui.close();
+                }
+                ui.separator();
                 if ui.button("Keybindings...").clicked() {
                     action = UiAction::OpenKeybindingsEditor;
                     ui.close();
@@ -476,6 +538,7 @@ pub fn draw_ui(
                 name: format!("Layer {n}"),
                 visible: true,
                 objects: Vec::new(),
+                palette_variant: None,
             });
         }
         layers_panel::LayerAction::DeleteLayer(i) => {
@@ -492,10 +555,12 @@ pub fn draw_ui(
                     name: format!("{} (copy)", layer.name),
                     visible: layer.visible,
                     objects: Vec::new(),
+                    palette_variant: layer.palette_variant.clone(),
                 };
                 for obj in &layer.objects {
                     let mut new_obj = crate::scene::Object::new(format!("{} (copy)", obj.name));
                     new_obj.faces = obj.faces.clone();
+                    new_obj.palette_variant = obj.palette_variant.clone();
                     dup.objects.push(new_obj);
                 }
                 scene.layers.insert(i + 1, dup);
@@ -532,6 +597,30 @@ pub fn draw_ui(
             tileset_panel::TilesetAction::RebuildMaterial(idx) => {
                 action = UiAction::RebuildMaterial(idx);
             }
+            tileset_panel::TilesetAction::TilePropertiesChanged => {
+                action = UiAction::TilePropertiesChanged;
+            }
+            tileset_panel::TilesetAction::LoadMap(idx, channel) => {
+                action = UiAction::LoadMaterialMap(idx, channel);
+            }
+            tileset_panel::TilesetAction::ClearMap(idx, channel) => {
+                action = UiAction::ClearMaterialMap(idx, channel);
+            }
+            tileset_panel::TilesetAction::ConvertToIndexed(idx) => {
+                action = UiAction::ConvertTilesetToIndexed(idx);
+            }
+            tileset_panel::TilesetAction::ConvertToIndexedWithPalette(idx) => {
+                action = UiAction::ConvertTilesetWithPalette(idx);
+            }
+            tileset_panel::TilesetAction::RevertToRgba(idx) => {
+                action = UiAction::RevertTilesetToRgba(idx);
+            }
+            tileset_panel::TilesetAction::IndexedColorsChanged(idx) => {
+                action = UiAction::IndexedColorsChanged(idx);
+            }
+            tileset_panel::TilesetAction::ImportPaletteVariant(idx) => {
+                action = UiAction::ImportPaletteVariant(idx);
+            }
             tileset_panel::TilesetAction::None => {}
         }
     }
@@ -661,6 +750,7 @@ pub fn draw_ui(
                     ui.selectable_value(settings_tab, SettingsTab::Display, "Display");
                     ui.selectable_value(settings_tab, SettingsTab::Draw, "Draw");
                     ui.selectable_value(settings_tab, SettingsTab::Edit, "Edit");
+                    ui.selectable_value(settings_tab, SettingsTab::File, "File");
                 });
                 ui.separator();
                 egui::ScrollArea::vertical().show(ui, |ui| {
@@ -767,6 +857,8 @@ pub fn draw_ui(
                                     d.undo_limit = val as usize;
                                 }
                             });
+                            ui.checkbox(&mut d.chunk_large_objects, "Chunk large objects")
+                                .on_hover_text("Draw objects with many faces in spatial chunks, skipping off-screen ones");
                         }
                         SettingsTab::Draw => {
                             let dr = &mut settings.draw;
@@ -798,6 +890,21 @@ pub fn draw_ui(
                             });
                             ui.checkbox(&mut e.auto_flatten_uvs, "Auto-flatten UVs on vertex edit");
                         }
+                        SettingsTab::File => {
+                            let f = &mut settings.file;
+                            ui.checkbox(&mut f.autosave_enabled, "Autosave for crash recovery");
+                            ui.add_enabled_ui(f.autosave_enabled, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Autosave interval (min):");
+                                    ui.add(egui::DragValue::new(&mut f.autosave_interval_minutes).range(0.5..=60.0).speed(0.1));
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Snapshots to keep:");
+                                    ui.add(egui::DragValue::new(&mut f.autosave_keep).range(1..=50));
+                                });
+                            });
+                            ui.checkbox(&mut f.tileset_hot_reload, "Reload tilesets when their image changes on disk");
+                        }
                     }
                 });
                 ui.separator();
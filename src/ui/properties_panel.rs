@@ -85,10 +85,11 @@ pub fn draw_properties_panel(
                 }
             }
 
-            if let Some(face) = scene.layers.get_mut(li)
-                .and_then(|l| l.objects.get_mut(oi))
-                .and_then(|o| o.faces.get_mut(fi))
+            if let Some(obj) = scene.layers.get_mut(li).and_then(|l| l.objects.get_mut(oi))
+                && let Some(face) = obj.faces.get_mut(fi)
             {
+                let mut edited = false;
+
                 // Editable vertex positions
                 egui::CollapsingHeader::new("Vertices").default_open(true).show(ui, |ui| {
                     let mut changed = false;
@@ -104,7 +105,7 @@ pub fn draw_properties_panel(
                         if snapshot.is_none() {
                             // Already mutated, but we need the OLD value. We'll take it from commit below.
                         }
-                        scene.dirty_objects.push((li, oi));
+                        edited = true;
                     }
                 });
 
@@ -123,7 +124,7 @@ pub fn draw_properties_panel(
                         });
                     }
                     if changed {
-                        scene.dirty_objects.push((li, oi));
+                        edited = true;
                     }
                 });
 
@@ -142,7 +143,7 @@ pub fn draw_properties_panel(
                         });
                     }
                     if changed {
-                        scene.dirty_objects.push((li, oi));
+                        edited = true;
                     }
                 });
 
@@ -155,6 +156,9 @@ pub fn draw_properties_panel(
                         colors: face.colors,
                     });
                 }
+                if edited {
+                    obj.mark_face_dirty(fi);
+                }
             }
         }
     }
@@ -174,9 +178,8 @@ pub fn draw_properties_panel(
         ui.label(format!("{} vertex(es) selected", sel.vertices.len()));
         if sel.vertices.len() == 1 {
             let (li, oi, fi, vi) = sel.vertices[0];
-            if let Some(face) = scene.layers.get_mut(li)
-                .and_then(|l| l.objects.get_mut(oi))
-                .and_then(|o| o.faces.get_mut(fi))
+            if let Some(obj) = scene.layers.get_mut(li).and_then(|l| l.objects.get_mut(oi))
+                && let Some(face) = obj.faces.get_mut(fi)
             {
                 let mut changed = false;
                 ui.horizontal(|ui| {
@@ -186,7 +189,7 @@ pub fn draw_properties_panel(
                     changed |= ui.add(egui::DragValue::new(&mut face.positions[vi].z).speed(0.05).prefix("z:")).changed();
                 });
                 if changed {
-                    scene.dirty_objects.push((li, oi));
+                    obj.mark_face_dirty(fi);
                 }
             }
         }
@@ -1,5 +1,8 @@
 use crate::scene::Scene;
-use crate::tile::{FilterMode, WrapMode, AlphaMode};
+use crate::tile::{FilterMode, MipFilter, WrapMode, AlphaMode};
+use crate::tile::properties::CollisionType;
+use crate::tile::indexed::PaletteVariant;
+use crate::tile::maps::MapChannel;
 use crate::tile::palette::{PaletteMode};
 use crate::tools::draw::DrawState;
 
@@ -15,6 +18,22 @@ pub enum TilesetAction {
     OpenPaintEditor,
     /// Material settings changed — rebuild sampler/bind_group for this tileset.
     RebuildMaterial(usize),
+    /// Per-tile metadata was edited.
+    TilePropertiesChanged,
+    /// Pick an image for one of a tileset's companion maps.
+    LoadMap(usize, MapChannel),
+    /// Remove one of a tileset's companion maps.
+    ClearMap(usize, MapChannel),
+    /// Index a tileset by its exact colors.
+    ConvertToIndexed(usize),
+    /// Pick a palette file and index a tileset against its colors.
+    ConvertToIndexedWithPalette(usize),
+    /// Drop a tileset's color table and variants, keeping its base colors.
+    RevertToRgba(usize),
+    /// The color table or palette variants of an indexed tileset were edited.
+    IndexedColorsChanged(usize),
+    /// Pick a palette file and add it as a palette variant.
+    ImportPaletteVariant(usize),
 }
 
 /// Draw the tileset browser panel — dispatches to docked or floating mode.
@@ -116,6 +135,11 @@ fn draw_tileset_content(
             action = TilesetAction::LoadTileset;
         }
 
+        if scene.tilesets.len() > 1 {
+            ui.checkbox(&mut draw_state.combine_tilesets, "Combine")
+                .on_hover_text("Place tiles from any tileset into the layer's existing object (rendered through a shared atlas)");
+        }
+
         // Tileset management context menu
         if let Some(idx) = scene.active_tileset {
             ui.menu_button("Manage", |ui| {
@@ -213,24 +237,48 @@ fn draw_tileset_content(
                         egui::Color32::WHITE,
                     );
 
-                    // Draw grid lines
-                    let cell_w = rect.width() / cols as f32;
-                    let cell_h = rect.height() / rows as f32;
-                    let grid_color = egui::Color32::from_rgba_premultiplied(100, 100, 100, 120);
+                    // Image pixel <-> panel position
+                    let px_scale = egui::vec2(
+                        rect.width() / tileset.image_width as f32,
+                        rect.height() / tileset.image_height as f32,
+                    );
+                    let to_screen = |x: u32, y: u32| rect.left_top() + egui::vec2(x as f32, y as f32) * px_scale;
+                    let to_pixel = |pos: egui::Pos2| (pos - rect.left_top()) / px_scale;
 
-                    for c in 0..=cols {
-                        let x = rect.left() + c as f32 * cell_w;
-                        painter.line_segment(
-                            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
-                            egui::Stroke::new(1.0, grid_color),
-                        );
+                    // Draw grid lines along both edges of every tile (they coincide without spacing)
+                    let grid_color = egui::Color32::from_rgba_premultiplied(100, 100, 100, 120);
+                    let (grid_left, grid_top) = tileset.tile_origin(0, 0);
+                    let (last_x, last_y) = tileset.tile_origin(cols - 1, rows - 1);
+                    let grid_min = to_screen(grid_left, grid_top);
+                    let grid_max = to_screen(last_x + tileset.tile_width, last_y + tileset.tile_height);
+
+                    for c in 0..cols {
+                        let (x0, _) = tileset.tile_origin(c, 0);
+                        for x in [x0, x0 + tileset.tile_width] {
+                            let x = to_screen(x, 0).x;
+                            painter.line_segment(
+                                [egui::pos2(x, grid_min.y), egui::pos2(x, grid_max.y)],
+                                egui::Stroke::new(1.0, grid_color),
+                            );
+                        }
                     }
-                    for r in 0..=rows {
-                        let y = rect.top() + r as f32 * cell_h;
-                        painter.line_segment(
-                            [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
-                            egui::Stroke::new(1.0, grid_color),
-                        );
+                    for r in 0..rows {
+                        let (_, y0) = tileset.tile_origin(0, r);
+                        for y in [y0, y0 + tileset.tile_height] {
+                            let y = to_screen(0, y).y;
+                            painter.line_segment(
+                                [egui::pos2(grid_min.x, y), egui::pos2(grid_max.x, y)],
+                                egui::Stroke::new(1.0, grid_color),
+                            );
+                        }
+                    }
+
+                    // Mark tiles that carry metadata with a dot in their top-right corner
+                    for &(c, r) in tileset.tile_properties.keys() {
+                        if c >= cols || r >= rows { continue; }
+                        let (x, y) = tileset.tile_origin(c, r);
+                        let corner = to_screen(x + tileset.tile_width, y);
+                        painter.circle_filled(corner + egui::vec2(-3.0, 3.0), 2.0, egui::Color32::LIGHT_BLUE);
                     }
 
                     // Highlight selected tile region with filled overlay + thick border
@@ -240,15 +288,11 @@ fn draw_tileset_content(
                     let r1 = draw_state.selected_tile.1.max(draw_state.selected_tile_end.1);
 
                     if c0 < cols && r0 < rows {
+                        let (x0, y0) = tileset.tile_origin(c0, r0);
+                        let (x1, y1) = tileset.tile_origin(c1.min(cols - 1), r1.min(rows - 1));
                         let sel_rect = egui::Rect::from_min_max(
-                            egui::pos2(
-                                rect.left() + c0 as f32 * cell_w,
-                                rect.top() + r0 as f32 * cell_h,
-                            ),
-                            egui::pos2(
-                                rect.left() + (c1 + 1).min(cols) as f32 * cell_w,
-                                rect.top() + (r1 + 1).min(rows) as f32 * cell_h,
-                            ),
+                            to_screen(x0, y0),
+                            to_screen(x1 + tileset.tile_width, y1 + tileset.tile_height),
                         );
                         // Semi-transparent yellow fill so selection is visible even on tiny tiles
                         painter.rect_filled(
@@ -268,10 +312,8 @@ fn draw_tileset_content(
                     if response.drag_started()
                         && let Some(pos) = response.interact_pointer_pos()
                     {
-                        let local = pos - rect.left_top();
-                        let col = (local.x / cell_w) as u32;
-                        let row = (local.y / cell_h) as u32;
-                        if col < cols && row < rows {
+                        let px = to_pixel(pos);
+                        if let Some((col, row)) = tileset.tile_at_pixel(px.x, px.y) {
                             draw_state.selected_tile = (col, row);
                             draw_state.selected_tile_end = (col, row);
                         }
@@ -280,10 +322,14 @@ fn draw_tileset_content(
                     if response.dragged()
                         && let Some(pos) = response.interact_pointer_pos()
                     {
-                        let local = pos - rect.left_top();
-                        let col = ((local.x / cell_w) as u32).min(cols.saturating_sub(1));
-                        let row = ((local.y / cell_h) as u32).min(rows.saturating_sub(1));
-                        draw_state.selected_tile_end = (col, row);
+                        // Clamp into the grid so dragging past its edges still extends the selection
+                        let px = to_pixel(pos).clamp(
+                            egui::vec2(grid_left as f32, grid_top as f32),
+                            egui::vec2((last_x + tileset.tile_width) as f32 - 0.5, (last_y + tileset.tile_height) as f32 - 0.5),
+                        );
+                        if let Some(tile) = tileset.tile_at_pixel(px.x, px.y) {
+                            draw_state.selected_tile_end = tile;
+                        }
                     }
                 });
             } else {
@@ -300,6 +346,7 @@ fn draw_tileset_content(
     {
         ui.separator();
         egui::CollapsingHeader::new("Material").default_open(false).show(ui, |ui| {
+            let padding_exceeds_limit = tileset.padding_exceeds_limit();
             let mat = &mut tileset.material;
             let mut changed = false;
 
@@ -315,6 +362,38 @@ fn draw_tileset_content(
                 if mat.filter != prev { changed = true; }
             });
 
+            ui.horizontal(|ui| {
+                ui.label("Mipmaps:");
+                let prev = mat.mip_filter;
+                egui::ComboBox::from_id_salt("mat_mip_filter")
+                    .selected_text(format!("{:?}", mat.mip_filter))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut mat.mip_filter, MipFilter::Off, "Off");
+                        ui.selectable_value(&mut mat.mip_filter, MipFilter::Nearest, "Nearest")
+                            .on_hover_text("Keep exact pixel colors in distant levels (pixel art)");
+                        ui.selectable_value(&mut mat.mip_filter, MipFilter::Linear, "Linear")
+                            .on_hover_text("Smoothly averaged levels, blended with trilinear filtering");
+                    });
+                if mat.mip_filter != prev { changed = true; }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Anisotropy:");
+                let prev = mat.anisotropy;
+                let usable = mat.filter == FilterMode::Linear && mat.mip_filter == MipFilter::Linear;
+                ui.add_enabled_ui(usable, |ui| {
+                    egui::ComboBox::from_id_salt("mat_anisotropy")
+                        .selected_text(if mat.anisotropy > 1 { format!("{}x", mat.anisotropy) } else { "Off".to_string() })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut mat.anisotropy, 1, "Off");
+                            for samples in [2, 4, 8, 16] {
+                                ui.selectable_value(&mut mat.anisotropy, samples, format!("{samples}x"));
+                            }
+                        });
+                }).response.on_disabled_hover_text("Needs Linear filter and Linear mipmaps");
+                if mat.anisotropy != prev { changed = true; }
+            });
+
             ui.horizontal(|ui| {
                 ui.label("Wrap:");
                 let prev = mat.wrap;
@@ -350,12 +429,243 @@ fn draw_tileset_content(
 
             ui.checkbox(&mut mat.decal, "Decal overlay");
 
+            ui.horizontal(|ui| {
+                ui.label("Edge extrusion:");
+                let prev = mat.extrude;
+                ui.add(egui::DragValue::new(&mut mat.extrude).range(0..=16).suffix(" px"))
+                    .on_hover_text("Repeat each tile's border pixels around it on the GPU so linear filtering and mipmaps don't bleed neighboring tiles");
+                if mat.extrude != prev { changed = true; }
+            });
+            if padding_exceeds_limit {
+                ui.colored_label(ui.visuals().warn_fg_color, "Padded image exceeds the GPU texture limit; extrusion is off")
+                    .on_hover_text(format!("Padded tiles must fit in {} px", crate::tile::max_texture_dimension()));
+            }
+
+            ui.separator();
+            ui.label("Maps (same tile grid as the image):");
+            for channel in MapChannel::ALL {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}:", channel.label()));
+                    if tileset.maps.get(channel).is_some() {
+                        if ui.small_button("Clear").clicked() {
+                            action = TilesetAction::ClearMap(active_idx, channel);
+                        }
+                    } else {
+                        ui.weak("none");
+                    }
+                    if ui.small_button("Load...").clicked() {
+                        action = TilesetAction::LoadMap(active_idx, channel);
+                    }
+                    let strength = match channel {
+                        MapChannel::Normal => &mut mat.normal_strength,
+                        MapChannel::Emissive => &mut mat.emissive_strength,
+                        MapChannel::Specular => &mut mat.specular_strength,
+                    };
+                    if ui.add(egui::DragValue::new(strength).range(0.0..=4.0).speed(0.01)).changed() {
+                        changed = true;
+                    }
+                });
+            }
+            if tileset.maps.specular.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("Shininess:");
+                    if ui.add(egui::DragValue::new(&mut mat.specular_power).range(1.0..=256.0)).changed() {
+                        changed = true;
+                    }
+                });
+            }
+            if tileset.maps.normal.is_some() || tileset.maps.specular.is_some() || tileset.maps.emissive.is_some() {
+                ui.weak("Maps show in the viewport with lighting enabled.");
+            }
+
             if changed {
                 action = TilesetAction::RebuildMaterial(active_idx);
             }
         });
     }
 
+    // Tile properties of the selected tile (collapsible)
+    if let Some(active_idx) = scene.active_tileset
+        && let Some(tileset) = scene.tilesets.get_mut(active_idx)
+    {
+        let tile = (
+            draw_state.selected_tile.0.min(draw_state.selected_tile_end.0),
+            draw_state.selected_tile.1.min(draw_state.selected_tile_end.1),
+        );
+        ui.separator();
+        egui::CollapsingHeader::new("Tile Properties").default_open(false).show(ui, |ui| {
+            if tile.0 >= tileset.cols() || tile.1 >= tileset.rows() {
+                ui.label("Select a tile to edit its properties.");
+                return;
+            }
+            ui.label(format!("Tile ({}, {})", tile.0, tile.1));
+
+            let mut props = tileset.tile_properties.get(&tile).cloned().unwrap_or_default();
+            let mut changed = false;
+
+            ui.horizontal(|ui| {
+                ui.label("Collision:");
+                let prev = props.collision;
+                egui::ComboBox::from_id_salt("tile_collision")
+                    .selected_text(format!("{:?}", props.collision))
+                    .show_ui(ui, |ui| {
+                        for kind in CollisionType::ALL {
+                            ui.selectable_value(&mut props.collision, kind, format!("{kind:?}"));
+                        }
+                    });
+                if props.collision != prev { changed = true; }
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Tags:");
+                let mut remove_idx = None;
+                for (i, tag) in props.tags.iter().enumerate() {
+                    if ui.small_button(format!("{tag} x")).on_hover_text("Remove tag").clicked() {
+                        remove_idx = Some(i);
+                    }
+                }
+                if let Some(i) = remove_idx {
+                    props.tags.remove(i);
+                    changed = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                // Pending tag text lives in egui memory so it survives between frames
+                let id = ui.id().with("new_tile_tag");
+                let mut new_tag: String = ui.data_mut(|d| d.get_temp(id).unwrap_or_default());
+                let response = ui.add(egui::TextEdit::singleline(&mut new_tag).desired_width(100.0));
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.small_button("Add Tag").clicked() || submitted) && !new_tag.trim().is_empty() {
+                    props.tags.push(new_tag.trim().to_string());
+                    new_tag.clear();
+                    changed = true;
+                }
+                ui.data_mut(|d| d.insert_temp(id, new_tag));
+            });
+
+            ui.label("Custom:");
+            let mut remove_idx = None;
+            for (i, (key, value)) in props.custom.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::TextEdit::singleline(key).desired_width(80.0).hint_text("key")).changed();
+                    changed |= ui.add(egui::TextEdit::singleline(value).desired_width(100.0).hint_text("value")).changed();
+                    if ui.small_button("x").clicked() {
+                        remove_idx = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove_idx {
+                props.custom.remove(i);
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                if ui.small_button("+").on_hover_text("Add custom property").clicked() {
+                    props.custom.push((String::new(), String::new()));
+                    changed = true;
+                }
+                if ui.small_button("Clear").on_hover_text("Remove all metadata from this tile").clicked() {
+                    props = Default::default();
+                    changed = true;
+                }
+            });
+
+            if changed {
+                if props.is_empty() {
+                    tileset.tile_properties.remove(&tile);
+                } else {
+                    tileset.tile_properties.insert(tile, props);
+                }
+                action = TilesetAction::TilePropertiesChanged;
+            }
+        });
+    }
+
+    // Indexed color table and palette variants (collapsible)
+    if let Some(active_idx) = scene.active_tileset
+        && let Some(tileset) = scene.tilesets.get_mut(active_idx)
+        && tileset.image_data.is_some()
+    {
+        ui.separator();
+        egui::CollapsingHeader::new("Indexed Color").default_open(false).show(ui, |ui| {
+            let Some(indexed) = tileset.indexed.as_mut() else {
+                ui.label("RGBA image.");
+                ui.horizontal(|ui| {
+                    if ui.button("Convert to Indexed").on_hover_text("Index the image by its exact colors (up to 256)").clicked() {
+                        action = TilesetAction::ConvertToIndexed(active_idx);
+                    }
+                    if ui.button("Convert with Palette...").on_hover_text("Snap the image to a .gpl, .hex or .pal palette").clicked() {
+                        action = TilesetAction::ConvertToIndexedWithPalette(active_idx);
+                    }
+                });
+                return;
+            };
+            let mut changed = false;
+
+            // Variant being edited lives in egui memory (None = base table)
+            let id = ui.id().with("palette_variant");
+            let mut selected: Option<usize> = ui.data_mut(|d| d.get_temp(id).unwrap_or_default());
+            selected = selected.filter(|&v| v < indexed.variants.len());
+
+            ui.horizontal(|ui| {
+                ui.label("Palette:");
+                egui::ComboBox::from_id_salt("palette_variant_select")
+                    .selected_text(selected.map_or("Base", |v| indexed.variants[v].name.as_str()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "Base");
+                        for (v, variant) in indexed.variants.iter().enumerate() {
+                            ui.selectable_value(&mut selected, Some(v), &variant.name);
+                        }
+                    });
+                if ui.small_button("+").on_hover_text("Add a variant starting from the base colors").clicked() {
+                    let n = indexed.variants.len() + 1;
+                    indexed.variants.push(PaletteVariant { name: format!("Variant {n}"), colors: indexed.colors.clone() });
+                    selected = Some(indexed.variants.len() - 1);
+                    changed = true;
+                }
+                if ui.small_button("Import...").on_hover_text("Add a variant from a .gpl, .hex or .pal palette").clicked() {
+                    action = TilesetAction::ImportPaletteVariant(active_idx);
+                }
+            });
+
+            if let Some(v) = selected {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut indexed.variants[v].name).desired_width(120.0))
+                        .on_hover_text("Layers and objects select variants by name");
+                    if ui.small_button("Delete").clicked() {
+                        indexed.variants.remove(v);
+                        selected = None;
+                        changed = true;
+                    }
+                });
+            }
+
+            ui.label(format!("{} colors:", indexed.colors.len()));
+            let mut table = indexed.table(selected);
+            let mut edited = false;
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+                for color in &mut table {
+                    edited |= ui.color_edit_button_srgba_unmultiplied(color).changed();
+                }
+            });
+            if edited {
+                match selected.and_then(|v| indexed.variants.get_mut(v)) {
+                    Some(variant) => variant.colors = table,
+                    None => indexed.colors = table,
+                }
+                changed = true;
+            }
+            ui.data_mut(|d| d.insert_temp(id, selected));
+
+            if ui.button("Revert to RGBA").on_hover_text("Drop the color table and variants, keeping the base colors").clicked() {
+                action = TilesetAction::RevertToRgba(active_idx);
+            } else if changed {
+                action = TilesetAction::IndexedColorsChanged(active_idx);
+            }
+        });
+    }
+
     // Palette section (collapsible)
     ui.separator();
     egui::CollapsingHeader::new("Palette").default_open(false).show(ui, |ui| {
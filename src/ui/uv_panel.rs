@@ -312,16 +312,12 @@ fn draw_uv_content(
                 }
             }
 
-            // Mark objects as dirty for GPU mesh rebuild
-            let mut dirty: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+            // Queue the dragged faces for GPU upload
             for &(sel_idx, _) in &drag.targets {
-                if let Some(&(li, oi, _)) = edit_state.selection.faces.get(sel_idx) {
-                    dirty.insert((li, oi));
+                if let Some(&(li, oi, fi)) = edit_state.selection.faces.get(sel_idx) {
+                    scene.layers[li].objects[oi].mark_face_dirty(fi);
                 }
             }
-            for (li, oi) in dirty {
-                scene.dirty_objects.push((li, oi));
-            }
         }
 
         // Handle UV vertex dragging — commit
@@ -352,16 +348,12 @@ fn draw_uv_content(
                 }
             }
 
-            // Mark dirty for rebuild
-            let mut dirty: std::collections::HashSet<(usize, usize)> = std::collections::HashSet::new();
+            // Queue the restored faces for GPU upload
             for &(sel_idx, _) in &uv_state.selected_uv_verts {
-                if let Some(&(li, oi, _)) = edit_state.selection.faces.get(sel_idx) {
-                    dirty.insert((li, oi));
+                if let Some(&(li, oi, fi)) = edit_state.selection.faces.get(sel_idx) {
+                    scene.layers[li].objects[oi].mark_face_dirty(fi);
                 }
             }
-            for (li, oi) in dirty {
-                scene.dirty_objects.push((li, oi));
-            }
         }
     });
 
@@ -0,0 +1,274 @@
+use glam::Vec3;
+
+use crate::scene::mesh::Face;
+use super::picking::Ray;
+
+/// Faces per leaf; below this, splitting further costs more than testing the faces.
+const LEAF_SIZE: usize = 4;
+
+/// Bounding volume hierarchy over one object's faces, in the object's local space. Instances
+/// share it by querying with a ray transformed into local space.
+///
+/// Hidden faces are included; callers skip them, so toggling visibility needs no rebuild.
+#[derive(Debug, Default)]
+pub struct FaceBvh {
+    /// Depth-first: a node's left child directly follows it.
+    nodes: Vec<Node>,
+    /// Face indices, each leaf owning a contiguous range.
+    faces: Vec<u32>,
+    /// Summed node surface area when built; refits that inflate it too much trigger a rebuild.
+    built_area: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    /// Leaf: first entry in `faces`. Interior: index of the right child.
+    start: u32,
+    /// Leaf: number of faces. Interior: 0.
+    count: u32,
+}
+
+impl Node {
+    fn area(&self) -> f32 {
+        let d = (self.max - self.min).max(Vec3::ZERO);
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+}
+
+fn face_bounds(face: &Face) -> (Vec3, Vec3) {
+    let p = &face.positions;
+    (p[0].min(p[1]).min(p[2]).min(p[3]), p[0].max(p[1]).max(p[2]).max(p[3]))
+}
+
+impl FaceBvh {
+    /// Build a hierarchy by splitting at the median centroid along the longest axis.
+    pub fn build(faces: &[Face]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(faces.len().div_ceil(LEAF_SIZE) * 2),
+            faces: (0..faces.len() as u32).collect(),
+            built_area: 0.0,
+        };
+        if !faces.is_empty() {
+            let bounds: Vec<(Vec3, Vec3)> = faces.iter().map(face_bounds).collect();
+            bvh.build_node(&bounds, 0, faces.len());
+        }
+        bvh.built_area = bvh.nodes.iter().map(Node::area).sum();
+        bvh
+    }
+
+    fn build_node(&mut self, bounds: &[(Vec3, Vec3)], start: usize, end: usize) -> usize {
+        let range = &mut self.faces[start..end];
+        let (min, max) = range.iter().fold((Vec3::MAX, Vec3::MIN), |(lo, hi), &f| {
+            (lo.min(bounds[f as usize].0), hi.max(bounds[f as usize].1))
+        });
+        let index = self.nodes.len();
+        self.nodes.push(Node { min, max, start: start as u32, count: (end - start) as u32 });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        let centroid = |f: u32| bounds[f as usize].0 + bounds[f as usize].1;
+        let extent = range.iter().fold((Vec3::MAX, Vec3::MIN), |(lo, hi), &f| (lo.min(centroid(f)), hi.max(centroid(f))));
+        let size = extent.1 - extent.0;
+        let axis = if size.x >= size.y && size.x >= size.z { 0 } else if size.y >= size.z { 1 } else { 2 };
+        let mid = range.len() / 2;
+        range.select_nth_unstable_by(mid, |&a, &b| centroid(a)[axis].total_cmp(&centroid(b)[axis]));
+
+        self.build_node(bounds, start, start + mid);
+        let right = self.build_node(bounds, start + mid, end);
+        self.nodes[index].start = right as u32;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    /// Number of faces the hierarchy was built for.
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Recompute node bounds after faces moved, keeping the tree shape. The face count must be
+    /// unchanged. Returns false when the bounds grew enough that a rebuild would pay off.
+    pub fn refit(&mut self, faces: &[Face]) -> bool {
+        debug_assert_eq!(faces.len(), self.faces.len());
+        // Children always follow their parent, so a reverse pass sees them first.
+        for i in (0..self.nodes.len()).rev() {
+            let node = self.nodes[i];
+            let (min, max) = if node.count > 0 {
+                let range = &self.faces[node.start as usize..(node.start + node.count) as usize];
+                range.iter().map(|&f| face_bounds(&faces[f as usize]))
+                    .fold((Vec3::MAX, Vec3::MIN), |(lo, hi), (a, b)| (lo.min(a), hi.max(b)))
+            } else {
+                let (left, right) = (self.nodes[i + 1], self.nodes[node.start as usize]);
+                (left.min.min(right.min), left.max.max(right.max))
+            };
+            self.nodes[i].min = min;
+            self.nodes[i].max = max;
+        }
+        let area: f32 = self.nodes.iter().map(Node::area).sum();
+        area <= self.built_area * 2.0 + f32::EPSILON
+    }
+
+    /// Find the nearest face along a ray. `hit(face)` tests one face and returns its hit
+    /// distance; nodes farther than the best hit so far are skipped.
+    pub fn raycast(&self, ray: &Ray, mut hit: impl FnMut(usize) -> Option<f32>) -> Option<(f32, usize)> {
+        let inv_dir = ray.direction.recip();
+        let mut best: Option<(f32, usize)> = None;
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0usize);
+        }
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            let limit = best.map_or(f32::INFINITY, |(t, _)| t);
+            if slab_entry(ray.origin, inv_dir, node.min, node.max).is_none_or(|t| t > limit) {
+                continue;
+            }
+            if node.count > 0 {
+                for &f in &self.faces[node.start as usize..(node.start + node.count) as usize] {
+                    if let Some(t) = hit(f as usize)
+                        && best.is_none_or(|(b, _)| t < b)
+                    {
+                        best = Some((t, f as usize));
+                    }
+                }
+                continue;
+            }
+            // Visit the nearer child first so its hits prune the other one.
+            let (left, right) = (i + 1, node.start as usize);
+            let near_left = slab_entry(ray.origin, inv_dir, self.nodes[left].min, self.nodes[left].max)
+                .unwrap_or(f32::INFINITY)
+                <= slab_entry(ray.origin, inv_dir, self.nodes[right].min, self.nodes[right].max).unwrap_or(f32::INFINITY);
+            if near_left {
+                stack.extend([right, left]);
+            } else {
+                stack.extend([left, right]);
+            }
+        }
+        best
+    }
+
+    /// Visit every face whose bounds overlap the box `min..=max`, once each. Faces sharing a leaf
+    /// with one of them are visited too, so callers test candidates exactly.
+    pub fn query_aabb(&self, min: Vec3, max: Vec3, mut visit: impl FnMut(usize)) {
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0usize);
+        }
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if node.min.cmpgt(max).any() || node.max.cmplt(min).any() {
+                continue;
+            }
+            if node.count > 0 {
+                for &f in &self.faces[node.start as usize..(node.start + node.count) as usize] {
+                    visit(f as usize);
+                }
+            } else {
+                stack.extend([node.start as usize, i + 1]);
+            }
+        }
+    }
+}
+
+/// Distance at which a ray enters a box (0 when it starts inside), or None if it misses.
+fn slab_entry(origin: Vec3, inv_dir: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let t1 = (min - origin) * inv_dir;
+    let t2 = (max - origin) * inv_dir;
+    // NaN (axis-parallel ray on a slab boundary) is dropped by min/max, keeping the test conservative.
+    let near = t1.min(t2).max_element().max(0.0);
+    let far = t1.max(t2).min_element();
+    (near <= far).then_some(near)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::*;
+
+    /// Deterministic xorshift so failures reproduce without a rand dependency.
+    struct Rng(u32);
+
+    impl Rng {
+        fn unit(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 >> 8) as f32 / (1 << 24) as f32
+        }
+
+        fn range(&mut self, lo: f32, hi: f32) -> f32 {
+            lo + (hi - lo) * self.unit()
+        }
+
+        fn vec3(&mut self, lo: f32, hi: f32) -> Vec3 {
+            Vec3::new(self.range(lo, hi), self.range(lo, hi), self.range(lo, hi))
+        }
+
+        fn face(&mut self) -> Face {
+            let normal = self.vec3(-1.0, 1.0).try_normalize().unwrap_or(Vec3::Y);
+            Face::new_quad(self.vec3(-20.0, 20.0), normal, self.range(0.1, 2.0), [Vec2::ZERO; 4])
+        }
+    }
+
+    fn assert_matches_linear_scan(bvh: &FaceBvh, faces: &[Face], rng: &mut Rng) {
+        for _ in 0..300 {
+            let origin = rng.vec3(-30.0, 30.0);
+            let ray = Ray { origin, direction: (rng.vec3(-10.0, 10.0) - origin).normalize() };
+            let linear = faces.iter()
+                .filter_map(|f| ray.intersect_quad(&f.positions))
+                .min_by(f32::total_cmp);
+            let found = bvh.raycast(&ray, |i| ray.intersect_quad(&faces[i].positions));
+            assert_eq!(found.map(|(t, _)| t), linear);
+            if let Some((t, i)) = found {
+                assert_eq!(ray.intersect_quad(&faces[i].positions), Some(t));
+            }
+        }
+
+        for _ in 0..100 {
+            let (a, b) = (rng.vec3(-25.0, 25.0), rng.vec3(-25.0, 25.0));
+            let (min, max) = (a.min(b), a.max(b));
+            let linear: Vec<usize> = (0..faces.len())
+                .filter(|&i| {
+                    let (lo, hi) = face_bounds(&faces[i]);
+                    lo.cmple(max).all() && hi.cmpge(min).all()
+                })
+                .collect();
+            let mut found = Vec::new();
+            bvh.query_aabb(min, max, |i| found.push(i));
+            found.sort_unstable();
+            let visited = found.len();
+            found.dedup();
+            assert_eq!(found.len(), visited, "a face was visited twice");
+            found.retain(|i| linear.binary_search(i).is_ok());
+            assert_eq!(found, linear);
+        }
+    }
+
+    #[test]
+    fn queries_match_a_linear_scan_before_and_after_refit() {
+        let mut rng = Rng(0x2545_f491);
+        let mut faces: Vec<Face> = (0..400).map(|_| rng.face()).collect();
+        let mut bvh = FaceBvh::build(&faces);
+        assert_eq!(bvh.face_count(), faces.len());
+        assert_matches_linear_scan(&bvh, &faces, &mut rng);
+
+        // Nudge every third face; refit must keep every query exact even if it asks for a rebuild.
+        for face in faces.iter_mut().step_by(3) {
+            let offset = rng.vec3(-3.0, 3.0);
+            face.positions.iter_mut().for_each(|p| *p += offset);
+        }
+        bvh.refit(&faces);
+        assert_matches_linear_scan(&bvh, &faces, &mut rng);
+
+        // Far moves inflate the bounds enough that refit should recommend rebuilding.
+        for face in faces.iter_mut().step_by(2) {
+            let offset = rng.vec3(-200.0, 200.0);
+            face.positions.iter_mut().for_each(|p| *p += offset);
+        }
+        assert!(!bvh.refit(&faces));
+        assert_matches_linear_scan(&bvh, &faces, &mut rng);
+    }
+}
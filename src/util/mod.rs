@@ -1 +1,2 @@
+pub mod bvh;
 pub mod picking;
@@ -154,64 +154,49 @@ fn pick_face_ex(
         }
         for (oi, object) in layer.objects.iter().enumerate() {
             // Test source object faces
-            for (fi, face) in object.faces.iter().enumerate() {
-                if face.hidden { continue; }
-                let normal = face.normal();
-                if cull_backfaces && normal.dot(ray.direction) > 0.0 {
-                    continue;
-                }
-                if let Some(t) = ray.intersect_quad(&face.positions) {
-                    let dominated = closest.as_ref().is_some_and(|c| c.distance <= t);
-                    if !dominated {
-                        closest = Some(HitResult {
-                            distance: t,
-                            position: ray.point_at(t),
-                            normal,
-                            layer_index: li,
-                            object_index: oi,
-                            face_index: fi,
-                            instance_index: None,
-                        });
-                    }
+            if let Some((t, fi)) = nearest_object_face(ray, object, cull_backfaces) {
+                let dominated = closest.as_ref().is_some_and(|c| c.distance <= t);
+                if !dominated {
+                    closest = Some(HitResult {
+                        distance: t,
+                        position: ray.point_at(t),
+                        normal: object.faces[fi].normal(),
+                        layer_index: li,
+                        object_index: oi,
+                        face_index: fi,
+                        instance_index: None,
+                    });
                 }
             }
 
-            // Test instance faces: transform ray into local space
+            // Test instance faces: transform ray into local space (sharing the object's BVH)
             for (ii, inst) in object.instances.iter().enumerate() {
                 let inv_model = inst.model_matrix().inverse();
                 let local_origin = inv_model.transform_point3(ray.origin);
                 let local_dir = inv_model.transform_vector3(ray.direction).normalize();
                 let local_ray = Ray { origin: local_origin, direction: local_dir };
 
+                let Some((t_local, fi)) = nearest_object_face(&local_ray, object, cull_backfaces) else { continue };
                 let model = inst.model_matrix();
                 // Normal matrix: transpose of inverse of upper-left 3x3 (handles non-uniform scale)
-                let normal_matrix = Mat3::from_mat4(model.inverse().transpose());
-
-                for (fi, face) in object.faces.iter().enumerate() {
-                    if face.hidden { continue; }
-                    let local_normal = face.normal();
-                    if cull_backfaces && local_normal.dot(local_dir) > 0.0 {
-                        continue;
-                    }
-                    if let Some(t_local) = local_ray.intersect_quad(&face.positions) {
-                        // Convert hit back to world space for distance comparison
-                        let local_hit = local_ray.point_at(t_local);
-                        let world_hit = model.transform_point3(local_hit);
-                        let t_world = (world_hit - ray.origin).length();
-                        let world_normal = (normal_matrix * local_normal).normalize();
-                        let dominated = closest.as_ref().is_some_and(|c| c.distance <= t_world);
-                        if !dominated {
-                            closest = Some(HitResult {
-                                distance: t_world,
-                                position: world_hit,
-                                normal: world_normal,
-                                layer_index: li,
-                                object_index: oi,
-                                face_index: fi,
-                                instance_index: Some(ii),
-                            });
-                        }
-                    }
+                let normal_matrix = Mat3::from_mat4(inv_model.transpose());
+
+                // Convert hit back to world space for distance comparison
+                let local_hit = local_ray.point_at(t_local);
+                let world_hit = model.transform_point3(local_hit);
+                let t_world = (world_hit - ray.origin).length();
+                let world_normal = (normal_matrix * object.faces[fi].normal()).normalize();
+                let dominated = closest.as_ref().is_some_and(|c| c.distance <= t_world);
+                if !dominated {
+                    closest = Some(HitResult {
+                        distance: t_world,
+                        position: world_hit,
+                        normal: world_normal,
+                        layer_index: li,
+                        object_index: oi,
+                        face_index: fi,
+                        instance_index: Some(ii),
+                    });
                 }
             }
         }
@@ -219,3 +204,24 @@ fn pick_face_ex(
 
     closest
 }
+
+/// Nearest visible face of an object hit by a ray in the object's space, as (distance, face index).
+fn nearest_object_face(
+    ray: &Ray,
+    object: &crate::scene::Object,
+    cull_backfaces: bool,
+) -> Option<(f32, usize)> {
+    let hit = |fi: usize| {
+        let face = &object.faces[fi];
+        if face.hidden || (cull_backfaces && face.normal().dot(ray.direction) > 0.0) {
+            return None;
+        }
+        ray.intersect_quad(&face.positions)
+    };
+    match object.bvh() {
+        Some(bvh) => bvh.raycast(ray, hit),
+        None => (0..object.faces.len())
+            .filter_map(|fi| hit(fi).map(|t| (t, fi)))
+            .min_by(|a, b| a.0.total_cmp(&b.0)),
+    }
+}